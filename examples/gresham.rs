@@ -0,0 +1,314 @@
+//! Gresham's law: bad money drives out good. A world of traders who each
+//! hold one good and keep coming back wanting another (see
+//! `examples/data/gresham`) have two moneys to pay with: gold coin, which
+//! everyone values for its metal as well, and copper coin, which nobody
+//! values for anything but what it'll buy. Left alone, sellers take gold and
+//! turn copper down, so gold is what goes round. Make copper legal tender
+//! for gold at a ratio that overvalues it (see `LegalTender`), and sellers
+//! have to take copper at face value, so buyers pay in copper and keep their
+//! gold back, to sit on or melt down.
+//!
+//! This runs the scenario over a number of seeds, each of which deals the
+//! traders' wants out differently, once as it is and once with the law in
+//! force from the start, and reports how much of each coin went round in
+//! trade, how much was still sitting with whoever started out with it, and
+//! how much gold was melted down.
+//!
+//! ```text
+//! cargo run --example gresham [seeds] [ticks] [ratio]
+//! ```
+
+use microeconomics::{
+    Actor, ActorId, GoalRegistry, GoalTemplate, ItemId, ItemRegistry, LegalTender, Quantity,
+    SimEvent, Simulation,
+};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+
+const ITEMS: &str = include_str!("data/gresham/items.toml");
+const TRADERS: &str = include_str!("data/gresham/traders.toml");
+
+/// How often each trader comes back wanting its good, in ticks.
+const WANT_EVERY: u32 = 4;
+
+/// The traders file.
+#[derive(Deserialize)]
+struct Traders {
+    trader: Vec<TraderDef>,
+}
+
+/// So many traders alike.
+#[derive(Deserialize)]
+struct TraderDef {
+    /// How many of them there are
+    count: u32,
+    /// What each of them starts out with, by name
+    holds: BTreeMap<String, Quantity>,
+    /// The goods each of them might want, by name (one, picked at random)
+    wants: Vec<String>,
+}
+
+/// What became of one coin over one run of the scenario.
+#[derive(Default)]
+struct Circulation {
+    /// How much of it there was to begin with
+    minted: Quantity,
+    /// How much of it changed hands in trade, all told
+    spent: Quantity,
+    /// How many trades it went in
+    trades: usize,
+    /// How much of it never left the trader it started with
+    hoarded: Quantity,
+    /// How much of it was melted down for plate
+    melted: Quantity,
+}
+
+/// Set up the scenario from seed `seed`.
+fn world(seed: u64, traders: &Traders) -> Simulation {
+    let mut sim = Simulation::with_seed(seed);
+    sim.set_items(ItemRegistry::from_toml(ITEMS).expect("bad scenario items"));
+    // Everyone can want any good, or want to keep it, and values gold as
+    // metal for plate
+    let mut goals: String = sim
+        .items()
+        .iter()
+        .filter(|(_, def)| !def.class.is_money())
+        .map(|(_, def)| {
+            format!(
+                "[[goal]]\nname = \"Want{name}\"\nunits_required = 1\n\
+                 [[goal]]\nname = \"Keep{name}\"\nunits_required = 12\n",
+                name = def.name
+            )
+        })
+        .collect();
+    goals.push_str("[[goal]]\nname = \"Plate\"\nunits_required = 100\n");
+    sim.set_goals(GoalRegistry::from_toml(&goals).expect("bad scenario goals"));
+    let gold = sim.items().id("GoldCoin").unwrap();
+    let plate = sim.goals().id("Plate").unwrap();
+    let mut n = 0;
+    for def in traders.trader.iter() {
+        for _ in 0..def.count {
+            let want = def.wants.choose(sim.rng()).expect("trader wants nothing");
+            let wanted = sim.items().id(want).expect("unknown good wanted");
+            let mut builder = Actor::builder()
+                .name(format!("Trader#{}", n))
+                .template(
+                    &GoalTemplate::recurring(
+                        sim.goals().id(&format!("Want{}", want)).unwrap(),
+                        WANT_EVERY,
+                        Quantity::ONE,
+                        vec![wanted],
+                    ),
+                    sim.rng(),
+                )
+                .template(
+                    &GoalTemplate::once(plate, Quantity::units(100), vec![gold]),
+                    sim.rng(),
+                );
+            for (name, &amount) in def.holds.iter() {
+                let held = sim.items().id(name).expect("unknown good held");
+                if let Some(keep) = sim.goals().id(&format!("Keep{}", name)) {
+                    builder = builder.template(
+                        &GoalTemplate::once(keep, Quantity::units(12), vec![held]),
+                        sim.rng(),
+                    );
+                }
+                builder = builder.starting_amount(held, amount);
+            }
+            sim.add_actor(builder.build().expect("bad trader"));
+            n += 1;
+        }
+    }
+    sim
+}
+
+/// Run the scenario from seed `seed` for `ticks` ticks, with copper legal
+/// tender for gold at `ratio` copper to the gold coin if that's set, and
+/// report what became of each coin.
+fn run(
+    seed: u64,
+    ticks: u64,
+    ratio: Option<Quantity>,
+    traders: &Traders,
+) -> BTreeMap<ItemId, Circulation> {
+    let mut sim = world(seed, traders);
+    let (gold, copper) = (
+        sim.items().id("GoldCoin").unwrap(),
+        sim.items().id("CopperCoin").unwrap(),
+    );
+    if let Some(ratio) = ratio {
+        sim.impose_legal_tender(LegalTender {
+            item: gold,
+            quote: copper,
+            ratio,
+        });
+    }
+    // What each trader started out with of each coin, and has spent of it
+    let mut held: BTreeMap<(ActorId, ItemId), (Quantity, Quantity)> = BTreeMap::new();
+    for actor in sim.actors() {
+        for &coin in [gold, copper].iter() {
+            held.insert(
+                (actor.id, coin),
+                (actor.inventory.count(coin), Quantity::ZERO),
+            );
+        }
+    }
+    let mut coins: BTreeMap<ItemId, Circulation> = [gold, copper]
+        .iter()
+        .map(|&coin| (coin, Circulation::default()))
+        .collect();
+    for _ in 0..ticks {
+        for event in sim.tick() {
+            match event {
+                SimEvent::TradeCompleted {
+                    actor,
+                    partner,
+                    gave,
+                    received,
+                } => {
+                    for &(from, bundle) in [(actor, &gave), (partner, &received)].iter() {
+                        for (item, amount) in bundle.iter() {
+                            if let Some(coin) = coins.get_mut(&item) {
+                                coin.spent += amount;
+                                coin.trades += 1;
+                                if let Some((_, spent)) = held.get_mut(&(from, item)) {
+                                    *spent += amount;
+                                }
+                            }
+                        }
+                    }
+                }
+                SimEvent::ItemUsed {
+                    actor,
+                    item,
+                    amount,
+                    ..
+                } => {
+                    if let Some(coin) = coins.get_mut(&item) {
+                        coin.melted += amount;
+                        // Melting it down is as good as spending it, as far
+                        // as what's left of what it started with goes
+                        if let Some((_, spent)) = held.get_mut(&(actor, item)) {
+                            *spent += amount;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    for (&(id, item), &(started, spent)) in held.iter() {
+        let coin = coins.get_mut(&item).unwrap();
+        coin.minted += started;
+        // Whatever it's still holding, up to what it started with less what
+        // it's spent or melted since, can't ever have left its hands
+        let now = sim.actor(id).unwrap().inventory.count(item);
+        coin.hoarded += now.min(started.saturating_sub(spent));
+    }
+    coins
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let seeds: u64 = args
+        .next()
+        .map_or(10, |s| s.parse().expect("bad seed count"));
+    let ticks: u64 = args
+        .next()
+        .map_or(40, |t| t.parse().expect("bad tick count"));
+    let ratio: Quantity = args
+        .next()
+        .map_or(Quantity::ONE, |r| r.parse().expect("bad ratio"));
+    let traders: Traders = toml::from_str(TRADERS).expect("bad scenario traders");
+    let items = ItemRegistry::from_toml(ITEMS).expect("bad scenario items");
+    let (gold, copper) = (
+        items.id("GoldCoin").unwrap(),
+        items.id("CopperCoin").unwrap(),
+    );
+    let population: u32 = traders.trader.iter().map(|def| def.count).sum();
+
+    println!(
+        "{} traders, {} ticks, {} seeds, copper legal tender at {} to the gold coin",
+        population, ticks, seeds, ratio
+    );
+    println!();
+    println!(
+        "{:5} | {:8} | {:18} | {:18} | {:12} | {:12} | {:14}",
+        "Seed",
+        "Law",
+        "Gold Spent",
+        "Copper Spent",
+        "Gold Hoarded",
+        "Gold Melted",
+        "Copper Hoarded"
+    );
+    println!(
+        "{}-+-{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+        "-".repeat(5),
+        "-".repeat(8),
+        "-".repeat(18),
+        "-".repeat(18),
+        "-".repeat(12),
+        "-".repeat(12),
+        "-".repeat(14)
+    );
+    // How each coin came out over every seed, without the law and with it
+    let mut totals: [BTreeMap<ItemId, Circulation>; 2] = Default::default();
+    for seed in 0..seeds {
+        for (n, &law) in [None, Some(ratio)].iter().enumerate() {
+            let coins = run(seed, ticks, law, &traders);
+            for (&coin, c) in coins.iter() {
+                let total = totals[n].entry(coin).or_default();
+                total.minted += c.minted;
+                total.spent += c.spent;
+                total.trades += c.trades;
+                total.hoarded += c.hoarded;
+                total.melted += c.melted;
+            }
+            let (g, c) = (&coins[&gold], &coins[&copper]);
+            println!(
+                "{:5} | {:8} | {:18} | {:18} | {:12} | {:12} | {:14}",
+                seed,
+                if law.is_some() { "in force" } else { "none" },
+                format!("{} in {} trades", g.spent, g.trades),
+                format!("{} in {} trades", c.spent, c.trades),
+                format!("{} of {}", g.hoarded, g.minted),
+                format!("{} of {}", g.melted, g.minted),
+                format!("{} of {}", c.hoarded, c.minted)
+            );
+        }
+    }
+    println!();
+    for (n, law) in ["without the law", "with the law"].iter().enumerate() {
+        let (g, c) = (&totals[n][&gold], &totals[n][&copper]);
+        let percent = |part: Quantity, whole: Quantity| {
+            if whole.is_zero() {
+                0.0
+            } else {
+                100.0 * part.as_f64() / whole.as_f64()
+            }
+        };
+        println!(
+            "- {}, gold was {:.0}% of the coin spent; {:.0}% of the gold was hoarded or \
+             melted down, and {:.0}% of the copper hoarded",
+            law,
+            percent(g.spent, g.spent + c.spent),
+            percent(g.hoarded + g.melted, g.minted),
+            percent(c.hoarded, c.minted)
+        );
+    }
+    let circulates = |n: usize| {
+        if totals[n][&gold].spent >= totals[n][&copper].spent {
+            "gold"
+        } else {
+            "copper"
+        }
+    };
+    println!(
+        "- {} circulated without the law, and {} with it",
+        circulates(0),
+        circulates(1)
+    );
+}
@@ -0,0 +1,208 @@
+//! Commodity money, emerging on its own. A world of traders who each hold
+//! one good and want another (see `examples/data/money`) can't get far by
+//! barter alone, since hardly anyone holds what the person they'd trade with
+//! wants. Traders who see a good accepted more often than their own take it
+//! in trade even though they've no use for it, to trade it on (see
+//! `ActorRef::would_take_as_medium`), which makes it more saleable still,
+//! until everyone takes it: it's become money.
+//!
+//! This runs the scenario over a number of seeds, each of which deals the
+//! traders' wants out differently, and reports which good came out most
+//! saleable in each, and how often each good did over all of them.
+//!
+//! ```text
+//! cargo run --example money [seeds] [ticks]
+//! ```
+
+use microeconomics::{
+    Actor, GoalRegistry, GoalTemplate, ItemId, ItemRegistry, Quantity, SimEvent, Simulation,
+};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+
+const ITEMS: &str = include_str!("data/money/items.toml");
+const TRADERS: &str = include_str!("data/money/traders.toml");
+
+/// The traders file.
+#[derive(Deserialize)]
+struct Traders {
+    trader: Vec<TraderDef>,
+}
+
+/// So many traders alike.
+#[derive(Deserialize)]
+struct TraderDef {
+    /// How many of them there are
+    count: u32,
+    /// What each of them starts out with, by name
+    holds: BTreeMap<String, Quantity>,
+    /// The goods each of them might want, by name (one, picked at random)
+    wants: Vec<String>,
+}
+
+/// How one run of the scenario came out.
+struct Outcome {
+    /// The good that went in the most trades, if anything traded at all,
+    /// and what share of the trades it went in
+    money: Option<(ItemId, f64)>,
+    /// How many times each good was taken as a medium of exchange
+    media: BTreeMap<ItemId, usize>,
+    /// How many traders got what they wanted
+    satisfied: usize,
+}
+
+/// Set up the scenario from seed `seed`.
+fn world(seed: u64, traders: &Traders) -> Simulation {
+    let mut sim = Simulation::with_seed(seed);
+    sim.set_items(ItemRegistry::from_toml(ITEMS).expect("bad scenario items"));
+    // Everyone can want any good, or want to keep it
+    let goals: String = sim
+        .items()
+        .iter()
+        .map(|(_, def)| {
+            format!(
+                "[[goal]]\nname = \"Want{name}\"\nunits_required = 1\n\
+                 [[goal]]\nname = \"Keep{name}\"\nunits_required = 5\n",
+                name = def.name
+            )
+        })
+        .collect();
+    sim.set_goals(GoalRegistry::from_toml(&goals).expect("bad scenario goals"));
+    let mut n = 0;
+    for def in traders.trader.iter() {
+        for _ in 0..def.count {
+            let want = def.wants.choose(sim.rng()).expect("trader wants nothing");
+            let wanted = sim.items().id(want).expect("unknown good wanted");
+            let mut builder = Actor::builder().name(format!("Trader#{}", n)).template(
+                &GoalTemplate::once(
+                    sim.goals().id(&format!("Want{}", want)).unwrap(),
+                    Quantity::ONE,
+                    vec![wanted],
+                ),
+                sim.rng(),
+            );
+            for (name, &amount) in def.holds.iter() {
+                let held = sim.items().id(name).expect("unknown good held");
+                builder = builder
+                    .template(
+                        &GoalTemplate::once(
+                            sim.goals().id(&format!("Keep{}", name)).unwrap(),
+                            Quantity::units(5),
+                            vec![held],
+                        ),
+                        sim.rng(),
+                    )
+                    .starting_amount(held, amount);
+            }
+            sim.add_actor(builder.build().expect("bad trader"));
+            n += 1;
+        }
+    }
+    sim
+}
+
+/// Run the scenario from seed `seed` for `ticks` ticks.
+fn run(seed: u64, ticks: u64, traders: &Traders) -> Outcome {
+    let mut sim = world(seed, traders);
+    let mut media = BTreeMap::new();
+    let mut accepted: BTreeMap<ItemId, usize> = BTreeMap::new();
+    let (mut trades, mut satisfied) = (0, 0);
+    for _ in 0..ticks {
+        for event in sim.tick() {
+            match event {
+                SimEvent::MediumAccepted { item, .. } => *media.entry(item).or_insert(0) += 1,
+                SimEvent::TradeCompleted { gave, received, .. } => {
+                    trades += 1;
+                    for (item, _) in gave.iter().chain(received.iter()) {
+                        *accepted.entry(item).or_insert(0) += 1;
+                    }
+                }
+                SimEvent::GoalSatisfied { goal, .. }
+                    if sim.goals().name(goal).starts_with("Want") =>
+                {
+                    satisfied += 1
+                }
+                _ => {}
+            }
+        }
+    }
+    // Everyone sees every trade, so they all agree on this (see
+    // `ValueScale::saleability`)
+    let money = accepted
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(item, count)| (item, count as f64 / trades as f64));
+    Outcome {
+        money,
+        media,
+        satisfied,
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let seeds: u64 = args
+        .next()
+        .map_or(10, |s| s.parse().expect("bad seed count"));
+    let ticks: u64 = args
+        .next()
+        .map_or(60, |t| t.parse().expect("bad tick count"));
+    let traders: Traders = toml::from_str(TRADERS).expect("bad scenario traders");
+    let items = ItemRegistry::from_toml(ITEMS).expect("bad scenario items");
+    let population: u32 = traders.trader.iter().map(|def| def.count).sum();
+
+    println!("{} traders, {} ticks, {} seeds", population, ticks, seeds);
+    println!();
+    println!(
+        "{:5} | {:25} | {:45} | {:10}",
+        "Seed", "Most Saleable", "Taken As A Medium", "Satisfied"
+    );
+    println!(
+        "{}-+-{}-+-{}-+-{}",
+        "-".repeat(5),
+        "-".repeat(25),
+        "-".repeat(45),
+        "-".repeat(10)
+    );
+    let mut tally: BTreeMap<ItemId, u64> = BTreeMap::new();
+    for seed in 0..seeds {
+        let outcome = run(seed, ticks, &traders);
+        let money = match outcome.money {
+            Some((item, share)) => {
+                *tally.entry(item).or_insert(0) += 1;
+                format!("{} ({:.0}% of trades)", items.name(item), 100.0 * share)
+            }
+            None => "nothing traded".to_string(),
+        };
+        let media: Vec<String> = outcome
+            .media
+            .iter()
+            .map(|(&item, count)| format!("{} x{}", items.name(item), count))
+            .collect();
+        println!(
+            "{:5} | {:25} | {:45} | {:10}",
+            seed,
+            money,
+            if media.is_empty() {
+                "nothing".to_string()
+            } else {
+                media.join(", ")
+            },
+            format!("{} of {}", outcome.satisfied, population)
+        );
+    }
+    println!();
+    let mut tally: Vec<_> = tally.into_iter().collect();
+    tally.sort_by(|(a, a_seeds), (b, b_seeds)| b_seeds.cmp(a_seeds).then(a.cmp(b)));
+    for (item, count) in tally {
+        println!(
+            "- {} came out most saleable in {} of {} seeds ({:.0}%)",
+            items.name(item),
+            count,
+            seeds,
+            100.0 * count as f64 / seeds as f64
+        );
+    }
+}
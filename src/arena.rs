@@ -0,0 +1,372 @@
+use crate::behavior::Behavior;
+use crate::credit::CreditBook;
+use crate::expectations::Expectations;
+use crate::items::inventory::Inventory;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::labor::Labor;
+use crate::ledger::SatisfactionLedger;
+use crate::ledger::{Accounts, TradeLedger};
+use crate::memory::TradeMemory;
+use crate::preference_list::{Actor, ActorMut, ActorRef, ActorState, ValueScale};
+use crate::technology::KnowHow;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Copyable handle to an actor living in an `ActorArena`. This is what actors
+/// use to refer to each other (in `ActorState`, events, etc) instead of names,
+/// so nothing ever has to parse `Actor#3` to figure out who `Actor#3` is.
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct ActorId(pub usize);
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Storage for every actor in a world. Actors are never removed, so an
+/// `ActorId` handed out by the arena stays valid for the arena's lifetime.
+///
+/// Actors aren't stored as whole structs: each of their components (names,
+/// value scales, inventories, states, behaviors) is kept in its own column,
+/// indexed by `ActorId`. That way batch questions about the whole world (how
+/// many `FoodUnit`s are there? who's trading?) only have to walk the one
+/// column they care about, and trades can change two actors' inventories at
+/// once without borrowing either actor as a whole. Use `get` and `get_mut` to
+/// work with one actor's components together.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ActorArena {
+    /// Actor names, for printouts
+    names: Vec<String>,
+    /// How each actor values things
+    values: Vec<ValueScale>,
+    /// Items each actor holds
+    inventories: Vec<Inventory>,
+    /// What each actor is in the middle of
+    states: Vec<ActorState>,
+    /// Each actor's hours of labor
+    labor: Vec<Labor>,
+    /// Each actor's history of seeing to its goals
+    #[serde(default)]
+    ledgers: Vec<SatisfactionLedger>,
+    /// Each actor's outstanding debts
+    #[serde(default)]
+    credit: Vec<CreditBook>,
+    /// Who each actor remembers trading with
+    #[serde(default)]
+    memory: Vec<TradeMemory>,
+    /// Every bid and trade each actor has been part of
+    #[serde(default)]
+    trades: Vec<TradeLedger>,
+    /// What each actor's given up and got, for working out its profit and
+    /// loss
+    #[serde(default)]
+    accounts: Vec<Accounts>,
+    /// The recipes each actor has found out about that not everyone knows
+    #[serde(default)]
+    know_how: Vec<KnowHow>,
+    /// What each actor expects goods to go for
+    #[serde(default)]
+    expectations: Vec<Expectations>,
+    /// Each actor's decision rule. This is only ever `None` while the
+    /// behavior itself is busy deciding.
+    #[serde(with = "saved_behaviors")]
+    behaviors: Vec<Option<Box<dyn Behavior>>>,
+    /// Reverse lookup from actor names to their IDs
+    by_name: HashMap<String, ActorId>,
+    /// The tick being run (or last run), so that whatever actors get can be
+    /// stamped with when they got it
+    #[serde(default)]
+    now: u64,
+}
+
+impl ActorArena {
+    /// Construct an empty arena.
+    pub fn new() -> Self {
+        ActorArena::default()
+    }
+
+    /// Moves an actor into the arena, splitting it up into its components and
+    /// assigning it a fresh ID.
+    pub fn insert(&mut self, actor: Actor) -> ActorId {
+        let id = ActorId(self.names.len());
+        self.by_name.insert(actor.name.clone(), id);
+        self.names.push(actor.name);
+        self.values.push(actor.values);
+        self.inventories.push(actor.inventory);
+        self.states.push(actor.state);
+        self.labor.push(actor.labor);
+        self.ledgers.push(actor.ledger);
+        self.credit.push(actor.credit);
+        self.memory.push(actor.memory);
+        self.trades.push(actor.trades);
+        self.accounts.push(actor.accounts);
+        self.know_how.push(actor.know_how);
+        self.expectations.push(actor.expectations);
+        self.behaviors.push(Some(actor.behavior));
+        id
+    }
+
+    /// Set the tick that's being run.
+    pub fn set_now(&mut self, tick: u64) {
+        self.now = tick;
+    }
+
+    /// The tick being run (or last run).
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Look at an actor, if the ID is valid.
+    pub fn get(&self, id: ActorId) -> Option<ActorRef<'_>> {
+        if id.0 >= self.len() {
+            return None;
+        }
+        Some(ActorRef {
+            id,
+            name: &self.names[id.0],
+            values: &self.values[id.0],
+            inventory: &self.inventories[id.0],
+            state: &self.states[id.0],
+            labor: &self.labor[id.0],
+            ledger: &self.ledgers[id.0],
+            credit: &self.credit[id.0],
+            memory: &self.memory[id.0],
+            trades: &self.trades[id.0],
+            accounts: &self.accounts[id.0],
+            know_how: &self.know_how[id.0],
+            expectations: &self.expectations[id.0],
+        })
+    }
+
+    /// Change an actor, if the ID is valid.
+    pub fn get_mut(&mut self, id: ActorId) -> Option<ActorMut<'_>> {
+        if id.0 >= self.len() {
+            return None;
+        }
+        Some(ActorMut {
+            id,
+            name: &self.names[id.0],
+            values: &mut self.values[id.0],
+            inventory: &mut self.inventories[id.0],
+            state: &mut self.states[id.0],
+            labor: &mut self.labor[id.0],
+            ledger: &mut self.ledgers[id.0],
+            credit: &mut self.credit[id.0],
+            memory: &mut self.memory[id.0],
+            trades: &mut self.trades[id.0],
+            accounts: &mut self.accounts[id.0],
+            know_how: &mut self.know_how[id.0],
+            expectations: &mut self.expectations[id.0],
+            now: self.now,
+        })
+    }
+
+    /// Change two different actors at once, for instance both sides of a
+    /// trade. Returns `None` if either ID is invalid or they're the same actor.
+    pub fn pair_mut(&mut self, a: ActorId, b: ActorId) -> Option<(ActorMut<'_>, ActorMut<'_>)> {
+        if a == b || a.0 >= self.len() || b.0 >= self.len() {
+            return None;
+        }
+        let (values_a, values_b) = two_mut(&mut self.values, a.0, b.0);
+        let (inventory_a, inventory_b) = two_mut(&mut self.inventories, a.0, b.0);
+        let (state_a, state_b) = two_mut(&mut self.states, a.0, b.0);
+        let (labor_a, labor_b) = two_mut(&mut self.labor, a.0, b.0);
+        let (ledger_a, ledger_b) = two_mut(&mut self.ledgers, a.0, b.0);
+        let (credit_a, credit_b) = two_mut(&mut self.credit, a.0, b.0);
+        let (memory_a, memory_b) = two_mut(&mut self.memory, a.0, b.0);
+        let (trades_a, trades_b) = two_mut(&mut self.trades, a.0, b.0);
+        let (accounts_a, accounts_b) = two_mut(&mut self.accounts, a.0, b.0);
+        let (know_how_a, know_how_b) = two_mut(&mut self.know_how, a.0, b.0);
+        let (expectations_a, expectations_b) = two_mut(&mut self.expectations, a.0, b.0);
+        Some((
+            ActorMut {
+                id: a,
+                name: &self.names[a.0],
+                values: values_a,
+                inventory: inventory_a,
+                state: state_a,
+                labor: labor_a,
+                ledger: ledger_a,
+                credit: credit_a,
+                memory: memory_a,
+                trades: trades_a,
+                accounts: accounts_a,
+                know_how: know_how_a,
+                expectations: expectations_a,
+                now: self.now,
+            },
+            ActorMut {
+                id: b,
+                name: &self.names[b.0],
+                values: values_b,
+                inventory: inventory_b,
+                state: state_b,
+                labor: labor_b,
+                ledger: ledger_b,
+                credit: credit_b,
+                memory: memory_b,
+                trades: trades_b,
+                accounts: accounts_b,
+                know_how: know_how_b,
+                expectations: expectations_b,
+                now: self.now,
+            },
+        ))
+    }
+
+    /// Take an actor's behavior out so it can decide without the arena being
+    /// borrowed. Put it back with `put_behavior`.
+    pub(crate) fn take_behavior(&mut self, id: ActorId) -> Box<dyn Behavior> {
+        self.behaviors[id.0]
+            .take()
+            .expect("actor's behavior is already busy deciding")
+    }
+
+    /// Look at an actor's behavior (which mustn't be busy deciding).
+    pub(crate) fn behavior(&self, id: ActorId) -> &dyn Behavior {
+        self.behaviors[id.0]
+            .as_deref()
+            .expect("actor's behavior is busy deciding")
+    }
+
+    /// Give an actor back the behavior taken out with `take_behavior`.
+    pub(crate) fn put_behavior(&mut self, id: ActorId, behavior: Box<dyn Behavior>) {
+        self.behaviors[id.0] = Some(behavior);
+    }
+
+    /// Find an actor either by its name (`Actor#3`) or by its raw ID (`3` or
+    /// `#3`).
+    pub fn resolve(&self, name_or_id: &str) -> Option<ActorId> {
+        self.by_name.get(name_or_id).copied().or_else(|| {
+            name_or_id
+                .trim_start_matches('#')
+                .parse::<usize>()
+                .ok()
+                .map(ActorId)
+                .filter(|id| id.0 < self.len())
+        })
+    }
+
+    /// Iterate over every actor in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = ActorRef<'_>> {
+        self.ids().map(move |id| self.get(id).unwrap())
+    }
+
+    /// All the IDs currently in use, in order.
+    pub fn ids(&self) -> impl Iterator<Item = ActorId> {
+        (0..self.len()).map(ActorId)
+    }
+
+    /// Every actor's name, indexed by ID
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Every actor's value scale, indexed by ID
+    pub fn values(&self) -> &[ValueScale] {
+        &self.values
+    }
+
+    /// Every actor's inventory, indexed by ID
+    pub fn inventories(&self) -> &[Inventory] {
+        &self.inventories
+    }
+
+    /// Every actor's state, indexed by ID
+    pub fn states(&self) -> &[ActorState] {
+        &self.states
+    }
+
+    /// How much of an item there is in the whole world.
+    pub fn count_item(&self, item: ItemId) -> Quantity {
+        self.inventories.iter().map(|inv| inv.count(item)).sum()
+    }
+
+    /// Number of actors in the arena
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Mutable references to two different elements of one column.
+fn two_mut<T>(column: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    if a < b {
+        let (left, right) = column.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = column.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
+
+/// Behaviors are saved as they save themselves (see `Behavior::save`), and
+/// any that can't be come back as `DefaultBehavior`. Arenas saved before
+/// behaviors could be only recorded how many there were, so all of those
+/// come back as `DefaultBehavior` too.
+mod saved_behaviors {
+    use crate::behavior::{Behavior, DefaultBehavior, SavedBehavior};
+    use serde::de::{SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    /// Reads behaviors saved either way: each one as it's saved, or just
+    /// how many there are.
+    struct Saved;
+
+    impl<'de> Visitor<'de> for Saved {
+        type Value = Vec<Option<SavedBehavior>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a list of saved behaviors, or how many there are")
+        }
+
+        fn visit_u64<E>(self, count: u64) -> Result<Self::Value, E> {
+            Ok((0..count).map(|_| None).collect())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut saved = vec![];
+            while let Some(behavior) = seq.next_element()? {
+                saved.push(behavior);
+            }
+            Ok(saved)
+        }
+    }
+
+    #[allow(clippy::ptr_arg)]
+    pub fn serialize<S: Serializer>(
+        behaviors: &Vec<Option<Box<dyn Behavior>>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            behaviors
+                .iter()
+                .map(|behavior| behavior.as_ref().and_then(|b| b.save())),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Option<Box<dyn Behavior>>>, D::Error> {
+        Ok(deserializer
+            .deserialize_any(Saved)?
+            .into_iter()
+            .map(|saved| {
+                Some(saved.map_or_else(
+                    || Box::new(DefaultBehavior) as Box<dyn Behavior>,
+                    SavedBehavior::restore,
+                ))
+            })
+            .collect())
+    }
+}
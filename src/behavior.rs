@@ -0,0 +1,947 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::credit::{self, BankBook};
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::inventory::Inventory;
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use crate::preference_list::{ActorRef, ActorState};
+use crate::prices::PriceHistory;
+use crate::resources::{ResourceId, ResourceNode};
+use crate::simulation::SimRng;
+use crate::trade::accepts;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Everything an actor gets to look at when it's deciding what to do with its
+/// tick. The actor making the decision is `me`; everyone else can be looked
+/// up through `actors`.
+pub struct WorldView<'a> {
+    /// The actor doing the deciding
+    pub me: ActorRef<'a>,
+    /// Every actor in the world (including `me`), as it was at the start of
+    /// the tick
+    pub actors: &'a ActorArena,
+    /// Public goods anyone can use (see `Simulation::commons`)
+    pub commons: &'a Inventory,
+    /// Every kind of item in the world, and the recipes for making them
+    pub items: &'a ItemRegistry,
+    /// Resource nodes anyone can gather from (see `ResourceNode`)
+    pub resources: &'a [ResourceNode],
+    /// Every exchange ratio there's been so far, for behaviors that go by
+    /// what things have been going for
+    pub prices: &'a PriceHistory,
+    /// The simulation's random number generator, for behaviors that make
+    /// random choices (use this rather than `thread_rng` so runs are
+    /// reproducible)
+    pub rng: &'a mut SimRng,
+}
+
+impl<'a> WorldView<'a> {
+    /// Look at another actor, if it exists.
+    pub fn actor(&self, id: ActorId) -> Option<ActorRef<'a>> {
+        self.actors.get(id)
+    }
+}
+
+/// The one thing an actor has decided to do with its tick. Carrying these out
+/// is handled by the simulation once everyone has decided (see
+/// `Simulation::tick`), so behaviors only ever have to make choices, never
+/// touch any state.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Action {
+    /// There are no goals in play, so there's nothing to do
+    Idle,
+    /// Use an item from the inventory towards a goal
+    Use { goal: GoalId, item: ItemId },
+    /// Use a public good from the commons towards a goal
+    UsePublic { goal: GoalId, item: ItemId },
+    /// Spend the tick resting instead of working, putting the labor that
+    /// would have gone into it towards a goal (see `ActorMut::rest`)
+    Rest { goal: GoalId },
+    /// Start (or keep) trading for something that can satisfy a goal
+    Trade { goal: GoalId },
+    /// Spend the tick gathering something from a resource node that can
+    /// satisfy a goal, or that the actor needs to make something that can
+    /// (see `ActorMut::gather`)
+    Gather { goal: GoalId, node: ResourceId },
+    /// Spend some of the tick maintaining a worn tool, so it lasts longer
+    /// (see `ActorRef::tool_to_maintain`), while going after a goal
+    Maintain { goal: GoalId, tool: ItemId },
+    /// Spend the tick experimenting with what the actor has, to find out how
+    /// to make something that can satisfy a goal by a recipe it doesn't know
+    /// yet (see `ActorMut::experiment`)
+    Experiment { goal: GoalId, recipe: RecipeId },
+    /// Start (or keep) making something that can satisfy a goal out of what
+    /// the actor has, by a recipe (see `ActorMut::produce`)
+    Produce { goal: GoalId, recipe: RecipeId },
+    /// There's a goal in play, but nothing to do about it this tick (either
+    /// because the actor has nothing to trade, or because someone else is in
+    /// the middle of bidding on its items)
+    Wait { goal: GoalId },
+    /// Buy a unit of an item the actor has no use for itself from `from`,
+    /// paying a unit of `pay`, to sell on later (see `Middleman`)
+    Buy {
+        item: ItemId,
+        from: ActorId,
+        pay: ItemId,
+    },
+    /// Sell a unit of an item the actor has no use for itself to `to`, for
+    /// a unit of `price`. `bought_with` is what the actor paid for it, if it
+    /// bought it to sell on.
+    Sell {
+        item: ItemId,
+        to: ActorId,
+        price: ItemId,
+        bought_with: Option<ItemId>,
+    },
+    /// Swap `give_amount` of `give` for `take_amount` of `take` with
+    /// `partner`, to make something on the difference between what they go
+    /// for with different partners (see `Arbitrageur`)
+    Arbitrage {
+        partner: ActorId,
+        give: ItemId,
+        give_amount: Quantity,
+        take: ItemId,
+        take_amount: Quantity,
+    },
+    /// Give a unit of an item the actor has no use for to `to`, who wants
+    /// it, for nothing at all (see `ValueScale::charitable`)
+    Give { item: ItemId, to: ActorId },
+    /// Take `amount` of `money` from `from` on deposit, promising to give it
+    /// back later (see `Bank`)
+    TakeDeposit {
+        from: ActorId,
+        money: ItemId,
+        amount: Quantity,
+    },
+    /// Lend `amount` of `money` to `to`, to be paid back later with
+    /// `interest` a tick (see `Bank`)
+    Lend {
+        to: ActorId,
+        money: ItemId,
+        amount: Quantity,
+        interest: Quantity,
+    },
+}
+
+impl Action {
+    /// The goal the action goes after, if there's one in play.
+    pub fn goal(&self) -> Option<GoalId> {
+        match *self {
+            Action::Idle
+            | Action::Buy { .. }
+            | Action::Sell { .. }
+            | Action::Arbitrage { .. }
+            | Action::Give { .. }
+            | Action::TakeDeposit { .. }
+            | Action::Lend { .. } => None,
+            Action::Use { goal, .. }
+            | Action::UsePublic { goal, .. }
+            | Action::Rest { goal }
+            | Action::Trade { goal }
+            | Action::Produce { goal, .. }
+            | Action::Gather { goal, .. }
+            | Action::Maintain { goal, .. }
+            | Action::Experiment { goal, .. }
+            | Action::Wait { goal } => Some(goal),
+        }
+    }
+}
+
+/// A decision rule for an actor. Each actor owns one, so different actors in
+/// the same world can follow completely different rules. Behaviors have to be
+/// `Send + Sync` because actors may make their decisions on different threads
+/// (see `Simulation::set_parallel`).
+pub trait Behavior: Send + Sync {
+    /// Choose what to do this tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `view` - the deciding actor and the world around it
+    ///
+    fn decide(&mut self, view: WorldView) -> Action;
+
+    /// Whether the actor should be woken up every tick, even when it's got
+    /// nothing in play, so it can keep an eye on everyone else. Most actors
+    /// only need waking when something happens to them.
+    fn stays_awake(&self) -> bool {
+        false
+    }
+
+    /// The behavior as it's saved along with its actor, if it can be.
+    /// Behaviors that can't be (anything that isn't built in) come back as
+    /// `DefaultBehavior` when the actor's loaded again.
+    fn save(&self) -> Option<SavedBehavior> {
+        None
+    }
+}
+
+/// One of the built-in behaviors, along with whatever it's kept track of so
+/// far, as it's saved along with its actor (see `Behavior::save`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SavedBehavior {
+    Default,
+    Middleman(Middleman),
+    Arbitrageur(Arbitrageur),
+    Bank(Bank),
+}
+
+impl SavedBehavior {
+    /// The behavior back again, just as it was saved.
+    pub fn restore(self) -> Box<dyn Behavior> {
+        match self {
+            SavedBehavior::Default => Box::new(DefaultBehavior),
+            SavedBehavior::Middleman(middleman) => Box::new(middleman),
+            SavedBehavior::Arbitrageur(arbitrageur) => Box::new(arbitrageur),
+            SavedBehavior::Bank(bank) => Box::new(bank),
+        }
+    }
+}
+
+/// Serializes an actor's behavior as it's saved (see `Behavior::save`), so
+/// it can be restored when deserializing. Use with `#[serde(with =
+/// "crate::behavior::saved")]`.
+pub mod saved {
+    use super::{Behavior, DefaultBehavior, SavedBehavior};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        behavior: &Box<dyn Behavior>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        behavior.save().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn Behavior>, D::Error> {
+        Ok(
+            Option::<SavedBehavior>::deserialize(deserializer)?.map_or_else(
+                || Box::new(DefaultBehavior) as Box<dyn Behavior>,
+                SavedBehavior::restore,
+            ),
+        )
+    }
+}
+
+/// The standard actor AI (see README section 2.1.3): go after the
+/// highest-valued goal in play, using a public good for it if there's one
+/// around (since that costs nothing), an item of its own if not, making one
+/// out of what it has if it knows how, and trading for one otherwise. If
+/// there's one to be had from a resource node (or something it needs to
+/// make one), gathering it comes before making or trading for it. It sees
+/// to any tool that's wearing out before going out to do any of that, if it
+/// thinks the upkeep's worth it. If it doesn't know how to make anything
+/// for the goal, but has what some recipe nobody's shown it yet takes, it
+/// spends the tick experimenting with that before trading (see
+/// `ActorRef::recipe_to_discover`). An actor that specializes (see
+/// `ValueScale::specializes`) would rather make something else and trade it
+/// for what it's after than make that itself, if it expects that to get it
+/// more, and trades away whatever it's made of that before making more.
+/// Leisure can also be had just by resting, which beats spending the tick
+/// working to trade for it. Once it's started making something, it sees it
+/// through before doing anything else.
+///
+/// An actor that weighs its leisure (see `ValueScale::weighs_leisure`) makes
+/// the choice between work and time off every tick: it takes its leisure by
+/// resting rather than by using things up, and takes a tick off anything it's
+/// making for a goal it ranks below its leisure, coming back to it once it's
+/// rested.
+///
+/// A charitable actor (see `ValueScale::charitable`) with no goals in play
+/// gives away anything it has no use for to whoever's after it. One that
+/// stockpiles (see `ValueScale::stockpiles`) trades for what its recurring
+/// goals will need next time they come around instead, if it looks cheap.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultBehavior;
+
+impl DefaultBehavior {
+    /// Someone free who wants a unit of something the actor has no use for
+    /// at all (its least valued first), and has room for it.
+    fn find_gift(me: ActorRef, actors: &ActorArena) -> Option<Action> {
+        let unit = |item| Bundle::single(item, Quantity::ONE);
+        let surplus = me.ordered_inventory().into_iter().filter(|&item| {
+            me.inventory.count(item) >= Quantity::ONE && !me.values.has_use_for(item)
+        });
+        for item in surplus {
+            let to = actors.iter().find(|other| {
+                other.id != me.id
+                    && is_free(other)
+                    && other
+                        .values
+                        .top_goal()
+                        .is_some_and(|goal| other.wanted_for(goal).contains(&item))
+                    && other.can_fit(&unit(item), &Bundle::new())
+            });
+            if let Some(to) = to {
+                return Some(Action::Give { item, to: to.id });
+            }
+        }
+        None
+    }
+}
+
+impl Behavior for DefaultBehavior {
+    fn decide(&mut self, view: WorldView) -> Action {
+        let me = view.me;
+        let now = view.actors.now();
+        // The leisure an hour of work would cost, if the actor's counting
+        let leisure = me
+            .values
+            .leisure_in_play()
+            .filter(|_| me.values.weighs_leisure && !me.labor.left(now).is_zero());
+        if let ActorState::Producing(recipe, goal, _) = *me.state {
+            return match leisure {
+                // Not worth working on today
+                Some(leisure) if me.values.rank(leisure) < me.values.rank(goal) => {
+                    Action::Rest { goal: leisure }
+                }
+                _ => Action::Produce { goal, recipe },
+            };
+        }
+        // Get the highest-valued goal of the ones that are in play
+        let goal = match me.values.top_goal() {
+            Some(goal) => goal,
+            None => match me.stockpile_goal() {
+                // Nothing needs seeing to now, so get ahead on what will
+                Some(goal) if !matches!(me.state, ActorState::BidRecipiant(..)) => {
+                    return Action::Trade { goal }
+                }
+                _ if me.values.charitable && *me.state == ActorState::SearchingForGoal => {
+                    return Self::find_gift(me, view.actors).unwrap_or(Action::Idle)
+                }
+                _ => return Action::Idle,
+            },
+        };
+        match me.state {
+            ActorState::SearchingForGoal => {
+                // ...try to find all of the items that *might* be able to satisfy this goal
+
+                // (Anything saved for a more valued goal that's coming back
+                // around won't turn up, see `ActorRef::savings`)
+                if me.is_served(goal) {
+                    // Something we've already got in use is seeing to it
+                    Action::Wait { goal }
+                } else if let Some(item) = me.find_public_good(goal, view.commons) {
+                    // Free for the taking, so no need to use up our own
+                    Action::UsePublic { goal, item }
+                } else if leisure == Some(goal) {
+                    // Time off is had by taking it, not by using things up
+                    Action::Rest { goal }
+                } else if let Some(&item) = me.find_item_for_goal(goal).last() {
+                    Action::Use { goal, item }
+                } else if me.values.is_leisure(goal) && !me.labor.left(now).is_zero() {
+                    Action::Rest { goal }
+                } else if let Some(tool) = me.tool_to_maintain(view.items, now) {
+                    Action::Maintain { goal, tool }
+                } else if let Some(node) =
+                    me.find_resource_for_goal(goal, view.resources, view.items, now)
+                {
+                    Action::Gather { goal, node }
+                } else if let Some(recipe) = me.recipe_to_trade_with(goal, view.items, view.actors)
+                {
+                    // Better to make something else and trade it for this,
+                    // once what's been made already is gone
+                    let stocked = view.items.recipe(recipe).is_some_and(|def| {
+                        def.outputs
+                            .iter()
+                            .any(|(item, _)| me.inventory.contains(item))
+                    });
+                    if stocked {
+                        Action::Trade { goal }
+                    } else {
+                        Action::Produce { goal, recipe }
+                    }
+                } else if let Some(recipe) = me.find_recipe_for_goal(goal, view.items) {
+                    Action::Produce { goal, recipe }
+                } else if let Some(recipe) = me.recipe_to_discover(goal, view.items, now) {
+                    Action::Experiment { goal, recipe }
+                } else if !me.inventory.is_empty() {
+                    // We need an item
+                    Action::Trade { goal }
+                } else {
+                    Action::Wait { goal }
+                }
+            }
+            ActorState::WillingToTrade(..)
+            | ActorState::FoundTradePartner(..)
+            | ActorState::Bidding(..) => Action::Trade { goal },
+            ActorState::BidRecipiant(..) => Action::Wait { goal },
+            ActorState::Producing(recipe, goal, _) => Action::Produce {
+                goal: *goal,
+                recipe: *recipe,
+            },
+        }
+    }
+
+    fn save(&self) -> Option<SavedBehavior> {
+        Some(SavedBehavior::Default)
+    }
+}
+
+/// How much of the demand a middleman has seen for an item it still counts on
+/// a tick later.
+const DEMAND_MEMORY: f64 = 0.8;
+
+/// A middleman (or market maker): an actor that buys things it has no use
+/// for, because it expects to be able to sell them on to someone who does
+/// for something it values more than what it paid. It sees to its own goals
+/// with what it's got like anyone else (see `DefaultBehavior`), but it would
+/// rather deal than go out and trade for them, so it only does that when
+/// there's no deal to be had.
+///
+/// Every tick it watches who's trading for what, and keeps a running count
+/// of how many actors it expects to want each item (its expectations,
+/// which fade if nobody's wanted something lately). It buys a unit of
+/// whatever's most in demand that it doesn't have in stock, from anyone
+/// who'll take something it values little for it, and sells what it has in
+/// stock to anyone who wants it, as soon as they'll give it something it
+/// values more than what it paid (so it only ever profits, in ordinal terms:
+/// it never sells at a loss, and might sit on stock for a while).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Middleman {
+    /// How many actors it expects to want each item
+    demand: BTreeMap<ItemId, Quantity>,
+    /// Items it has in stock to sell on, and what it paid for each (if it
+    /// bought it, rather than coming by it some other way)
+    stock: BTreeMap<ItemId, Option<ItemId>>,
+}
+
+impl Middleman {
+    /// How many actors the middleman expects to want an item, going by how
+    /// many it's seen trading for it lately.
+    pub fn expected_demand(&self, item: ItemId) -> Quantity {
+        self.demand.get(&item).copied().unwrap_or(Quantity::ZERO)
+    }
+
+    /// Items in stock to sell on, and what was paid for each (if anything).
+    pub fn stock(&self) -> &BTreeMap<ItemId, Option<ItemId>> {
+        &self.stock
+    }
+
+    /// Update its expectations with who's trading for what right now.
+    fn observe(&mut self, me: ActorId, actors: &ActorArena) {
+        let memory = Quantity::from(DEMAND_MEMORY);
+        for demand in self.demand.values_mut() {
+            *demand = demand.times(memory);
+        }
+        for other in actors.iter().filter(|other| other.id != me) {
+            let trading = matches!(
+                other.state,
+                ActorState::WillingToTrade(..)
+                    | ActorState::FoundTradePartner(..)
+                    | ActorState::Bidding(..)
+            );
+            if let (true, Some(goal)) = (trading, other.values.top_goal()) {
+                for item in other.wanted_for(goal) {
+                    *self.demand.entry(item).or_insert(Quantity::ZERO) += Quantity::ONE;
+                }
+            }
+        }
+    }
+
+    /// Someone free to trade who wants something in stock, and will give
+    /// something for it worth more to the middleman than what it paid.
+    fn find_sale(&self, me: ActorRef, actors: &ActorArena) -> Option<Action> {
+        for (&item, &bought_with) in self.stock.iter() {
+            let unit = Bundle::single(item, Quantity::ONE);
+            // Anything it didn't pay for just has to fetch more than it's
+            // worth to it
+            let paid = Bundle::single(bought_with.unwrap_or(item), Quantity::ONE);
+            for buyer in actors.iter().filter(|b| b.id != me.id && is_free(b)) {
+                let wants = buyer
+                    .values
+                    .top_goal()
+                    .is_some_and(|goal| buyer.wanted_for(goal).contains(&item));
+                if !wants {
+                    continue;
+                }
+                // Whatever it has that we'd like best
+                let price = me
+                    .values
+                    .order_inventory(buyer.inventory)
+                    .into_iter()
+                    .rev()
+                    .filter(|&price| price != item)
+                    .map(|price| Bundle::single(price, Quantity::ONE))
+                    .find(|price| {
+                        me.compare_bundles(price, &paid) == Ordering::Greater
+                            && me.can_fit(price, &unit)
+                            && accepts(buyer, &unit, price)
+                    });
+                if let Some((price, _)) = price.and_then(|p| p.first()) {
+                    return Some(Action::Sell {
+                        item,
+                        to: buyer.id,
+                        price,
+                        bought_with,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Someone free to trade who'll sell it the item it expects the most
+    /// demand for (of the ones it has no use for and hasn't got in stock),
+    /// for whatever it values least that they'll take.
+    fn find_purchase(&self, me: ActorRef, actors: &ActorArena) -> Option<Action> {
+        let mut wanted: Vec<(ItemId, Quantity)> = self
+            .demand
+            .iter()
+            .map(|(&item, &demand)| (item, demand))
+            .filter(|&(item, demand)| {
+                demand >= Quantity::ONE
+                    && !self.stock.contains_key(&item)
+                    && me.values.get_best_goal(item).is_none()
+            })
+            .collect();
+        wanted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let payments: Vec<ItemId> = me
+            .ordered_inventory()
+            .into_iter()
+            .filter(|pay| !matches!(self.stock.get(pay), Some(Some(_))))
+            .collect();
+        for (item, _) in wanted {
+            let unit = Bundle::single(item, Quantity::ONE);
+            for seller in actors.iter().filter(|s| s.id != me.id && is_free(s)) {
+                if seller.inventory.count(item) < Quantity::ONE {
+                    continue;
+                }
+                let pay = payments.iter().copied().find(|&pay| {
+                    let offer = Bundle::single(pay, Quantity::ONE);
+                    pay != item
+                        && me.inventory.count(pay) >= Quantity::ONE
+                        && me.can_fit(&unit, &offer)
+                        && accepts(seller, &offer, &unit)
+                });
+                if let Some(pay) = pay {
+                    return Some(Action::Buy {
+                        item,
+                        from: seller.id,
+                        pay,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Whether an actor isn't in the middle of negotiating anything.
+fn is_free(actor: &ActorRef) -> bool {
+    matches!(
+        actor.state,
+        ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+    )
+}
+
+impl Behavior for Middleman {
+    fn decide(&mut self, view: WorldView) -> Action {
+        let (me, actors) = (view.me, view.actors);
+        self.observe(me.id, actors);
+        // Anything we've sold (or never managed to buy) isn't in stock, but
+        // anything else we've come by that we've no use for is
+        self.stock
+            .retain(|&item, _| me.inventory.count(item) >= Quantity::ONE);
+        for (item, amount) in me.inventory.iter() {
+            if amount >= Quantity::ONE && me.values.get_best_goal(item).is_none() {
+                self.stock.entry(item).or_insert(None);
+            }
+        }
+        if *me.state != ActorState::SearchingForGoal {
+            return DefaultBehavior.decide(view);
+        }
+        // Selling is what it's all for, so that comes first
+        if let Some(sale) = self.find_sale(me, actors) {
+            return sale;
+        }
+        // Seeing to its own goals with what it's got comes before buying,
+        // but going out to trade for them can wait
+        let default = DefaultBehavior.decide(view);
+        if let Action::Use { .. }
+        | Action::UsePublic { .. }
+        | Action::Rest { .. }
+        | Action::Produce { .. }
+        | Action::Gather { .. }
+        | Action::Maintain { .. }
+        | Action::Experiment { .. } = default
+        {
+            return default;
+        }
+        match self.find_purchase(me, actors) {
+            Some(Action::Buy { item, from, pay }) => {
+                self.stock.insert(item, Some(pay));
+                Action::Buy { item, from, pay }
+            }
+            _ => default,
+        }
+    }
+
+    fn stays_awake(&self) -> bool {
+        true
+    }
+
+    fn save(&self) -> Option<SavedBehavior> {
+        Some(SavedBehavior::Middleman(self.clone()))
+    }
+}
+
+/// How many of the latest trades of a pair of goods an arbitrageur goes by
+/// to see what they've been going for.
+pub const ARBITRAGE_WINDOW: usize = 6;
+
+/// An arbitrageur: an actor that watches what goods have been going for (see
+/// `PriceHistory`), and when the same pair has been swapped at different
+/// ratios with different partners lately, buys where it's cheap and sells
+/// where it's dear, pocketing the difference. It sees to its own goals with
+/// what it's got like anyone else (see `DefaultBehavior`), but it would
+/// rather deal than go out and trade for them.
+///
+/// It doesn't go all the way to either end, though: it offers a quarter of
+/// the spread more than the cheapest ratio lately to buy, and asks a quarter
+/// of it less than the dearest to sell, which is what wins it the business.
+/// That's how arbitrage closes the spread: the trades at either end dry up,
+/// and the ones it makes in between are all there is to go by, so the next
+/// time round it buys and sells closer together still. It only ever sells
+/// for more than it paid, so it never makes a loss, but it might sit on
+/// stock for a while.
+///
+/// # Notes
+///
+/// Unlike everyone else, it goes by ratios rather than its own values: it
+/// only trades goods (and pays with goods) it has no use for itself, so all
+/// that matters to it is ending up with more of what it started with. It
+/// always buys a pair's base good (see `PricePoint`) with the other one,
+/// and never the other way round.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Arbitrageur {
+    /// Goods it's bought to sell on, and what it paid a unit for each: how
+    /// much of what
+    stock: BTreeMap<ItemId, (ItemId, Quantity)>,
+}
+
+impl Arbitrageur {
+    /// Goods bought to sell on, and what was paid a unit for each.
+    pub fn stock(&self) -> &BTreeMap<ItemId, (ItemId, Quantity)> {
+        &self.stock
+    }
+
+    /// The ratios it would buy and sell a pair of goods at, going by their
+    /// latest trades: a quarter of the way in from either end of what
+    /// they've gone for.
+    fn quotes(prices: &PriceHistory, base: ItemId, quote: ItemId) -> Option<(Quantity, Quantity)> {
+        let (low, high) = prices.range(base, quote, ARBITRAGE_WINDOW)?;
+        let quarter = (high - low).times(Quantity::from(0.25));
+        Some((low + quarter, high - quarter))
+    }
+
+    /// Someone free to trade who'll give it more for something in stock
+    /// than it paid for it.
+    fn find_sale(
+        &self,
+        me: ActorRef,
+        actors: &ActorArena,
+        prices: &PriceHistory,
+    ) -> Option<Action> {
+        for (&item, &(paid_in, paid)) in self.stock.iter() {
+            let sell = match Arbitrageur::quotes(prices, item, paid_in) {
+                Some((_, sell)) if sell > paid => sell,
+                _ => continue,
+            };
+            let unit = Bundle::single(item, Quantity::ONE);
+            let price = Bundle::single(paid_in, sell);
+            let buyer = actors.iter().find(|b| {
+                b.id != me.id
+                    && is_free(b)
+                    && b.inventory.count(paid_in) >= sell
+                    && me.can_fit(&price, &unit)
+                    && accepts(*b, &unit, &price)
+            });
+            if let Some(buyer) = buyer {
+                return Some(Action::Arbitrage {
+                    partner: buyer.id,
+                    give: item,
+                    give_amount: Quantity::ONE,
+                    take: paid_in,
+                    take_amount: sell,
+                });
+            }
+        }
+        None
+    }
+
+    /// Someone free to trade who'll sell it a unit of a good that's been
+    /// going for different ratios lately, at the cheap end, for another
+    /// good it has no use for.
+    fn find_purchase(
+        &self,
+        me: ActorRef,
+        actors: &ActorArena,
+        prices: &PriceHistory,
+    ) -> Option<Action> {
+        for (base, quote) in prices.pairs() {
+            let buy = match Arbitrageur::quotes(prices, base, quote) {
+                Some((buy, sell)) if buy < sell => buy,
+                _ => continue,
+            };
+            if self.stock.contains_key(&base)
+                || me.values.get_best_goal(base).is_some()
+                || me.values.get_best_goal(quote).is_some()
+                || me.inventory.count(quote) < buy
+            {
+                continue;
+            }
+            let unit = Bundle::single(base, Quantity::ONE);
+            let pay = Bundle::single(quote, buy);
+            let seller = actors.iter().find(|s| {
+                s.id != me.id
+                    && is_free(s)
+                    && s.inventory.count(base) >= Quantity::ONE
+                    && me.can_fit(&unit, &pay)
+                    && accepts(*s, &pay, &unit)
+            });
+            if let Some(seller) = seller {
+                return Some(Action::Arbitrage {
+                    partner: seller.id,
+                    give: quote,
+                    give_amount: buy,
+                    take: base,
+                    take_amount: Quantity::ONE,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl Behavior for Arbitrageur {
+    fn decide(&mut self, view: WorldView) -> Action {
+        let (me, actors, prices) = (view.me, view.actors, view.prices);
+        // Anything we've sold (or never managed to buy) isn't in stock
+        self.stock
+            .retain(|&item, _| me.inventory.count(item) >= Quantity::ONE);
+        if *me.state != ActorState::SearchingForGoal {
+            return DefaultBehavior.decide(view);
+        }
+        if let Some(sale) = self.find_sale(me, actors, prices) {
+            return sale;
+        }
+        // Seeing to its own goals with what it's got comes before buying,
+        // but going out to trade for them can wait
+        let default = DefaultBehavior.decide(view);
+        if let Action::Use { .. }
+        | Action::UsePublic { .. }
+        | Action::Rest { .. }
+        | Action::Produce { .. }
+        | Action::Gather { .. }
+        | Action::Maintain { .. }
+        | Action::Experiment { .. } = default
+        {
+            return default;
+        }
+        match self.find_purchase(me, actors, prices) {
+            Some(Action::Arbitrage {
+                partner,
+                give,
+                give_amount,
+                take,
+                take_amount,
+            }) => {
+                self.stock.insert(take, (give, give_amount));
+                Action::Arbitrage {
+                    partner,
+                    give,
+                    give_amount,
+                    take,
+                    take_amount,
+                }
+            }
+            _ => default,
+        }
+    }
+
+    fn stays_awake(&self) -> bool {
+        true
+    }
+
+    fn save(&self) -> Option<SavedBehavior> {
+        Some(SavedBehavior::Arbitrageur(self.clone()))
+    }
+}
+
+/// How much of what a bank owes its depositors it keeps in reserve, unless
+/// it's told otherwise (see `Bank::new`).
+pub const RESERVE_RATIO: f64 = 0.2;
+
+/// The least interest per tick a bank lends at, whatever its own time
+/// preference: it's in the business to make something on its loans.
+pub const LENDING_PREMIUM: f64 = 0.02;
+
+/// A bank: an actor that takes money on deposit from anyone with some to
+/// spare, promising it back in `LOAN_TERM` ticks (see `credit::deposit`),
+/// and lends it out again to anyone with a goal they're trading for and no
+/// debts, at interest of its time preference or `LENDING_PREMIUM`,
+/// whichever's more (see `credit::borrow_at`). It sees to its own goals with
+/// what it's got like anyone else (see `DefaultBehavior`), but it would
+/// rather do business than go out and trade for them.
+///
+/// It only lends out so much of what it holds: it keeps a fraction of what
+/// it owes its depositors in reserve (see `BankBook::lendable`), to pay
+/// back deposits as they come due. That's fractional reserve banking, and
+/// it works as long as its loans are paid back; every loan that goes bad
+/// eats into its reserves, and once it owes its depositors more than it
+/// holds and is owed put together, or can't pay a deposit back when it
+/// comes due, it's failed (see `BankBook::has_failed`). A failed bank stops
+/// taking deposits and lending for good, and its depositors get back
+/// whatever it has when their deposits come due.
+///
+/// # Notes
+///
+/// It deals in whichever money it knows of first (see `ValueScale::money`),
+/// and lends each borrower what the borrower expects what it's after to go
+/// for in it (see `Expectations::expected`), or a unit if it has no idea.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bank {
+    /// How much of what it owes its depositors it keeps in reserve
+    reserve_ratio: Quantity,
+    /// Whether it's failed
+    failed: bool,
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Bank::new(Quantity::from(RESERVE_RATIO))
+    }
+}
+
+impl Bank {
+    /// A bank keeping `reserve_ratio` of what it owes its depositors in
+    /// reserve.
+    pub fn new(reserve_ratio: Quantity) -> Self {
+        Bank {
+            reserve_ratio,
+            failed: false,
+        }
+    }
+
+    /// How much of what it owes its depositors it keeps in reserve.
+    pub fn reserve_ratio(&self) -> Quantity {
+        self.reserve_ratio
+    }
+
+    /// Whether it's failed.
+    pub fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Someone with a goal it's trading for, no debts, and no money on
+    /// deposit with the bank to draw on instead, who'd take a loan of what
+    /// it expects what it's after to go for, out of what the bank can lend.
+    fn find_borrower(&self, me: ActorRef, actors: &ActorArena, book: &BankBook) -> Option<Action> {
+        let money = book.money;
+        let lendable = book
+            .lendable(self.reserve_ratio)
+            .min(me.inventory.spare(money));
+        if lendable.is_zero() {
+            return None;
+        }
+        for borrower in actors.iter().filter(|b| b.id != me.id) {
+            let goal = match (borrower.state, borrower.values.top_goal()) {
+                (ActorState::WillingToTrade(_), Some(goal)) => goal,
+                _ => continue,
+            };
+            if book.deposits.iter().any(|iou| iou.creditor == borrower.id) {
+                continue;
+            }
+            let amount = borrower
+                .wanted_for(goal)
+                .into_iter()
+                .filter_map(|item| borrower.expectations.expected(item, money))
+                .min()
+                .unwrap_or(Quantity::ONE)
+                .min(lendable);
+            if !amount.is_zero() && credit::would_lend(me, borrower, &Bundle::single(money, amount))
+            {
+                return Some(Action::Lend {
+                    to: borrower.id,
+                    money,
+                    amount,
+                    interest: me
+                        .values
+                        .time_preference
+                        .max(Quantity::from(LENDING_PREMIUM)),
+                });
+            }
+        }
+        None
+    }
+
+    /// Someone free with money to spare and no debts to pay back with it,
+    /// who'd put half of it on deposit (see `credit::would_deposit`).
+    fn find_depositor(me: ActorRef, actors: &ActorArena, money: ItemId) -> Option<Action> {
+        actors
+            .iter()
+            .filter(|d| {
+                d.id != me.id
+                    && *d.state == ActorState::SearchingForGoal
+                    && d.credit.ious().iter().all(|iou| iou.debtor != d.id)
+            })
+            .find_map(|d| {
+                let amount = d.inventory.spare(money).divided_by(Quantity::units(2));
+                let deposit = Bundle::single(money, amount);
+                (credit::would_deposit(d, &deposit) && me.can_fit(&deposit, &Bundle::new()))
+                    .then_some(Action::TakeDeposit {
+                        from: d.id,
+                        money,
+                        amount,
+                    })
+            })
+    }
+}
+
+impl Behavior for Bank {
+    fn decide(&mut self, view: WorldView) -> Action {
+        let (me, actors) = (view.me, view.actors);
+        let money = match me.values.money.iter().min() {
+            Some(&money) => money,
+            None => return DefaultBehavior.decide(view),
+        };
+        if self.failed || *me.state != ActorState::SearchingForGoal {
+            return DefaultBehavior.decide(view);
+        }
+        let book = BankBook::of(me, actors, money);
+        if book.has_failed() {
+            self.failed = true;
+            return DefaultBehavior.decide(view);
+        }
+        // Seeing to its own goals with what it's got comes before business,
+        // but going out to trade for them can wait
+        let default = DefaultBehavior.decide(view);
+        if let Action::Use { .. }
+        | Action::UsePublic { .. }
+        | Action::Rest { .. }
+        | Action::Produce { .. }
+        | Action::Gather { .. }
+        | Action::Maintain { .. }
+        | Action::Experiment { .. } = default
+        {
+            return default;
+        }
+        self.find_borrower(me, actors, &book)
+            .or_else(|| Bank::find_depositor(me, actors, money))
+            .unwrap_or(default)
+    }
+
+    fn stays_awake(&self) -> bool {
+        true
+    }
+
+    fn save(&self) -> Option<SavedBehavior> {
+        Some(SavedBehavior::Bank(self.clone()))
+    }
+}
@@ -0,0 +1,423 @@
+use crate::behavior::{Behavior, DefaultBehavior};
+use crate::goals::{GoalId, GoalTemplate};
+use crate::items::inventory::Inventory;
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::labor::Labor;
+use crate::memory::TradeMemory;
+use crate::preference_list::{Actor, GoalData, HierarchyError};
+use crate::trade::TradeCost;
+use rand::Rng;
+use std::error::Error;
+use std::fmt;
+
+/// Why an `ActorBuilder` refused to build an actor.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum BuildError {
+    /// No name was given
+    MissingName,
+    /// The same goal was added to the hierarchy more than once
+    DuplicateGoal(GoalId),
+    /// Items were listed as satisfying (or complementing) a goal that isn't in
+    /// the hierarchy
+    UnknownGoal(GoalId),
+    /// The goal hierarchy isn't strictly ordinal
+    Hierarchy(HierarchyError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingName => write!(f, "actor has no name"),
+            BuildError::DuplicateGoal(goal) => {
+                write!(f, "goal {:?} appears in the hierarchy more than once", goal)
+            }
+            BuildError::UnknownGoal(goal) => write!(
+                f,
+                "items given for goal {:?}, which isn't in the hierarchy",
+                goal
+            ),
+            BuildError::Hierarchy(e) => write!(f, "invalid goal hierarchy: {}", e),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// Step-by-step construction of an `Actor`, which is a lot easier to read than
+/// `Actor::new`'s positional arguments when setting up big populations of
+/// actors that all differ a little. Goals are added most valued first.
+///
+/// ```
+/// use microeconomics::{Actor, GoalRegistry, ItemRegistry, Quantity};
+///
+/// let items = ItemRegistry::default_items();
+/// let goals = GoalRegistry::default_goals();
+/// let food = items.id("FoodUnit").unwrap();
+/// // Eating is marginal, so this is just the first meal
+/// let eat = goals.wants("Eat")[0];
+/// let actor = Actor::builder()
+///     .name("Crusoe")
+///     .goal(goals.goal_data(eat).unwrap())
+///     .satisfaction(eat, vec![food])
+///     .starting_item(food)
+///     .starting_amount(food, Quantity::from_f64(0.5))
+///     .build()
+///     .unwrap();
+/// assert_eq!(actor.inventory.count(food), Quantity::from_f64(1.5));
+/// ```
+pub struct ActorBuilder {
+    name: Option<String>,
+    hierarchy: Vec<GoalData>,
+    ties: Vec<(GoalId, GoalId)>,
+    satisfactions: Vec<(GoalId, Vec<ItemId>)>,
+    complements: Vec<(GoalId, Vec<ItemId>)>,
+    inventory: Inventory,
+    capacity: Option<Quantity>,
+    labor: Option<Quantity>,
+    patience: Option<u32>,
+    time_preference: Quantity,
+    trade_cost: Option<TradeCost>,
+    imperfect_information: bool,
+    charitable: bool,
+    walks_away: bool,
+    stockpiles: bool,
+    hires_labor: bool,
+    specializes: bool,
+    weighs_leisure: bool,
+    weighs_bundles: bool,
+    saves: bool,
+    watches_market: bool,
+    holds_out: Option<Quantity>,
+    recipes: Vec<RecipeId>,
+    behavior: Option<Box<dyn Behavior>>,
+}
+
+impl Default for ActorBuilder {
+    fn default() -> Self {
+        ActorBuilder::new()
+    }
+}
+
+impl ActorBuilder {
+    /// Start building an actor with nothing set.
+    pub fn new() -> Self {
+        ActorBuilder {
+            name: None,
+            hierarchy: vec![],
+            ties: vec![],
+            satisfactions: vec![],
+            complements: vec![],
+            inventory: Inventory::new(),
+            capacity: None,
+            labor: None,
+            patience: None,
+            time_preference: Quantity::ZERO,
+            trade_cost: None,
+            imperfect_information: false,
+            charitable: false,
+            walks_away: false,
+            stockpiles: false,
+            hires_labor: false,
+            specializes: false,
+            weighs_leisure: false,
+            weighs_bundles: false,
+            saves: false,
+            watches_market: false,
+            holds_out: None,
+            recipes: vec![],
+            behavior: None,
+        }
+    }
+
+    /// Set the actor's name.
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Add a goal to the bottom of the actor's hierarchy (so call this in
+    /// order from most to least valued).
+    pub fn goal(mut self, goal: GoalData) -> Self {
+        self.hierarchy.push(goal);
+        self
+    }
+
+    /// Add a goal to the bottom of the actor's hierarchy, valued exactly the
+    /// same as the goal added just before it (see
+    /// `ValueScale::make_indifferent`). If it's the first goal, it's just
+    /// added.
+    pub fn indifferent_goal(mut self, goal: GoalData) -> Self {
+        if let Some(last) = self.hierarchy.last() {
+            self.ties.push((goal.get_goal(), last.get_goal()));
+        }
+        self.hierarchy.push(goal);
+        self
+    }
+
+    /// Add this actor's own version of a goal template to the bottom of its
+    /// hierarchy, along with the items that satisfy it (see
+    /// `GoalTemplate::instantiate`).
+    pub fn template<R: Rng + ?Sized>(mut self, template: &GoalTemplate, rng: &mut R) -> Self {
+        let goal = template.instantiate(rng);
+        if !template.items.is_empty() {
+            self.satisfactions
+                .push((goal.get_goal(), template.items.clone()));
+        }
+        self.hierarchy.push(goal);
+        self
+    }
+
+    /// Say which items can be used to satisfy a goal. Calling this more than
+    /// once for the same goal adds to the list.
+    pub fn satisfaction(mut self, goal: GoalId, items: Vec<ItemId>) -> Self {
+        self.satisfactions.push((goal, items));
+        self
+    }
+
+    /// Say which items a goal needs *on top of* one of its satisfactions,
+    /// all at once (a cooked meal needs fuel as well as food). Calling this
+    /// more than once for the same goal adds to the list.
+    pub fn complement(mut self, goal: GoalId, items: Vec<ItemId>) -> Self {
+        self.complements.push((goal, items));
+        self
+    }
+
+    /// Give the actor an item to start out with.
+    pub fn starting_item(mut self, item: ItemId) -> Self {
+        self.inventory.add(item, Quantity::ONE);
+        self
+    }
+
+    /// Give the actor some amount of an item to start out with. The builder
+    /// doesn't know which items are divisible, so it's up to the caller not to
+    /// hand out fractions of ones that aren't.
+    pub fn starting_amount(mut self, item: ItemId, amount: Quantity) -> Self {
+        self.inventory.add(item, amount);
+        self
+    }
+
+    /// Give the actor several items to start out with.
+    pub fn starting_items<I: IntoIterator<Item = ItemId>>(mut self, items: I) -> Self {
+        self.inventory.extend(items);
+        self
+    }
+
+    /// Limit how much the actor can carry (by default there's no limit).
+    /// Starting items aren't held to it, though anything the actor takes on
+    /// after that is.
+    pub fn capacity(mut self, capacity: Quantity) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Set how many hours of labor the actor has every tick (by default
+    /// `Labor::HOURS_PER_TICK`).
+    pub fn labor(mut self, hours: Quantity) -> Self {
+        self.labor = Some(hours);
+        self
+    }
+
+    /// Have the actor give up on a goal after going after it for so many
+    /// ticks in a row without getting any closer (by default it never does).
+    pub fn patience(mut self, ticks: u32) -> Self {
+        self.patience = Some(ticks);
+        self
+    }
+
+    /// Set how much more of something the actor wants back per tick it waits
+    /// for it (see `ValueScale::time_preference`; by default nothing).
+    pub fn time_preference(mut self, rate: Quantity) -> Self {
+        self.time_preference = rate;
+        self
+    }
+
+    /// Set what it costs the actor to make a trade, on top of whatever it
+    /// gives up in it (see `TradeCost`; by default nothing).
+    pub fn trade_cost(mut self, cost: TradeCost) -> Self {
+        self.trade_cost = Some(cost);
+        self
+    }
+
+    /// Have the actor only know what other actors hold from having seen it
+    /// (see `trade::search`), instead of just knowing.
+    pub fn imperfect_information(mut self) -> Self {
+        self.imperfect_information = true;
+        self
+    }
+
+    /// Have the actor give away what it has no use for once it's got no
+    /// goals in play (see `ValueScale::charitable`), instead of sitting on it.
+    pub fn charitable(mut self) -> Self {
+        self.charitable = true;
+        self
+    }
+
+    /// Have the actor walk away from negotiations as soon as it's clear they
+    /// can't meet the worst trade it'd make (see `ValueScale::walks_away`),
+    /// instead of bidding until it runs out of things to offer.
+    pub fn walks_away(mut self) -> Self {
+        self.walks_away = true;
+        self
+    }
+
+    /// Have the actor stock up on what its recurring goals will need before
+    /// they come back around, when it's cheap (see `ValueScale::stockpiles`),
+    /// instead of waiting until it needs it.
+    pub fn stockpiles(mut self) -> Self {
+        self.stockpiles = true;
+        self
+    }
+
+    /// Have the actor hire other actors' labor for wages when it doesn't
+    /// have enough hours of its own for something it's making (see
+    /// `ValueScale::hires_labor`), instead of giving up on making it.
+    pub fn hires_labor(mut self) -> Self {
+        self.hires_labor = true;
+        self
+    }
+
+    /// Have the actor make something else to trade for what it wants, when
+    /// it expects that to get it more than making it itself (see
+    /// `ValueScale::specializes`).
+    pub fn specializes(mut self) -> Self {
+        self.specializes = true;
+        self
+    }
+
+    /// Have the actor weigh every hour of work against the leisure it could
+    /// be taking instead (see `ValueScale::weighs_leisure`).
+    pub fn weighs_leisure(mut self) -> Self {
+        self.weighs_leisure = true;
+        self
+    }
+
+    /// Have the actor weigh bundles by how many of its wants they'd go
+    /// towards, so enough goods it values less can outweigh one it values
+    /// more (see `ValueScale::weighs_bundles`).
+    pub fn weighs_bundles(mut self) -> Self {
+        self.weighs_bundles = true;
+        self
+    }
+
+    /// Have the actor set goods and money aside for the recurring goals it
+    /// knows are coming back around (see `ValueScale::saves`).
+    pub fn saves(mut self) -> Self {
+        self.saves = true;
+        self
+    }
+
+    /// Have the actor learn what to expect goods to go for from every trade
+    /// anyone makes (see `Expectations::watching_market`), not just its own.
+    pub fn watches_market(mut self) -> Self {
+        self.watches_market = true;
+        self
+    }
+
+    /// Have the actor turn down trades more than `tolerance` (a fraction)
+    /// worse than it expects, and wait for a better one (see
+    /// `Expectations::holds_out`). By default it takes whatever's going.
+    pub fn holds_out(mut self, tolerance: Quantity) -> Self {
+        self.holds_out = Some(tolerance);
+        self
+    }
+
+    /// Have the actor start out knowing a recipe that has to be discovered
+    /// (see `KnowHow`).
+    pub fn knows(mut self, recipe: RecipeId) -> Self {
+        self.recipes.push(recipe);
+        self
+    }
+
+    /// Set the decision rule the actor uses (defaults to `DefaultBehavior`).
+    pub fn behavior(mut self, behavior: Box<dyn Behavior>) -> Self {
+        self.behavior = Some(behavior);
+        self
+    }
+
+    /// Check everything is consistent and construct the actor.
+    pub fn build(self) -> Result<Actor, BuildError> {
+        let name = match self.name {
+            Some(name) if !name.is_empty() => name,
+            _ => return Err(BuildError::MissingName),
+        };
+        for (i, goal) in self.hierarchy.iter().enumerate() {
+            if self.hierarchy[..i]
+                .iter()
+                .any(|g| g.get_goal() == goal.get_goal())
+            {
+                return Err(BuildError::DuplicateGoal(goal.get_goal()));
+            }
+        }
+
+        let mut satisfactions: Vec<(GoalId, Vec<ItemId>)> = vec![];
+        for (goal, items) in self.satisfactions {
+            if !self.hierarchy.iter().any(|g| g.get_goal() == goal) {
+                return Err(BuildError::UnknownGoal(goal));
+            }
+            match satisfactions.iter_mut().find(|(g, _)| *g == goal) {
+                Some((_, existing)) => existing.extend(items),
+                None => satisfactions.push((goal, items)),
+            }
+        }
+
+        let mut complements: Vec<(GoalId, Vec<ItemId>)> = vec![];
+        for (goal, items) in self.complements {
+            if !self.hierarchy.iter().any(|g| g.get_goal() == goal) {
+                return Err(BuildError::UnknownGoal(goal));
+            }
+            match complements.iter_mut().find(|(g, _)| *g == goal) {
+                Some((_, existing)) => existing.extend(items),
+                None => complements.push((goal, items)),
+            }
+        }
+
+        let mut actor = Actor::new(
+            name,
+            self.hierarchy,
+            satisfactions,
+            self.behavior.unwrap_or_else(|| Box::new(DefaultBehavior)),
+        )
+        .map_err(BuildError::Hierarchy)?;
+        for (goal, to) in self.ties {
+            actor.values.make_indifferent(goal, to);
+        }
+        actor
+            .values
+            .validate_hierarchy()
+            .map_err(BuildError::Hierarchy)?;
+        for (goal, items) in complements {
+            actor.values.set_complements(goal, items);
+        }
+        actor.inventory = self.inventory;
+        actor.inventory.set_capacity(self.capacity);
+        if let Some(hours) = self.labor {
+            actor.labor = Labor::new(hours);
+        }
+        actor.values.patience = self.patience;
+        actor.values.time_preference = self.time_preference;
+        actor.values.trade_cost = self.trade_cost;
+        actor.values.charitable = self.charitable;
+        actor.values.walks_away = self.walks_away;
+        actor.values.stockpiles = self.stockpiles;
+        actor.values.hires_labor = self.hires_labor;
+        actor.values.specializes = self.specializes;
+        actor.values.weighs_leisure = self.weighs_leisure;
+        actor.values.weighs_bundles = self.weighs_bundles;
+        actor.values.saves = self.saves;
+        if self.watches_market {
+            actor.expectations = actor.expectations.watching_market();
+        }
+        if let Some(tolerance) = self.holds_out {
+            actor.expectations = actor.expectations.holding_out(tolerance);
+        }
+        if self.imperfect_information {
+            actor.memory = TradeMemory::imperfect();
+        }
+        for recipe in self.recipes {
+            actor.know_how.learn(recipe);
+        }
+        Ok(actor)
+    }
+}
@@ -0,0 +1,797 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::inventory::Acquisition;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::preference_list::{ActorMut, ActorRef};
+use crate::trade::{can_pay, exchange};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// How many ticks a borrower has to pay back a loan.
+pub const LOAN_TERM: u64 = 10;
+
+/// How many ticks after a forward contract is agreed the items change hands.
+pub const FORWARD_TERM: u64 = 5;
+
+/// A promise from one actor to hand another some items on a later tick, in
+/// return for something it got from them now.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Iou {
+    /// Who owes the items
+    pub debtor: ActorId,
+    /// Who they're owed to
+    pub creditor: ActorId,
+    /// What the debtor got
+    pub lent: Bundle,
+    /// What's owed (what was lent, plus interest)
+    pub owed: Bundle,
+    /// The tick the debt was taken on
+    pub issued: u64,
+    /// The tick it has to be paid back on
+    pub due: u64,
+    /// Whether it's a bank's promise to pay back a deposit (see `deposit`),
+    /// rather than a loan
+    #[serde(default)]
+    pub deposit: bool,
+}
+
+/// An agreement between two actors to swap some items on a later tick, at
+/// terms settled now.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Forward {
+    /// Who bid for the contract
+    pub buyer: ActorId,
+    /// Who took the bid
+    pub seller: ActorId,
+    /// What the buyer has to hand over
+    pub pays: Bundle,
+    /// What the seller has to hand over
+    pub gets: Bundle,
+    /// The tick the contract was agreed on
+    pub agreed: u64,
+    /// The tick the items change hands on
+    pub due: u64,
+}
+
+/// An agreement for one actor to let another have the use of some goods
+/// (usually a durable good, like a house) for a while, for so much rent a
+/// tick, after which it gets them back.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Lease {
+    /// Who the goods belong to
+    pub landlord: ActorId,
+    /// Who has the use of them
+    pub tenant: ActorId,
+    /// What's been leased
+    pub leased: Bundle,
+    /// What the tenant pays the landlord every tick
+    pub rent: Bundle,
+    /// The tick the lease was agreed on
+    pub agreed: u64,
+    /// The tick the goods go back to the landlord on
+    pub due: u64,
+}
+
+/// Everything an actor has promised other actors that hasn't been settled
+/// yet: IOUs it's written, and forward contracts and leases it's a party to.
+/// IOUs are only kept by whoever owes them (to find out what an actor is
+/// owed, look through everyone else's books for IOUs made out to it), but
+/// both parties to a forward contract or a lease keep a copy of it.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct CreditBook {
+    /// Outstanding IOUs, oldest first
+    ious: Vec<Iou>,
+    /// Open forward contracts, oldest first
+    #[serde(default)]
+    forwards: Vec<Forward>,
+    /// Leases running, oldest first
+    #[serde(default)]
+    leases: Vec<Lease>,
+    /// IOUs it's defaulted on, oldest first, each with what it didn't pay
+    /// back of it
+    #[serde(default)]
+    defaulted: Vec<Iou>,
+}
+
+impl CreditBook {
+    /// An empty book.
+    pub fn new() -> Self {
+        CreditBook::default()
+    }
+
+    /// Every outstanding IOU, oldest first.
+    pub fn ious(&self) -> &[Iou] {
+        &self.ious
+    }
+
+    /// Every open forward contract, oldest first.
+    pub fn forwards(&self) -> &[Forward] {
+        &self.forwards
+    }
+
+    /// Every lease running, oldest first.
+    pub fn leases(&self) -> &[Lease] {
+        &self.leases
+    }
+
+    /// Every IOU it's defaulted on, oldest first, each with what it didn't
+    /// pay back of it.
+    pub fn defaulted(&self) -> &[Iou] {
+        &self.defaulted
+    }
+
+    /// Takes a lease that's over out of the book.
+    pub fn end_lease(&mut self, lease: &Lease) {
+        self.leases.retain(|l| l != lease);
+    }
+
+    /// Whether the actor's out of debt. (Forward contracts don't count:
+    /// nobody's been given anything on credit.)
+    pub fn is_empty(&self) -> bool {
+        self.ious.is_empty()
+    }
+
+    /// Writes a new IOU into the book.
+    pub fn issue(&mut self, iou: Iou) {
+        self.ious.push(iou);
+    }
+
+    /// Takes every IOU that's come due as of tick `tick` out of the book, so
+    /// it can be settled.
+    pub fn take_due(&mut self, tick: u64) -> Vec<Iou> {
+        let (due, rest) = self.ious.drain(..).partition(|iou| iou.due <= tick);
+        self.ious = rest;
+        due
+    }
+
+    /// Takes every forward contract that's come due as of tick `tick` out
+    /// of the book, so it can be settled.
+    pub fn take_due_forwards(&mut self, tick: u64) -> Vec<Forward> {
+        let (due, rest) = self.forwards.drain(..).partition(|f| f.due <= tick);
+        self.forwards = rest;
+        due
+    }
+
+    /// Whether the actor has bought something on a forward contract that
+    /// it's still waiting on, that's one of `items`.
+    pub fn expecting(&self, actor: ActorId, items: &[ItemId]) -> bool {
+        self.forwards
+            .iter()
+            .any(|f| f.buyer == actor && items.iter().any(|&i| f.gets.contains(i)))
+    }
+}
+
+/// What `lender` wants back in `term` ticks for lending the items in `lent`
+/// now: the same again, plus a premium of its time preference for every
+/// tick it has to wait (see `ValueScale::time_preference`). Whole amounts
+/// are paid back in whole amounts, rounding the premium up.
+pub fn repayment(lender: ActorRef, lent: &Bundle, term: u64) -> Bundle {
+    let mut owed = Bundle::new();
+    for (item, back) in repayment_at(lender.values.time_preference, lent, term).iter() {
+        if lent.count(item).is_whole() && !back.is_whole() {
+            owed.add(item, back.whole() + Quantity::ONE);
+        } else {
+            owed.add(item, back);
+        }
+    }
+    owed
+}
+
+/// What a lender wants back in `term` ticks for lending the items in `lent`
+/// now at `interest` a tick, to the last fraction of a unit (fine for money,
+/// which a bank lends; see `Bank`).
+pub fn repayment_at(interest: Quantity, lent: &Bundle, term: u64) -> Bundle {
+    let rate = interest.times(Quantity::from(term as u32));
+    let mut owed = Bundle::new();
+    for (item, amount) in lent.iter() {
+        owed.add(item, amount + amount.times(rate));
+    }
+    owed
+}
+
+/// Whether `lender` has more of everything in `want` than all of its goals
+/// in play need, so it can spare it for a while.
+fn can_spare(lender: ActorRef, want: &Bundle) -> bool {
+    want.iter().all(|(item, amount)| {
+        let needed: Quantity = lender
+            .values
+            .current_goals
+            .iter()
+            .filter(|g| lender.needs_for(g.goal, item))
+            .filter_map(|g| lender.values.units_needed(g.goal))
+            .sum();
+        lender.inventory.count(item) >= needed + amount
+    })
+}
+
+/// Whether `lender` would lend `borrower` the items in `want`, to be paid
+/// back later with interest (see `repayment`), and `borrower` would take
+/// it. The lender only will if it can spare them (it has more than all its
+/// goals in play need), and only to someone who isn't already in debt to
+/// anyone; the borrower only will if it doesn't care about the future any
+/// more than the lender does, since otherwise the interest isn't worth it.
+///
+/// # Notes
+///
+/// This is the time-preference theory of interest: nobody lends without a
+/// premium for waiting, and nobody borrows at a premium higher than they'd
+/// pay for having things sooner, so loans go from the patient to the
+/// impatient, at a rate in between.
+pub fn would_lend(lender: ActorRef, borrower: ActorRef, want: &Bundle) -> bool {
+    !want.is_empty()
+        && lender.values.time_preference <= borrower.values.time_preference
+        && borrower.credit.is_empty()
+        && borrower.can_fit(want, &Bundle::new())
+        && can_spare(lender, want)
+}
+
+/// `lender` hands `borrower` the items in `want`, and `borrower` writes it an
+/// IOU for the same again plus interest (see `repayment`), due `LOAN_TERM`
+/// ticks from now. Returns whether the loan went ahead (it doesn't if the
+/// lender doesn't have the items).
+pub fn borrow(
+    borrower: &mut ActorMut,
+    lender: &mut ActorMut,
+    want: &Bundle,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    let interest = lender.values.time_preference;
+    borrow_at(borrower, lender, want, interest, events)
+}
+
+/// Like `borrow`, but at `interest` a tick instead of out of the lender's
+/// time preference (a bank's rate, say; see `Bank`).
+pub fn borrow_at(
+    borrower: &mut ActorMut,
+    lender: &mut ActorMut,
+    want: &Bundle,
+    interest: Quantity,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if want
+        .iter()
+        .any(|(item, amount)| lender.inventory.count(item) < amount)
+    {
+        return false;
+    }
+    for (item, amount) in want.iter() {
+        lender
+            .inventory
+            .transfer(borrower.inventory, item, amount, lender.id, lender.now);
+    }
+    let iou = Iou {
+        debtor: borrower.id,
+        creditor: lender.id,
+        lent: want.clone(),
+        owed: repayment_at(interest, want, LOAN_TERM),
+        issued: borrower.now,
+        due: borrower.now + LOAN_TERM,
+        deposit: false,
+    };
+    events.push(SimEvent::IouIssued {
+        debtor: iou.debtor,
+        creditor: iou.creditor,
+        lent: iou.lent.clone(),
+        owed: iou.owed.clone(),
+        due: iou.due,
+        interest,
+    });
+    borrower.credit.issue(iou);
+    for (item, _) in want.iter() {
+        events.extend(borrower.discard_overflow(item));
+    }
+    true
+}
+
+/// Whether `depositor` would put the money in `amount` in a bank for a
+/// while: only if it's no more than half of what it has spare (see
+/// `Inventory::spare`), so it's still got some to trade with in the
+/// meantime.
+pub fn would_deposit(depositor: ActorRef, amount: &Bundle) -> bool {
+    !amount.is_empty()
+        && amount.iter().all(|(item, amount)| {
+            depositor
+                .inventory
+                .spare(item)
+                .divided_by(Quantity::units(2))
+                >= amount
+        })
+}
+
+/// `depositor` hands `bank` the money in `amount` for safekeeping, and the
+/// bank writes it an IOU for the same (no interest: the bank's the one
+/// doing it a service), due `LOAN_TERM` ticks from now. Until then, the bank
+/// can lend it out (see `Bank`), so if too many of its loans go bad, it
+/// mightn't have it to give back. Returns whether the deposit was made (it
+/// isn't if the depositor doesn't have the money).
+pub fn deposit(
+    depositor: &mut ActorMut,
+    bank: &mut ActorMut,
+    amount: &Bundle,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if amount
+        .iter()
+        .any(|(item, amount)| depositor.inventory.count(item) < amount)
+    {
+        return false;
+    }
+    for (item, amount) in amount.iter() {
+        depositor
+            .inventory
+            .transfer(bank.inventory, item, amount, depositor.id, depositor.now);
+    }
+    let iou = Iou {
+        debtor: bank.id,
+        creditor: depositor.id,
+        lent: amount.clone(),
+        owed: amount.clone(),
+        issued: bank.now,
+        due: bank.now + LOAN_TERM,
+        deposit: true,
+    };
+    events.push(SimEvent::DepositMade {
+        bank: iou.debtor,
+        depositor: iou.creditor,
+        amount: iou.lent.clone(),
+        due: iou.due,
+    });
+    bank.credit.issue(iou);
+    for (item, _) in amount.iter() {
+        events.extend(bank.discard_overflow(item));
+    }
+    true
+}
+
+/// A bank's balance sheet in one kind of money, worked out from everyone's
+/// credit books (see `Bank`): what it holds in reserve and what it's owed
+/// on its loans, against what it owes its depositors.
+#[derive(PartialEq, Clone, Debug)]
+pub struct BankBook {
+    /// The money it's all in
+    pub money: ItemId,
+    /// How much of it the bank holds
+    pub reserves: Quantity,
+    /// Every loan it's made that's still outstanding, oldest first
+    pub loans: Vec<Iou>,
+    /// Every deposit it's taken that it hasn't paid back yet, oldest first
+    pub deposits: Vec<Iou>,
+    /// Every deposit it couldn't pay back in full, oldest first, each with
+    /// what it still owes of it
+    pub defaulted: Vec<Iou>,
+}
+
+impl BankBook {
+    /// The balance sheet of `bank`, in `money`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank` - the bank
+    /// * `actors` - everyone, for the loans the bank's made them
+    /// * `money` - the money to draw it up in
+    ///
+    pub fn of(bank: ActorRef, actors: &ActorArena, money: ItemId) -> Self {
+        BankBook {
+            money,
+            reserves: bank.inventory.count(money),
+            loans: actors
+                .iter()
+                .flat_map(|actor| actor.credit.ious().to_vec())
+                .filter(|iou| iou.creditor == bank.id && !iou.deposit && iou.owed.contains(money))
+                .collect(),
+            deposits: bank
+                .credit
+                .ious()
+                .iter()
+                .filter(|iou| iou.deposit && iou.owed.contains(money))
+                .cloned()
+                .collect(),
+            defaulted: bank
+                .credit
+                .defaulted()
+                .iter()
+                .filter(|iou| iou.deposit && iou.owed.contains(money))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Everything the bank's owed on its loans.
+    pub fn lent(&self) -> Quantity {
+        self.loans
+            .iter()
+            .map(|iou| iou.owed.count(self.money))
+            .sum()
+    }
+
+    /// Everything the bank owes its depositors, including whatever it
+    /// didn't pay back of deposits it's defaulted on.
+    pub fn owed(&self) -> Quantity {
+        self.deposits
+            .iter()
+            .chain(self.defaulted.iter())
+            .map(|iou| iou.owed.count(self.money))
+            .sum()
+    }
+
+    /// Whether the bank owes its depositors more than it holds and is owed
+    /// put together: bad loans have eaten through its reserves, and some of
+    /// its depositors aren't getting their money back.
+    pub fn is_insolvent(&self) -> bool {
+        self.owed() > self.reserves + self.lent()
+    }
+
+    /// Whether the bank's failed: it's insolvent, or it's already let a
+    /// depositor down, not having the reserves to pay a deposit back when
+    /// it came due.
+    pub fn has_failed(&self) -> bool {
+        self.is_insolvent() || !self.defaulted.is_empty()
+    }
+
+    /// How much the bank can lend out and still hold `ratio` of what it
+    /// owes its depositors in reserve.
+    pub fn lendable(&self, ratio: Quantity) -> Quantity {
+        self.reserves.saturating_sub(self.owed().times(ratio))
+    }
+}
+
+/// The debtor pays back an IOU that's come due, out of whatever it has. If
+/// it hasn't got everything it owes, it hands over what it can and defaults
+/// on the rest.
+pub fn repay(
+    iou: &Iou,
+    debtor: &mut ActorMut,
+    creditor: &mut ActorMut,
+    events: &mut Vec<SimEvent>,
+) {
+    let mut paid = Bundle::new();
+    let mut unpaid = Bundle::new();
+    for (item, amount) in iou.owed.iter() {
+        let have = debtor.inventory.count(item).min(amount);
+        if have > Quantity::ZERO {
+            debtor
+                .inventory
+                .transfer(creditor.inventory, item, have, debtor.id, debtor.now);
+            paid.add(item, have);
+        }
+        if have < amount {
+            unpaid.add(item, amount - have);
+        }
+    }
+    if unpaid.is_empty() {
+        events.push(SimEvent::IouRepaid {
+            debtor: iou.debtor,
+            creditor: iou.creditor,
+            owed: iou.owed.clone(),
+        });
+    } else {
+        debtor.credit.defaulted.push(Iou {
+            owed: unpaid.clone(),
+            ..iou.clone()
+        });
+        events.push(SimEvent::IouDefaulted {
+            debtor: iou.debtor,
+            creditor: iou.creditor,
+            paid,
+            unpaid,
+        });
+    }
+    for (item, _) in iou.owed.iter() {
+        events.extend(creditor.discard_overflow(item));
+    }
+}
+
+/// `buyer` and `seller` agree to swap `pays` for `gets` in `term` ticks,
+/// each writing the contract into its book. Returns whether they did (they
+/// don't if either side doesn't have its items right now).
+pub fn agree_forward(
+    buyer: &mut ActorMut,
+    pays: &Bundle,
+    seller: &mut ActorMut,
+    gets: &Bundle,
+    term: u64,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if pays
+        .iter()
+        .any(|(item, amount)| buyer.inventory.count(item) < amount)
+        || gets
+            .iter()
+            .any(|(item, amount)| seller.inventory.count(item) < amount)
+    {
+        return false;
+    }
+    let forward = Forward {
+        buyer: buyer.id,
+        seller: seller.id,
+        pays: pays.clone(),
+        gets: gets.clone(),
+        agreed: buyer.now,
+        due: buyer.now + term,
+    };
+    events.push(SimEvent::ForwardAgreed {
+        buyer: forward.buyer,
+        seller: forward.seller,
+        pays: forward.pays.clone(),
+        gets: forward.gets.clone(),
+        due: forward.due,
+    });
+    buyer.credit.forwards.push(forward.clone());
+    seller.credit.forwards.push(forward);
+    true
+}
+
+/// The parties to a forward contract that's come due swap what they agreed
+/// to. If either of them hasn't got everything it promised any more (or
+/// can't pay what the trade costs it, see `TradeCost`), it defaults and
+/// nothing changes hands.
+pub fn deliver(
+    forward: &Forward,
+    buyer: &mut ActorMut,
+    seller: &mut ActorMut,
+    events: &mut Vec<SimEvent>,
+) {
+    let short = |actor: &ActorMut, promised: &Bundle| !can_pay(actor.as_ref(), promised, actor.now);
+    let defaulted: Vec<ActorId> = [
+        (forward.buyer, short(buyer, &forward.pays)),
+        (forward.seller, short(seller, &forward.gets)),
+    ]
+    .iter()
+    .filter(|&&(_, short)| short)
+    .map(|&(id, _)| id)
+    .collect();
+    if defaulted.is_empty() && exchange(buyer, &forward.pays, seller, &forward.gets, events) {
+        events.push(SimEvent::ForwardSettled {
+            buyer: forward.buyer,
+            seller: forward.seller,
+        });
+    } else {
+        events.push(SimEvent::ForwardDefaulted {
+            buyer: forward.buyer,
+            seller: forward.seller,
+            defaulted,
+        });
+    }
+}
+
+/// Which of `landlord` and `tenant` would turn down leasing `leased` for
+/// `rent` a tick, if either would (the landlord gets asked first). The
+/// landlord only lets out what it can spare (see `would_lend`), and then
+/// anything's better than nothing; the tenant only takes it if it values
+/// the use of it at least as much as a tick's rent, and has the first
+/// tick's rent, and room for it.
+pub fn refuses_lease(
+    landlord: ActorRef,
+    tenant: ActorRef,
+    leased: &Bundle,
+    rent: &Bundle,
+) -> Option<ActorId> {
+    if leased.is_empty() || rent.is_empty() || !can_spare(landlord, leased) {
+        Some(landlord.id)
+    } else if tenant.compare_bundles(leased, rent) == Ordering::Less
+        || rent
+            .iter()
+            .any(|(item, amount)| tenant.inventory.count(item) < amount)
+        || !tenant.can_fit(leased, rent)
+    {
+        Some(tenant.id)
+    } else {
+        None
+    }
+}
+
+/// `landlord` hands `tenant` the items in `leased` for `term` ticks, for
+/// `rent` a tick, and both write the lease into their books. Rent's paid
+/// every tick from this one on, as debts are settled (see `collect_rent`),
+/// until the lease is up. Returns whether the lease went ahead (it doesn't
+/// if either side doesn't have its items right now).
+pub fn sign_lease(
+    landlord: &mut ActorMut,
+    tenant: &mut ActorMut,
+    leased: &Bundle,
+    rent: &Bundle,
+    term: u64,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if leased
+        .iter()
+        .any(|(item, amount)| landlord.inventory.count(item) < amount)
+        || rent
+            .iter()
+            .any(|(item, amount)| tenant.inventory.count(item) < amount)
+    {
+        return false;
+    }
+    for (item, amount) in leased.iter() {
+        landlord
+            .inventory
+            .transfer(tenant.inventory, item, amount, landlord.id, landlord.now);
+    }
+    let lease = Lease {
+        landlord: landlord.id,
+        tenant: tenant.id,
+        leased: leased.clone(),
+        rent: rent.clone(),
+        agreed: landlord.now,
+        due: landlord.now + term,
+    };
+    events.push(SimEvent::LeaseAgreed {
+        landlord: lease.landlord,
+        tenant: lease.tenant,
+        leased: lease.leased.clone(),
+        rent: lease.rent.clone(),
+        due: lease.due,
+    });
+    landlord.credit.leases.push(lease.clone());
+    tenant.credit.leases.push(lease);
+    for (item, _) in leased.iter() {
+        events.extend(tenant.discard_overflow(item));
+    }
+    true
+}
+
+/// The tenant pays a tick's rent, if it has all of it. Returns whether it
+/// did.
+fn pay_rent(
+    lease: &Lease,
+    tenant: &mut ActorMut,
+    landlord: &mut ActorMut,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if lease
+        .rent
+        .iter()
+        .any(|(item, amount)| tenant.inventory.count(item) < amount)
+    {
+        return false;
+    }
+    for (item, amount) in lease.rent.iter() {
+        tenant
+            .inventory
+            .transfer(landlord.inventory, item, amount, tenant.id, tenant.now);
+    }
+    events.push(SimEvent::RentPaid {
+        tenant: lease.tenant,
+        landlord: lease.landlord,
+        rent: lease.rent.clone(),
+    });
+    for (item, _) in lease.rent.iter() {
+        events.extend(landlord.discard_overflow(item));
+    }
+    true
+}
+
+/// Sees to a lease for another tick: once it's up, the leased goods go back
+/// to the landlord, and until then the tenant pays the rent, or (if it
+/// can't) loses the lease early. Returns whether the lease is over.
+///
+/// # Notes
+///
+/// The goods go back whether the tenant's been using them or not (taking
+/// them back out of service if it has), but anything it's used up, thrown
+/// out, or traded on in the meantime is gone, and the landlord only gets
+/// back what's left.
+pub fn collect_rent(
+    lease: &Lease,
+    tenant: &mut ActorMut,
+    landlord: &mut ActorMut,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    let defaulted = if tenant.now >= lease.due {
+        false
+    } else if pay_rent(lease, tenant, landlord, events) {
+        return false;
+    } else {
+        true
+    };
+    let mut returned = Bundle::new();
+    for (item, amount) in lease.leased.iter() {
+        let held = tenant.inventory.count(item).min(amount);
+        if held > Quantity::ZERO {
+            tenant.inventory.remove(item, held);
+        }
+        let mut back = held;
+        while back < amount && tenant.inventory.take_out_of_service(item, Quantity::ONE) {
+            back += Quantity::ONE;
+        }
+        if back > Quantity::ZERO {
+            landlord.inventory.add_at(
+                item,
+                back,
+                Acquisition {
+                    from: Some(tenant.id),
+                    tick: tenant.now,
+                },
+            );
+            returned.add(item, back);
+        }
+    }
+    events.push(SimEvent::LeaseEnded {
+        landlord: lease.landlord,
+        tenant: lease.tenant,
+        leased: lease.leased.clone(),
+        returned: returned.clone(),
+        defaulted,
+    });
+    tenant.credit.end_lease(lease);
+    landlord.credit.end_lease(lease);
+    for (item, _) in returned.iter() {
+        events.extend(landlord.discard_overflow(item));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preference_list::Actor;
+
+    #[test]
+    fn a_bank_whose_loans_go_bad_fails_and_its_depositors_lose_out() {
+        let money = ItemId(0);
+        let ten = Quantity::from(10u32);
+        let mut actors = ActorArena::new();
+        let actor = |name: &str| Actor::builder().name(name).build().unwrap();
+        let depositor = actors.insert(
+            Actor::builder()
+                .name("Depositor")
+                .starting_amount(money, ten)
+                .build()
+                .unwrap(),
+        );
+        let bank = actors.insert(actor("Bank"));
+        let borrower = actors.insert(actor("Borrower"));
+        let mut events = vec![];
+        let (mut d, mut b) = actors.pair_mut(depositor, bank).unwrap();
+        assert!(deposit(
+            &mut d,
+            &mut b,
+            &Bundle::new().with(money, ten),
+            &mut events
+        ));
+        // The bank lends most of it out, and the borrower spends it all
+        let lent = Quantity::from(8u32);
+        let (mut r, mut b) = actors.pair_mut(borrower, bank).unwrap();
+        let loan = Bundle::new().with(money, lent);
+        assert!(borrow_at(
+            &mut r,
+            &mut b,
+            &loan,
+            Quantity::ZERO,
+            &mut events
+        ));
+        assert!(r.inventory.remove(money, lent));
+        let book = BankBook::of(actors.get(bank).unwrap(), &actors, money);
+        assert!(!book.has_failed());
+        // Then the loan goes bad, and the bank can't cover its deposits
+        actors.set_now(LOAN_TERM);
+        for iou in actors.get_mut(borrower).unwrap().credit.take_due(LOAN_TERM) {
+            let (mut r, mut b) = actors.pair_mut(borrower, bank).unwrap();
+            repay(&iou, &mut r, &mut b, &mut events);
+        }
+        let book = BankBook::of(actors.get(bank).unwrap(), &actors, money);
+        assert!(book.is_insolvent() && book.has_failed());
+        // So the depositor gets back only what it's got left
+        events.clear();
+        for iou in actors.get_mut(bank).unwrap().credit.take_due(LOAN_TERM) {
+            let (mut b, mut d) = actors.pair_mut(bank, depositor).unwrap();
+            repay(&iou, &mut b, &mut d, &mut events);
+        }
+        let left = ten - lent;
+        assert_eq!(
+            events,
+            vec![SimEvent::IouDefaulted {
+                debtor: bank,
+                creditor: depositor,
+                paid: Bundle::new().with(money, left),
+                unpaid: Bundle::new().with(money, lent),
+            }]
+        );
+        assert_eq!(actors.get(depositor).unwrap().inventory.count(money), left);
+        let book = BankBook::of(actors.get(bank).unwrap(), &actors, money);
+        assert_eq!(book.owed(), lent);
+        assert!(book.has_failed());
+    }
+}
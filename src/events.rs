@@ -0,0 +1,441 @@
+use crate::arena::ActorId;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::labor::Work;
+use crate::resources::ResourceId;
+use crate::trade::TradeCost;
+use serde::{Deserialize, Serialize};
+
+/// Something that happened during a tick. Actors never print anything
+/// themselves; instead they report what they did as a list of these, and it's
+/// up to whoever is driving the simulation (the REPL, a test, some analysis
+/// tool) to decide what to do with them.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum SimEvent {
+    /// A recurring goal came back around and was put back in play
+    GoalReintroduced { actor: ActorId, goal: GoalId },
+    /// Everything a goal was waiting on has been seen to, so it's been put in
+    /// play
+    GoalActivated { actor: ActorId, goal: GoalId },
+    /// The actor's preferences drifted, moving a goal from one place in its
+    /// hierarchy to another
+    GoalDrifted {
+        actor: ActorId,
+        goal: GoalId,
+        from: usize,
+        to: usize,
+    },
+    /// The actor picked the highest-valued goal currently in play
+    GoalSelected { actor: ActorId, goal: GoalId },
+    /// The actor has no goals in play at all
+    NoGoals { actor: ActorId },
+    /// Some of an item was consumed in service of a goal
+    ItemUsed {
+        actor: ActorId,
+        item: ItemId,
+        amount: Quantity,
+        goal: GoalId,
+    },
+    /// A public good in the commons was used towards a goal (without using
+    /// it up)
+    PublicGoodUsed {
+        actor: ActorId,
+        item: ItemId,
+        goal: GoalId,
+    },
+    /// Some of a perishable item was held too long and went off
+    ItemSpoiled {
+        actor: ActorId,
+        item: ItemId,
+        amount: Quantity,
+    },
+    /// A durable good in use put another tick's worth towards its goal
+    ItemServed {
+        actor: ActorId,
+        item: ItemId,
+        goal: GoalId,
+    },
+    /// A durable good has been used for as long as it lasts, and is gone
+    ItemWornOut { actor: ActorId, item: ItemId },
+    /// The actor spent the rest of its labor for the tick resting, towards a
+    /// goal
+    Rested {
+        actor: ActorId,
+        hours: Quantity,
+        goal: GoalId,
+    },
+    /// The actor spent some of its labor for the tick working, when it could
+    /// have been resting towards a leisure goal instead: what the work cost
+    /// it
+    LeisureForgone {
+        actor: ActorId,
+        hours: Quantity,
+        goal: GoalId,
+    },
+    /// The actor didn't have enough labor left this tick for some work, so
+    /// it'll have to wait until next tick to do it
+    OutOfLabor { actor: ActorId, work: Work },
+    /// Land the actor holds yielded some of an item
+    ItemYielded {
+        actor: ActorId,
+        land: ItemId,
+        item: ItemId,
+        amount: Quantity,
+    },
+    /// The actor spent the tick gathering `amount` of `item` from a resource
+    /// node, towards a goal
+    ResourceGathered {
+        actor: ActorId,
+        node: ResourceId,
+        item: ItemId,
+        amount: Quantity,
+        goal: GoalId,
+    },
+    /// The actor went to gather from a resource node, but everything it
+    /// yields this tick had already been gathered
+    ResourceExhausted { actor: ActorId, node: ResourceId },
+    /// The actor spent the tick experimenting, and found out how to make
+    /// things by a recipe that not everyone knows
+    RecipeDiscovered { actor: ActorId, recipe: RecipeId },
+    /// The actor saw how a trade partner made what it got from it, and
+    /// learned the recipe
+    RecipeLearned {
+        actor: ActorId,
+        from: ActorId,
+        recipe: RecipeId,
+    },
+    /// The actor put the inputs to a recipe in, and started making something
+    /// towards a goal
+    ProductionStarted {
+        actor: ActorId,
+        recipe: RecipeId,
+        goal: GoalId,
+    },
+    /// The actor set goods aside for the later stages of what it's making
+    /// towards a goal, so it won't use them up or trade them away before it
+    /// gets to them
+    ItemsReserved {
+        actor: ActorId,
+        goal: GoalId,
+        items: Bundle,
+    },
+    /// The actor set goods (or money) aside for a recurring goal that isn't
+    /// in play yet, rather than use them up or trade them away before it
+    /// comes back around (see `ActorRef::savings`)
+    GoodsSaved {
+        actor: ActorId,
+        goal: GoalId,
+        items: Bundle,
+    },
+    /// The actor finished making something, and `outputs` came out of it
+    ItemsProduced {
+        actor: ActorId,
+        recipe: RecipeId,
+        outputs: Bundle,
+    },
+    /// The actor put `inputs` into maintaining a tool it uses, putting it
+    /// back as good as new
+    ToolMaintained {
+        actor: ActorId,
+        tool: ItemId,
+        inputs: Bundle,
+    },
+    /// The actor threw some of an item out, because it didn't have room for
+    /// it and something it valued more
+    ItemDiscarded {
+        actor: ActorId,
+        item: ItemId,
+        amount: Quantity,
+    },
+    /// The actor tried to use an item it doesn't actually have
+    ItemMissing {
+        actor: ActorId,
+        item: ItemId,
+        goal: GoalId,
+    },
+    /// Enough units were put towards a goal that it's been taken out of play
+    GoalSatisfied { actor: ActorId, goal: GoalId },
+    /// The actor went after a goal for so many ticks in a row without getting
+    /// any closer to it that it gave up on it, for now at least
+    GoalAbandoned {
+        actor: ActorId,
+        goal: GoalId,
+        ticks: u32,
+    },
+    /// The actor couldn't satisfy its goal itself, and will go looking to trade
+    WillingToTrade { actor: ActorId },
+    /// The actor, which only knows what others hold from having seen it,
+    /// spent the tick looking to see what `looked_at` have
+    Searched {
+        actor: ActorId,
+        looked_at: Vec<ActorId>,
+    },
+    /// The world paired the actor up with a partner that has something it
+    /// wants and wants something it has (see `Simulation::set_matching`), so
+    /// it can start bidding without going looking
+    PartnersMatched { actor: ActorId, partner: ActorId },
+    /// A potential trade partner was skipped because it's busy with someone else
+    PartnerOccupied { actor: ActorId, partner: ActorId },
+    /// A trade partner with something the actor wants was found
+    PartnerFound {
+        actor: ActorId,
+        partner: ActorId,
+        items: Quantity,
+        engaged: bool,
+    },
+    /// The actor put up a standing offer on the market's bulletin board
+    /// (or sweetened the one it already had up there): it'll give `offer` to
+    /// anyone who gives it `want`
+    OfferPosted {
+        actor: ActorId,
+        offer: Bundle,
+        want: Bundle,
+    },
+    /// The actor put in a bid on the market for a unit of `item`, paying
+    /// `price` of `pay_with` for it (or raised the bid it already had in)
+    OrderPlaced {
+        actor: ActorId,
+        item: ItemId,
+        pay_with: ItemId,
+        price: Quantity,
+    },
+    /// The initiating actor offered a bundle of its items for a bundle of the
+    /// partner's (often just one item each)
+    BidMade {
+        actor: ActorId,
+        partner: ActorId,
+        offer: Bundle,
+        want: Bundle,
+    },
+    /// The actor on the receiving end of a bid said what it would rather have
+    /// for its items instead: some of the bidder's items (often just one)
+    CounterOfferMade {
+        actor: ActorId,
+        partner: ActorId,
+        offer: Bundle,
+        want: Bundle,
+    },
+    /// Before bidding started, the actor and the partner worked out the worst
+    /// trade each would make for `want` (the most the actor would give, and
+    /// the least the partner would take, if there's any at all), and there's
+    /// nothing the actor would give that the partner would take, so they
+    /// broke off negotiations
+    NoZoneOfAgreement {
+        actor: ActorId,
+        partner: ActorId,
+        want: Bundle,
+        most_given: Option<Bundle>,
+        least_taken: Option<Bundle>,
+    },
+    /// A negotiation was called off: either it went on for `ticks` ticks (as
+    /// many as it's allowed, see `Simulation::set_negotiation_timeout`)
+    /// without a deal, and the actor that started it gives up on `partner`,
+    /// or the actor was waiting on bids from `partner` (if it's known who)
+    /// that nobody's making any more. Either way, both go back to looking
+    /// for a goal.
+    NegotiationExpired {
+        actor: ActorId,
+        partner: Option<ActorId>,
+        ticks: u64,
+    },
+    /// The actor on the receiving end of bids from more than one actor is
+    /// holding on to the best one so far (which beat `outbid`'s, if it was
+    /// better than someone else's), giving the others a tick to beat it
+    BidHeld {
+        actor: ActorId,
+        bidder: ActorId,
+        offer: Bundle,
+        outbid: Option<ActorId>,
+    },
+    /// A bid (or counter-offer) was rejected by one side, so bidding will
+    /// proceed upwards
+    BidRejected {
+        actor: ActorId,
+        partner: ActorId,
+        rejected_by: ActorId,
+    },
+    /// Both sides accepted a bid and swapped items
+    TradeCompleted {
+        actor: ActorId,
+        partner: ActorId,
+        gave: Bundle,
+        received: Bundle,
+    },
+    /// Nobody would trade with an actor directly, so it traded around a
+    /// circle instead: each of `actors` gave its bundle in `gave` to the next
+    /// one along, and the last to the first
+    TradeCycleCompleted {
+        actors: Vec<ActorId>,
+        gave: Vec<Bundle>,
+    },
+    /// The actor paid what it costs it to make a trade that just went
+    /// through (see `TradeCost`)
+    TradeCostPaid { actor: ActorId, cost: TradeCost },
+    /// The actor took a good it has no use for itself in a trade, because it
+    /// thinks it'll be able to trade it on for something towards `goal`.
+    /// `swapped` is the good it gave up for it, if that was only being held
+    /// to trade on too.
+    MediumAccepted {
+        actor: ActorId,
+        item: ItemId,
+        goal: GoalId,
+        swapped: Option<ItemId>,
+    },
+    /// The actor had nothing the partner would take for what it wanted, so
+    /// it borrowed `lent` instead, promising to give `owed` back on tick
+    /// `due`. The difference is interest, at a rate of `interest` a tick.
+    IouIssued {
+        debtor: ActorId,
+        creditor: ActorId,
+        lent: Bundle,
+        owed: Bundle,
+        due: u64,
+        interest: Quantity,
+    },
+    /// The depositor put `amount` in the bank, which promised to give it
+    /// back on tick `due` (see `credit::deposit`)
+    DepositMade {
+        bank: ActorId,
+        depositor: ActorId,
+        amount: Bundle,
+        due: u64,
+    },
+    /// The actor paid `items` in tax (see `Collector`)
+    TaxCollected { actor: ActorId, items: Bundle },
+    /// The actor was handed `items` out of what was collected in tax
+    TaxPaidOut { actor: ActorId, items: Bundle },
+    /// Everything in `items` that was collected in tax was destroyed
+    TaxDestroyed { items: Bundle },
+    /// The bank owes its depositors more than it holds and is owed put
+    /// together, or couldn't pay a deposit back (see `BankBook::has_failed`),
+    /// so it's stopped taking deposits and lending, and will pay its
+    /// depositors back what it can
+    BankFailed {
+        bank: ActorId,
+        owed: Quantity,
+        assets: Quantity,
+    },
+    /// A debt came due and the debtor paid it back in full
+    IouRepaid {
+        debtor: ActorId,
+        creditor: ActorId,
+        owed: Bundle,
+    },
+    /// A debt came due and the debtor didn't have everything it owed, so it
+    /// paid what it could and defaulted on the rest
+    IouDefaulted {
+        debtor: ActorId,
+        creditor: ActorId,
+        paid: Bundle,
+        unpaid: Bundle,
+    },
+    /// Two actors agreed to swap `pays` (from the buyer) for `gets` (from the
+    /// seller) on tick `due`
+    ForwardAgreed {
+        buyer: ActorId,
+        seller: ActorId,
+        pays: Bundle,
+        gets: Bundle,
+        due: u64,
+    },
+    /// A forward contract came due and both sides delivered
+    ForwardSettled { buyer: ActorId, seller: ActorId },
+    /// A forward contract came due and `defaulted` didn't have what they'd
+    /// promised any more, so the contract's off
+    ForwardDefaulted {
+        buyer: ActorId,
+        seller: ActorId,
+        defaulted: Vec<ActorId>,
+    },
+    /// `landlord` let `tenant` have the use of `leased` until tick `due`, for
+    /// `rent` every tick until then
+    LeaseAgreed {
+        landlord: ActorId,
+        tenant: ActorId,
+        leased: Bundle,
+        rent: Bundle,
+        due: u64,
+    },
+    /// A landlord turned down a lease, or the tenant did
+    LeaseRefused {
+        landlord: ActorId,
+        tenant: ActorId,
+        refused_by: ActorId,
+    },
+    /// A tenant paid a tick's rent
+    RentPaid {
+        tenant: ActorId,
+        landlord: ActorId,
+        rent: Bundle,
+    },
+    /// A lease is over, either because it was up or because the tenant
+    /// couldn't pay the rent (`defaulted`), and the tenant handed back
+    /// whatever of `leased` it still had (`returned`)
+    LeaseEnded {
+        landlord: ActorId,
+        tenant: ActorId,
+        leased: Bundle,
+        returned: Bundle,
+        defaulted: bool,
+    },
+    /// `employer` paid `worker` `wage` to work `hours` of its labor for it
+    /// this tick, making something by `recipe`
+    LaborHired {
+        employer: ActorId,
+        worker: ActorId,
+        recipe: RecipeId,
+        hours: Quantity,
+        wage: Bundle,
+    },
+    /// `worker` wouldn't give up its time for the wage `employer` offered,
+    /// so the employer will offer more, if it would pay more
+    WageRejected {
+        employer: ActorId,
+        worker: ActorId,
+        offered: Bundle,
+    },
+    /// A middleman sold a unit of `item` on to `partner` for a unit of
+    /// `sold_for`, having bought it with a unit of `bought_with` (if it
+    /// bought it at all)
+    Resold {
+        actor: ActorId,
+        partner: ActorId,
+        item: ItemId,
+        bought_with: Option<ItemId>,
+        sold_for: ItemId,
+    },
+    /// The actor gave `gift` to `to`, getting nothing back for it (this
+    /// isn't a trade, so it never counts as one)
+    GiftGiven {
+        actor: ActorId,
+        to: ActorId,
+        gift: Bundle,
+    },
+    /// `amount` of new `item` was minted and handed to the actor (see
+    /// `Simulation::mint`)
+    MoneyMinted {
+        actor: ActorId,
+        item: ItemId,
+        amount: Quantity,
+    },
+    /// The actor passed `amount` of the `item` minted on tick `minted` on to
+    /// `to`, getting `received` for it (nothing, if it wasn't a trade), so
+    /// that it's now gone through `hands` hands since it was minted (see
+    /// `MoneyTrace`)
+    NewMoneySpent {
+        actor: ActorId,
+        to: ActorId,
+        item: ItemId,
+        amount: Quantity,
+        minted: u64,
+        hands: u32,
+        received: Bundle,
+    },
+    /// The initiator has nothing left to offer, so it moves on to another actor
+    TradeAbandoned { actor: ActorId, partner: ActorId },
+    /// The actor is on the receiving end of a bid and is waiting it out
+    WaitingInBid { actor: ActorId },
+}
@@ -0,0 +1,144 @@
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::prices::PricePoint;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How far an actor moves what it expects a pair of goods to go for towards
+/// each new ratio it sees them go for, as a fraction of the gap.
+pub const ADJUSTMENT: f64 = 0.5;
+
+/// What an actor expects a pair of goods to go for, and how many trades of
+/// them that's based on.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Expectation {
+    /// How much of the pair's quote item it expects to go for each unit of
+    /// its base item (like `PricePoint::ratio`)
+    pub ratio: Quantity,
+    /// How many trades it's seen them go for each other in
+    pub seen: u32,
+}
+
+/// What an actor has come to expect goods to go for, from the trades it's
+/// seen, so it can tell a good deal from a bad one and hold out for a better
+/// one instead of taking whatever's going now. The simulation keeps it up to
+/// date from the events each tick produces, like the actor's trade ledger:
+/// every actor learns from the trades it makes itself, and one that watches
+/// the market (see `watching_market`) from everyone else's too.
+///
+/// Expectations are adaptive: the first trade of a pair sets what the actor
+/// expects, and each one after moves it part of the way (`ADJUSTMENT`)
+/// towards whatever that one went for. Like the price history, they're kept
+/// with the lower-ID item of each pair as the base.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Expectations {
+    /// What the actor expects each pair of goods it's seen traded to go for
+    #[serde(with = "crate::snapshot::pairs")]
+    ratios: HashMap<(ItemId, ItemId), Expectation>,
+    /// Whether it learns from everyone's trades, not just its own
+    #[serde(default)]
+    public: bool,
+    /// How much worse than it expects a trade can be (as a fraction) before
+    /// it would rather wait for a better one, if it ever would
+    #[serde(default)]
+    tolerance: Option<Quantity>,
+}
+
+impl Expectations {
+    /// No expectations yet, learning only from the actor's own trades and
+    /// never holding out.
+    pub fn new() -> Self {
+        Expectations::default()
+    }
+
+    /// The same, but learning from every trade anyone makes.
+    pub fn watching_market(self) -> Self {
+        Expectations {
+            public: true,
+            ..self
+        }
+    }
+
+    /// The same, but turning down trades more than `tolerance` (a fraction)
+    /// worse than it expects, to wait for a better one.
+    pub fn holding_out(self, tolerance: Quantity) -> Self {
+        Expectations {
+            tolerance: Some(tolerance),
+            ..self
+        }
+    }
+
+    /// Whether the actor learns from everyone's trades, not just its own.
+    pub fn watches_market(&self) -> bool {
+        self.public
+    }
+
+    /// How much worse than it expects a trade can be before the actor would
+    /// rather wait, if it ever would.
+    pub fn tolerance(&self) -> Option<Quantity> {
+        self.tolerance
+    }
+
+    /// Moves what the actor expects the pair in `point` to go for towards
+    /// what it just went for.
+    pub fn observe(&mut self, point: &PricePoint) {
+        let seen = self
+            .ratios
+            .entry((point.base, point.quote))
+            .or_insert(Expectation {
+                ratio: point.ratio,
+                seen: 0,
+            });
+        let (old, new) = (seen.ratio.as_f64(), point.ratio.as_f64());
+        seen.ratio = Quantity::from_f64(old + (new - old) * ADJUSTMENT);
+        seen.seen += 1;
+    }
+
+    /// How much of `b` the actor expects to go for each unit of `a`, if it's
+    /// ever seen them traded for each other.
+    pub fn expected(&self, a: ItemId, b: ItemId) -> Option<Quantity> {
+        if a <= b {
+            self.ratios.get(&(a, b)).map(|e| e.ratio)
+        } else {
+            self.ratios
+                .get(&(b, a))
+                .filter(|e| !e.ratio.is_zero())
+                .map(|e| Quantity::ONE.divided_by(e.ratio))
+        }
+    }
+
+    /// Whether the actor would rather wait than give `give` for `take`,
+    /// because it expects to get `take` for less than that (by more than its
+    /// tolerance) if it holds out. Only trades of one kind of item for
+    /// another have a ratio to go by, so it never holds out on anything
+    /// else, or on pairs it's never seen traded.
+    pub fn holds_out(&self, give: &Bundle, take: &Bundle) -> bool {
+        let tolerance = match self.tolerance {
+            Some(tolerance) => tolerance,
+            None => return false,
+        };
+        if give.len() != 1 || take.len() != 1 {
+            return false;
+        }
+        let ((gave, gave_amount), (took, took_amount)) =
+            (give.first().unwrap(), take.first().unwrap());
+        if gave == took || took_amount.is_zero() {
+            return false;
+        }
+        match self.expected(took, gave) {
+            Some(expected) => {
+                gave_amount.divided_by(took_amount) > expected.times(Quantity::ONE + tolerance)
+            }
+            None => false,
+        }
+    }
+
+    /// Every pair of goods the actor has expectations about, as base and
+    /// quote items, in order.
+    pub fn all(&self) -> Vec<((ItemId, ItemId), Expectation)> {
+        let mut all: Vec<_> = self.ratios.iter().map(|(&pair, &e)| (pair, e)).collect();
+        all.sort_by_key(|&(pair, _)| pair);
+        all
+    }
+}
@@ -0,0 +1,555 @@
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::preference_list::GoalData;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The goals every world gets unless it's given its own goals file.
+pub const DEFAULT_GOALS: &str = include_str!("../data/goals.toml");
+
+/// Copyable handle to a goal registered in a `GoalRegistry`. This is what
+/// value scales, events, and everything else hold; to find out what the goal
+/// actually is, look it up in the registry.
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct GoalId(pub usize);
+
+impl fmt::Display for GoalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "goal#{}", self.0)
+    }
+}
+
+/// Everything there is to know about a goal.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct GoalDef {
+    /// Unique name, used to refer to the goal in files and the REPL
+    pub name: String,
+    /// What the goal is, for humans
+    #[serde(default)]
+    pub description: String,
+    /// How many units of whatever satisfies the goal it takes to satisfy it
+    pub units_required: Quantity,
+    /// How many ticks it takes to come back around after it's satisfied, if
+    /// it's recurring (otherwise, once it's satisfied it's gone for good)
+    #[serde(default)]
+    pub recurrence: Option<u32>,
+    /// The chance, from 0 to 1, of the goal coming up on any given tick, if
+    /// it comes up at random (again and again, after it's satisfied) rather
+    /// than being there from the start
+    #[serde(default)]
+    pub arrival: Option<Quantity>,
+    /// Whether time off goes towards the goal, so that actors can satisfy it
+    /// just by resting (see `Labor`)
+    #[serde(default)]
+    pub leisure: bool,
+    /// Names of the goals that have to be seen to before this one comes into
+    /// play (see `ValueScale::prerequisites_met`)
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Whether each unit the goal takes is a want of its own, so the first
+    /// unit is valued above the second, the second above the third, and so
+    /// on (see `GoalRegistry::register`), rather than all of them counting
+    /// towards the one want
+    #[serde(default)]
+    pub marginal: bool,
+}
+
+/// Why a goals file couldn't be loaded.
+#[derive(Debug)]
+pub enum GoalRegistryError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML, or doesn't describe goals
+    Parse(toml::de::Error),
+    /// Two goals were given the same name
+    DuplicateGoal(String),
+    /// A goal doesn't take anything to satisfy
+    NoUnitsRequired(String),
+    /// A recurring goal comes back around on the same tick it's satisfied
+    ZeroRecurrence(String),
+    /// A random goal's chance of coming up isn't above 0 and at most 1
+    BadArrival(String),
+    /// A goal is given both a recurrence and a chance of coming up at random
+    RecurringAndRandom(String),
+    /// A goal requires a goal that doesn't exist
+    UnknownPrerequisite { goal: String, prerequisite: String },
+    /// A goal requires itself, or requires a goal that (eventually) requires
+    /// it, so it could never come into play
+    CircularPrerequisite(String),
+}
+
+impl fmt::Display for GoalRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoalRegistryError::Io(e) => write!(f, "couldn't read goals file: {}", e),
+            GoalRegistryError::Parse(e) => write!(f, "couldn't parse goals file: {}", e),
+            GoalRegistryError::DuplicateGoal(name) => {
+                write!(f, "goal {} is defined more than once", name)
+            }
+            GoalRegistryError::NoUnitsRequired(name) => {
+                write!(f, "goal {} has to require some units", name)
+            }
+            GoalRegistryError::ZeroRecurrence(name) => {
+                write!(f, "goal {} has to recur after at least a tick", name)
+            }
+            GoalRegistryError::BadArrival(name) => write!(
+                f,
+                "goal {}'s chance of coming up has to be above 0 and at most 1",
+                name
+            ),
+            GoalRegistryError::RecurringAndRandom(name) => write!(
+                f,
+                "goal {} can recur regularly or come up at random, but not both",
+                name
+            ),
+            GoalRegistryError::UnknownPrerequisite { goal, prerequisite } => write!(
+                f,
+                "goal {} requires {}, which isn't defined",
+                goal, prerequisite
+            ),
+            GoalRegistryError::CircularPrerequisite(name) => {
+                write!(f, "goal {} ends up requiring itself", name)
+            }
+        }
+    }
+}
+
+impl Error for GoalRegistryError {}
+
+impl From<io::Error> for GoalRegistryError {
+    fn from(e: io::Error) -> Self {
+        GoalRegistryError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for GoalRegistryError {
+    fn from(e: toml::de::Error) -> Self {
+        GoalRegistryError::Parse(e)
+    }
+}
+
+/// Shape of a goals file: a list of `[[goal]]` tables.
+#[derive(Deserialize)]
+struct GoalsFile {
+    #[serde(default)]
+    goal: Vec<GoalDef>,
+}
+
+/// Every goal actors in a world might pursue. Like items, goals are data, not
+/// code, so scenarios with dozens of them can be set up by editing a TOML
+/// file like this one:
+///
+/// ```toml
+/// [[goal]]
+/// name = "Eat"
+/// description = "Keep from going hungry"
+/// units_required = 2
+/// recurrence = 10
+/// ```
+///
+/// The order goals are defined in doesn't mean anything to the registry, but
+/// `hierarchy` hands them out in that order, so it's a handy place to keep a
+/// typical actor's ranking of them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GoalRegistry {
+    /// Goal definitions, indexed by `GoalId`
+    goals: Vec<GoalDef>,
+    /// Reverse lookup from goal names to their IDs
+    by_name: HashMap<String, GoalId>,
+    /// Every want each goal was registered as, by the name it was registered
+    /// under (just the one, unless it's marginal), most valued first
+    #[serde(default)]
+    wants: HashMap<String, Vec<GoalId>>,
+}
+
+impl GoalRegistry {
+    /// Construct a registry with no goals in it.
+    pub fn new() -> Self {
+        GoalRegistry::default()
+    }
+
+    /// The built-in goals (see `DEFAULT_GOALS`).
+    pub fn default_goals() -> Self {
+        GoalRegistry::from_toml(DEFAULT_GOALS).expect("built-in goals file is broken")
+    }
+
+    /// Parse a registry out of the contents of a goals file.
+    pub fn from_toml(source: &str) -> Result<Self, GoalRegistryError> {
+        let file: GoalsFile = toml::from_str(source)?;
+        let mut registry = GoalRegistry::new();
+        for def in file.goal {
+            registry.register(def)?;
+        }
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Load a registry from a goals file on disk.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GoalRegistryError> {
+        GoalRegistry::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Add a new goal, returning its ID.
+    ///
+    /// # Notes
+    ///
+    /// A marginal goal that takes `N` units is registered as `N` wants of a
+    /// unit each, named `Goal#1` to `Goal#N` and ranked in that order by
+    /// `hierarchy`, so that diminishing marginal utility falls out of the
+    /// ordinal ranking itself: once the first unit's want is seen to, the
+    /// next unit is only worth as much as the second want. The ID returned is
+    /// the first want's; look them all up by the goal's name with `wants`.
+    pub fn register(&mut self, def: GoalDef) -> Result<GoalId, GoalRegistryError> {
+        if self.by_name.contains_key(&def.name) || self.wants.contains_key(&def.name) {
+            return Err(GoalRegistryError::DuplicateGoal(def.name));
+        }
+        if def.units_required.is_zero() {
+            return Err(GoalRegistryError::NoUnitsRequired(def.name));
+        }
+        if def.recurrence == Some(0) {
+            return Err(GoalRegistryError::ZeroRecurrence(def.name));
+        }
+        match def.arrival {
+            Some(_) if def.recurrence.is_some() => {
+                return Err(GoalRegistryError::RecurringAndRandom(def.name))
+            }
+            Some(p) if p.is_zero() || p > Quantity::ONE => {
+                return Err(GoalRegistryError::BadArrival(def.name))
+            }
+            _ => {}
+        }
+        let name = def.name.clone();
+        let wants = if def.marginal {
+            let mut wants = vec![];
+            let mut left = def.units_required;
+            while !left.is_zero() {
+                let units = left.min(Quantity::ONE);
+                left = left.saturating_sub(units);
+                wants.push(GoalDef {
+                    name: format!("{}#{}", def.name, wants.len() + 1),
+                    units_required: units,
+                    marginal: false,
+                    ..def.clone()
+                });
+            }
+            wants
+        } else {
+            vec![def]
+        };
+        let ids: Vec<GoalId> = wants.into_iter().map(|want| self.add(want)).collect();
+        let first = ids[0];
+        self.wants.insert(name, ids);
+        Ok(first)
+    }
+
+    /// Push a goal that's been checked over, returning its ID.
+    fn add(&mut self, def: GoalDef) -> GoalId {
+        let id = GoalId(self.goals.len());
+        self.by_name.insert(def.name.clone(), id);
+        self.goals.push(def);
+        id
+    }
+
+    /// Check that every goal's prerequisites exist, and that none of them
+    /// (eventually) require the goal itself.
+    pub fn validate(&self) -> Result<(), GoalRegistryError> {
+        for def in self.goals.iter() {
+            for prerequisite in def.requires.iter() {
+                if self.wants(prerequisite).is_empty() {
+                    return Err(GoalRegistryError::UnknownPrerequisite {
+                        goal: def.name.clone(),
+                        prerequisite: prerequisite.clone(),
+                    });
+                }
+            }
+        }
+        for (id, def) in self.iter() {
+            let mut seen = HashSet::new();
+            let mut to_visit = self.prerequisites(id);
+            while let Some(next) = to_visit.pop() {
+                if next == id {
+                    return Err(GoalRegistryError::CircularPrerequisite(def.name.clone()));
+                }
+                if seen.insert(next) {
+                    to_visit.extend(self.prerequisites(next));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The goals that have to be seen to before a goal comes into play
+    /// (every want of any marginal ones).
+    pub fn prerequisites(&self, id: GoalId) -> Vec<GoalId> {
+        self.get(id)
+            .map(|def| def.requires.iter().flat_map(|n| self.wants(n)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Every want a goal was registered as, most valued first: just the goal
+    /// itself, unless it's marginal (see `register`). Nothing if there's no
+    /// goal by that name.
+    pub fn wants(&self, name: &str) -> Vec<GoalId> {
+        match self.wants.get(name) {
+            Some(wants) => wants.clone(),
+            None => self.id(name).into_iter().collect(),
+        }
+    }
+
+    /// The names of every marginal goal, each of which stands for all the
+    /// wants it was registered as (see `register`), sorted.
+    pub fn marginal_names(&self) -> impl Iterator<Item = &str> {
+        let mut names: Vec<&str> = self
+            .wants
+            .iter()
+            .filter(|(name, _)| !self.by_name.contains_key(*name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.into_iter()
+    }
+
+    /// Look up a goal's definition.
+    pub fn get(&self, id: GoalId) -> Option<&GoalDef> {
+        self.goals.get(id.0)
+    }
+
+    /// Whether time off goes towards a goal.
+    pub fn is_leisure(&self, id: GoalId) -> bool {
+        self.get(id).map(|def| def.leisure).unwrap_or(false)
+    }
+
+    /// A fresh `GoalData` for a goal, with nothing put towards it yet, ready
+    /// to be added to an actor's hierarchy.
+    pub fn goal_data(&self, id: GoalId) -> Option<GoalData> {
+        let def = self.get(id)?;
+        if let Some(probability) = def.arrival {
+            return Some(GoalData::Stochastic {
+                goal: id,
+                id: id.0 as i32,
+                probability,
+                units_required: def.units_required,
+                units: Quantity::ZERO,
+            });
+        }
+        Some(match def.recurrence {
+            Some(ticks) => GoalData::RegularSatisfaction {
+                goal: id,
+                id: id.0 as i32,
+                time_required: ticks as i32,
+                time: 0,
+                units_required: def.units_required,
+                units: Quantity::ZERO,
+            },
+            None => GoalData::Satisfaction {
+                goal: id,
+                id: id.0 as i32,
+                units_required: def.units_required,
+                units: Quantity::ZERO,
+            },
+        })
+    }
+
+    /// Every goal as a fresh `GoalData`, in the order they were defined in
+    /// (most valued first, if that's how the file was written).
+    pub fn hierarchy(&self) -> Vec<GoalData> {
+        self.iter()
+            .filter_map(|(id, _)| self.goal_data(id))
+            .collect()
+    }
+
+    /// Find a goal by name.
+    pub fn id(&self, name: &str) -> Option<GoalId> {
+        self.by_name.get(name).copied()
+    }
+
+    /// The name of a goal, or its raw ID if it isn't registered.
+    pub fn name(&self, id: GoalId) -> String {
+        self.get(id)
+            .map(|def| def.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Iterate over every goal in ID order, along with its ID.
+    pub fn iter(&self) -> impl Iterator<Item = (GoalId, &GoalDef)> {
+        self.goals.iter().enumerate().map(|(i, d)| (GoalId(i), d))
+    }
+
+    /// Number of goals
+    pub fn len(&self) -> usize {
+        self.goals.len()
+    }
+
+    /// Whether there are no goals at all
+    pub fn is_empty(&self) -> bool {
+        self.goals.is_empty()
+    }
+}
+
+/// A recipe for a goal ("a recurring need every T ticks, requiring U units of
+/// any of items I") that can be stamped out for a whole population of actors,
+/// each getting their own slightly different version of it, so that
+/// heterogeneous populations are easy to set up (see
+/// `ActorBuilder::template`).
+///
+/// ```
+/// use microeconomics::{Actor, GoalTemplate, Quantity, Simulation};
+///
+/// let mut sim = Simulation::with_seed(7);
+/// let food = sim.items().id("FoodUnit").unwrap();
+/// let eat = sim.goals().wants("Eat")[0];
+/// // Everyone eats 2 meals about every 10 ticks, give or take 30%
+/// let hunger = GoalTemplate::recurring(eat, 10, Quantity::units(2), vec![food])
+///     .jitter(Quantity::from_f64(0.3));
+/// for i in 0..10 {
+///     let actor = Actor::builder()
+///         .name(format!("Actor#{}", i))
+///         .template(&hunger, sim.rng())
+///         .build()
+///         .unwrap();
+///     sim.add_actor(actor);
+/// }
+/// ```
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct GoalTemplate {
+    /// The goal as it'd be with no jitter at all
+    pub goal: GoalData,
+    /// Items that satisfy the goal (any one of them will do)
+    pub items: Vec<ItemId>,
+    /// How far, as a fraction, each actor's units required, recurrence, and
+    /// chance of the goal coming up can stray from the template's, either way
+    pub jitter: Quantity,
+}
+
+impl GoalTemplate {
+    /// A template for a goal that only has to be satisfied once.
+    pub fn once(goal: GoalId, units: Quantity, items: Vec<ItemId>) -> Self {
+        GoalTemplate::from_data(
+            GoalData::Satisfaction {
+                goal,
+                units_required: units,
+                units: Quantity::ZERO,
+                id: goal.0 as i32,
+            },
+            items,
+        )
+    }
+
+    /// A template for a goal that comes back around every `every` ticks.
+    pub fn recurring(goal: GoalId, every: u32, units: Quantity, items: Vec<ItemId>) -> Self {
+        GoalTemplate::from_data(
+            GoalData::RegularSatisfaction {
+                goal,
+                time_required: every as i32,
+                time: 0,
+                units_required: units,
+                units: Quantity::ZERO,
+                id: goal.0 as i32,
+            },
+            items,
+        )
+    }
+
+    /// A template for a goal that comes up at random, with a `probability`
+    /// chance on any given tick.
+    pub fn random(
+        goal: GoalId,
+        probability: Quantity,
+        units: Quantity,
+        items: Vec<ItemId>,
+    ) -> Self {
+        GoalTemplate::from_data(
+            GoalData::Stochastic {
+                goal,
+                probability,
+                units_required: units,
+                units: Quantity::ZERO,
+                id: goal.0 as i32,
+            },
+            items,
+        )
+    }
+
+    /// A template for a goal as it's already described somewhere else (say,
+    /// by `GoalRegistry::goal_data`), with no jitter.
+    pub fn from_data(goal: GoalData, items: Vec<ItemId>) -> Self {
+        GoalTemplate {
+            goal,
+            items,
+            jitter: Quantity::ZERO,
+        }
+    }
+
+    /// Let each actor's version of the goal stray by up to `fraction` (0.2
+    /// for 20%) either way from the template's.
+    pub fn jitter(mut self, fraction: Quantity) -> Self {
+        self.jitter = fraction;
+        self
+    }
+
+    /// Stamp out one actor's version of the goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - where the jitter comes from (use `Simulation::rng` so it's
+    ///   covered by the seed)
+    ///
+    /// # Notes
+    ///
+    /// Units required that start out whole stay whole, and never go below one
+    /// (there's no satisfying half a goal with goods that don't come in
+    /// halves); recurrences are always at least a tick, and chances of coming
+    /// up stay above 0 and at most 1.
+    pub fn instantiate<R: Rng + ?Sized>(&self, rng: &mut R) -> GoalData {
+        let jitter = self.jitter.as_f64();
+        let mut scale = || {
+            if jitter > 0.0 {
+                1.0 + rng.gen_range(-jitter, jitter)
+            } else {
+                1.0
+            }
+        };
+        let mut goal = self.goal;
+        match &mut goal {
+            GoalData::Satisfaction { units_required, .. } => {
+                *units_required = jitter_units(*units_required, scale())
+            }
+            GoalData::RegularSatisfaction {
+                units_required,
+                time_required,
+                ..
+            } => {
+                *units_required = jitter_units(*units_required, scale());
+                *time_required = ((*time_required as f64 * scale()).round() as i32).max(1);
+            }
+            GoalData::Stochastic {
+                units_required,
+                probability,
+                ..
+            } => {
+                *units_required = jitter_units(*units_required, scale());
+                let p = (probability.as_f64() * scale()).min(1.0);
+                *probability = Quantity::from_f64(p).max(Quantity::EPSILON);
+            }
+        }
+        goal
+    }
+}
+
+/// Scale an amount of units, keeping whole amounts whole and at least one.
+fn jitter_units(units: Quantity, scale: f64) -> Quantity {
+    let scaled = units.as_f64() * scale;
+    if units.is_whole() {
+        Quantity::from_f64(scaled.round().max(1.0))
+    } else {
+        Quantity::from_f64(scaled).max(Quantity::EPSILON)
+    }
+}
@@ -0,0 +1,172 @@
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use crate::prices::PriceHistory;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// A means of production: some good that's only wanted for what it helps
+/// make (a producer's good, or land), or an hour of anyone's labor.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum Factor {
+    Good(ItemId),
+    Labor,
+}
+
+/// What a unit of a factor is worth, imputed back from the most valuable
+/// thing it goes into making.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ImputedValue {
+    /// How much of the quote item a unit of the factor is worth
+    pub value: Quantity,
+    /// The recipe that puts it to its most valuable use, if it's worth what
+    /// it is because of what it goes into (land is worth its yield instead)
+    pub recipe: Option<RecipeId>,
+}
+
+/// The values of the factors of production, imputed back from what
+/// consumers' goods last went for, Menger-style. Nobody wants ore, or ovens,
+/// or an hour of work for their own sake, only for the bread they go into
+/// making in the end, so they're worth whatever that is: a unit of a factor
+/// gets the value of everything that comes out of the most valuable recipe
+/// it goes into (for each unit of it that recipe takes, or each hour of
+/// labor), and land the value of its yield every tick. Once an oven has a
+/// value, the metal that goes into making it gets one from that, and so on
+/// up through the orders of goods.
+///
+/// The numbers are only as good as the prices they come from, and take no
+/// account of the other factors it takes to make anything (they're all
+/// given the whole of the product), so they're only really good for ranking
+/// factors against each other: that's what an actor's value scale does with
+/// them, too (see `ValueScale::set_imputations`).
+#[derive(PartialEq, Clone, Debug)]
+pub struct Imputation {
+    /// The item everything's valued in
+    quote: ItemId,
+    /// What each factor with a value is worth
+    values: BTreeMap<Factor, ImputedValue>,
+}
+
+impl Imputation {
+    /// Impute values to the factors of production.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the world's items and recipes
+    /// * `prices` - what consumers' goods have gone for
+    /// * `quote` - the item to value everything in
+    /// * `active` - whether a recipe should count (say, because somebody
+    ///   knows it)
+    ///
+    pub fn new<F: Fn(RecipeId) -> bool>(
+        items: &ItemRegistry,
+        prices: &PriceHistory,
+        quote: ItemId,
+        active: F,
+    ) -> Self {
+        let mut prices_of: BTreeMap<ItemId, Quantity> = items
+            .iter()
+            .filter(|(_, def)| def.order.is_consumer_good() && !def.class.is_land())
+            .filter_map(|(id, _)| prices.price_of(id, quote).map(|price| (id, price)))
+            .collect();
+        let mut values = BTreeMap::new();
+        for (id, _) in items.iter().filter(|(_, def)| def.class.is_land()) {
+            let value: Quantity = items
+                .yields(id)
+                .into_iter()
+                .filter_map(|(item, amount)| prices_of.get(&item).map(|&p| amount.times(p)))
+                .sum();
+            if !value.is_zero() {
+                values.insert(
+                    Factor::Good(id),
+                    ImputedValue {
+                        value,
+                        recipe: None,
+                    },
+                );
+            }
+        }
+        // Every pass values goods one order further up than the last, so
+        // there can't be more passes than there are items.
+        for _ in 0..items.len() {
+            let mut changed = false;
+            for (id, recipe) in items.recipes().filter(|&(id, _)| active(id)) {
+                let product: Quantity = recipe
+                    .outputs
+                    .iter()
+                    .filter_map(|(item, amount)| prices_of.get(&item).map(|&p| amount.times(p)))
+                    .sum();
+                if product.is_zero() {
+                    continue;
+                }
+                let hours = recipe.labor.times(Quantity::from(recipe.duration));
+                let factors = recipe
+                    .inputs
+                    .iter()
+                    .chain(recipe.tools.iter())
+                    .filter(|&(item, _)| !items.get(item).unwrap().order.is_consumer_good())
+                    .map(|(item, amount)| (Factor::Good(item), amount))
+                    .chain(std::iter::once((Factor::Labor, hours)));
+                for (factor, amount) in factors {
+                    if amount.is_zero() {
+                        continue;
+                    }
+                    let value = product.divided_by(amount);
+                    let better = values
+                        .get(&factor)
+                        .is_none_or(|old: &ImputedValue| old.value < value);
+                    if better {
+                        values.insert(
+                            factor,
+                            ImputedValue {
+                                value,
+                                recipe: Some(id),
+                            },
+                        );
+                        if let Factor::Good(item) = factor {
+                            prices_of.insert(item, value);
+                        }
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Imputation { quote, values }
+    }
+
+    /// The item everything's valued in.
+    pub fn quote(&self) -> ItemId {
+        self.quote
+    }
+
+    /// What a unit of a factor is worth, if anything it goes into making
+    /// has gone for anything.
+    pub fn value_of(&self, factor: Factor) -> Option<ImputedValue> {
+        self.values.get(&factor).copied()
+    }
+
+    /// Every factor with a value, most valuable first.
+    pub fn values(&self) -> Vec<(Factor, ImputedValue)> {
+        self.rank(self.values.keys().copied())
+    }
+
+    /// Some factors (the ones an actor has, say) in order of how much a
+    /// unit of each is worth, most valuable first. Ones that aren't worth
+    /// anything that can be told are left out.
+    pub fn rank<I: IntoIterator<Item = Factor>>(&self, factors: I) -> Vec<(Factor, ImputedValue)> {
+        let mut ranked: Vec<_> = factors
+            .into_iter()
+            .filter_map(|factor| self.value_of(factor).map(|value| (factor, value)))
+            .collect();
+        ranked.sort_by(
+            |(a, a_value), (b, b_value)| match b_value.value.cmp(&a_value.value) {
+                Ordering::Equal => a.cmp(b),
+                ordering => ordering,
+            },
+        );
+        ranked
+    }
+}
@@ -0,0 +1,98 @@
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+
+/// Some amount of each of several items, offered or asked for all together
+/// in a trade, so that it either all changes hands or none of it does. A trade
+/// of one thing for another is just a trade of two bundles with one item
+/// each.
+///
+/// Items are kept in the order they were added (the first is the "main" one,
+/// for printouts), and an item added twice is only listed once, with the
+/// amounts added together. Nothing is ever listed with an amount of zero.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Bundle {
+    items: Vec<(ItemId, Quantity)>,
+}
+
+impl Bundle {
+    /// A bundle with nothing in it.
+    pub fn new() -> Self {
+        Bundle::default()
+    }
+
+    /// A bundle of just one item.
+    pub fn single(item: ItemId, amount: Quantity) -> Self {
+        Bundle::new().with(item, amount)
+    }
+
+    /// Add some of an item to the bundle.
+    pub fn add(&mut self, item: ItemId, amount: Quantity) {
+        if amount.is_zero() {
+            return;
+        }
+        match self.items.iter_mut().find(|(i, _)| *i == item) {
+            Some((_, existing)) => *existing += amount,
+            None => self.items.push((item, amount)),
+        }
+    }
+
+    /// This bundle with some of an item added.
+    pub fn with(mut self, item: ItemId, amount: Quantity) -> Self {
+        self.add(item, amount);
+        self
+    }
+
+    /// How much of an item is in the bundle.
+    pub fn count(&self, item: ItemId) -> Quantity {
+        self.items
+            .iter()
+            .find(|(i, _)| *i == item)
+            .map(|&(_, amount)| amount)
+            .unwrap_or(Quantity::ZERO)
+    }
+
+    /// Whether there's any of an item in the bundle
+    pub fn contains(&self, item: ItemId) -> bool {
+        self.items.iter().any(|(i, _)| *i == item)
+    }
+
+    /// The first item added to the bundle, and how much of it there is.
+    pub fn first(&self) -> Option<(ItemId, Quantity)> {
+        self.items.first().copied()
+    }
+
+    /// Every item in the bundle and how much of it there is, in the order
+    /// they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, Quantity)> + '_ {
+        self.items.iter().copied()
+    }
+
+    /// Everything in the bundle that matches a predicate, as a bundle of its
+    /// own.
+    pub fn filter<F: Fn(ItemId) -> bool>(&self, keep: F) -> Self {
+        Bundle {
+            items: self
+                .items
+                .iter()
+                .copied()
+                .filter(|&(i, _)| keep(i))
+                .collect(),
+        }
+    }
+
+    /// How much there is in the bundle, all told
+    pub fn total(&self) -> Quantity {
+        self.items.iter().map(|&(_, amount)| amount).sum()
+    }
+
+    /// Number of kinds of item in the bundle
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether there's nothing in the bundle at all
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
@@ -1,15 +1,74 @@
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
-pub enum Item {
-    FoodUnit,
-    HouseUnit,
-    LeisureUnit1,
-    LeisureUnit2,
-}
-
-#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug)]
-pub enum Goal {
-    Eat,
-    Shelter,
-    Rest,
-    Leisure,
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Menger's classification of goods by how far removed they are from
+/// satisfying anyone's wants. Goods of the first order (bread) satisfy wants
+/// directly; goods of the second order (flour, ovens) are only good for
+/// producing goods of the first order, goods of the third order (wheat,
+/// mills) for producing those of the second, and so on. Everything above the
+/// first order is a producer's (capital) good, and only has value because of
+/// the value of what it helps produce.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Order(pub u8);
+
+impl Order {
+    /// Consumers' goods
+    pub const FIRST: Order = Order(1);
+
+    /// Whether goods of this order satisfy wants directly
+    pub fn is_consumer_good(self) -> bool {
+        self.0 <= 1
+    }
+
+    /// Whether goods of this order are producers' goods (tools, raw
+    /// materials, and so on)
+    pub fn is_capital_good(self) -> bool {
+        !self.is_consumer_good()
+    }
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::FIRST
+    }
+}
+
+/// Whether an item is an ordinary good, wanted for what it can do, money,
+/// wanted only for what it can be exchanged for, or land.
+///
+/// Money is always divisible, never spoils or wears out, and is never used up
+/// satisfying a goal; it's fungible, so any amount of it is as good as the
+/// same amount of any other. Land is the original, non-reproducible factor of
+/// production: there's a fixed supply of it that can never be added to, only
+/// change hands, and whoever holds it gets its yield every tick.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemClass {
+    #[default]
+    Good,
+    Money,
+    Land,
+}
+
+impl ItemClass {
+    /// Whether items of this class are money
+    pub fn is_money(self) -> bool {
+        self == ItemClass::Money
+    }
+
+    /// Whether items of this class are land
+    pub fn is_land(self) -> bool {
+        self == ItemClass::Land
+    }
+}
+
+impl fmt::Display for ItemClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemClass::Good => write!(f, "a good"),
+            ItemClass::Money => write!(f, "money"),
+            ItemClass::Land => write!(f, "land"),
+        }
+    }
 }
@@ -0,0 +1,534 @@
+use crate::arena::ActorId;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// The goods an actor holds, kept as an amount per kind of item rather than
+/// one entry per unit, so holding 500 `FoodUnit`s costs the same as holding
+/// one, and divisible goods can be held in fractions.
+///
+/// The counts themselves are in no particular order; anything that needs to go
+/// through the inventory in order of value should use `sorted_by` (or
+/// `ValueScale::order_inventory`).
+///
+/// Units aren't all alike, though: the inventory also keeps track of each lot
+/// of an item (units that are the same in every respect: how old they are,
+/// who they came from, and when), so that perishable goods can go off (see
+/// `age`) and anyone who cares can see where their goods came from. Lots are
+/// always used up or handed over oldest first, and keep their history when
+/// they change hands (see `transfer`).
+///
+/// Durable goods that have been put to use are still held, but they're tied
+/// up serving a goal (see `put_in_service`), so they don't count as being in
+/// the inventory proper: they can't be used for anything else or traded away
+/// until they wear out.
+///
+/// Goods can also be set aside for a production plan that's under way (see
+/// `reserve`), so the actor doesn't use them up on something else or trade
+/// them away before it gets to the stage that needs them, or saved for a goal
+/// that's coming back around (see `ActorRef::savings`). Reserved goods are
+/// still in the inventory proper, and still count, but only what's spare
+/// (see `spare`) is up for grabs.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    /// How much of each kind of item there is (never zero)
+    counts: HashMap<ItemId, Quantity>,
+    /// Lots of each item that came in at different times, oldest first
+    #[serde(default)]
+    lots: HashMap<ItemId, VecDeque<Lot>>,
+    /// Durable goods currently in use, in the order they were put to use
+    #[serde(default)]
+    in_service: Vec<InService>,
+    /// Most the inventory can hold in total, if there's a limit
+    #[serde(default)]
+    capacity: Option<Quantity>,
+    /// For each tool that wears out, how many ticks of work the one being
+    /// used has put in since it was new (or last maintained)
+    #[serde(default)]
+    tool_wear: HashMap<ItemId, u32>,
+    /// Goods set aside for the production plan under way for each goal, or
+    /// saved for it
+    #[serde(default)]
+    reserved: BTreeMap<GoalId, Bundle>,
+}
+
+/// A durable good that's been put to use for a goal, which it keeps serving
+/// every tick until it wears out.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct InService {
+    /// What the good is
+    pub item: ItemId,
+    /// How much of it is tied up
+    pub amount: Quantity,
+    /// The goal it's serving
+    pub goal: GoalId,
+    /// How many more ticks it'll serve for before it wears out
+    pub ticks_left: u32,
+    /// How many ticks it's served for so far
+    #[serde(default)]
+    pub worn: u32,
+    /// Where the actor got it from
+    #[serde(default)]
+    pub acquired: Acquisition,
+}
+
+/// Who some goods were got from, and when.
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct Acquisition {
+    /// The actor they were got from (`None` if they were made, found, or
+    /// handed out rather than traded for)
+    pub from: Option<ActorId>,
+    /// The tick they were got on
+    pub tick: u64,
+}
+
+/// Some amount of an item whose units all have the same history, so there's
+/// nothing to tell them apart by.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Lot {
+    /// How much of the item there is
+    pub amount: Quantity,
+    /// Ticks it's been kept for, by anyone (only counted for perishable
+    /// goods; for anything else, see `acquired`)
+    pub age: u32,
+    /// Where the current holder got it from
+    #[serde(default)]
+    pub acquired: Acquisition,
+}
+
+impl Lot {
+    /// Whether two lots are the same in every respect but their amounts
+    fn alike(&self, other: &Lot) -> bool {
+        self.age == other.age && self.acquired == other.acquired
+    }
+}
+
+impl Inventory {
+    /// Construct an empty inventory.
+    pub fn new() -> Self {
+        Inventory::default()
+    }
+
+    /// Add some amount of an item, fresh, from nowhere in particular, as of
+    /// the very start of the simulation (for setting up endowments).
+    pub fn add(&mut self, item: ItemId, amount: Quantity) {
+        self.add_at(item, amount, Acquisition::default());
+    }
+
+    /// Add some amount of an item, fresh, acquired as described.
+    pub fn add_at(&mut self, item: ItemId, amount: Quantity, acquired: Acquisition) {
+        self.add_lot(
+            item,
+            Lot {
+                amount,
+                age: 0,
+                acquired,
+            },
+        );
+    }
+
+    /// Add a lot of an item, keeping the lots oldest first.
+    fn add_lot(&mut self, item: ItemId, lot: Lot) {
+        if lot.amount.is_zero() {
+            return;
+        }
+        *self.counts.entry(item).or_default() += lot.amount;
+        let lots = self.lots.entry(item).or_default();
+        if let Some(same) = lots.iter_mut().find(|l| l.alike(&lot)) {
+            same.amount += lot.amount;
+            return;
+        }
+        match lots.iter().position(|l| l.age < lot.age) {
+            Some(i) => lots.insert(i, lot),
+            None => lots.push_back(lot),
+        }
+    }
+
+    /// Take lots adding up to `amount` off an item, oldest first. Returns
+    /// what was taken (which may be less than `amount`, if lots for the item
+    /// aren't being kept).
+    fn take_lots(&mut self, item: ItemId, mut amount: Quantity) -> Vec<Lot> {
+        let mut taken = vec![];
+        if let Some(lots) = self.lots.get_mut(&item) {
+            while !amount.is_zero() {
+                let oldest = match lots.front_mut() {
+                    Some(oldest) => oldest,
+                    None => break,
+                };
+                let part = oldest.amount.min(amount);
+                oldest.amount -= part;
+                amount -= part;
+                taken.push(Lot {
+                    amount: part,
+                    ..*oldest
+                });
+                if oldest.amount.is_zero() {
+                    lots.pop_front();
+                }
+            }
+            if lots.is_empty() {
+                self.lots.remove(&item);
+            }
+        }
+        taken
+    }
+
+    /// Take some amount of an item out, if there's that much. Returns whether
+    /// it was there to take (if not, nothing is taken).
+    pub fn remove(&mut self, item: ItemId, amount: Quantity) -> bool {
+        if self.count(item) < amount {
+            return false;
+        }
+        self.take_lots(item, amount);
+        self.take_count(item, amount);
+        true
+    }
+
+    /// Knock some amount off an item's count, which there has to be enough of.
+    fn take_count(&mut self, item: ItemId, amount: Quantity) {
+        if let Some(have) = self.counts.get_mut(&item) {
+            *have -= amount;
+            if have.is_zero() {
+                self.counts.remove(&item);
+                self.tool_wear.remove(&item);
+            }
+        }
+        // Whatever's gone can't be kept for anything any more
+        let mut over = self.reserved(item).saturating_sub(self.count(item));
+        for reserved in self.reserved.values_mut() {
+            if over.is_zero() {
+                break;
+            }
+            let cut = over.min(reserved.count(item));
+            *reserved = reserved
+                .iter()
+                .map(|(i, amount)| (i, if i == item { amount - cut } else { amount }))
+                .fold(Bundle::new(), |all, (i, amount)| all.with(i, amount));
+            over -= cut;
+        }
+        self.reserved.retain(|_, reserved| !reserved.is_empty());
+    }
+
+    /// Move some amount of an item into another inventory, if there's that
+    /// much, as handed over by actor `from` on tick `tick`. Unlike taking it
+    /// out and adding it there, this keeps track of how old it is, so
+    /// perishable goods don't come out of a trade any fresher than they went
+    /// in.
+    pub fn transfer(
+        &mut self,
+        to: &mut Inventory,
+        item: ItemId,
+        amount: Quantity,
+        from: ActorId,
+        tick: u64,
+    ) -> bool {
+        if self.count(item) < amount {
+            return false;
+        }
+        let acquired = Acquisition {
+            from: Some(from),
+            tick,
+        };
+        let lots = self.take_lots(item, amount);
+        self.take_count(item, amount);
+        let moved: Quantity = lots.iter().map(|l| l.amount).sum();
+        for lot in lots {
+            to.add_lot(item, Lot { acquired, ..lot });
+        }
+        to.add_at(item, amount - moved, acquired);
+        true
+    }
+
+    /// Move some amount of an item into another inventory just as it is, if
+    /// there's that much, keeping where it was got from and when. This is for
+    /// holding goods aside (see `Escrow`) rather than handing them over.
+    pub fn set_aside(&mut self, to: &mut Inventory, item: ItemId, amount: Quantity) -> bool {
+        if self.count(item) < amount {
+            return false;
+        }
+        let lots = self.take_lots(item, amount);
+        self.take_count(item, amount);
+        let moved: Quantity = lots.iter().map(|l| l.amount).sum();
+        for lot in lots {
+            to.add_lot(item, lot);
+        }
+        to.add(item, amount - moved);
+        true
+    }
+
+    /// Every lot of an item held, oldest first.
+    pub fn lots(&self, item: ItemId) -> impl Iterator<Item = &Lot> + '_ {
+        self.lots.get(&item).into_iter().flatten()
+    }
+
+    /// Let a tick go by, spoiling anything that's been held longer than its
+    /// shelf life. Returns how much of each item spoiled (in item order).
+    ///
+    /// # Arguments
+    ///
+    /// * `shelf_life` - how many ticks an item keeps for, or `None` if it
+    ///   keeps forever (usually `ItemRegistry::shelf_life`)
+    ///
+    pub fn age<F: Fn(ItemId) -> Option<u32>>(&mut self, shelf_life: F) -> Vec<(ItemId, Quantity)> {
+        let mut spoiled = vec![];
+        let counts = &mut self.counts;
+        self.lots.retain(|&item, lots| {
+            let keeps_for = match shelf_life(item) {
+                Some(ticks) => ticks,
+                // No need to count the age of things that never go off
+                None => return true,
+            };
+            let mut gone = Quantity::ZERO;
+            for lot in lots.iter_mut() {
+                lot.age += 1;
+            }
+            while lots.front().map(|l| l.age >= keeps_for).unwrap_or(false) {
+                gone += lots.pop_front().unwrap().amount;
+            }
+            if !gone.is_zero() {
+                if let Some(have) = counts.get_mut(&item) {
+                    *have -= gone;
+                    if have.is_zero() {
+                        counts.remove(&item);
+                    }
+                }
+                spoiled.push((item, gone));
+            }
+            !lots.is_empty()
+        });
+        spoiled.sort();
+        spoiled
+    }
+
+    /// How much of an item there is.
+    pub fn count(&self, item: ItemId) -> Quantity {
+        self.counts.get(&item).copied().unwrap_or_default()
+    }
+
+    /// Whether there's any of an item at all (even a fraction of a unit).
+    pub fn contains(&self, item: ItemId) -> bool {
+        self.counts.contains_key(&item)
+    }
+
+    /// Every kind of item held, along with how many, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, Quantity)> + '_ {
+        self.counts.iter().map(|(&item, &count)| (item, count))
+    }
+
+    /// Every kind of item held, sorted with `compare` (ties are broken by
+    /// item ID, so the order is always the same).
+    pub fn sorted_by<F: Fn(ItemId, ItemId) -> Ordering>(&self, compare: F) -> Vec<ItemId> {
+        let mut kinds: Vec<_> = self.counts.keys().copied().collect();
+        kinds.sort_by(|&a, &b| compare(a, b).then(a.cmp(&b)));
+        kinds
+    }
+
+    /// Tie some of an item up serving a goal for a number of ticks. Returns
+    /// whether there was enough of it to do so.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - durable good to put to use
+    /// * `amount` - how much of it
+    /// * `goal` - goal it'll serve
+    /// * `ticks` - how many more ticks it'll serve for
+    ///
+    pub fn put_in_service(
+        &mut self,
+        item: ItemId,
+        amount: Quantity,
+        goal: GoalId,
+        ticks: u32,
+    ) -> bool {
+        if self.count(item) < amount {
+            return false;
+        }
+        let acquired = self
+            .lots(item)
+            .next()
+            .map(|lot| lot.acquired)
+            .unwrap_or_default();
+        self.remove(item, amount);
+        if ticks > 0 {
+            self.in_service.push(InService {
+                item,
+                amount,
+                goal,
+                ticks_left: ticks,
+                worn: 1,
+                acquired,
+            });
+        }
+        true
+    }
+
+    /// Stop using some amount of a durable good, and take it out of the
+    /// inventory altogether (for handing back to whoever it belongs to).
+    /// Returns whether there was that much of it in use. Wear is only
+    /// tracked while it's in use, so it comes out good as new.
+    pub fn take_out_of_service(&mut self, item: ItemId, amount: Quantity) -> bool {
+        match self
+            .in_service
+            .iter()
+            .position(|s| s.item == item && s.amount >= amount)
+        {
+            Some(n) => {
+                self.in_service[n].amount -= amount;
+                if self.in_service[n].amount.is_zero() {
+                    self.in_service.remove(n);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every durable good currently in use.
+    pub fn in_service(&self) -> &[InService] {
+        &self.in_service
+    }
+
+    /// Whether any durable good is currently serving a goal.
+    pub fn is_serving(&self, goal: GoalId) -> bool {
+        self.in_service.iter().any(|s| s.goal == goal)
+    }
+
+    /// Let a tick go by for the durable goods in use. Returns every one that
+    /// served this tick, with the ticks it has left after this one; the ones
+    /// with none left have worn out and are gone.
+    pub fn wear(&mut self) -> Vec<InService> {
+        for s in self.in_service.iter_mut() {
+            s.ticks_left -= 1;
+            s.worn += 1;
+        }
+        let served = self.in_service.clone();
+        self.in_service.retain(|s| s.ticks_left > 0);
+        served
+    }
+
+    /// How many ticks of work the tool of a kind being used has put in
+    /// since it was new (or last maintained).
+    pub fn tool_wear(&self, item: ItemId) -> u32 {
+        self.tool_wear.get(&item).copied().unwrap_or(0)
+    }
+
+    /// Put a tick's work into a tool that lasts `durability` ticks of it,
+    /// if there's one there. Returns whether that wore it out, in which case
+    /// it's gone, and the next one of its kind (if there is one) is new.
+    pub fn use_tool(&mut self, item: ItemId, durability: u32) -> bool {
+        if self.count(item) < Quantity::ONE {
+            return false;
+        }
+        let wear = self.tool_wear.entry(item).or_insert(0);
+        *wear += 1;
+        if *wear < durability {
+            return false;
+        }
+        self.tool_wear.remove(&item);
+        self.remove(item, Quantity::ONE);
+        true
+    }
+
+    /// Put the tool of a kind being used back as good as new.
+    pub fn repair_tool(&mut self, item: ItemId) {
+        self.tool_wear.remove(&item);
+    }
+
+    /// Set goods aside for the production plan under way for a goal, in
+    /// place of whatever was set aside for it before: as much of each as is
+    /// held and isn't already set aside for something else. Returns what
+    /// was set aside.
+    pub fn reserve(&mut self, goal: GoalId, goods: &Bundle) -> Bundle {
+        self.release(goal);
+        let reserved = goods
+            .iter()
+            .map(|(item, amount)| (item, amount.min(self.spare(item))))
+            .fold(Bundle::new(), |all, (item, amount)| all.with(item, amount));
+        if !reserved.is_empty() {
+            self.reserved.insert(goal, reserved.clone());
+        }
+        reserved
+    }
+
+    /// Stop setting anything aside for a goal (once the plan for it is done
+    /// with, one way or another). Returns what had been set aside.
+    pub fn release(&mut self, goal: GoalId) -> Bundle {
+        self.reserved.remove(&goal).unwrap_or_default()
+    }
+
+    /// How much of an item is set aside, for every goal.
+    pub fn reserved(&self, item: ItemId) -> Quantity {
+        self.reserved.values().map(|goods| goods.count(item)).sum()
+    }
+
+    /// Everything set aside, by the goal it's set aside for.
+    pub fn reservations(&self) -> impl Iterator<Item = (GoalId, &Bundle)> + '_ {
+        self.reserved.iter().map(|(&goal, goods)| (goal, goods))
+    }
+
+    /// How much of an item isn't set aside for anything.
+    pub fn spare(&self, item: ItemId) -> Quantity {
+        self.count(item).saturating_sub(self.reserved(item))
+    }
+
+    /// How much of an item could go towards a goal: whatever isn't set
+    /// aside for anything else.
+    pub fn spare_for(&self, item: ItemId, goal: GoalId) -> Quantity {
+        let elsewhere: Quantity = self
+            .reserved
+            .iter()
+            .filter(|&(&g, _)| g != goal)
+            .map(|(_, goods)| goods.count(item))
+            .sum();
+        self.count(item).saturating_sub(elsewhere)
+    }
+
+    /// Limit how much the inventory can hold in total (or lift the limit).
+    /// Nothing's thrown out if it's already holding more than that; it's up
+    /// to the owner to decide what goes (see `ActorMut::discard_overflow`).
+    pub fn set_capacity(&mut self, capacity: Option<Quantity>) {
+        self.capacity = capacity;
+    }
+
+    /// Most the inventory can hold in total, if there's a limit.
+    pub fn capacity(&self) -> Option<Quantity> {
+        self.capacity
+    }
+
+    /// How much more is being held than there's room for.
+    pub fn overflow(&self) -> Quantity {
+        match self.capacity {
+            Some(capacity) => self.total().saturating_sub(capacity),
+            None => Quantity::ZERO,
+        }
+    }
+
+    /// Number of different kinds of item held
+    pub fn kinds(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Total amount held, of every kind
+    pub fn total(&self) -> Quantity {
+        self.counts.values().copied().sum()
+    }
+
+    /// Whether there's nothing in here at all
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+impl Extend<ItemId> for Inventory {
+    fn extend<I: IntoIterator<Item = ItemId>>(&mut self, items: I) {
+        for item in items {
+            self.add(item, Quantity::ONE);
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod bundle;
 pub mod discretes;
+pub mod inventory;
 pub mod production;
+pub mod quantity;
+pub mod registry;
@@ -0,0 +1,166 @@
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use crate::labor::Work;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Copyable handle to a recipe registered in an `ItemRegistry`, like an
+/// `ItemId` is to an item.
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct RecipeId(pub usize);
+
+impl fmt::Display for RecipeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "recipe#{}", self.0)
+    }
+}
+
+/// The most stages a production plan can have (see `ProductionPlan`), so
+/// actors don't spend forever working out how to make the things they need
+/// to make the things they need.
+pub const MAX_STAGES: usize = 4;
+
+/// A way of making some goods out of others: an actor that has all of the
+/// inputs (and tools) can put them in and work on them for so many ticks,
+/// spending so many hours of labor each tick, and gets the outputs at the
+/// end (all of them, whether it was after them all or not). The inputs are
+/// used up as soon as it starts, so there's no taking them back out half way
+/// through; the tools aren't used up at all.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Recipe {
+    /// Unique name, used to refer to the recipe in files and the REPL
+    pub name: String,
+    /// What goes in
+    pub inputs: Bundle,
+    /// What has to be to hand to do the work, without being used up
+    #[serde(default)]
+    pub tools: Bundle,
+    /// What comes out
+    pub outputs: Bundle,
+    /// Hours of labor each tick of work takes
+    pub labor: Quantity,
+    /// How many ticks of work it takes
+    pub duration: u32,
+    /// If it isn't common knowledge, the chance of finding out how to make
+    /// things by it for every hour spent experimenting (see `KnowHow`)
+    #[serde(default)]
+    pub discovery: Option<Quantity>,
+}
+
+/// A recipe as it appears in an items file, with its goods given by name:
+///
+/// ```toml
+/// [[recipe]]
+/// name = "Baking"
+/// inputs = { Grain = 1 }
+/// outputs = { FoodUnit = 2 }
+/// duration = 1
+/// ```
+///
+/// Tools, which are needed but not used up, are listed under `tools` the
+/// same way. `labor` is how many hours each tick of work takes, and
+/// defaults to
+/// however long production work usually does (see `Work::hours`);
+/// `duration` defaults to a single tick. A recipe with a `discovery` chance
+/// isn't known to anyone at first: actors have to find it out for
+/// themselves, or learn it from someone who has.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RecipeDef {
+    /// Unique name for the recipe
+    pub name: String,
+    /// How much of each good goes in, by name
+    #[serde(default)]
+    pub inputs: BTreeMap<String, Quantity>,
+    /// How much of each good has to be to hand, by name
+    #[serde(default)]
+    pub tools: BTreeMap<String, Quantity>,
+    /// How much of each good comes out, by name
+    pub outputs: BTreeMap<String, Quantity>,
+    /// Hours of labor each tick of work takes
+    #[serde(default = "production_hours")]
+    pub labor: Quantity,
+    /// How many ticks of work it takes
+    #[serde(default = "one_tick")]
+    pub duration: u32,
+    /// Chance of discovering it for every hour spent experimenting, if it
+    /// has to be discovered
+    #[serde(default)]
+    pub discovery: Option<Quantity>,
+}
+
+impl Recipe {
+    /// Everything that has to be to hand to start: the inputs and the tools.
+    pub fn needs(&self) -> impl Iterator<Item = (ItemId, Quantity)> + '_ {
+        self.inputs.iter().chain(self.tools.iter())
+    }
+}
+
+/// A way for an actor to get something it's after by making it, maybe in
+/// more than one stage: each recipe in `stages` makes something the next
+/// one needs (an input, or a tool), and the last one makes what's wanted.
+/// Longer, more roundabout ways of making things (making a tool first, say)
+/// can make more in the end, but they take longer to pay off, so which
+/// plan an actor goes for comes down to its time preference (see
+/// `present_value`). A recipe can make more than one thing at once, so a
+/// plan can leave the actor with byproducts on top of what it's after,
+/// which it'll have to find a use for, trade away, or throw out.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct ProductionPlan {
+    /// The recipes to work through, in order
+    pub stages: Vec<RecipeId>,
+    /// How many ticks of work they take, all told
+    pub ticks: u32,
+    /// How much of what's wanted comes out at the end
+    pub output: Quantity,
+    /// Whatever else comes out along the way
+    #[serde(default)]
+    pub byproducts: Bundle,
+}
+
+impl ProductionPlan {
+    /// What the plan's output is worth now to an actor with the given time
+    /// preference (see `ValueScale::time_preference`): the output, less a
+    /// premium of the time preference for every tick it takes to make, the
+    /// same way a lender works out what it wants back (see
+    /// `credit::repayment`).
+    pub fn present_value(&self, time_preference: Quantity) -> Quantity {
+        self.output
+            .divided_by(Quantity::ONE + time_preference.times(Quantity::from(self.ticks)))
+    }
+
+    /// Everything the stages after the first take (inputs and tools) that
+    /// no stage before them makes: what has to be kept for them while the
+    /// first one's under way.
+    pub fn later_needs(&self, items: &ItemRegistry) -> Bundle {
+        let mut needs = Bundle::new();
+        let mut made = Bundle::new();
+        for (n, recipe) in self
+            .stages
+            .iter()
+            .filter_map(|&id| items.recipe(id))
+            .enumerate()
+        {
+            if n > 0 {
+                for (item, amount) in recipe.needs().filter(|&(item, _)| !made.contains(item)) {
+                    needs.add(item, amount);
+                }
+            }
+            for (item, amount) in recipe.outputs.iter() {
+                made.add(item, amount);
+            }
+        }
+        needs
+    }
+}
+
+fn production_hours() -> Quantity {
+    Work::Production.hours()
+}
+
+fn one_tick() -> u32 {
+    1
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// How many thousandths of a unit make up a whole unit
+const SCALE: u64 = 1000;
+
+/// An amount of some item. Most goods only ever come in whole units, but
+/// divisible ones (water, grain, cloth) can be held, used, and traded in
+/// fractions, like 2.5 litres of water.
+///
+/// Amounts are kept as a whole number of thousandths of a unit rather than as
+/// a float, so that adding and taking away is exact, amounts can be compared
+/// and hashed, and a run with a given seed always comes out the same. In
+/// files they're written as plain numbers (`2.5`).
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+#[serde(from = "f64", into = "f64")]
+pub struct Quantity(u64);
+
+impl Quantity {
+    /// Nothing at all
+    pub const ZERO: Quantity = Quantity(0);
+    /// A single whole unit
+    pub const ONE: Quantity = Quantity(SCALE);
+    /// The smallest amount that can be told apart from nothing
+    pub const EPSILON: Quantity = Quantity(1);
+
+    /// A whole number of units.
+    pub fn units(units: u32) -> Self {
+        Quantity(units as u64 * SCALE)
+    }
+
+    /// Convert from a float, rounding to the nearest thousandth of a unit.
+    /// Negative (and NaN) amounts become zero.
+    pub fn from_f64(amount: f64) -> Self {
+        if amount.is_nan() || amount <= 0.0 {
+            Quantity::ZERO
+        } else {
+            Quantity((amount * SCALE as f64).round() as u64)
+        }
+    }
+
+    /// This amount as a float, for display and statistics.
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    /// Whether this is nothing at all
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether this is a whole number of units
+    pub fn is_whole(self) -> bool {
+        self.0.is_multiple_of(SCALE)
+    }
+
+    /// This amount scaled by a factor (itself given as an amount, so scaling
+    /// by `Quantity::ONE` leaves it as it is), rounding down.
+    pub fn times(self, factor: Quantity) -> Self {
+        Quantity((self.0 as u128 * factor.0 as u128 / SCALE as u128) as u64)
+    }
+
+    /// The amount that, scaled by `factor`, comes to at least this one (the
+    /// opposite of `times`, rounding up). Dividing by nothing gives nothing.
+    pub fn divided_by(self, factor: Quantity) -> Self {
+        if factor.is_zero() {
+            return Quantity::ZERO;
+        }
+        let scaled = self.0 as u128 * SCALE as u128;
+        Quantity(scaled.div_ceil(factor.0 as u128) as u64)
+    }
+
+    /// This amount rounded down to a whole number of units.
+    pub fn whole(self) -> Self {
+        Quantity(self.0 - self.0 % SCALE)
+    }
+
+    /// Take `other` away, stopping at zero instead of going negative.
+    pub fn saturating_sub(self, other: Quantity) -> Self {
+        Quantity(self.0.saturating_sub(other.0))
+    }
+}
+
+impl From<u32> for Quantity {
+    fn from(units: u32) -> Self {
+        Quantity::units(units)
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(amount: f64) -> Self {
+        Quantity::from_f64(amount)
+    }
+}
+
+impl From<Quantity> for f64 {
+    fn from(q: Quantity) -> Self {
+        q.as_f64()
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+
+    fn add(self, other: Quantity) -> Quantity {
+        Quantity(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Quantity {
+    fn add_assign(&mut self, other: Quantity) {
+        self.0 += other.0;
+    }
+}
+
+// Like the integer types, this panics (in debug builds) if it would go
+// negative; use `saturating_sub` when that's a possibility
+impl Sub for Quantity {
+    type Output = Quantity;
+
+    fn sub(self, other: Quantity) -> Quantity {
+        Quantity(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Quantity {
+    fn sub_assign(&mut self, other: Quantity) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Quantity {
+    fn sum<I: Iterator<Item = Quantity>>(iter: I) -> Self {
+        iter.fold(Quantity::ZERO, Add::add)
+    }
+}
+
+// Printed without trailing zeroes, so whole amounts look like the plain unit
+// counts they are ("3", "2.5", "0.125")
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / SCALE;
+        let frac = self.0 % SCALE;
+        if frac == 0 {
+            write!(f, "{}", whole)
+        } else {
+            let digits = format!("{:03}", frac);
+            write!(f, "{}.{}", whole, digits.trim_end_matches('0'))
+        }
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<f64>().map(Quantity::from_f64)
+    }
+}
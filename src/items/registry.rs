@@ -0,0 +1,690 @@
+use crate::items::bundle::Bundle;
+use crate::items::discretes::{ItemClass, Order};
+use crate::items::production::{Recipe, RecipeDef, RecipeId};
+use crate::items::quantity::Quantity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The items every world gets unless it's given its own items file.
+pub const DEFAULT_ITEMS: &str = include_str!("../../data/items.toml");
+
+/// Copyable handle to a kind of item registered in an `ItemRegistry`. This is
+/// what inventories, preference lists, and everything else hold; to find out
+/// what the item actually is, look it up in the registry.
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct ItemId(pub usize);
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "item#{}", self.0)
+    }
+}
+
+/// Everything there is to know about a kind of item.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ItemDef {
+    /// Unique name, used to refer to the item in files and the REPL
+    pub name: String,
+    /// What the item is, for humans
+    #[serde(default)]
+    pub description: String,
+    /// Free-form labels for grouping items (`food`, `shelter`, ...)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether the item can be split up and held in fractions of a unit
+    /// (water, grain) rather than only ever coming in whole units (houses)
+    #[serde(default)]
+    pub divisible: bool,
+    /// How many ticks the item keeps for before it spoils, if it's perishable
+    #[serde(default)]
+    pub shelf_life: Option<u32>,
+    /// How many ticks the item keeps serving a goal for once it's put to use,
+    /// if it's durable (otherwise using it consumes it on the spot). For a
+    /// tool, how many ticks of production work it lasts for instead (see
+    /// `Recipe::tools`), if it wears out at all.
+    #[serde(default)]
+    pub durability: Option<u32>,
+    /// For a tool that wears out, the goods it takes to maintain it, which
+    /// put it back as good as new
+    #[serde(default)]
+    pub maintenance: BTreeMap<String, Quantity>,
+    /// Which order of goods the item belongs to (consumers' goods, the
+    /// default, are the first)
+    #[serde(default)]
+    pub order: Order,
+    /// Names of the lower-order goods this item helps produce, if it's a
+    /// producer's good
+    #[serde(default)]
+    pub produces: Vec<String>,
+    /// How much each unit counts for towards a goal, compared to a standard
+    /// unit (so a grade A `FoodUnit` might have a quality of 1.5, and a grade
+    /// C one 0.5). Actors also prefer higher qualities of goods that are
+    /// otherwise valued the same.
+    #[serde(default = "standard_quality")]
+    pub quality: Quantity,
+    /// Whether the item is an ordinary good (the default), money, or land
+    #[serde(default)]
+    pub class: ItemClass,
+    /// Whether the item is non-rival: one in the world's commons can be used
+    /// by any number of actors at once, every tick, without being used up (a
+    /// campfire, a well)
+    #[serde(default)]
+    pub public: bool,
+    /// For land, how much of it there is in the world, all told
+    #[serde(default)]
+    pub supply: Option<Quantity>,
+    /// For land, how much of each item a unit of it yields to whoever holds
+    /// it, every tick
+    #[serde(default)]
+    pub yields: BTreeMap<String, Quantity>,
+}
+
+fn standard_quality() -> Quantity {
+    Quantity::ONE
+}
+
+impl ItemDef {
+    /// Whether the item has been labelled with a tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Why an items file couldn't be loaded.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML, or doesn't describe items
+    Parse(toml::de::Error),
+    /// Two items were given the same name
+    DuplicateItem(String),
+    /// An item is said to produce an item that doesn't exist
+    UnknownProduct { item: String, product: String },
+    /// An item is said to produce an item of the same or a higher order
+    OrderMismatch { item: String, product: String },
+    /// A money or land item was given a property it can't have (a shelf
+    /// life, a durability, or products)
+    BadClass {
+        item: String,
+        class: ItemClass,
+        property: &'static str,
+    },
+    /// A land item doesn't say how much of it there is
+    MissingSupply(String),
+    /// An item is said to yield an item that doesn't exist, or land
+    BadYield { item: String, product: String },
+    /// An item is said to produce land, which can't be produced
+    LandProduced { item: String, product: String },
+    /// A group of substitutes lists an item that doesn't exist
+    UnknownSubstitute(String),
+    /// An item is listed in more than one group of substitutes
+    SubstituteTwice(String),
+    /// Two recipes were given the same name
+    DuplicateRecipe(String),
+    /// A recipe uses or makes an item that doesn't exist
+    UnknownIngredient { recipe: String, item: String },
+    /// An item is said to be maintained with an item that doesn't exist
+    UnknownMaintenance { item: String, input: String },
+    /// A recipe can't be carried out (it makes nothing, or land, or takes
+    /// no time)
+    BadRecipe {
+        recipe: String,
+        problem: &'static str,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Io(e) => write!(f, "couldn't read items file: {}", e),
+            RegistryError::Parse(e) => write!(f, "couldn't parse items file: {}", e),
+            RegistryError::DuplicateItem(name) => {
+                write!(f, "item {} is defined more than once", name)
+            }
+            RegistryError::UnknownProduct { item, product } => {
+                write!(f, "item {} produces {}, which isn't defined", item, product)
+            }
+            RegistryError::OrderMismatch { item, product } => write!(
+                f,
+                "item {} produces {}, so it has to be of a higher order",
+                item, product
+            ),
+            RegistryError::BadClass {
+                item,
+                class,
+                property,
+            } => write!(
+                f,
+                "item {} is {}, so it can't have a {}",
+                item, class, property
+            ),
+            RegistryError::MissingSupply(item) => {
+                write!(f, "item {} is land, so it needs a supply", item)
+            }
+            RegistryError::BadYield { item, product } => write!(
+                f,
+                "item {} yields {}, which isn't a good that's defined",
+                item, product
+            ),
+            RegistryError::LandProduced { item, product } => write!(
+                f,
+                "item {} produces {}, but land can't be produced",
+                item, product
+            ),
+            RegistryError::UnknownSubstitute(item) => {
+                write!(f, "substitute {} isn't defined", item)
+            }
+            RegistryError::SubstituteTwice(item) => {
+                write!(f, "item {} is in more than one group of substitutes", item)
+            }
+            RegistryError::DuplicateRecipe(name) => {
+                write!(f, "recipe {} is defined more than once", name)
+            }
+            RegistryError::UnknownIngredient { recipe, item } => {
+                write!(f, "recipe {} uses {}, which isn't defined", recipe, item)
+            }
+            RegistryError::UnknownMaintenance { item, input } => write!(
+                f,
+                "item {} is maintained with {}, which isn't defined",
+                item, input
+            ),
+            RegistryError::BadRecipe { recipe, problem } => {
+                write!(f, "recipe {} {}", recipe, problem)
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+impl From<io::Error> for RegistryError {
+    fn from(e: io::Error) -> Self {
+        RegistryError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for RegistryError {
+    fn from(e: toml::de::Error) -> Self {
+        RegistryError::Parse(e)
+    }
+}
+
+/// Shape of an items file: a list of `[[item]]` tables, a list of
+/// `[[substitutes]]` tables, and a list of `[[recipe]]` tables.
+#[derive(Deserialize)]
+struct ItemsFile {
+    #[serde(default)]
+    item: Vec<ItemDef>,
+    #[serde(default)]
+    substitutes: Vec<SubstitutesDef>,
+    #[serde(default)]
+    recipe: Vec<RecipeDef>,
+}
+
+/// A group of items that can stand in for each other, as it appears in an
+/// items file: each item's name, and how effective it is.
+#[derive(Deserialize)]
+struct SubstitutesDef {
+    items: BTreeMap<String, Quantity>,
+}
+
+/// Every kind of item that exists in a world. Items are data, not code, so new
+/// goods can be added by editing a TOML file like this one, no recompiling
+/// needed:
+///
+/// ```toml
+/// [[item]]
+/// name = "FoodUnit"
+/// description = "A day's worth of food"
+/// tags = ["food"]
+/// divisible = true
+/// shelf_life = 20
+/// ```
+///
+/// Items that can stand in for each other, though some do the job better than
+/// others, are listed as substitutes, each with how effective it is:
+///
+/// ```toml
+/// [[substitutes]]
+/// items = { LeisureUnit1 = 1, LeisureUnit2 = 0.5 }
+/// ```
+///
+/// It also keeps the recipes for turning some goods into others (see
+/// `RecipeDef` for how they're written).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ItemRegistry {
+    /// Item definitions, indexed by `ItemId`
+    items: Vec<ItemDef>,
+    /// Reverse lookup from item names to their IDs
+    by_name: HashMap<String, ItemId>,
+    /// Groups of substitutes, with how effective each item in them is
+    #[serde(default)]
+    substitutes: Vec<BTreeMap<ItemId, Quantity>>,
+    /// Ways of making goods out of other goods, indexed by `RecipeId`
+    #[serde(default)]
+    recipes: Vec<Recipe>,
+}
+
+impl ItemRegistry {
+    /// Construct a registry with no items in it.
+    pub fn new() -> Self {
+        ItemRegistry::default()
+    }
+
+    /// The built-in items (see `DEFAULT_ITEMS`).
+    pub fn default_items() -> Self {
+        ItemRegistry::from_toml(DEFAULT_ITEMS).expect("built-in items file is broken")
+    }
+
+    /// Parse a registry out of the contents of an items file.
+    pub fn from_toml(source: &str) -> Result<Self, RegistryError> {
+        let file: ItemsFile = toml::from_str(source)?;
+        let mut registry = ItemRegistry::new();
+        for def in file.item {
+            registry.register(def)?;
+        }
+        for group in file.substitutes {
+            let group = group
+                .items
+                .into_iter()
+                .map(|(name, effectiveness)| match registry.id(&name) {
+                    Some(id) => Ok((id, effectiveness)),
+                    None => Err(RegistryError::UnknownSubstitute(name)),
+                })
+                .collect::<Result<_, _>>()?;
+            registry.add_substitutes(group)?;
+        }
+        for def in file.recipe {
+            registry.add_recipe(def)?;
+        }
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Load a registry from an items file on disk.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RegistryError> {
+        ItemRegistry::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Add a new kind of item, returning its ID.
+    pub fn register(&mut self, def: ItemDef) -> Result<ItemId, RegistryError> {
+        if self.by_name.contains_key(&def.name) {
+            return Err(RegistryError::DuplicateItem(def.name));
+        }
+        let id = ItemId(self.items.len());
+        self.by_name.insert(def.name.clone(), id);
+        self.items.push(def);
+        Ok(id)
+    }
+
+    /// Say that some items can stand in for each other, and how effective
+    /// each one is at it (1 being as good as the best of them). Anything any
+    /// of them satisfies, the others do too, but it takes more of a less
+    /// effective one to do the job.
+    pub fn add_substitutes(
+        &mut self,
+        group: BTreeMap<ItemId, Quantity>,
+    ) -> Result<(), RegistryError> {
+        if let Some(&item) = group.keys().find(|&&i| self.substitutes_of(i).is_some()) {
+            return Err(RegistryError::SubstituteTwice(self.name(item)));
+        }
+        self.substitutes.push(group);
+        Ok(())
+    }
+
+    /// Add a recipe for making some goods out of others, returning its ID.
+    /// Everything it uses or makes has to be registered already.
+    pub fn add_recipe(&mut self, def: RecipeDef) -> Result<RecipeId, RegistryError> {
+        if self.recipe_id(&def.name).is_some() {
+            return Err(RegistryError::DuplicateRecipe(def.name));
+        }
+        let resolve = |goods: &BTreeMap<String, Quantity>| {
+            let mut bundle = Bundle::new();
+            for (name, &amount) in goods.iter() {
+                match self.id(name) {
+                    Some(item) => bundle.add(item, amount),
+                    None => {
+                        return Err(RegistryError::UnknownIngredient {
+                            recipe: def.name.clone(),
+                            item: name.clone(),
+                        })
+                    }
+                }
+            }
+            Ok(bundle)
+        };
+        let (inputs, tools, outputs) = (
+            resolve(&def.inputs)?,
+            resolve(&def.tools)?,
+            resolve(&def.outputs)?,
+        );
+        let problem = if outputs.is_empty() {
+            Some("makes nothing")
+        } else if outputs.iter().any(|(item, _)| self.is_land(item)) {
+            Some("makes land, which can't be produced")
+        } else if def.duration == 0 {
+            Some("takes no time")
+        } else if def.discovery.is_some_and(|chance| chance.is_zero()) {
+            Some("can never be discovered")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(RegistryError::BadRecipe {
+                recipe: def.name,
+                problem,
+            });
+        }
+        self.recipes.push(Recipe {
+            name: def.name,
+            inputs,
+            tools,
+            outputs,
+            labor: def.labor,
+            duration: def.duration,
+            discovery: def.discovery,
+        });
+        Ok(RecipeId(self.recipes.len() - 1))
+    }
+
+    /// Look up a recipe.
+    pub fn recipe(&self, id: RecipeId) -> Option<&Recipe> {
+        self.recipes.get(id.0)
+    }
+
+    /// The name of a recipe, or its raw ID if it isn't registered.
+    pub fn recipe_name(&self, id: RecipeId) -> String {
+        self.recipe(id)
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Find a recipe by name.
+    pub fn recipe_id(&self, name: &str) -> Option<RecipeId> {
+        self.recipes
+            .iter()
+            .position(|r| r.name == name)
+            .map(RecipeId)
+    }
+
+    /// Iterate over every recipe in ID order, along with its ID.
+    pub fn recipes(&self) -> impl Iterator<Item = (RecipeId, &Recipe)> {
+        self.recipes
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (RecipeId(i), r))
+    }
+
+    fn substitutes_of(&self, id: ItemId) -> Option<&BTreeMap<ItemId, Quantity>> {
+        self.substitutes
+            .iter()
+            .find(|group| group.contains_key(&id))
+    }
+
+    /// Every item that has substitutes, along with what they are.
+    pub fn substitutions(&self) -> HashMap<ItemId, Vec<ItemId>> {
+        let mut substitutions = HashMap::new();
+        for group in self.substitutes.iter() {
+            for &item in group.keys() {
+                let others = group.keys().copied().filter(|&i| i != item).collect();
+                substitutions.insert(item, others);
+            }
+        }
+        substitutions
+    }
+
+    /// How effective an item is as a substitute for the others in its group
+    /// (see `add_substitutes`). Items without substitutes are fully
+    /// effective.
+    pub fn effectiveness(&self, id: ItemId) -> Quantity {
+        self.substitutes_of(id)
+            .and_then(|group| group.get(&id).copied())
+            .unwrap_or(Quantity::ONE)
+    }
+
+    /// Check that every producer's good produces items that exist, and that
+    /// are of a lower order than it is (and aren't land), that money and land
+    /// don't spoil, wear out, or produce anything, and that land has a supply
+    /// and yields goods that exist. Items can be registered in any order, so
+    /// this can only be done once they all have been.
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        for def in self.items.iter() {
+            if def.class.is_land() && def.supply.is_none() {
+                return Err(RegistryError::MissingSupply(def.name.clone()));
+            }
+            if let Some(input) = def
+                .maintenance
+                .keys()
+                .find(|input| self.id(input).is_none())
+            {
+                return Err(RegistryError::UnknownMaintenance {
+                    item: def.name.clone(),
+                    input: input.clone(),
+                });
+            }
+            for product in def.yields.keys() {
+                let product_def = self.id(product).and_then(|id| self.get(id));
+                if product_def.is_none_or(|product_def| product_def.class.is_land()) {
+                    return Err(RegistryError::BadYield {
+                        item: def.name.clone(),
+                        product: product.clone(),
+                    });
+                }
+            }
+            if def.class != ItemClass::Good {
+                let property = if def.shelf_life.is_some() {
+                    Some("shelf_life")
+                } else if def.durability.is_some() {
+                    Some("durability")
+                } else if !def.maintenance.is_empty() {
+                    Some("maintenance")
+                } else if !def.produces.is_empty() {
+                    Some("produces")
+                } else {
+                    None
+                };
+                if let Some(property) = property {
+                    return Err(RegistryError::BadClass {
+                        item: def.name.clone(),
+                        class: def.class,
+                        property,
+                    });
+                }
+            }
+            for product in def.produces.iter() {
+                let product_def =
+                    self.id(product)
+                        .and_then(|id| self.get(id))
+                        .ok_or_else(|| RegistryError::UnknownProduct {
+                            item: def.name.clone(),
+                            product: product.clone(),
+                        })?;
+                if product_def.class.is_land() {
+                    return Err(RegistryError::LandProduced {
+                        item: def.name.clone(),
+                        product: product.clone(),
+                    });
+                }
+                if product_def.order >= def.order {
+                    return Err(RegistryError::OrderMismatch {
+                        item: def.name.clone(),
+                        product: product.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every producer's good (and piece of land), along with the lower-order
+    /// goods it helps produce (or yields). This is what actors impute the
+    /// value of capital goods from (see `ValueScale::set_imputations`).
+    pub fn imputations(&self) -> HashMap<ItemId, Vec<ItemId>> {
+        self.iter()
+            .filter(|(_, def)| !def.produces.is_empty() || !def.yields.is_empty())
+            .map(|(id, def)| {
+                let products = def
+                    .produces
+                    .iter()
+                    .chain(def.yields.keys())
+                    .filter_map(|p| self.id(p))
+                    .collect();
+                (id, products)
+            })
+            .collect()
+    }
+
+    /// Look up an item's definition.
+    pub fn get(&self, id: ItemId) -> Option<&ItemDef> {
+        self.items.get(id.0)
+    }
+
+    /// Whether an item can be held in fractions of a unit (money always can).
+    /// Unregistered items can't.
+    pub fn is_divisible(&self, id: ItemId) -> bool {
+        self.get(id)
+            .map(|def| def.divisible || def.class.is_money())
+            .unwrap_or(false)
+    }
+
+    /// Whether an item is money. Unregistered items aren't.
+    pub fn is_money(&self, id: ItemId) -> bool {
+        self.get(id)
+            .map(|def| def.class.is_money())
+            .unwrap_or(false)
+    }
+
+    /// Whether an item is a non-rival public good. Unregistered items aren't.
+    pub fn is_public(&self, id: ItemId) -> bool {
+        self.get(id).map(|def| def.public).unwrap_or(false)
+    }
+
+    /// Whether an item is land. Unregistered items aren't.
+    pub fn is_land(&self, id: ItemId) -> bool {
+        self.get(id).map(|def| def.class.is_land()).unwrap_or(false)
+    }
+
+    /// Whether any items at all are land
+    pub fn has_land(&self) -> bool {
+        self.items.iter().any(|def| def.class.is_land())
+    }
+
+    /// How much of an item there can ever be, if it's land (anything else
+    /// can be had in any amount).
+    pub fn supply(&self, id: ItemId) -> Option<Quantity> {
+        self.get(id)
+            .filter(|def| def.class.is_land())
+            .and_then(|def| def.supply)
+    }
+
+    /// What a unit of an item yields every tick, as items and how much of
+    /// each. Empty for anything that isn't land.
+    pub fn yields(&self, id: ItemId) -> Vec<(ItemId, Quantity)> {
+        self.get(id)
+            .map(|def| {
+                def.yields
+                    .iter()
+                    .filter_map(|(name, &amount)| self.id(name).map(|p| (p, amount)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// What it takes to maintain a tool (see `ItemDef::maintenance`). Empty
+    /// for anything that can't be maintained.
+    pub fn maintenance(&self, id: ItemId) -> Bundle {
+        let mut bundle = Bundle::new();
+        if let Some(def) = self.get(id) {
+            for (name, &amount) in def.maintenance.iter() {
+                if let Some(input) = self.id(name) {
+                    bundle.add(input, amount);
+                }
+            }
+        }
+        bundle
+    }
+
+    /// Every item that's money.
+    pub fn money(&self) -> HashSet<ItemId> {
+        self.iter()
+            .filter(|(_, def)| def.class.is_money())
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// How many ticks an item keeps for, or `None` if it never goes off.
+    pub fn shelf_life(&self, id: ItemId) -> Option<u32> {
+        self.get(id).and_then(|def| def.shelf_life)
+    }
+
+    /// Whether any items at all are perishable
+    pub fn has_perishables(&self) -> bool {
+        self.items.iter().any(|def| def.shelf_life.is_some())
+    }
+
+    /// How many ticks an item serves for once it's used, or `None` if it's
+    /// used up straight away.
+    pub fn durability(&self, id: ItemId) -> Option<u32> {
+        self.get(id).and_then(|def| def.durability)
+    }
+
+    /// Whether any items at all are durable
+    pub fn has_durables(&self) -> bool {
+        self.items.iter().any(|def| def.durability.is_some())
+    }
+
+    /// How much a unit of an item counts for towards a goal: its quality (see
+    /// `ItemDef::quality`), times how effective it is as a substitute (see
+    /// `effectiveness`). Unregistered items are standard quality, and no item
+    /// counts for less than the smallest possible amount.
+    pub fn quality(&self, id: ItemId) -> Quantity {
+        self.get(id)
+            .map(|def| def.quality.times(self.effectiveness(id)))
+            .unwrap_or(Quantity::ONE)
+            .max(Quantity::EPSILON)
+    }
+
+    /// How much a unit of every item counts for, for items where it isn't
+    /// exactly one (see `quality`).
+    pub fn qualities(&self) -> HashMap<ItemId, Quantity> {
+        self.iter()
+            .filter(|(id, _)| self.quality(*id) != Quantity::ONE)
+            .map(|(id, _)| (id, self.quality(id)))
+            .collect()
+    }
+
+    /// Find an item by name.
+    pub fn id(&self, name: &str) -> Option<ItemId> {
+        self.by_name.get(name).copied()
+    }
+
+    /// The name of an item, or its raw ID if it isn't registered.
+    pub fn name(&self, id: ItemId) -> String {
+        self.get(id)
+            .map(|def| def.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Iterate over every item in ID order, along with its ID.
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, &ItemDef)> {
+        self.items.iter().enumerate().map(|(i, d)| (ItemId(i), d))
+    }
+
+    /// Number of kinds of item
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether there are no items at all
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
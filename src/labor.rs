@@ -0,0 +1,247 @@
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::production::{Recipe, RecipeId};
+use crate::items::quantity::Quantity;
+use crate::preference_list::{ActorMut, ActorRef};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// The kinds of work an actor can spend its labor on.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Work {
+    /// Making something
+    Production,
+    /// Looking around for someone to trade with
+    Search,
+    /// Haggling with a trade partner
+    Negotiation,
+    /// Gathering something from a resource node
+    Gathering,
+    /// Keeping a tool in working order
+    Maintenance,
+    /// Trying out new ways of making things
+    Experimenting,
+}
+
+impl Work {
+    /// How many hours of labor a tick's worth of this work takes.
+    pub fn hours(self) -> Quantity {
+        match self {
+            Work::Production => Quantity::units(4),
+            Work::Search => Quantity::units(2),
+            Work::Negotiation => Quantity::ONE,
+            Work::Gathering => Quantity::units(4),
+            Work::Maintenance => Quantity::units(2),
+            Work::Experimenting => Quantity::units(4),
+        }
+    }
+}
+
+/// An actor's own time: so many hours of labor every tick, which it can
+/// either spend working or keep for itself as leisure. Labor is a means like
+/// any other, and a scarce one, but unlike items it can't be saved up:
+/// whatever isn't used by the end of a tick is gone. It can't be traded
+/// outright either, but an actor can hire some of another's hours for the
+/// tick, paying a wage in goods for them (see `hire`), and they're added to
+/// its own for that tick.
+///
+/// Rather than visiting every actor at the start of every tick to hand out
+/// their hours, the budget remembers which tick it was last spent on, and
+/// starts over whenever it's used on a new one.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Labor {
+    /// Hours available every tick
+    pub budget: Quantity,
+    /// Hours already spent on tick `tick`
+    spent: Quantity,
+    /// Hours hired from other actors on tick `tick`, on top of the budget
+    #[serde(default)]
+    hired: Quantity,
+    /// Hours taken off on tick `tick`, out of the ones spent
+    #[serde(default)]
+    rested: Quantity,
+    /// The tick `spent` and `hired` count for
+    tick: u64,
+}
+
+impl Default for Labor {
+    fn default() -> Self {
+        Labor::new(Quantity::units(Labor::HOURS_PER_TICK))
+    }
+}
+
+impl Labor {
+    /// How many hours an actor has every tick unless it's told otherwise
+    pub const HOURS_PER_TICK: u32 = 8;
+
+    /// A budget of so many hours a tick, none of them spent.
+    pub fn new(budget: Quantity) -> Self {
+        Labor {
+            budget,
+            spent: Quantity::ZERO,
+            hired: Quantity::ZERO,
+            rested: Quantity::ZERO,
+            tick: 0,
+        }
+    }
+
+    /// How many hours are left to spend on tick `now`.
+    pub fn left(&self, now: u64) -> Quantity {
+        if self.tick == now {
+            (self.budget + self.hired).saturating_sub(self.spent)
+        } else {
+            self.budget
+        }
+    }
+
+    /// How many hours have been hired from other actors for tick `now`.
+    pub fn hired(&self, now: u64) -> Quantity {
+        if self.tick == now {
+            self.hired
+        } else {
+            Quantity::ZERO
+        }
+    }
+
+    /// Spend some hours on tick `now`, if there are that many left. Returns
+    /// whether there were.
+    pub fn spend(&mut self, hours: Quantity, now: u64) -> bool {
+        if self.left(now) < hours {
+            return false;
+        }
+        self.start(now);
+        self.spent += hours;
+        true
+    }
+
+    /// Add some hours someone else is working for the actor on tick `now`
+    /// to what it has to spend then.
+    pub fn hire(&mut self, hours: Quantity, now: u64) {
+        self.start(now);
+        self.hired += hours;
+    }
+
+    /// Start counting for tick `now`, if it isn't already.
+    fn start(&mut self, now: u64) {
+        if self.tick != now {
+            self.tick = now;
+            self.spent = Quantity::ZERO;
+            self.hired = Quantity::ZERO;
+            self.rested = Quantity::ZERO;
+        }
+    }
+
+    /// Take whatever's left of tick `now` off, returning how much that was.
+    pub fn spend_rest(&mut self, now: u64) -> Quantity {
+        let left = self.left(now);
+        self.spend(left, now);
+        self.rested += left;
+        left
+    }
+
+    /// How many of its own hours the actor's spent working on tick `now`
+    /// (rather than resting, or having someone else work them for it).
+    pub fn worked(&self, now: u64) -> Quantity {
+        if self.tick == now {
+            self.spent.saturating_sub(self.rested + self.hired)
+        } else {
+            Quantity::ZERO
+        }
+    }
+}
+
+/// Whether `worker` would work `hours` of its time on tick `now` for `wage`.
+/// It has to have the hours left, and room for the wage (see
+/// `ActorRef::can_fit`), and the wage has to be something it has a use for.
+/// Past that, it's down to what the hours would go towards otherwise: time
+/// it isn't working is time off, so if it has a leisure goal in play it'll
+/// only give it up for a wage that goes towards a goal it ranks higher, the
+/// same way it'd only give up items in a trade for something it values more
+/// (see `trade::accepts`). Without one, its time's worth nothing to it.
+pub fn accepts_wage(worker: ActorRef, hours: Quantity, wage: &Bundle, now: u64) -> bool {
+    if wage.is_empty()
+        || worker.labor.left(now) < hours
+        || !worker.can_fit(wage, &Bundle::new())
+        || !wage.iter().all(|(item, _)| worker.values.has_use_for(item))
+    {
+        return false;
+    }
+    let leisure = match worker.values.leisure_in_play() {
+        Some(leisure) => leisure,
+        None => return true,
+    };
+    wage.iter()
+        .filter_map(|(item, _)| worker.values.get_best_goal(item))
+        .any(|goal| worker.values.rank(goal) < worker.values.rank(leisure))
+}
+
+/// Whether `employer` would pay `wage` for the labor to make something by
+/// `recipe`: it has to have the wage, not need any of it for the recipe
+/// itself, and value it less than what the recipe makes (see
+/// `ValueScale::compare_bundles`).
+pub fn would_pay(employer: ActorRef, wage: &Bundle, recipe: &Recipe) -> bool {
+    wage.iter().all(|(item, amount)| {
+        employer.inventory.count(item) >= amount && recipe.needs().all(|(need, _)| need != item)
+    }) && employer.compare_bundles(wage, &recipe.outputs) == Ordering::Less
+}
+
+/// Have `employer` bid for `hours` of `worker`'s labor, to make something
+/// by `recipe`. Wages are bid upwards the same way trades are (see
+/// `trade::AscendingBid`): the employer offers a unit of whatever it values
+/// least first, and works up from there until the worker takes one (see
+/// `accepts_wage`) or there's nothing left the employer would pay (see
+/// `would_pay`). If the worker takes it, the wage is handed over, and the
+/// hours come off the worker's labor for the tick and go on the employer's.
+/// Returns whether they came to terms.
+///
+/// # Arguments
+///
+/// * `employer` - the actor hiring
+/// * `worker` - the actor being hired
+/// * `hours` - how many hours of labor the employer's after
+/// * `id` - the recipe the labor's for
+/// * `recipe` - what it takes
+/// * `events` - where to report what happened
+///
+pub fn hire(
+    employer: &mut ActorMut,
+    worker: &mut ActorMut,
+    hours: Quantity,
+    id: RecipeId,
+    recipe: &Recipe,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    let now = employer.now;
+    for item in employer.ordered_inventory() {
+        let wage = Bundle::single(item, Quantity::ONE);
+        if recipe.needs().any(|(need, _)| need == item) {
+            continue;
+        }
+        if !would_pay(employer.as_ref(), &wage, recipe) {
+            break;
+        }
+        if !accepts_wage(worker.as_ref(), hours, &wage, now) {
+            events.push(SimEvent::WageRejected {
+                employer: employer.id,
+                worker: worker.id,
+                offered: wage,
+            });
+            continue;
+        }
+        employer
+            .inventory
+            .transfer(worker.inventory, item, Quantity::ONE, employer.id, now);
+        worker.labor.spend(hours, now);
+        employer.labor.hire(hours, now);
+        events.push(SimEvent::LaborHired {
+            employer: employer.id,
+            worker: worker.id,
+            recipe: id,
+            hours,
+            wage,
+        });
+        events.extend(worker.discard_overflow(item));
+        return true;
+    }
+    false
+}
@@ -0,0 +1,503 @@
+use crate::arena::ActorId;
+use crate::events::SimEvent;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemRegistry;
+use crate::trade::TradeCost;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// One goal being satisfied, as the actor that satisfied it remembers it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SatisfactionRecord {
+    /// The goal that was satisfied
+    pub goal: GoalId,
+    /// The tick it was satisfied on
+    pub tick: u64,
+    /// How much of whatever items went towards it were used up doing so
+    /// (public goods, durable goods in use, and time off don't count, since
+    /// they aren't used up)
+    pub units: Quantity,
+    /// How many ticks it was in play before it was satisfied
+    pub waited: u64,
+}
+
+/// An actor's history of seeing to its goals: which it satisfied when, how
+/// much it used up doing it, and how long each one waited, so its welfare
+/// over a run can be looked back on. The simulation keeps it up to date from
+/// the events each tick produces (see `record`), so nothing else has to
+/// remember to.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct SatisfactionLedger {
+    /// Every goal satisfied so far, oldest first
+    records: Vec<SatisfactionRecord>,
+    /// Goals still waiting to be satisfied: when they came into play, and how
+    /// much has been used up on them since
+    open: HashMap<GoalId, (u64, Quantity)>,
+}
+
+impl SatisfactionLedger {
+    /// An empty ledger.
+    pub fn new() -> Self {
+        SatisfactionLedger::default()
+    }
+
+    /// Notes that a goal came into play on tick `tick` (if it isn't already
+    /// waiting).
+    pub fn open(&mut self, goal: GoalId, tick: u64) {
+        self.open.entry(goal).or_insert((tick, Quantity::ZERO));
+    }
+
+    /// Forgets a goal that's waiting, because it's been given up on or taken
+    /// away.
+    pub fn close(&mut self, goal: GoalId) {
+        self.open.remove(&goal);
+    }
+
+    /// Updates the ledger with something that happened to the actor on tick
+    /// `tick`. Events that have nothing to do with goals being seen to are
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - what happened
+    /// * `tick` - when it happened
+    ///
+    pub fn record(&mut self, event: &SimEvent, tick: u64) {
+        match *event {
+            SimEvent::GoalReintroduced { goal, .. } | SimEvent::GoalActivated { goal, .. } => {
+                self.open(goal, tick)
+            }
+            SimEvent::ItemUsed { goal, amount, .. } => {
+                self.open(goal, tick);
+                if let Some((_, units)) = self.open.get_mut(&goal) {
+                    *units += amount;
+                }
+            }
+            SimEvent::GoalSatisfied { goal, .. } => {
+                let (since, units) = self.open.remove(&goal).unwrap_or((tick, Quantity::ZERO));
+                self.records.push(SatisfactionRecord {
+                    goal,
+                    tick,
+                    units,
+                    waited: tick.saturating_sub(since),
+                });
+            }
+            SimEvent::GoalAbandoned { goal, .. } => self.close(goal),
+            _ => {}
+        }
+    }
+
+    /// Every goal satisfied so far, oldest first.
+    pub fn records(&self) -> &[SatisfactionRecord] {
+        &self.records
+    }
+
+    /// Every goal still waiting to be satisfied, along with how many ticks
+    /// it's waited as of tick `now`, longest waiting first.
+    pub fn waiting(&self, now: u64) -> Vec<(GoalId, u64)> {
+        let mut waiting: Vec<_> = self
+            .open
+            .iter()
+            .map(|(&goal, &(since, _))| (goal, now.saturating_sub(since)))
+            .collect();
+        waiting.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        waiting
+    }
+}
+
+/// How a step of a negotiation an actor was part of went.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TradeOutcome {
+    /// `by` made a bid
+    Bid,
+    /// `by` held out for something else instead
+    CounterOffer,
+    /// `by` turned the last bid down
+    Rejected,
+    /// The items changed hands
+    Completed,
+    /// The items changed hands around a circle: the actor gave to the
+    /// counterparty, and got from someone else
+    Circle,
+    /// `by` gave up on the negotiation and moved on
+    Abandoned,
+}
+
+/// One step of a negotiation an actor was part of, as it remembers it.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TradeRecord {
+    /// The tick it happened on
+    pub tick: u64,
+    /// Who the actor was dealing with
+    pub counterparty: ActorId,
+    /// Whichever of the two made this move (see `TradeOutcome`)
+    pub by: ActorId,
+    /// What the actor was to give up (or did)
+    pub gave: Bundle,
+    /// What the actor was to get (or did)
+    pub got: Bundle,
+    /// How it went
+    pub outcome: TradeOutcome,
+}
+
+/// Every bid, rejection, and trade an actor has been part of, oldest first,
+/// so how its inventory came to be what it is can be traced back. Like the
+/// satisfaction ledger, the simulation keeps it up to date from the events
+/// each tick produces (see `record`).
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TradeLedger {
+    /// Everything so far, oldest first
+    records: Vec<TradeRecord>,
+}
+
+impl TradeLedger {
+    /// An empty ledger.
+    pub fn new() -> Self {
+        TradeLedger::default()
+    }
+
+    /// Updates the ledger of actor `me` with something that happened on tick
+    /// `tick`. Events it had no part in, and that aren't bids, rejections, or
+    /// trades, are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `me` - the actor whose ledger this is
+    /// * `event` - what happened
+    /// * `tick` - when it happened
+    ///
+    pub fn record(&mut self, me: ActorId, event: &SimEvent, tick: u64) {
+        // Which side of it we were on, and what each side was giving
+        let sides = |actor: ActorId, partner: ActorId, offer: &Bundle, want: &Bundle| {
+            if actor == me {
+                Some((partner, offer.clone(), want.clone()))
+            } else if partner == me {
+                Some((actor, want.clone(), offer.clone()))
+            } else {
+                None
+            }
+        };
+        let (counterparty, by, gave, got, outcome) = match *event {
+            SimEvent::BidMade {
+                actor,
+                partner,
+                ref offer,
+                ref want,
+            } => match sides(actor, partner, offer, want) {
+                Some((other, gave, got)) => (other, actor, gave, got, TradeOutcome::Bid),
+                None => return,
+            },
+            // The counter-offer is from the one being bid on, so it's
+            // giving `offer` for `want`
+            SimEvent::CounterOfferMade {
+                actor,
+                partner,
+                ref offer,
+                ref want,
+            } => match sides(actor, partner, offer, want) {
+                Some((other, gave, got)) => (other, actor, gave, got, TradeOutcome::CounterOffer),
+                None => return,
+            },
+            SimEvent::TradeCompleted {
+                actor,
+                partner,
+                ref gave,
+                ref received,
+            } => match sides(actor, partner, gave, received) {
+                Some((other, gave, got)) => (other, actor, gave, got, TradeOutcome::Completed),
+                None => return,
+            },
+            SimEvent::BidRejected {
+                actor,
+                partner,
+                rejected_by,
+            } => match self.last_on_table(me, actor, partner) {
+                Some((other, gave, got)) => (other, rejected_by, gave, got, TradeOutcome::Rejected),
+                None => return,
+            },
+            SimEvent::TradeAbandoned { actor, partner } => {
+                match self.last_on_table(me, actor, partner) {
+                    Some((other, gave, got)) => (other, actor, gave, got, TradeOutcome::Abandoned),
+                    None => return,
+                }
+            }
+            SimEvent::TradeCycleCompleted {
+                ref actors,
+                ref gave,
+            } => match actors.iter().position(|&a| a == me) {
+                Some(n) => {
+                    let len = actors.len();
+                    let next = actors[(n + 1) % len];
+                    let got = gave[(n + len - 1) % len].clone();
+                    (next, actors[0], gave[n].clone(), got, TradeOutcome::Circle)
+                }
+                None => return,
+            },
+            _ => return,
+        };
+        self.records.push(TradeRecord {
+            tick,
+            counterparty,
+            by,
+            gave,
+            got,
+            outcome,
+        });
+    }
+
+    /// Who `me` was dealing with, if it's one of `actor` and `partner`, and
+    /// what was last on the table between them: what it was to give and to
+    /// get (nothing, if nothing ever was).
+    fn last_on_table(
+        &self,
+        me: ActorId,
+        actor: ActorId,
+        partner: ActorId,
+    ) -> Option<(ActorId, Bundle, Bundle)> {
+        let other = if actor == me {
+            partner
+        } else if partner == me {
+            actor
+        } else {
+            return None;
+        };
+        let (gave, got) = self
+            .records
+            .iter()
+            .rev()
+            .find(|r| r.counterparty == other)
+            .map(|r| (r.gave.clone(), r.got.clone()))
+            .unwrap_or_else(|| (Bundle::new(), Bundle::new()));
+        Some((other, gave, got))
+    }
+
+    /// Everything so far, oldest first.
+    pub fn records(&self) -> &[TradeRecord] {
+        &self.records
+    }
+}
+
+/// What an actor's goods came and went for, as far as working out whether
+/// it's coming out ahead goes.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Account {
+    /// Trading goods for goods (and whatever it cost to trade)
+    Trading,
+    /// Making things by a recipe: the inputs and wages that went in, and
+    /// what came out
+    Production(RecipeId),
+    /// Working for someone else, for wages
+    Labor,
+    /// Letting out durable goods for rent, or renting them
+    Rent,
+    /// Maintaining tools
+    Upkeep,
+}
+
+/// Something an actor gave up, got, or both, on one of its accounts.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct AccountEntry {
+    /// The tick it happened on
+    pub tick: u64,
+    /// What it was for
+    pub account: Account,
+    /// What the actor gave up
+    pub spent: Bundle,
+    /// What the actor got
+    pub got: Bundle,
+}
+
+/// An actor's books: every good it's given up and every good it's got, in
+/// trade, production, wages, rent and upkeep, so whether it's making a
+/// profit or a loss (and on what) can be worked out (see
+/// `PriceHistory::value_in`). What it's made, and traded away, also shows
+/// how far it's specialized (see `specialization`).
+/// Gifts, loans, and goods used up on its own goals aren't in it, since
+/// they're not about coming out ahead. Like the trade ledger, the simulation
+/// keeps it up to date from the events each tick produces (see `record`).
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Accounts {
+    /// Everything so far, oldest first
+    entries: Vec<AccountEntry>,
+}
+
+impl Accounts {
+    /// Empty books.
+    pub fn new() -> Self {
+        Accounts::default()
+    }
+
+    /// Updates the books of actor `me` with something that happened on tick
+    /// `tick`. Events that didn't change hands or make anything for it are
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `me` - the actor whose books these are
+    /// * `event` - what happened
+    /// * `tick` - when it happened
+    /// * `items` - the world's items, for looking up what went into a recipe
+    ///
+    pub fn record(&mut self, me: ActorId, event: &SimEvent, tick: u64, items: &ItemRegistry) {
+        let (account, spent, got) = match *event {
+            SimEvent::TradeCompleted {
+                actor,
+                partner,
+                ref gave,
+                ref received,
+            } if actor == me || partner == me => {
+                if actor == me {
+                    (Account::Trading, gave.clone(), received.clone())
+                } else {
+                    (Account::Trading, received.clone(), gave.clone())
+                }
+            }
+            SimEvent::TradeCycleCompleted {
+                ref actors,
+                ref gave,
+            } => match actors.iter().position(|&a| a == me) {
+                Some(n) => {
+                    let len = actors.len();
+                    let got = gave[(n + len - 1) % len].clone();
+                    (Account::Trading, gave[n].clone(), got)
+                }
+                None => return,
+            },
+            SimEvent::TradeCostPaid {
+                actor,
+                cost: TradeCost::Item(item, amount),
+            } if actor == me => (
+                Account::Trading,
+                Bundle::single(item, amount),
+                Bundle::new(),
+            ),
+            SimEvent::ProductionStarted { actor, recipe, .. } if actor == me => {
+                match items.recipe(recipe) {
+                    Some(def) => (
+                        Account::Production(recipe),
+                        def.inputs.clone(),
+                        Bundle::new(),
+                    ),
+                    None => return,
+                }
+            }
+            SimEvent::ItemsProduced {
+                actor,
+                recipe,
+                ref outputs,
+            } if actor == me => (Account::Production(recipe), Bundle::new(), outputs.clone()),
+            SimEvent::LaborHired {
+                employer,
+                worker,
+                recipe,
+                ref wage,
+                ..
+            } => {
+                if employer == me {
+                    (Account::Production(recipe), wage.clone(), Bundle::new())
+                } else if worker == me {
+                    (Account::Labor, Bundle::new(), wage.clone())
+                } else {
+                    return;
+                }
+            }
+            SimEvent::ToolMaintained {
+                actor, ref inputs, ..
+            } if actor == me => (Account::Upkeep, inputs.clone(), Bundle::new()),
+            SimEvent::RentPaid {
+                tenant,
+                landlord,
+                ref rent,
+            } => {
+                if tenant == me {
+                    (Account::Rent, rent.clone(), Bundle::new())
+                } else if landlord == me {
+                    (Account::Rent, Bundle::new(), rent.clone())
+                } else {
+                    return;
+                }
+            }
+            _ => return,
+        };
+        self.entries.push(AccountEntry {
+            tick,
+            account,
+            spent,
+            got,
+        });
+    }
+
+    /// Everything so far, oldest first.
+    pub fn entries(&self) -> &[AccountEntry] {
+        &self.entries
+    }
+
+    /// Everything spent and got on each account so far, in order of account.
+    pub fn totals(&self) -> Vec<(Account, Bundle, Bundle)> {
+        let mut totals: BTreeMap<Account, (Bundle, Bundle)> = BTreeMap::new();
+        for entry in self.entries.iter() {
+            let (spent, got) = totals.entry(entry.account).or_default();
+            for (item, amount) in entry.spent.iter() {
+                spent.add(item, amount);
+            }
+            for (item, amount) in entry.got.iter() {
+                got.add(item, amount);
+            }
+        }
+        totals
+            .into_iter()
+            .map(|(account, (spent, got))| (account, spent, got))
+            .collect()
+    }
+
+    /// Everything the actor's made by any recipe since tick `since`.
+    pub fn output(&self, since: u64) -> Bundle {
+        let mut output = Bundle::new();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.tick >= since && matches!(entry.account, Account::Production(_)))
+        {
+            for (item, amount) in entry.got.iter() {
+                output.add(item, amount);
+            }
+        }
+        output
+    }
+
+    /// Everything the actor's given up in trade since tick `since`.
+    pub fn traded_away(&self, since: u64) -> Bundle {
+        let mut traded = Bundle::new();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.tick >= since && entry.account == Account::Trading)
+        {
+            for (item, amount) in entry.spent.iter() {
+                traded.add(item, amount);
+            }
+        }
+        traded
+    }
+
+    /// How specialized the actor's been since tick `since`: the sum of the
+    /// squares of each good's share of everything it's made (counting every
+    /// unit the same). That's 1 for an actor that's only made the one good,
+    /// and comes down towards 0 the more goods it's split its work between.
+    /// `None` if it hasn't made anything.
+    pub fn specialization(&self, since: u64) -> Option<Quantity> {
+        let output = self.output(since);
+        let total = output.total().as_f64();
+        if total <= 0.0 {
+            return None;
+        }
+        let index = output
+            .iter()
+            .map(|(_, amount)| (amount.as_f64() / total).powi(2))
+            .sum();
+        Some(Quantity::from_f64(index))
+    }
+}
@@ -0,0 +1,81 @@
+//! # Austrian Microeconomics
+//!
+//! A simple proof-of-concept simulation of microeconomic actors along the lines
+//! of praxeological theory. Actors value items according to ordinal value
+//! scales, use items to satisfy their goals, and trade with each other when
+//! they can't satisfy a goal with what they already have.
+//!
+//! The interactive REPL in `main.rs` is just one consumer of this library; the
+//! `Simulation` type can be driven directly from other programs and tests.
+
+pub mod arena;
+pub mod behavior;
+pub mod builder;
+pub mod credit;
+pub mod events;
+pub mod expectations;
+pub mod goals;
+pub mod imputation;
+pub mod items;
+pub mod labor;
+pub mod ledger;
+pub mod market;
+pub mod matching;
+pub mod memory;
+pub mod minting;
+pub mod network;
+pub mod observer;
+pub mod ordering;
+pub mod preference_list;
+pub mod prices;
+pub mod replay;
+pub mod resources;
+pub mod scenario;
+pub mod scheduler;
+pub mod simulation;
+pub mod snapshot;
+pub mod taxation;
+pub mod technology;
+pub mod trade;
+
+pub use arena::{ActorArena, ActorId};
+pub use behavior::{
+    Action, Arbitrageur, Bank, Behavior, DefaultBehavior, Middleman, SavedBehavior, WorldView,
+};
+pub use builder::{ActorBuilder, BuildError};
+pub use credit::{BankBook, CreditBook, Iou};
+pub use events::SimEvent;
+pub use expectations::{Expectation, Expectations};
+pub use goals::{GoalDef, GoalId, GoalRegistry, GoalRegistryError, GoalTemplate};
+pub use imputation::{Factor, Imputation, ImputedValue};
+pub use items::bundle::Bundle;
+pub use items::inventory::Inventory;
+pub use items::production::{ProductionPlan, Recipe, RecipeId};
+pub use items::quantity::Quantity;
+pub use items::registry::{ItemDef, ItemId, ItemRegistry, RegistryError};
+pub use labor::{Labor, Work};
+pub use ledger::{
+    Account, AccountEntry, Accounts, SatisfactionLedger, SatisfactionRecord, TradeLedger,
+    TradeOutcome, TradeRecord,
+};
+pub use market::{DoubleAuction, Order, OrderBook};
+pub use matching::Matching;
+pub use memory::{PartnerRecord, Sighting, TradeMemory};
+pub use minting::{Holding, Injection, MoneyTrace};
+pub use network::{TradeLink, TradeNetwork};
+pub use observer::SimObserver;
+pub use ordering::TickOrder;
+pub use preference_list::{Actor, ActorState, GoalData, HierarchyError};
+pub use prices::{PriceHistory, PricePoint, WageHistory, WagePoint};
+pub use replay::{Recorder, Replay, ReplayedTick};
+pub use resources::{ResourceId, ResourceNode};
+pub use scenario::{ActorGroup, Satisfaction, Scenario, ScenarioError};
+pub use scheduler::{Scheduler, Wakeup};
+pub use simulation::{Drift, SimRng, Simulation};
+pub use snapshot::{SnapshotError, SNAPSHOT_VERSION};
+pub use taxation::{Collector, Disposal, TaxBase};
+pub use technology::KnowHow;
+pub use trade::{
+    AscendingBid, LegalTender, PostedPrice, PriceControl, SavedProtocol, TakeItOrLeaveIt,
+    TradeCost, TradeProtocol,
+};
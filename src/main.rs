@@ -1,309 +1,4034 @@
-mod items;
-mod preference_list;
-
 use colored::*;
-use items::discretes::{Goal, Item};
 use linefeed::complete::{Completer, Completion};
 use linefeed::terminal::Terminal;
 use linefeed::{Interface, Prompter, ReadResult};
-use preference_list::{Actor, GoalData};
-use rand::seq::IteratorRandom;
-use std::cell::RefCell;
+use microeconomics::matching::MATCHING_NAMES;
+use microeconomics::ordering::TICK_ORDER_NAMES;
+use microeconomics::preference_list::ACTOR_STATE_NAMES;
+use microeconomics::trade;
+use microeconomics::{
+    Account, ActorId, ActorState, Arbitrageur, Bank, Bundle, Collector, Disposal, Drift, Factor,
+    GoalData, GoalId, GoalRegistry, ItemId, ItemRegistry, LegalTender, Matching, Middleman,
+    PriceControl, Quantity, Recorder, Replay, ResourceNode, Scenario, SimEvent, Simulation,
+    TaxBase, TickOrder, TradeCost, TradeOutcome,
+};
+use rand::Rng;
+use rayon::prelude::*;
+use serde::Serialize;
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::io;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
 fn main() -> io::Result<()> {
-    let goal_hierarchy: Vec<GoalData> = vec![
-        GoalData::RegularSatisfaction {
-            goal: Goal::Eat,
-            id: 0,
-            time_required: 10,
-            time: 0,
-            units_required: 2,
-            units: 0,
+    let opts: Cli = Cli::from_args();
+    if let Some(Command::Experiment(experiment)) = &opts.command {
+        run_experiment(&opts, experiment);
+        return Ok(());
+    }
+    // Always run from a known seed, so any run can be reproduced later
+    let seed = opts.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut sim = setup(&opts, seed);
+
+    if let Some(path) = &opts.record {
+        match Recorder::create(path) {
+            Ok(recorder) => sim.start_recording(recorder),
+            Err(e) => {
+                eprintln!(
+                    "{} couldn't record to {}: {}",
+                    "error:".red(),
+                    path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if opts.no_repl {
+        // Nobody's watching, so just run it through and write out where
+        // it got to
+        let mut summary = TickSummary::new(sim.ticks());
+        for _ in 0..opts.ticks.unwrap_or_default() {
+            let events = sim.tick();
+            summary.note(&events);
+        }
+        let results = BatchResults::new(&sim, seed, &summary);
+        let json = serde_json::to_string_pretty(&results).expect("results always serialize");
+        match &opts.output {
+            Some(path) => {
+                if let Err(e) = std::fs::write(path, json + "\n") {
+                    eprintln!(
+                        "{} couldn't write {}: {}",
+                        "error:".red(),
+                        path.display(),
+                        e
+                    );
+                    std::process::exit(1);
+                }
+            }
+            None => println!("{}", json),
+        }
+        return Ok(());
+    }
+
+    println!("Welcome to the microeconomic actor prototype interactive interface.");
+    println!("Enter \"help\" for a list of commands.");
+    println!("Press Ctrl-D or enter \"quit\" to exit.");
+    println!("Using random seed {}.", seed.to_string().yellow());
+    println!();
+    sim.set_history(opts.history);
+    if let Some(ticks) = opts.ticks {
+        tick_many(&mut sim, ticks, true, &mut []);
+    }
+
+    let reader = Interface::new("microeconomics")?;
+    reader.set_completer(completer(&sim));
+    reader.set_prompt(&"interaction> ".bold().blue().to_string())?;
+
+    // The event log being stepped through, if one is
+    let mut replay: Option<Replay> = None;
+    // The world being run, and every other world in the session (see
+    // `world create`), by name, with the event log being stepped through in
+    // it, if one is
+    let mut world = String::from("main");
+    let mut worlds: BTreeMap<String, (Simulation, Option<Replay>)> = BTreeMap::new();
+    // Every property of an actor being shown again whenever the world's run
+    // on (see `watch`)
+    let mut watches: Vec<Watch> = vec![];
+    // How often to run a tick on its own, if the world's playing (see
+    // `play`), and when the next one's due
+    let mut playing: Option<Duration> = None;
+    let mut next_tick = Instant::now();
+    loop {
+        let input = match playing {
+            None => match reader.read_line()? {
+                ReadResult::Input(input) => input,
+                _ => break,
+            },
+            // Wait for a command only until the next tick's due
+            Some(every) => match reader
+                .read_line_step(Some(next_tick.saturating_duration_since(Instant::now())))?
+            {
+                Some(ReadResult::Input(input)) => input,
+                Some(_) => break,
+                None if Instant::now() >= next_tick => {
+                    // Run it above whatever's being typed in
+                    let _erased = reader.lock_writer_erase()?;
+                    for event in sim.tick() {
+                        render_event(&sim, &event);
+                    }
+                    println!();
+                    catch_up(&mut sim, &mut watches);
+                    next_tick = Instant::now() + every;
+                    continue;
+                }
+                None => continue,
+            },
+        };
+        if !input.trim().is_empty() {
+            reader.add_history_unique(input.clone());
+        }
+        let cmd: Vec<&str> = input.trim().split_ascii_whitespace().collect();
+        if changes_world(&cmd) {
+            sim.record_intervention(input.trim());
+        }
+        match &*cmd {
+            ["help"] => {
+                println!("actor interface commands:");
+                println!();
+                for &(cmd, help) in INT_COMMANDS {
+                    println!("  {:20} - {}", cmd.green(), help);
+                }
+                println!();
+            }
+            ["get-actor", property, actorid] => match describe_actor(&sim, property, actorid) {
+                Ok(description) => print!("{}", description),
+                Err(e) => println!("{}", e),
+            },
+            ["compare-item-values", actor, item1, item2] => {
+                let actor = match sim.find_actor(actor).and_then(|id| sim.actor(id)) {
+                    Some(actor) => actor,
+                    None => {
+                        println!("{} {}", "cannot find actor:".red(), actor);
+                        continue;
+                    }
+                };
+                let (i1, i2) = match (sim.items().id(item1), sim.items().id(item2)) {
+                    (Some(i1), Some(i2)) => (i1, i2),
+                    (None, _) => {
+                        println!("{} {}", "unrecognized item:".red(), item1);
+                        continue;
+                    }
+                    (_, None) => {
+                        println!("{} {}", "unrecognized item:".red(), item2);
+                        continue;
+                    }
+                };
+                match actor.compare_item_values(i1, i2) {
+                    Ordering::Equal => println!("These items are valued the same!"),
+                    Ordering::Less => println!(
+                        "{} is valued less than {}",
+                        item1.to_string().green(),
+                        item2.to_string().green()
+                    ),
+                    Ordering::Greater => println!(
+                        "{} is valued more than {}",
+                        item1.to_string().green(),
+                        item2.to_string().green()
+                    ),
+                }
+            }
+            ["tick"] => {
+                for event in sim.tick() {
+                    render_event(&sim, &event);
+                }
+                println!();
+            }
+            ["tick", "--quiet"] => tick_many(&mut sim, 1, true, &mut watches),
+            ["run-until", condition @ ..] => {
+                let quiet = condition.contains(&"--quiet");
+                let condition: Vec<&str> = condition
+                    .iter()
+                    .copied()
+                    .filter(|&word| word != "--quiet")
+                    .collect();
+                match parse_condition(&sim, &condition) {
+                    Ok(condition) if quiet => run_until(&mut sim, condition, &mut []),
+                    Ok(condition) => run_until(&mut sim, condition, &mut watches),
+                    Err(error) => println!("{}", error.red()),
+                }
+            }
+            ["tick", count] | ["tick", count, "--quiet"] | ["tick", "--quiet", count] => {
+                match count.parse::<u64>() {
+                    Ok(count) if count > 0 => {
+                        tick_many(&mut sim, count, cmd.contains(&"--quiet"), &mut watches)
+                    }
+                    _ => println!("{} {}", "not a valid number of ticks:".red(), count),
+                }
+            }
+            ["give-item", actor, item] => give_item(&mut sim, actor, item, Quantity::ONE),
+            ["give-item", actor, item, amount] => match amount.parse::<Quantity>() {
+                Ok(amount) if !amount.is_zero() => give_item(&mut sim, actor, item, amount),
+                _ => println!("{} {}", "not a valid amount:".red(), amount),
+            },
+            ["mint", actor, amount] => match amount.parse::<Quantity>() {
+                Ok(amount) if !amount.is_zero() => mint(&mut sim, actor, amount, None),
+                _ => println!("{} {}", "not a valid amount:".red(), amount),
+            },
+            ["mint", actor, amount, item] => match amount.parse::<Quantity>() {
+                Ok(amount) if !amount.is_zero() => mint(&mut sim, actor, amount, Some(item)),
+                _ => println!("{} {}", "not a valid amount:".red(), amount),
+            },
+            ["gift", from, to, item] => gift(&mut sim, from, to, item, Quantity::ONE),
+            ["gift", from, to, item, amount] => match amount.parse::<Quantity>() {
+                Ok(amount) if !amount.is_zero() => gift(&mut sim, from, to, item, amount),
+                _ => println!("{} {}", "not a valid amount:".red(), amount),
+            },
+            ["trade", from, give, to, take] => trade_items(&mut sim, from, give, to, take),
+            ["lease", landlord, tenant, item, term, rent_item] => lease_item(
+                &mut sim,
+                landlord,
+                tenant,
+                item,
+                term,
+                rent_item,
+                Quantity::ONE,
+            ),
+            ["lease", landlord, tenant, item, term, rent_item, rent] => {
+                match rent.parse::<Quantity>() {
+                    Ok(rent) if !rent.is_zero() => {
+                        lease_item(&mut sim, landlord, tenant, item, term, rent_item, rent)
+                    }
+                    _ => println!("{} {}", "not a valid amount:".red(), rent),
+                }
+            }
+            ["impose-price-ceiling", item, quote, ratio] => {
+                impose_price_control(&mut sim, item, quote, ratio, true)
+            }
+            ["impose-price-floor", item, quote, ratio] => {
+                impose_price_control(&mut sim, item, quote, ratio, false)
+            }
+            ["impose-legal-tender", item, quote, ratio] => {
+                impose_legal_tender(&mut sim, item, quote, ratio)
+            }
+            ["impose-tax", rate] => impose_tax(&mut sim, rate, "trades", "redistribute"),
+            ["impose-tax", rate, base] => impose_tax(&mut sim, rate, base, "redistribute"),
+            ["impose-tax", rate, base, disposal] => impose_tax(&mut sim, rate, base, disposal),
+            ["add-public", item] => add_public(&mut sim, item, Quantity::ONE),
+            ["add-public", item, amount] => match amount.parse::<Quantity>() {
+                Ok(amount) if !amount.is_zero() => add_public(&mut sim, item, amount),
+                _ => println!("{} {}", "not a valid amount:".red(), amount),
+            },
+            ["add-resource", item, per_tick, name @ ..] => match per_tick.parse::<Quantity>() {
+                Ok(per_tick) if !per_tick.is_zero() => {
+                    add_resource(&mut sim, item, per_tick, &name.join(" "))
+                }
+                _ => println!("{} {}", "not a valid amount:".red(), per_tick),
+            },
+            ["stats", "specialization"] => show_specialization(&sim, 0),
+            ["stats", "mint"] => show_minting(&sim),
+            ["stats", "savings"] => show_savings(&sim),
+            ["stats", "price-controls"] => show_price_controls(&sim),
+            ["stats", "legal-tender"] => show_legal_tender(&sim),
+            ["stats", "tax"] => show_tax(&sim),
+            ["stats", "purchasing-power"] => match sim.prices().most_traded() {
+                Some(money) => show_purchasing_power(&sim, money),
+                None => println!("nothing's been traded, so nothing's bought anything"),
+            },
+            ["stats", "purchasing-power", item] => match sim.items().id(item) {
+                Some(money) => show_purchasing_power(&sim, money),
+                None => println!("{} {}", "unrecognized item:".red(), item),
+            },
+            ["stats", "imputation"] => match sim.prices().most_traded() {
+                Some(quote) => show_imputation(&sim, quote),
+                None => println!("nothing's been traded to impute values from"),
+            },
+            ["stats", "imputation", item] => match sim.items().id(item) {
+                Some(quote) => show_imputation(&sim, quote),
+                None => println!("{} {}", "unrecognized item:".red(), item),
+            },
+            ["stats", "specialization", ticks] => match ticks.parse::<u64>() {
+                Ok(ticks) => show_specialization(&sim, sim.ticks().saturating_sub(ticks)),
+                Err(_) => println!("{} {}", "not a valid number of ticks:".red(), ticks),
+            },
+            ["resources"] => {
+                if sim.resources().is_empty() {
+                    println!("there's nowhere to gather anything from");
+                } else {
+                    println!(
+                        "{:20} | {:20} | {:20}",
+                        "Resource".bold(),
+                        "Yields".bold(),
+                        "Per Tick".bold()
+                    );
+                    let twenty = "-".to_string().repeat(20);
+                    println!("{}-+-{}-+-{}", twenty, twenty, twenty);
+                    for node in sim.resources() {
+                        println!(
+                            "{:20} | {:20} | {:20}",
+                            node.name.green(),
+                            sim.items().name(node.item),
+                            node.yield_per_tick.to_string()
+                        );
+                    }
+                    println!();
+                }
+            }
+            ["add-goal", actor, goal, how @ ..] => add_goal(&mut sim, actor, goal, how),
+            ["remove-goal", actor, goal] => remove_goal(&mut sim, actor, goal),
+            ["prices"] => {
+                let prices = sim.prices();
+                let pairs = prices.pairs();
+                if pairs.is_empty() {
+                    println!("nothing has been traded yet");
+                } else {
+                    println!(
+                        "{:30} | {:20} | {:20} | {:20}",
+                        "Pair".bold(),
+                        "Last Ratio (Tick)".bold(),
+                        "Average Ratio".bold(),
+                        "# Trades".bold()
+                    );
+                    let twenty = "-".to_string().repeat(20);
+                    println!("{}-+-{}-+-{}-+-{}", "-".repeat(30), twenty, twenty, twenty);
+                    for (base, quote) in pairs {
+                        let latest = prices.latest(base, quote).unwrap();
+                        let (average, trades) = prices.average(base, quote).unwrap();
+                        println!(
+                            "{:30} | {:20} | {:20} | {:20}",
+                            format!("{} in {}", sim.items().name(base), sim.items().name(quote))
+                                .green(),
+                            format!("{} ({})", latest.ratio, latest.tick),
+                            average.to_string(),
+                            trades.to_string()
+                        );
+                    }
+                    println!();
+                }
+            }
+            ["wages"] => {
+                let wages = sim.wages();
+                let items = wages.items();
+                if items.is_empty() {
+                    println!("nobody has hired anyone yet");
+                } else {
+                    println!(
+                        "{:30} | {:20} | {:20} | {:20}",
+                        "Paid In".bold(),
+                        "Last Rate (Tick)".bold(),
+                        "Average Rate".bold(),
+                        "# Hires".bold()
+                    );
+                    let twenty = "-".to_string().repeat(20);
+                    println!("{}-+-{}-+-{}-+-{}", "-".repeat(30), twenty, twenty, twenty);
+                    for item in items {
+                        let latest = wages.latest(item).unwrap();
+                        let (average, hires) = wages.average(item).unwrap();
+                        println!(
+                            "{:30} | {:20} | {:20} | {:20}",
+                            format!("{} an hour", sim.items().name(item)).green(),
+                            format!("{} ({})", latest.rate, latest.tick),
+                            average.to_string(),
+                            hires.to_string()
+                        );
+                    }
+                    println!();
+                }
+            }
+            ["recipes"] => {
+                if sim.items().recipes().next().is_none() {
+                    println!("nothing can be made out of anything else");
+                } else {
+                    println!(
+                        "{:20} | {:30} | {:30} | {:10} | {:10} | {:10}",
+                        "Recipe".bold(),
+                        "Inputs".bold(),
+                        "Outputs".bold(),
+                        "Labor".bold(),
+                        "Ticks".bold(),
+                        "Known By".bold()
+                    );
+                    let (ten, thirty) = ("-".repeat(10), "-".repeat(30));
+                    println!(
+                        "{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+                        "-".repeat(20),
+                        thirty,
+                        thirty,
+                        ten,
+                        ten,
+                        ten
+                    );
+                    for (id, recipe) in sim.items().recipes() {
+                        let known_by = if recipe.discovery.is_none() {
+                            "everyone".to_string()
+                        } else {
+                            sim.actors()
+                                .filter(|actor| actor.know_how.knows(id, recipe))
+                                .count()
+                                .to_string()
+                        };
+                        println!(
+                            "{:20} | {:30} | {:30} | {:10} | {:10} | {:10}",
+                            recipe.name.green(),
+                            if recipe.tools.is_empty() {
+                                render_plain_bundle(&sim, &recipe.inputs)
+                            } else {
+                                format!(
+                                    "{} (with {})",
+                                    render_plain_bundle(&sim, &recipe.inputs),
+                                    render_plain_bundle(&sim, &recipe.tools)
+                                )
+                            },
+                            render_plain_bundle(&sim, &recipe.outputs),
+                            recipe.labor.to_string(),
+                            recipe.duration.to_string(),
+                            known_by
+                        );
+                    }
+                    println!();
+                }
+            }
+            ["export-trades", "dot", file] => {
+                let dot = sim.trades().to_dot(sim.arena(), sim.items());
+                match std::fs::write(file, dot) {
+                    Ok(()) => println!(
+                        "wrote {} trading links to {}",
+                        sim.trades().links().len(),
+                        file.green()
+                    ),
+                    Err(e) => println!("{} {}", "error:".red(), e),
+                }
+            }
+            ["export-trades", format, _] => {
+                println!("{} {}", "unrecognized format:".red(), format)
+            }
+            ["export-purchasing-power", "csv", file] => match sim.prices().most_traded() {
+                Some(money) => export_purchasing_power(&sim, money, file),
+                None => println!("nothing's been traded, so nothing's bought anything"),
+            },
+            ["export-purchasing-power", "csv", file, item] => match sim.items().id(item) {
+                Some(money) => export_purchasing_power(&sim, money, file),
+                None => println!("{} {}", "unrecognized item:".red(), item),
+            },
+            ["export-purchasing-power", format, ..] => {
+                println!("{} {}", "unrecognized format:".red(), format)
+            }
+            ["save", file] => match sim.save(file) {
+                Ok(()) => println!(
+                    "saved the world at tick {} to {}",
+                    sim.ticks(),
+                    file.green()
+                ),
+                Err(e) => println!("{} {}", "error:".red(), e),
+            },
+            ["load", file] => match Simulation::load(file) {
+                Ok(loaded) => {
+                    // Whatever was being recorded carries on in the new world
+                    let recorder = sim.stop_recording();
+                    sim = loaded;
+                    sim.set_history(opts.history);
+                    if let Some(recorder) = recorder {
+                        sim.start_recording(recorder);
+                    }
+                    // Whatever's in it now is what there is to complete
+                    reader.set_completer(completer(&sim));
+                    println!(
+                        "loaded the world at tick {} from {}",
+                        sim.ticks(),
+                        file.green()
+                    );
+                }
+                Err(e) => println!("{} {}", "error:".red(), e),
+            },
+            ["record", "stop"] => match sim.stop_recording() {
+                Some(_) => println!("stopped recording"),
+                None => println!("not recording anything"),
+            },
+            ["record", file] => match Recorder::create(file) {
+                Ok(recorder) => {
+                    sim.start_recording(recorder);
+                    println!(
+                        "recording everything from tick {} on to {}",
+                        sim.ticks(),
+                        file.green()
+                    );
+                }
+                Err(e) => println!("{} {}", "error:".red(), e),
+            },
+            ["replay", file] => match Replay::open(file) {
+                Ok((log, world)) => {
+                    if sim.recorder().is_some() {
+                        println!("stopped recording");
+                    }
+                    sim = world;
+                    sim.set_history(opts.history);
+                    reader.set_completer(completer(&sim));
+                    replay = Some(log);
+                    println!(
+                        "replaying {} from tick {} (\"step\" to go on a tick)",
+                        file.green(),
+                        sim.ticks()
+                    );
+                }
+                Err(e) => println!("{} {}", "error:".red(), e),
+            },
+            ["step"] => step_replay(&mut sim, &mut replay, 1),
+            ["step", count] => match count.parse::<u64>() {
+                Ok(count) if count > 0 => step_replay(&mut sim, &mut replay, count),
+                _ => println!("{} {}", "not a valid number of ticks:".red(), count),
+            },
+            ["rewind"] => rewind(&mut sim, 1),
+            ["rewind", count] => match count.parse::<usize>() {
+                Ok(count) if count > 0 => rewind(&mut sim, count),
+                _ => println!("{} {}", "not a valid number of ticks:".red(), count),
+            },
+            ["world"] => {
+                println!("{} (tick {}, being run)", world.green(), sim.ticks());
+                for (name, (other, _)) in worlds.iter() {
+                    println!("{} (tick {})", name.green(), other.ticks());
+                }
+            }
+            ["world", "create", name] => {
+                if *name == world || worlds.contains_key(*name) {
+                    println!("{} {}", "there's already a world named".red(), name);
+                } else {
+                    worlds.insert(name.to_string(), (sim.fork(), None));
+                    println!(
+                        "created world {} as a copy of {} at tick {} (\"world switch {}\" to run it)",
+                        name.green(),
+                        world.green(),
+                        sim.ticks(),
+                        name
+                    );
+                }
+            }
+            ["world", "switch", name] => match worlds.remove(*name) {
+                Some((other, other_replay)) => {
+                    let (last, last_replay) = (
+                        std::mem::replace(&mut sim, other),
+                        std::mem::replace(&mut replay, other_replay),
+                    );
+                    worlds.insert(
+                        std::mem::replace(&mut world, name.to_string()),
+                        (last, last_replay),
+                    );
+                    reader.set_completer(completer(&sim));
+                    println!("switched to world {} at tick {}", name.green(), sim.ticks());
+                }
+                None if *name == world => println!("already running world {}", name.green()),
+                None => println!("{} {}", "unrecognized world:".red(), name),
+            },
+            ["world", "diff", name] => match worlds.get(*name) {
+                Some((other, _)) => diff_worlds(&world, &sim, name, other),
+                None if *name == world => println!("that's the world being run"),
+                None => println!("{} {}", "unrecognized world:".red(), name),
+            },
+            ["watch"] if watches.is_empty() => println!("not watching anything"),
+            ["watch"] => {
+                for watch in watches.iter() {
+                    println!("{} {}", watch.actor.yellow(), watch.property.green());
+                }
+            }
+            ["watch", actor, property] => {
+                if watches
+                    .iter()
+                    .any(|w| w.actor == *actor && w.property == *property)
+                {
+                    println!("already watching {} {}", actor.yellow(), property.green());
+                } else {
+                    match describe_actor(&sim, property, actor) {
+                        Ok(description) => {
+                            print!("{}", description);
+                            watches.push(Watch {
+                                actor: actor.to_string(),
+                                property: property.to_string(),
+                                last: description,
+                                tick: sim.ticks(),
+                            });
+                        }
+                        Err(e) => println!("{}", e),
+                    }
+                }
+            }
+            ["unwatch"] => {
+                watches.clear();
+                println!("stopped watching everything");
+            }
+            ["unwatch", actor] | ["unwatch", actor, _] => {
+                let before = watches.len();
+                watches
+                    .retain(|w| w.actor != *actor || cmd.get(2).is_some_and(|p| w.property != *p));
+                match before - watches.len() {
+                    0 => println!("{} {}", "not watching".red(), cmd[1..].join(" ")),
+                    n => println!(
+                        "stopped watching {} propert{}",
+                        n,
+                        if n == 1 { "y" } else { "ies" }
+                    ),
+                }
+            }
+            ["play"] => {
+                playing = Some(Duration::from_millis(PLAY_SPEED));
+                next_tick = Instant::now();
+                println!("playing a tick every {}ms (\"pause\" to stop)", PLAY_SPEED);
+            }
+            ["play", ms] => match ms.parse::<u64>() {
+                Ok(ms) if ms > 0 => {
+                    playing = Some(Duration::from_millis(ms));
+                    next_tick = Instant::now();
+                    println!("playing a tick every {}ms (\"pause\" to stop)", ms);
+                }
+                _ => println!("{} {}", "not a valid number of milliseconds:".red(), ms),
+            },
+            ["pause"] => match playing.take() {
+                Some(_) => println!("paused at tick {}", sim.ticks()),
+                None => println!("not playing"),
+            },
+            ["quit"] => return Ok(()),
+            _ => println!("{} {}", "unrecognized command".red(), cmd.join(" ")),
+        }
+        catch_up(&mut sim, &mut watches);
+    }
+
+    println!("Exiting...");
+    Ok(())
+}
+
+/// Describe one property of an actor, the way `get-actor` shows it, or say
+/// why it can't be.
+fn describe_actor(sim: &Simulation, property: &str, actorid: &str) -> Result<String, String> {
+    let actor = match sim.find_actor(actorid).and_then(|id| sim.actor(id)) {
+        Some(actor) => actor,
+        None => return Err(format!("cannot find actor: {}", actorid)),
+    };
+    let mut out = String::new();
+    match property {
+        "preference-list" => {
+            writeln!(out, "ordinal hierarchy of items for {}:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20}",
+                "Item".bold(),
+                "Highest-Valued Goal".bold(),
+                "# Goals".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for (item, bh) in actor.values.preference_list.iter() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:20}",
+                    sim.items().name(*item).green(),
+                    if let Some(g) = bh.peek() {
+                        sim.goals().name(g.goal).blue()
+                    } else {
+                        "N/A".to_string().blue()
+                    },
+                    format!("{:?}", bh.capacity())
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "state" => {
+            writeln!(out, "general AI state for {}:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "- ACTOR STATE").unwrap();
+            writeln!(out, "  {}", format!("{:?}", actor.state).yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "- CURRENT GOALS IN PLAY").unwrap();
+            writeln!(out, "  {:10} | {:10}", "Goal".bold(), "Index".bold()).unwrap();
+            writeln!(out, "  {:-^1$}", "+", 23).unwrap();
+            let mut sorted_goals: Vec<_> = actor
+                .values
+                .current_goals
+                .iter()
+                .map(|x| (x.goal, actor.values.goal_hierarchy.get(&x.goal).unwrap()))
+                .collect();
+            sorted_goals.sort_by_key(|f| f.1);
+            for (goal, index) in sorted_goals {
+                writeln!(
+                    out,
+                    "  {:10} | {:10}",
+                    sim.goals().name(goal).blue(),
+                    format!("{:?}", index)
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            writeln!(out, "- INVENTORY").unwrap();
+            writeln!(
+                out,
+                "  {:20} | {:20} | {:20}",
+                "Item".bold(),
+                "Highest-Valued Goal".bold(),
+                "# Goals".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "  {}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for item in actor.ordered_inventory() {
+                let bh = actor.values.preference_list.get(&item);
+                writeln!(
+                    out,
+                    "  {:20} | {:20} | {:20}",
+                    format!(
+                        "{} x{}",
+                        sim.items().name(item),
+                        actor.inventory.count(item)
+                    )
+                    .green(),
+                    if let Some(g) = bh.and_then(|x| x.peek()) {
+                        sim.goals().name(g.goal).blue()
+                    } else {
+                        "N/A".to_string().blue()
+                    },
+                    format!("{:?}", bh.map(|x| x.capacity()).unwrap_or(0))
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            if !actor.inventory.in_service().is_empty() {
+                writeln!(out, "- IN SERVICE").unwrap();
+                writeln!(
+                    out,
+                    "  {:20} | {:20} | {:20}",
+                    "Item".bold(),
+                    "Serving Goal".bold(),
+                    "Ticks Left".bold()
+                )
+                .unwrap();
+                writeln!(out, "  {}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+                for service in actor.inventory.in_service() {
+                    writeln!(
+                        out,
+                        "  {:20} | {:20} | {:20}",
+                        format!("{} x{}", sim.items().name(service.item), service.amount).green(),
+                        sim.goals().name(service.goal).blue(),
+                        service.ticks_left.to_string()
+                    )
+                    .unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+            if actor.inventory.reservations().next().is_some() {
+                writeln!(out, "- RESERVED").unwrap();
+                writeln!(out, "  {:20} | {:41}", "Goal".bold(), "Kept For It".bold()).unwrap();
+                writeln!(out, "  {}-+-{}", twenty, "-".repeat(41)).unwrap();
+                for (goal, goods) in actor.inventory.reservations() {
+                    writeln!(
+                        out,
+                        "  {:20} | {:41}",
+                        sim.goals().name(goal).blue(),
+                        format!(
+                            "{} ({})",
+                            render_plain_bundle(sim, goods),
+                            if actor.values.is_in_play(goal) {
+                                "for its plan"
+                            } else {
+                                "saved"
+                            }
+                        )
+                    )
+                    .unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        }
+        "goal-registry" => {
+            writeln!(out, "goal details for {}:", actorid.yellow()).unwrap();
+            let mut registry: Vec<(&GoalId, &GoalData)> =
+                actor.values.goal_registry.iter().collect();
+            registry.sort_by_key(|(g, _)| actor.values.goal_hierarchy.get(g).unwrap());
+            for (goal, goal_data) in registry {
+                writeln!(out).unwrap();
+                writeln!(out, "- {}", sim.goals().name(*goal).blue()).unwrap();
+                writeln!(out, "  {:?}", goal_data).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "goal-hierarchy" => {
+            writeln!(out, "ordinal hierarchy of values for {}:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "{:10} | {:10}", "Goal".bold(), "Index".bold()).unwrap();
+            writeln!(out, "{:-^1$}", "+", 23).unwrap();
+            let mut sorted_goals: Vec<_> = actor.values.goal_hierarchy.iter().collect();
+            sorted_goals.sort_by_key(|f| f.1);
+            for (goal, index) in sorted_goals {
+                writeln!(
+                    out,
+                    "{:10} | {:10}",
+                    sim.goals().name(*goal).blue(),
+                    format!("{:?}", index)
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "provenance" => {
+            writeln!(out, "where {} got what it has:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20}",
+                "Item".bold(),
+                "Got From".bold(),
+                "On Tick".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for item in actor.ordered_inventory() {
+                for lot in actor.inventory.lots(item) {
+                    let from = match lot.acquired.from.and_then(|id| sim.actor(id)) {
+                        Some(from) => from.name.to_string(),
+                        None => "nobody".to_string(),
+                    };
+                    writeln!(
+                        out,
+                        "{:20} | {:20} | {:20}",
+                        format!("{} x{}", sim.items().name(item), lot.amount).green(),
+                        from.yellow(),
+                        lot.acquired.tick.to_string()
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(out).unwrap();
+        }
+        "satisfaction-history" => {
+            writeln!(out, "goals {} has seen to:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20} | {:20}",
+                "Goal".bold(),
+                "Satisfied On Tick".bold(),
+                "Units Used".bold(),
+                "Ticks Waited".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}-+-{}", twenty, twenty, twenty, twenty).unwrap();
+            let records = actor.ledger.records();
+            for record in records {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:20} | {:20}",
+                    sim.goals().name(record.goal).blue(),
+                    record.tick.to_string(),
+                    record.units.to_string(),
+                    record.waited.to_string()
+                )
+                .unwrap();
+            }
+            if !records.is_empty() {
+                let waited: u64 = records.iter().map(|r| r.waited).sum();
+                writeln!(out).unwrap();
+                writeln!(
+                    out,
+                    "{} goals satisfied, after waiting {:.1} ticks on average",
+                    records.len(),
+                    waited as f64 / records.len() as f64
+                )
+                .unwrap();
+            }
+            let waiting = actor.ledger.waiting(sim.ticks());
+            if !waiting.is_empty() {
+                writeln!(out).unwrap();
+                writeln!(out, "- STILL WAITING").unwrap();
+                for (goal, ticks) in waiting {
+                    writeln!(
+                        out,
+                        "  {} for {} ticks",
+                        sim.goals().name(goal).blue(),
+                        ticks
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(out).unwrap();
+        }
+        "trade-history" => {
+            writeln!(
+                out,
+                "every bid and trade {} has been part of:",
+                actorid.yellow()
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:10} | {:20} | {:40} | {:20}",
+                "Tick".bold(),
+                "Counterparty".bold(),
+                "Items (Gave for Got)".bold(),
+                "Outcome".bold()
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "{}-+-{}-+-{}-+-{}",
+                "-".repeat(10),
+                "-".repeat(20),
+                "-".repeat(40),
+                "-".repeat(20)
+            )
+            .unwrap();
+            for record in actor.trades.records() {
+                let by = sim.actor_name(record.by);
+                let outcome = match record.outcome {
+                    TradeOutcome::Bid => format!("bid by {}", by),
+                    TradeOutcome::CounterOffer => format!("countered by {}", by),
+                    TradeOutcome::Rejected => format!("rejected by {}", by),
+                    TradeOutcome::Completed => "completed".to_string(),
+                    TradeOutcome::Circle => "completed in a circle".to_string(),
+                    TradeOutcome::Abandoned => format!("abandoned by {}", by),
+                };
+                writeln!(
+                    out,
+                    "{:10} | {:20} | {:40} | {:20}",
+                    record.tick.to_string(),
+                    sim.actor_name(record.counterparty).yellow(),
+                    if record.gave.is_empty() && record.got.is_empty() {
+                        "-".to_string()
+                    } else {
+                        format!(
+                            "{} for {}",
+                            render_plain_bundle(sim, &record.gave),
+                            render_plain_bundle(sim, &record.got)
+                        )
+                    },
+                    outcome
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "credit" => {
+            writeln!(out, "what {} has promised:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            for iou in actor.credit.ious() {
+                writeln!(
+                    out,
+                    "- owes {} {} by tick {} (borrowed {} on tick {})",
+                    sim.actor_name(iou.creditor).yellow(),
+                    render_bundle(sim, &iou.owed),
+                    iou.due,
+                    render_bundle(sim, &iou.lent),
+                    iou.issued
+                )
+                .unwrap();
+            }
+            for forward in actor.credit.forwards() {
+                writeln!(
+                    out,
+                    "- {} {} for {} with {} on tick {}",
+                    if forward.buyer == actor.id {
+                        "swaps"
+                    } else {
+                        "takes"
+                    },
+                    render_bundle(sim, &forward.pays),
+                    render_bundle(sim, &forward.gets),
+                    sim.actor_name(if forward.buyer == actor.id {
+                        forward.seller
+                    } else {
+                        forward.buyer
+                    })
+                    .yellow(),
+                    forward.due
+                )
+                .unwrap();
+            }
+            for lease in actor.credit.leases() {
+                if lease.landlord == actor.id {
+                    writeln!(
+                        out,
+                        "- lets {} to {} for {} a tick until tick {}",
+                        render_bundle(sim, &lease.leased),
+                        sim.actor_name(lease.tenant).yellow(),
+                        render_bundle(sim, &lease.rent),
+                        lease.due
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(
+                        out,
+                        "- rents {} from {} for {} a tick until tick {}",
+                        render_bundle(sim, &lease.leased),
+                        sim.actor_name(lease.landlord).yellow(),
+                        render_bundle(sim, &lease.rent),
+                        lease.due
+                    )
+                    .unwrap();
+                }
+            }
+            let owed: Vec<_> = sim
+                .actors()
+                .flat_map(|a| a.credit.ious().iter())
+                .filter(|iou| iou.creditor == actor.id)
+                .collect();
+            for iou in owed {
+                writeln!(
+                    out,
+                    "- is owed {} by {} by tick {}",
+                    render_bundle(sim, &iou.owed),
+                    sim.actor_name(iou.debtor).yellow(),
+                    iou.due
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "saleability" => {
+            writeln!(
+                out,
+                "how readily {} thinks goods are accepted:",
+                actorid.yellow()
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20}",
+                "Item".bold(),
+                "Times Accepted".bold(),
+                "Holding For".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for (item, times) in actor.values.most_saleable() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:20}",
+                    sim.items().name(item).green(),
+                    times.to_string(),
+                    match actor.values.media.get(&item) {
+                        Some(&goal) => sim.goals().name(goal).blue(),
+                        None => "N/A".to_string().blue(),
+                    }
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "trade-memory" => {
+            writeln!(out, "who {} remembers trading with:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20}",
+                "Partner".bold(),
+                "Trades Accepted".bold(),
+                "Bids Rejected".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for (partner, record) in actor.memory.partners() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:20}",
+                    sim.actor_name(partner).yellow(),
+                    record.accepted.to_string(),
+                    record.rejected.to_string()
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            if actor.memory.is_imperfect() {
+                writeln!(out, "- WHAT IT'S SEEN").unwrap();
+                for (other, sighting) in actor.memory.sightings() {
+                    let items: Vec<_> = sighting
+                        .items
+                        .iter()
+                        .map(|&i| sim.items().name(i))
+                        .collect();
+                    writeln!(
+                        out,
+                        "  {} had {} on tick {}",
+                        sim.actor_name(other).yellow(),
+                        items.join(", ").green(),
+                        sighting.tick
+                    )
+                    .unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        }
+        "expectations" => {
+            writeln!(out, "what {} expects goods to go for:", actorid.yellow()).unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:20}",
+                "Pair".bold(),
+                "Expected Ratio".bold(),
+                "Trades Seen".bold()
+            )
+            .unwrap();
+            let twenty = "-".to_string().repeat(20);
+            writeln!(out, "{}-+-{}-+-{}", twenty, twenty, twenty).unwrap();
+            for ((base, quote), expected) in actor.expectations.all() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:20}",
+                    format!("{}/{}", sim.items().name(base), sim.items().name(quote)).green(),
+                    expected.ratio.to_string(),
+                    expected.seen.to_string()
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            if let Some(tolerance) = actor.expectations.tolerance() {
+                writeln!(
+                    out,
+                    "- holds out for trades no more than {} worse than that",
+                    tolerance
+                )
+                .unwrap();
+                writeln!(out).unwrap();
+            }
+        }
+        "bank-book" => {
+            let book = match sim.bank_book(actor.id) {
+                Some(book) => book,
+                None => {
+                    writeln!(out, "{} hasn't taken deposits or lent anything", actorid).unwrap();
+                    return Ok(out);
+                }
+            };
+            let money = sim.items().name(book.money);
+            writeln!(
+                out,
+                "balance sheet for {}, in {}:",
+                actorid.yellow(),
+                money.green()
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:20} | {:10}",
+                "Owed By/To".bold(),
+                "Owed".bold(),
+                "Due".bold()
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "{}-+-{}-+-{}",
+                "-".repeat(20),
+                "-".repeat(20),
+                "-".repeat(10)
+            )
+            .unwrap();
+            writeln!(out, "{}", "LOANS".bold()).unwrap();
+            for iou in book.loans.iter() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:10}",
+                    sim.actor_name(iou.debtor),
+                    iou.owed.count(book.money).to_string(),
+                    iou.due
+                )
+                .unwrap();
+            }
+            writeln!(out, "{}", "DEPOSITS".bold()).unwrap();
+            for iou in book.deposits.iter() {
+                writeln!(
+                    out,
+                    "{:20} | {:20} | {:10}",
+                    sim.actor_name(iou.creditor),
+                    iou.owed.count(book.money).to_string(),
+                    iou.due
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            writeln!(out, "- holds {} {} in reserve", book.reserves, money).unwrap();
+            writeln!(out, "- is owed {} {} on its loans", book.lent(), money).unwrap();
+            writeln!(out, "- owes its depositors {} {}", book.owed(), money).unwrap();
+            let unpaid: Quantity = book
+                .defaulted
+                .iter()
+                .map(|iou| iou.owed.count(book.money))
+                .sum();
+            if !unpaid.is_zero() {
+                writeln!(
+                    out,
+                    "- of which {} {} is on deposits it couldn't pay back",
+                    unpaid, money
+                )
+                .unwrap();
+            }
+            if book.has_failed() {
+                writeln!(out, "- has {}", "FAILED".red()).unwrap();
+            } else {
+                writeln!(out, "- is {}", "solvent".green()).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        "pnl" => {
+            let totals = actor.accounts.totals();
+            let quote = match sim.prices().most_traded() {
+                Some(quote) if !totals.is_empty() => quote,
+                _ => {
+                    writeln!(out, "{} hasn't bought, sold, or made anything", actorid).unwrap();
+                    return Ok(out);
+                }
+            };
+            writeln!(
+                out,
+                "profit and loss for {}, in {} at the last prices:",
+                actorid.yellow(),
+                sim.items().name(quote).green()
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "{:20} | {:30} | {:30} | {:10} | {:10} | {:15}",
+                "Account".bold(),
+                "Spent".bold(),
+                "Got".bold(),
+                "Cost".bold(),
+                "Revenue".bold(),
+                "Profit/Loss".bold()
+            )
+            .unwrap();
+            let (ten, thirty) = ("-".repeat(10), "-".repeat(30));
+            writeln!(
+                out,
+                "{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+                "-".repeat(20),
+                thirty,
+                thirty,
+                ten,
+                ten,
+                "-".repeat(15)
+            )
+            .unwrap();
+            let (mut total_cost, mut total_revenue) = (Quantity::ZERO, Quantity::ZERO);
+            let mut unpriced = Bundle::new();
+            for (account, spent, got) in totals {
+                let (cost, spent_unpriced) = sim.prices().value_in(&spent, quote);
+                let (revenue, got_unpriced) = sim.prices().value_in(&got, quote);
+                for (item, amount) in spent_unpriced.iter().chain(got_unpriced.iter()) {
+                    unpriced.add(item, amount);
+                }
+                total_cost += cost;
+                total_revenue += revenue;
+                writeln!(
+                    out,
+                    "{:20} | {:30} | {:30} | {:10} | {:10} | {:15}",
+                    match account {
+                        Account::Trading => "Trading".to_string(),
+                        Account::Production(recipe) => {
+                            sim.items().recipe_name(recipe).to_string()
+                        }
+                        Account::Labor => "Wages".to_string(),
+                        Account::Rent => "Rent".to_string(),
+                        Account::Upkeep => "Upkeep".to_string(),
+                    }
+                    .green(),
+                    render_plain_bundle(sim, &spent),
+                    render_plain_bundle(sim, &got),
+                    cost.to_string(),
+                    revenue.to_string(),
+                    render_profit(cost, revenue)
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "- all told, {} spent for {} got: {}",
+                total_cost,
+                total_revenue,
+                render_profit(total_cost, total_revenue)
+            )
+            .unwrap();
+            if !unpriced.is_empty() {
+                writeln!(
+                    out,
+                    "- {} never traded for {}, so not counted",
+                    render_plain_bundle(sim, &unpriced),
+                    sim.items().name(quote)
+                )
+                .unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        x => return Err(format!("{} {}", "unknown subcommand:".red(), x)),
+    }
+    Ok(out)
+}
+
+/// Bring everything that keeps up with the world up to date after a
+/// command, or a tick played on its own: show every watch again if the
+/// world's moved on since it was last shown, and stop recording if writing
+/// to the event log has failed.
+fn catch_up(sim: &mut Simulation, watches: &mut [Watch]) {
+    for watch in watches.iter_mut().filter(|w| w.tick != sim.ticks()) {
+        watch.show(sim);
+    }
+    if let Some(e) = sim.recorder().and_then(|recorder| recorder.error()) {
+        println!("{} stopped recording: {}", "error:".red(), e);
+        sim.stop_recording();
+    }
+}
+
+/// A property of an actor shown again whenever the world's run on (see
+/// `watch`).
+struct Watch {
+    /// The actor, by name
+    actor: String,
+    /// The property, as `get-actor` takes it
+    property: String,
+    /// How it was last shown
+    last: String,
+    /// The tick the world was at when it was
+    tick: u64,
+}
+
+impl Watch {
+    /// Show it again as it is in `sim` now, with every line that's changed
+    /// since it was last shown marked: `+` for what's new, and `-` for what's
+    /// gone.
+    fn show(&mut self, sim: &Simulation) {
+        self.tick = sim.ticks();
+        let description = match describe_actor(sim, &self.property, &self.actor) {
+            Ok(description) => description,
+            Err(e) => return println!("{} {}", "watch:".red(), e),
+        };
+        println!(
+            "{} {} {}",
+            "watching".bold(),
+            self.actor.yellow(),
+            format!("{} at tick {}:", self.property, self.tick).bold()
+        );
+        for (change, line) in diff_lines(&self.last, &description) {
+            match change {
+                Some(true) => println!("{} {}", "+".green().bold(), line),
+                Some(false) => println!("{} {}", "-".red().bold(), line.dimmed()),
+                None => println!("  {}", line),
+            }
+        }
+        self.last = description;
+    }
+}
+
+/// The lines of `old` and `new` in order, lined up so the most lines
+/// possible are left as they were, each with whether it's been put in
+/// (`Some(true)`), taken out (`Some(false)`), or left as it was (`None`).
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(Option<bool>, &'a str)> {
+    let (old, new): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+    // How many lines the rest of each from here on have in common, at most
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push((None, new[j]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            diff.push((Some(true), new[j]));
+            j += 1;
+        } else {
+            diff.push((Some(false), old[i]));
+            i += 1;
+        }
+    }
+    diff
+}
+
+/// Set up the world as the command line says, from seed `seed`: the
+/// scenario's actors, with the goals and items it or the command line gives,
+/// and every option asked for. Gives up on the spot if any of it can't be
+/// done.
+fn setup(opts: &Cli, seed: u64) -> Simulation {
+    let mut sim = Simulation::with_seed(seed);
+    let scenario = match &opts.scenario {
+        Some(path) => match Scenario::load(path) {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                eprintln!("{} {}", "error:".red(), e);
+                std::process::exit(1);
+            }
         },
-        GoalData::Satisfaction {
-            goal: Goal::Shelter,
-            id: 1,
-            units_required: 10,
-            units: 0,
+        None => Scenario::default_scenario(),
+    };
+    // Goals and items files given on the command line win out over the
+    // scenario's own
+    if let Some(goals) = scenario.goals.clone() {
+        sim.set_goals(goals);
+    }
+    if let Some(items) = scenario.items.clone() {
+        sim.set_items(items);
+    }
+    if let Some(path) = &opts.goals {
+        match GoalRegistry::load(path) {
+            Ok(goals) => sim.set_goals(goals),
+            Err(e) => {
+                eprintln!("{} {}", "error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(path) = &opts.items {
+        match ItemRegistry::load(path) {
+            Ok(items) => sim.set_items(items),
+            Err(e) => {
+                eprintln!("{} {}", "error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let trade_cost = opts.trade_cost.map(|amount| match &opts.trade_cost_in {
+        Some(name) => match sim.items().id(name) {
+            Some(item) => TradeCost::Item(item, amount),
+            None => {
+                eprintln!("{} items file has no item named {}", "error:".red(), name);
+                std::process::exit(1);
+            }
         },
-        GoalData::RegularSatisfaction {
-            goal: Goal::Rest,
-            id: 2,
-            time_required: 30,
-            time: 0,
-            units_required: 10,
-            units: 0,
+        None => TradeCost::Labor(amount),
+    });
+    sim.set_trade_protocol(trade::protocol_by_name(&opts.market).unwrap());
+    sim.set_parallel(opts.parallel);
+    match (opts.drift, opts.drift_every) {
+        (Some(chance), _) => sim.set_drift(Some(Drift::Random(chance))),
+        (_, Some(ticks)) => sim.set_drift(Some(Drift::Every(ticks))),
+        _ => {}
+    }
+    sim.set_memory_decay(opts.memory_decay);
+    sim.set_matching(opts.matching);
+    sim.set_order(opts.tick_order);
+    sim.set_negotiation_timeout(opts.negotiation_timeout);
+    // Whoever the scenario says is in the world, with whatever the command
+    // line asks for on top
+    let populated = scenario.populate(&mut sim, opts.actor_number, opts.jitter, |i, a, sim| {
+        let i = i as i32;
+        let a = a.labor(opts.labor);
+        let a = if opts.imperfect_information {
+            a.imperfect_information()
+        } else {
+            a
+        };
+        let a = if opts.charity { a.charitable() } else { a };
+        let a = if opts.walk_away { a.walks_away() } else { a };
+        let a = if opts.stockpile { a.stockpiles() } else { a };
+        let a = if opts.hire_labor { a.hires_labor() } else { a };
+        let a = if opts.specialize { a.specializes() } else { a };
+        let a = if opts.weigh_leisure {
+            a.weighs_leisure()
+        } else {
+            a
+        };
+        let a = if opts.weigh_bundles {
+            a.weighs_bundles()
+        } else {
+            a
+        };
+        let a = if opts.save { a.saves() } else { a };
+        let a = if opts.watch_market {
+            a.watches_market()
+        } else {
+            a
+        };
+        let a = match opts.hold_out {
+            Some(tolerance) => a.holds_out(tolerance),
+            None => a,
+        };
+        let a = match trade_cost {
+            Some(cost) => a.trade_cost(cost),
+            None => a,
+        };
+        let a = if i < opts.middlemen {
+            a.behavior(Box::new(Middleman::default()))
+        } else if i < opts.middlemen + opts.arbitrageurs {
+            a.behavior(Box::new(Arbitrageur::default()))
+        } else if i < opts.middlemen + opts.arbitrageurs + opts.banks {
+            a.behavior(Box::new(match opts.reserve_ratio {
+                Some(ratio) => Bank::new(ratio),
+                None => Bank::default(),
+            }))
+        } else {
+            a
+        };
+        let a = match opts.patience {
+            Some(patience) => a.patience(patience),
+            None => a,
+        };
+        // (Only roll for it if there's any to be had, so runs without it
+        // come out the same for the same seed)
+        let a = if opts.time_preference.is_zero() {
+            a
+        } else {
+            let impatience: f64 = sim.rng().gen();
+            a.time_preference(Quantity::from(impatience * opts.time_preference.as_f64()))
+        };
+        match opts.capacity {
+            Some(capacity) => a.capacity(capacity),
+            None => a,
+        }
+    });
+    if let Err(e) = populated {
+        eprintln!("{} {}", "error:".red(), e);
+        std::process::exit(1);
+    }
+    sim
+}
+
+/// Add some of an item to an actor's inventory, as long as the item comes in
+/// that size.
+fn give_item(sim: &mut Simulation, actor: &str, item: &str, amount: Quantity) {
+    match (sim.find_actor(actor), sim.items().id(item)) {
+        (Some(id), Some(item)) => {
+            if !amount.is_whole() && !sim.items().is_divisible(item) {
+                println!(
+                    "{} {} only comes in whole units",
+                    "error:".red(),
+                    sim.items().name(item)
+                );
+                return;
+            }
+            if let Some(supply) = sim.items().supply(item) {
+                let unclaimed = supply.saturating_sub(sim.arena().count_item(item));
+                if amount > unclaimed {
+                    println!(
+                        "{} there's only {} {} left unclaimed, and land can't be made",
+                        "error:".red(),
+                        unclaimed,
+                        sim.items().name(item)
+                    );
+                    return;
+                }
+            }
+            let events = sim.actor_mut(id).unwrap().receive(item, amount);
+            for event in events.iter() {
+                render_event(sim, event);
+            }
+            sim.wake(id);
+        }
+        (None, _) => println!("{}", "unrecognized actor".red()),
+        (_, None) => println!("{} {}", "unrecognized item:".red(), item),
+    }
+}
+
+/// Mint some new money and hand it to an actor, to watch where it goes from
+/// there (see `stats mint`). Without an item, it's whichever money comes first
+/// in the items file.
+fn mint(sim: &mut Simulation, actor: &str, amount: Quantity, item: Option<&str>) {
+    let money = match item {
+        Some(name) => match sim.items().id(name) {
+            Some(id) if sim.items().get(id).unwrap().class.is_money() => id,
+            Some(_) => return println!("{} {} isn't money", "error:".red(), name),
+            None => return println!("{} {}", "unrecognized item:".red(), name),
         },
-        GoalData::Satisfaction {
-            goal: Goal::Leisure,
-            id: 3,
-            units_required: 4,
-            units: 1,
+        None => match sim.items().money().into_iter().min() {
+            Some(id) => id,
+            None => return println!("{} there's no money in the world", "error:".red()),
         },
-    ];
-    let opts: Cli = Cli::from_args();
-    let mut trng = rand::thread_rng();
-    let actors: Vec<RefCell<Actor>> = (0..opts.actor_number)
-        .map(|i: i32| {
-            let mut a = Actor::new(
-                format!("Actor#{}", i),
-                goal_hierarchy.clone(),
-                vec![
-                    (Goal::Eat, vec![Item::FoodUnit]),
-                    (Goal::Shelter, vec![Item::HouseUnit]),
-                    (
-                        Goal::Leisure,
-                        vec![
-                            Item::FoodUnit,
-                            Item::HouseUnit,
-                            *vec![Item::LeisureUnit1, Item::LeisureUnit2]
-                                .iter()
-                                .choose(&mut trng)
-                                .unwrap(),
-                        ],
-                    ),
-                ],
+    };
+    match sim.find_actor(actor) {
+        Some(id) => {
+            for event in sim.mint(id, money, amount).iter() {
+                render_event(sim, event);
+            }
+        }
+        None => println!("{}", "unrecognized actor".red()),
+    }
+}
+
+/// Put a ceiling (or a floor) on how much of `quote` a unit of `item` can go
+/// for, from now on.
+fn impose_price_control(sim: &mut Simulation, item: &str, quote: &str, ratio: &str, ceiling: bool) {
+    let ratio = match ratio.parse::<Quantity>() {
+        Ok(ratio) if !ratio.is_zero() => ratio,
+        _ => return println!("{} {}", "not a valid ratio:".red(), ratio),
+    };
+    match (sim.items().id(item), sim.items().id(quote)) {
+        (Some(item), Some(quote)) if item != quote => {
+            sim.impose_price_control(PriceControl {
+                item,
+                quote,
+                ratio,
+                ceiling,
+            });
+            println!(
+                "a unit of {} can go for no {} than {} {} from now on",
+                sim.items().name(item).green(),
+                if ceiling { "more" } else { "less" },
+                ratio,
+                sim.items().name(quote).green()
             );
-            if let Some(ri) = vec![
-                vec![Item::FoodUnit, Item::FoodUnit, Item::FoodUnit],
-                vec![Item::HouseUnit, Item::FoodUnit],
-                vec![Item::LeisureUnit1, Item::LeisureUnit2],
-                vec![
-                    Item::FoodUnit,
-                    Item::FoodUnit,
-                    Item::FoodUnit,
-                    Item::LeisureUnit2,
-                ],
-            ]
+        }
+        (Some(_), Some(_)) => println!("{}", "a good can't be priced in itself".red()),
+        (None, _) => println!("{} {}", "unrecognized item:".red(), item),
+        (_, None) => println!("{} {}", "unrecognized item:".red(), quote),
+    }
+}
+
+/// The most ticks `run-until` will run for before giving up on its condition
+/// ever coming true.
+const RUN_UNTIL_LIMIT: u64 = 10_000;
+
+/// How many milliseconds `play` waits between ticks, if it's not told.
+const PLAY_SPEED: u64 = 1000;
+
+/// The metrics an experiment can sum up (see `BatchResults::measure`).
+const METRIC_NAMES: &[&str] = &["trades", "satisfied", "satisfaction", "defaults", "prices"];
+
+/// What happened over a run of ticks, summed up (see `run_ticks`).
+struct TickSummary {
+    /// The tick the run started on
+    start: u64,
+    /// How many ticks it ran for
+    ticks: u64,
+    /// How many trades went through
+    trades: usize,
+    /// How many goals were satisfied
+    satisfied: usize,
+    /// How many goals were given up on
+    abandoned: usize,
+    /// Everything made
+    produced: Bundle,
+    /// Everything that spoiled
+    spoiled: Bundle,
+    /// How many IOUs and forward contracts were defaulted on
+    defaults: usize,
+}
+
+impl TickSummary {
+    /// Nothing happened yet, starting on tick `start`.
+    fn new(start: u64) -> Self {
+        TickSummary {
+            start,
+            ticks: 0,
+            trades: 0,
+            satisfied: 0,
+            abandoned: 0,
+            produced: Bundle::new(),
+            spoiled: Bundle::new(),
+            defaults: 0,
+        }
+    }
+
+    /// Count in everything that happened in one more tick.
+    fn note(&mut self, events: &[SimEvent]) {
+        self.ticks += 1;
+        for event in events {
+            match event {
+                SimEvent::TradeCompleted { .. } | SimEvent::TradeCycleCompleted { .. } => {
+                    self.trades += 1
+                }
+                SimEvent::GoalSatisfied { .. } => self.satisfied += 1,
+                SimEvent::GoalAbandoned { .. } => self.abandoned += 1,
+                SimEvent::ItemsProduced { outputs, .. } => {
+                    for (item, amount) in outputs.iter() {
+                        self.produced.add(item, amount);
+                    }
+                }
+                SimEvent::ItemSpoiled { item, amount, .. } => self.spoiled.add(*item, *amount),
+                SimEvent::IouDefaulted { .. } | SimEvent::ForwardDefaulted { .. } => {
+                    self.defaults += 1
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Print it all out.
+    fn show(&self, sim: &Simulation) {
+        println!(
+            "ran {} ticks (from tick {} to tick {})",
+            self.ticks,
+            self.start,
+            self.start + self.ticks
+        );
+        println!("- {} trades", self.trades);
+        println!(
+            "- {} goals satisfied, {} given up on",
+            self.satisfied, self.abandoned
+        );
+        println!("- produced {}", render_plain_bundle(sim, &self.produced));
+        println!("- spoiled {}", render_plain_bundle(sim, &self.spoiled));
+        println!("- {} defaults", self.defaults);
+    }
+}
+
+/// Run up to `limit` ticks in a row, until `stop` says to (it's asked after
+/// every tick, with what happened in it), narrating each one as it goes
+/// (with a header saying how far along it is), or, if `quiet`, only showing
+/// how far along it is. Every one of `watches` is shown again after each
+/// tick either way. Returns what happened, summed up.
+fn run_ticks<F: FnMut(&Simulation, &[SimEvent]) -> bool>(
+    sim: &mut Simulation,
+    limit: u64,
+    quiet: bool,
+    watches: &mut [Watch],
+    mut stop: F,
+) -> TickSummary {
+    let mut summary = TickSummary::new(sim.ticks());
+    for n in 1..=limit {
+        if !quiet {
+            println!(
+                "{}",
+                format!("--- tick {} ({} of {}) ---", sim.ticks(), n, limit).bold()
+            );
+        }
+        let events = sim.tick();
+        summary.note(&events);
+        if quiet {
+            // Written over in place, so it doesn't scroll everything away
+            print!("\rticking... {} of {}", n, limit);
+            io::stdout().flush().ok();
+        } else {
+            for event in events.iter() {
+                render_event(sim, event);
+            }
+            println!();
+        }
+        if !watches.is_empty() {
+            if quiet {
+                // Leave how far along it is where it was
+                println!();
+            }
+            for watch in watches.iter_mut() {
+                watch.show(sim);
+            }
+        }
+        if stop(sim, &events) {
+            break;
+        }
+    }
+    if quiet {
+        print!("\r{}\r", " ".repeat(40));
+    }
+    summary
+}
+
+/// Run `count` ticks in a row, narrating each one and showing `watches`
+/// again after it (unless `quiet`), and sum up what happened at the end.
+fn tick_many(sim: &mut Simulation, count: u64, quiet: bool, watches: &mut [Watch]) {
+    let watches = if quiet { &mut [] } else { watches };
+    run_ticks(sim, count, quiet, watches, |_, _| false).show(sim);
+    println!();
+}
+
+/// Where a headless run (see `Cli::no_repl`) got to, for writing out as
+/// JSON. Items and actors go by name, so it can be read without knowing how
+/// the simulation numbers them.
+#[derive(Serialize)]
+struct BatchResults {
+    /// The seed it ran from
+    seed: u64,
+    /// How many ticks it ran for
+    ticks: u64,
+    /// How many trades went through
+    trades: usize,
+    /// How many goals were satisfied
+    goals_satisfied: usize,
+    /// How many goals were given up on
+    goals_abandoned: usize,
+    /// Everything made, by item
+    produced: BTreeMap<String, Quantity>,
+    /// Everything that spoiled, by item
+    spoiled: BTreeMap<String, Quantity>,
+    /// How many IOUs and forward contracts were defaulted on
+    defaults: usize,
+    /// What every pair of items that's been traded went for
+    prices: Vec<BatchPrice>,
+    /// Where every actor ended up
+    actors: Vec<BatchActor>,
+}
+
+/// What one pair of items went for each other over a headless run, the way
+/// `prices` shows it.
+#[derive(Serialize)]
+struct BatchPrice {
+    /// The item the ratios are a price of
+    base: String,
+    /// The item the ratios are priced in
+    quote: String,
+    /// The last ratio they went for
+    last: Quantity,
+    /// The average ratio they went for
+    average: Quantity,
+    /// How many times they were traded for each other
+    trades: u32,
+}
+
+/// Where one actor ended up after a headless run.
+#[derive(Serialize)]
+struct BatchActor {
+    name: String,
+    /// Its state, by the name `get-actor state` shows for it
+    state: String,
+    /// Everything it has, by item
+    inventory: BTreeMap<String, Quantity>,
+    /// How many goals it's satisfied
+    goals_satisfied: usize,
+    /// How many goals it's still waiting to satisfy
+    goals_waiting: usize,
+}
+
+impl BatchResults {
+    /// Sum up a run from seed `seed`, given what happened over it.
+    fn new(sim: &Simulation, seed: u64, summary: &TickSummary) -> Self {
+        let by_name = |bundle: &Bundle| -> BTreeMap<String, Quantity> {
+            bundle
+                .iter()
+                .map(|(item, amount)| (sim.items().name(item).to_string(), amount))
+                .collect()
+        };
+        let prices = sim.prices();
+        BatchResults {
+            seed,
+            ticks: summary.ticks,
+            trades: summary.trades,
+            goals_satisfied: summary.satisfied,
+            goals_abandoned: summary.abandoned,
+            produced: by_name(&summary.produced),
+            spoiled: by_name(&summary.spoiled),
+            defaults: summary.defaults,
+            prices: prices
+                .pairs()
+                .into_iter()
+                .map(|(base, quote)| {
+                    let (average, trades) = prices.average(base, quote).unwrap();
+                    BatchPrice {
+                        base: sim.items().name(base).to_string(),
+                        quote: sim.items().name(quote).to_string(),
+                        last: prices.latest(base, quote).unwrap().ratio,
+                        average,
+                        trades,
+                    }
+                })
+                .collect(),
+            actors: sim
+                .actors()
+                .map(|actor| BatchActor {
+                    name: actor.name.to_string(),
+                    state: state_name(actor.state),
+                    inventory: actor
+                        .inventory
+                        .iter()
+                        .map(|(item, amount)| (sim.items().name(item).to_string(), amount))
+                        .collect(),
+                    goals_satisfied: actor.ledger.records().len(),
+                    goals_waiting: actor.ledger.waiting(sim.ticks()).len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl BatchResults {
+    /// What each of `metrics` (see `METRIC_NAMES`) came to over the run:
+    /// how many trades went through, how many goals were satisfied, what
+    /// share of the goals that came up were (rather than given up on or still
+    /// waiting at the end), how many debts were defaulted on, and the average
+    /// price of every pair of items that was traded.
+    fn measure(&self, metrics: &[String]) -> Vec<(String, f64)> {
+        let mut measured = vec![];
+        for metric in metrics {
+            match metric.as_str() {
+                "trades" => measured.push((metric.clone(), self.trades as f64)),
+                "satisfied" => measured.push((metric.clone(), self.goals_satisfied as f64)),
+                "satisfaction" => {
+                    let waiting: usize = self.actors.iter().map(|a| a.goals_waiting).sum();
+                    let seen = self.goals_satisfied + self.goals_abandoned + waiting;
+                    let rate = if seen == 0 {
+                        0.0
+                    } else {
+                        self.goals_satisfied as f64 / seen as f64
+                    };
+                    measured.push((metric.clone(), rate));
+                }
+                "defaults" => measured.push((metric.clone(), self.defaults as f64)),
+                "prices" => measured.extend(self.prices.iter().map(|price| {
+                    (
+                        format!("price:{}/{}", price.base, price.quote),
+                        price.average.as_f64(),
+                    )
+                })),
+                _ => {}
+            }
+        }
+        measured
+    }
+}
+
+/// How one metric came out over every run of an experiment.
+#[derive(Serialize)]
+struct MetricSummary {
+    /// What it measures (`price:Base/Quote` for the price of one item in
+    /// another)
+    metric: String,
+    /// How many runs it came up in (a price only does in runs where the pair
+    /// was traded)
+    runs: usize,
+    /// What it came to on average
+    mean: f64,
+    /// How much it varied from run to run (the sample variance, so 0 if it
+    /// only came up once)
+    variance: f64,
+}
+
+impl MetricSummary {
+    /// Sum up what `metric` came to in every run it came up in.
+    fn new(metric: String, values: &[f64]) -> Self {
+        let runs = values.len();
+        let mean = values.iter().sum::<f64>() / runs as f64;
+        let variance = if runs < 2 {
+            0.0
+        } else {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (runs - 1) as f64
+        };
+        MetricSummary {
+            metric,
+            runs,
+            mean,
+            variance,
+        }
+    }
+}
+
+/// Everything an experiment (see `Command::Experiment`) found, for writing
+/// out as JSON or CSV.
+#[derive(Serialize)]
+struct ExperimentResults {
+    /// The seeds it ran, in order
+    seeds: Vec<u64>,
+    /// How many ticks it ran each of them for
+    ticks: u64,
+    /// How every metric came out, in the order they first came up
+    metrics: Vec<MetricSummary>,
+}
+
+impl ExperimentResults {
+    /// The metrics as CSV, one to a line.
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("metric,runs,mean,variance\n");
+        for m in self.metrics.iter() {
+            writeln!(csv, "{},{},{},{}", m.metric, m.runs, m.mean, m.variance).unwrap();
+        }
+        csv
+    }
+}
+
+/// Run the world the command line describes over every seed `experiment`
+/// asks for, all at once, and write out how each metric came out over them.
+fn run_experiment(opts: &Cli, experiment: &Experiment) {
+    let first = opts.seed.unwrap_or(0);
+    let seeds: Vec<u64> = (first..first + experiment.seeds).collect();
+    // Every run's a world of its own, so they can all go at once
+    let runs: Vec<Vec<(String, f64)>> = seeds
+        .par_iter()
+        .map(|&seed| {
+            let mut sim = setup(opts, seed);
+            let mut summary = TickSummary::new(sim.ticks());
+            for _ in 0..experiment.ticks {
+                let events = sim.tick();
+                summary.note(&events);
+            }
+            BatchResults::new(&sim, seed, &summary).measure(&experiment.metrics)
+        })
+        .collect();
+    let mut values: Vec<(String, Vec<f64>)> = vec![];
+    for (metric, value) in runs.into_iter().flatten() {
+        match values.iter_mut().find(|(m, _)| *m == metric) {
+            Some((_, v)) => v.push(value),
+            None => values.push((metric, vec![value])),
+        }
+    }
+    let results = ExperimentResults {
+        seeds,
+        ticks: experiment.ticks,
+        metrics: values
+            .into_iter()
+            .map(|(metric, values)| MetricSummary::new(metric, &values))
+            .collect(),
+    };
+    let summary = match experiment.format.as_str() {
+        "csv" => results.to_csv(),
+        _ => serde_json::to_string_pretty(&results).expect("results always serialize") + "\n",
+    };
+    match &experiment.output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, summary) {
+                eprintln!(
+                    "{} couldn't write {}: {}",
+                    "error:".red(),
+                    path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", summary),
+    }
+}
+
+/// Everything there is to compare about a world (see `diff_worlds`), each
+/// with what it's called, in the order it's to be shown. Items and actors go
+/// by name, so worlds that number them differently can still be compared.
+fn describe_world(sim: &Simulation) -> Vec<(String, String)> {
+    let mut described = vec![
+        ("Tick".to_string(), sim.ticks().to_string()),
+        (
+            "# Trades".to_string(),
+            sim.trades()
+                .links()
+                .iter()
+                .map(|link| link.trades)
+                .sum::<u32>()
+                .to_string(),
+        ),
+        (
+            "# Goals Satisfied".to_string(),
+            sim.actors()
+                .map(|actor| actor.ledger.records().len())
+                .sum::<usize>()
+                .to_string(),
+        ),
+    ];
+    let prices = sim.prices();
+    for (base, quote) in prices.pairs() {
+        let (average, _) = prices.average(base, quote).unwrap();
+        described.push((
+            format!("{} in {}", sim.items().name(base), sim.items().name(quote)),
+            average.to_string(),
+        ));
+    }
+    for actor in sim.actors() {
+        described.push((format!("{} State", actor.name), state_name(actor.state)));
+        // (By name, since the order an inventory's kept in isn't)
+        let inventory: BTreeMap<String, Quantity> = actor
+            .inventory
+            .iter()
+            .map(|(item, amount)| (sim.items().name(item), amount))
+            .collect();
+        described.push((
+            format!("{} Inventory", actor.name),
+            inventory
+                .iter()
+                .map(|(item, amount)| format!("{} {}", amount, item))
+                .collect::<Vec<_>>()
+                .join(" + "),
+        ));
+    }
+    described
+}
+
+/// Show everything that's different between the world being run and
+/// another world in the session: how far along each is, what's been traded
+/// and satisfied, the average price of every pair traded, and what state
+/// every actor's in and has.
+///
+/// # Arguments
+///
+/// * `name` - what the world being run is called
+/// * `sim` - the world being run
+/// * `other_name` - what the other world is called
+/// * `other` - the other world
+///
+fn diff_worlds(name: &str, sim: &Simulation, other_name: &str, other: &Simulation) {
+    let ours = describe_world(sim);
+    let theirs = describe_world(other);
+    let find = |described: &[(String, String)], what: &str| {
+        described
             .iter()
-            .choose(&mut trng)
+            .find(|(w, _)| w == what)
+            .map_or_else(|| "-".to_string(), |(_, value)| value.clone())
+    };
+    // Everything in either, ours first, then anything only they have
+    let mut whats: Vec<&String> = ours.iter().map(|(what, _)| what).collect();
+    for (what, _) in theirs.iter() {
+        if !whats.contains(&what) {
+            whats.push(what);
+        }
+    }
+    let differences: Vec<(&String, String, String)> = whats
+        .into_iter()
+        .map(|what| (what, find(&ours, what), find(&theirs, what)))
+        .filter(|(_, ours, theirs)| ours != theirs)
+        .collect();
+    if differences.is_empty() {
+        return println!("{} and {} are the same", name.green(), other_name.green());
+    }
+    println!(
+        "{:30} | {:30} | {:30}",
+        "".bold(),
+        name.bold(),
+        other_name.bold()
+    );
+    let thirty = "-".repeat(30);
+    println!("{}-+-{}-+-{}", thirty, thirty, thirty);
+    for (what, ours, theirs) in differences {
+        println!("{:30} | {:30} | {:30}", what.green(), ours, theirs);
+    }
+    println!();
+}
+
+/// Whether a command might change the world, rather than just look at it or
+/// run it on (ticks are recorded on their own), so it's noted down in the
+/// event log if the run's being recorded.
+fn changes_world(cmd: &[&str]) -> bool {
+    !matches!(
+        cmd.first(),
+        None | Some(&"help")
+            | Some(&"get-actor")
+            | Some(&"tick")
+            | Some(&"run-until")
+            | Some(&"compare-item-values")
+            | Some(&"prices")
+            | Some(&"resources")
+            | Some(&"wages")
+            | Some(&"recipes")
+            | Some(&"stats")
+            | Some(&"export-trades")
+            | Some(&"export-purchasing-power")
+            | Some(&"save")
+            | Some(&"world")
+            | Some(&"watch")
+            | Some(&"unwatch")
+            | Some(&"play")
+            | Some(&"pause")
+            | Some(&"record")
+            | Some(&"replay")
+            | Some(&"step")
+            | Some(&"quit")
+    )
+}
+
+/// Put the world back the way it was `ticks` ticks ago, if there's the
+/// history kept to.
+fn rewind(sim: &mut Simulation, ticks: usize) {
+    match sim.rewind(ticks) {
+        Some(tick) => println!("rewound to the start of tick {}", tick),
+        None if sim.history() == 0 => println!("there's no history to rewind (see --history)"),
+        None => println!(
+            "can only rewind {} ticks, back to the start of tick {}",
+            sim.history(),
+            sim.ticks() - sim.history() as u64
+        ),
+    }
+}
+
+/// Replay up to `count` more ticks of the event log being stepped through
+/// (see `Replay`), narrating each one and what was done to the world before
+/// it, and stopping early at the end of the log or as soon as a tick goes
+/// differently from how the log has it.
+fn step_replay(sim: &mut Simulation, replay: &mut Option<Replay>, count: u64) {
+    let log = match replay {
+        Some(log) => log,
+        None => return println!("not replaying anything (see \"replay\")"),
+    };
+    for _ in 0..count {
+        match log.step(sim) {
+            Ok(Some(replayed)) => {
+                for what in replayed.interventions.iter() {
+                    println!("{} {}", "before this tick:".yellow(), what);
+                }
+                println!("{}", format!("--- tick {} ---", replayed.tick).bold());
+                for event in replayed.events.iter() {
+                    render_event(sim, event);
+                }
+                println!();
+                if replayed.diverged {
+                    return println!(
+                        "{} tick {} went differently from how the log has it",
+                        "diverged:".red(),
+                        replayed.tick
+                    );
+                }
+            }
+            Ok(None) => {
+                *replay = None;
+                return println!("that's the end of the log");
+            }
+            Err(e) => {
+                *replay = None;
+                return println!("{} {}", "error:".red(), e);
+            }
+        }
+    }
+}
+
+/// Something `run-until` runs the simulation until.
+enum StopCondition {
+    /// So many ticks in a row without a trade
+    NoTrades(u64),
+    /// An actor being in a state (by the name `get-actor state` shows for
+    /// it, like `SearchingForGoal`)
+    ActorState(ActorId, String),
+    /// A tick being reached
+    Tick(u64),
+}
+
+/// Whether an actor's state goes by `name` (case aside), the way `get-actor
+/// state` shows it, without whatever it's holding on to.
+fn state_is(state: &ActorState, name: &str) -> bool {
+    state_name(state).eq_ignore_ascii_case(name)
+}
+
+/// The name an actor's state goes by, the way `get-actor state` shows it,
+/// without whatever it's holding on to.
+fn state_name(state: &ActorState) -> String {
+    let shown = format!("{:?}", state);
+    shown.split('(').next().unwrap_or_default().to_string()
+}
+
+/// Run the simulation quietly until `condition` comes true (giving up after
+/// `RUN_UNTIL_LIMIT` ticks), showing `watches` again after every tick, and
+/// sum up what happened on the way.
+fn run_until(sim: &mut Simulation, condition: StopCondition, watches: &mut [Watch]) {
+    let already = match &condition {
+        StopCondition::Tick(tick) => sim.ticks() >= *tick,
+        StopCondition::ActorState(id, state) => sim
+            .actor(*id)
+            .is_some_and(|actor| state_is(actor.state, state)),
+        StopCondition::NoTrades(_) => false,
+    };
+    if already {
+        return println!("that's already the case");
+    }
+    let limit = match condition {
+        StopCondition::Tick(tick) => tick - sim.ticks(),
+        _ => RUN_UNTIL_LIMIT,
+    };
+    let mut quiet_for = 0;
+    let mut met = false;
+    let summary = run_ticks(sim, limit, true, watches, |sim, events| {
+        met = match &condition {
+            StopCondition::NoTrades(ticks) => {
+                let traded = events.iter().any(|event| {
+                    matches!(
+                        event,
+                        SimEvent::TradeCompleted { .. } | SimEvent::TradeCycleCompleted { .. }
+                    )
+                });
+                quiet_for = if traded { 0 } else { quiet_for + 1 };
+                quiet_for >= *ticks
+            }
+            StopCondition::ActorState(id, state) => sim
+                .actor(*id)
+                .is_some_and(|actor| state_is(actor.state, state)),
+            StopCondition::Tick(tick) => sim.ticks() >= *tick,
+        };
+        met
+    });
+    if met {
+        println!("{}", "stopped: the condition came true".green());
+    } else {
+        println!(
+            "{} {} {}",
+            "gave up waiting after".red(),
+            summary.ticks,
+            "ticks".red()
+        );
+    }
+    summary.show(sim);
+    println!();
+}
+
+/// Work out a `run-until` condition from the words after the command.
+fn parse_condition(sim: &Simulation, words: &[&str]) -> Result<StopCondition, String> {
+    match words {
+        ["no-trades", ticks] => match ticks.parse::<u64>() {
+            Ok(ticks) if ticks > 0 => Ok(StopCondition::NoTrades(ticks)),
+            _ => Err(format!("not a valid number of ticks: {}", ticks)),
+        },
+        ["actor", actor, "state", state] => match sim.find_actor(actor) {
+            Some(_)
+                if !ACTOR_STATE_NAMES
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(state)) =>
             {
-                a.inventory.extend(ri.iter());
+                Err(format!(
+                    "unrecognized state: {} (one of {})",
+                    state,
+                    ACTOR_STATE_NAMES.join(", ")
+                ))
             }
-            RefCell::new(a)
+            Some(id) => Ok(StopCondition::ActorState(id, state.to_string())),
+            None => Err(format!("unrecognized actor: {}", actor)),
+        },
+        ["tick", tick] => match tick.parse::<u64>() {
+            Ok(tick) => Ok(StopCondition::Tick(tick)),
+            _ => Err(format!("not a valid tick: {}", tick)),
+        },
+        _ => Err(
+            "not a condition (no-trades <ticks>, actor <actor> state <state>, or tick <tick>)"
+                .to_string(),
+        ),
+    }
+}
+
+/// Make `quote` legal tender for `item` at `ratio` of it to the unit, from
+/// now on. Both have to be money.
+fn impose_legal_tender(sim: &mut Simulation, item: &str, quote: &str, ratio: &str) {
+    let ratio = match ratio.parse::<Quantity>() {
+        Ok(ratio) if !ratio.is_zero() => ratio,
+        _ => return println!("{} {}", "not a valid ratio:".red(), ratio),
+    };
+    match (sim.items().id(item), sim.items().id(quote)) {
+        (Some(item), Some(quote))
+            if !sim.items().is_money(item) || !sim.items().is_money(quote) =>
+        {
+            println!("{}", "only money can be legal tender".red())
+        }
+        (Some(item), Some(quote)) if item != quote => {
+            sim.impose_legal_tender(LegalTender { item, quote, ratio });
+            println!(
+                "a unit of {} has to be taken as {} {} from now on",
+                sim.items().name(item).green(),
+                ratio,
+                sim.items().name(quote).green()
+            );
+        }
+        (Some(_), Some(_)) => println!("{}", "a money can't be legal tender for itself".red()),
+        (None, _) => println!("{} {}", "unrecognized item:".red(), item),
+        (_, None) => println!("{} {}", "unrecognized item:".red(), quote),
+    }
+}
+
+/// Have a collector take `rate` of whatever's traded or made (`base`) from
+/// now on, and hand it out or destroy it (`disposal`).
+fn impose_tax(sim: &mut Simulation, rate: &str, base: &str, disposal: &str) {
+    let rate = match rate.parse::<Quantity>() {
+        Ok(rate) if !rate.is_zero() && rate <= Quantity::ONE => rate,
+        _ => return println!("{} {}", "not a valid tax rate:".red(), rate),
+    };
+    let base = match base {
+        "trades" => TaxBase::Trades,
+        "output" => TaxBase::Output,
+        "both" => TaxBase::Both,
+        _ => return println!("{} {}", "not something to tax:".red(), base),
+    };
+    let disposal = match disposal {
+        "redistribute" => Disposal::Redistribute,
+        "destroy" => Disposal::Destroy,
+        _ => return println!("{} {}", "not something to do with it:".red(), disposal),
+    };
+    let since = sim.ticks();
+    sim.set_collector(Some(Collector::new(rate, base, disposal, since)));
+    println!("taxes will be collected from the end of this tick on");
+}
+
+/// Have one actor give some of an item to another, as long as it has it.
+fn gift(sim: &mut Simulation, from: &str, to: &str, item: &str, amount: Quantity) {
+    match (
+        sim.find_actor(from),
+        sim.find_actor(to),
+        sim.items().id(item),
+    ) {
+        (Some(from), Some(to), Some(item)) if from != to => {
+            let gift = Bundle::single(item, amount);
+            let events = sim.gift(from, to, &gift);
+            if events.is_empty() {
+                println!(
+                    "{} {} doesn't have {} {}",
+                    "error:".red(),
+                    sim.actor_name(from),
+                    amount,
+                    sim.items().name(item)
+                );
+            }
+            for event in events.iter() {
+                render_event(sim, event);
+            }
+        }
+        (Some(_), Some(_), Some(_)) => println!("{}", "an actor can't give to itself".red()),
+        (None, _, _) | (_, None, _) => println!("{}", "unrecognized actor".red()),
+        (_, _, None) => println!("{} {}", "unrecognized item:".red(), item),
+    }
+}
+
+/// Have one actor offer a unit of an item to another for a unit of one of
+/// theirs, and swap them if they both go for it.
+fn trade_items(sim: &mut Simulation, from: &str, give: &str, to: &str, take: &str) {
+    let (from, to) = match (sim.find_actor(from), sim.find_actor(to)) {
+        (Some(from), Some(to)) if from != to => (from, to),
+        (Some(_), Some(_)) => {
+            println!("{}", "an actor can't trade with itself".red());
+            return;
+        }
+        _ => {
+            println!("{}", "unrecognized actor".red());
+            return;
+        }
+    };
+    let (give, take) = match (sim.items().id(give), sim.items().id(take)) {
+        (Some(give), Some(take)) => (give, take),
+        (None, _) => {
+            println!("{} {}", "unrecognized item:".red(), give);
+            return;
+        }
+        (_, None) => {
+            println!("{} {}", "unrecognized item:".red(), take);
+            return;
+        }
+    };
+    for (id, item) in [(from, give), (to, take)] {
+        if sim.actor(id).unwrap().inventory.count(item) < Quantity::ONE {
+            println!(
+                "{} {} doesn't have {} {}",
+                "error:".red(),
+                sim.actor_name(id),
+                Quantity::ONE,
+                sim.items().name(item)
+            );
+            return;
+        }
+    }
+    let events = sim.trade(
+        from,
+        &Bundle::single(give, Quantity::ONE),
+        to,
+        &Bundle::single(take, Quantity::ONE),
+    );
+    for event in events.iter() {
+        render_event(sim, event);
+    }
+}
+
+/// Have one actor let a unit of an item to another for so many ticks, for
+/// `rent` of another item a tick.
+fn lease_item(
+    sim: &mut Simulation,
+    landlord: &str,
+    tenant: &str,
+    item: &str,
+    term: &str,
+    rent_item: &str,
+    rent: Quantity,
+) {
+    let (landlord, tenant) = match (sim.find_actor(landlord), sim.find_actor(tenant)) {
+        (Some(landlord), Some(tenant)) if landlord != tenant => (landlord, tenant),
+        (Some(_), Some(_)) => {
+            println!("{}", "an actor can't lease to itself".red());
+            return;
+        }
+        _ => {
+            println!("{}", "unrecognized actor".red());
+            return;
+        }
+    };
+    let (item, rent_item) = match (sim.items().id(item), sim.items().id(rent_item)) {
+        (Some(item), Some(rent_item)) => (item, rent_item),
+        (None, _) => {
+            println!("{} {}", "unrecognized item:".red(), item);
+            return;
+        }
+        (_, None) => {
+            println!("{} {}", "unrecognized item:".red(), rent_item);
+            return;
+        }
+    };
+    let term = match term.parse::<u64>() {
+        Ok(term) if term > 0 => term,
+        _ => {
+            println!("{} {}", "not a valid number of ticks:".red(), term);
+            return;
+        }
+    };
+    let events = sim.lease(
+        landlord,
+        tenant,
+        &Bundle::single(item, Quantity::ONE),
+        &Bundle::single(rent_item, rent),
+        term,
+    );
+    for event in events.iter() {
+        render_event(sim, event);
+    }
+}
+
+/// Put some of a public good in the commons, as long as it is one.
+fn add_public(sim: &mut Simulation, item: &str, amount: Quantity) {
+    match sim.items().id(item) {
+        Some(id) if sim.add_to_commons(id, amount) => println!(
+            "{} {} is now there for anyone to use",
+            amount,
+            sim.items().name(id).green()
+        ),
+        Some(id) => println!(
+            "{} {} isn't a public good",
+            "error:".red(),
+            sim.items().name(id)
+        ),
+        None => println!("{} {}", "unrecognized item:".red(), item),
+    }
+}
+
+/// Put a resource node in the world that yields `per_tick` of an item every
+/// tick to whoever gathers it, named `name` (or after the item, if that's
+/// empty).
+fn add_resource(sim: &mut Simulation, item: &str, per_tick: Quantity, name: &str) {
+    let id = match sim.items().id(item) {
+        Some(id) => id,
+        None => return println!("{} {}", "unrecognized item:".red(), item),
+    };
+    let name = if name.is_empty() {
+        format!("{} source", sim.items().name(id))
+    } else {
+        name.to_string()
+    };
+    sim.add_resource(ResourceNode::new(&name, id, per_tick));
+    println!(
+        "{} now yields {} {} a tick to whoever gathers it",
+        name.green(),
+        per_tick,
+        sim.items().name(id)
+    );
+}
+
+/// Show how far each actor that's made anything since tick `since` has
+/// specialized in one good (see `Accounts::specialization`), and how much of
+/// what it made it traded away.
+fn show_specialization(sim: &Simulation, since: u64) {
+    let producers: Vec<_> = sim
+        .actors()
+        .filter_map(|actor| {
+            actor
+                .accounts
+                .specialization(since)
+                .map(|index| (actor, index))
         })
         .collect();
+    if producers.is_empty() {
+        return println!("nobody has made anything");
+    }
+    println!(
+        "{:20} | {:30} | {:20} | {:30} | {:15}",
+        "Actor".bold(),
+        "Made".bold(),
+        "Mostly".bold(),
+        "Traded Away".bold(),
+        "Specialization".bold()
+    );
+    let (twenty, thirty) = ("-".repeat(20), "-".repeat(30));
+    println!(
+        "{}-+-{}-+-{}-+-{}-+-{}",
+        twenty,
+        thirty,
+        twenty,
+        thirty,
+        "-".repeat(15)
+    );
+    let (mut made, mut sold, mut specialists, mut total_index) =
+        (Quantity::ZERO, Quantity::ZERO, 0, Quantity::ZERO);
+    for &(actor, index) in producers.iter() {
+        let output = actor.accounts.output(since);
+        let traded = actor.accounts.traded_away(since);
+        let (main, amount) = output.iter().max_by_key(|&(_, amount)| amount).unwrap();
+        made += output.total();
+        sold += output
+            .iter()
+            .map(|(item, amount)| std::cmp::min(amount, traded.count(item)))
+            .sum();
+        total_index += index;
+        if output.len() == 1 {
+            specialists += 1;
+        }
+        println!(
+            "{:20} | {:30} | {:20} | {:30} | {:15}",
+            actor.name.yellow(),
+            render_plain_bundle(sim, &output),
+            format!(
+                "{} ({:.0}%)",
+                sim.items().name(main),
+                100.0 * amount.as_f64() / output.total().as_f64()
+            ),
+            render_plain_bundle(sim, &traded),
+            index.to_string()
+        );
+    }
+    println!();
+    println!(
+        "- {} of {} actors that made anything only made the one good; {} on average",
+        specialists,
+        producers.len(),
+        total_index.divided_by(Quantity::from(producers.len() as u32))
+    );
+    println!(
+        "- {:.0}% of everything made was traded away",
+        100.0 * sold.as_f64() / made.as_f64()
+    );
+    println!();
+}
 
-    println!("Welcome to the microeconomic actor prototype interactive interface.");
-    println!("Enter \"help\" for a list of commands.");
-    println!("Press Ctrl-D or enter \"quit\" to exit.");
-    println!("");
+/// Show what the factors of production are worth in `quote`, imputed back
+/// from what consumers' goods have gone for (see `Imputation`), and how each
+/// actor's own factors rank against each other by that.
+fn show_imputation(sim: &Simulation, quote: ItemId) {
+    let imputation = sim.imputation(quote);
+    let values = imputation.values();
+    if values.is_empty() {
+        return println!(
+            "no consumers' good anything's made from has gone for any {}",
+            sim.items().name(quote)
+        );
+    }
+    let factor_name = |factor: Factor| match factor {
+        Factor::Good(item) => sim.items().name(item),
+        Factor::Labor => "Labor (an hour)".to_string(),
+    };
+    println!(
+        "factors of production, valued in {} by what they help make:",
+        sim.items().name(quote).green()
+    );
+    println!();
+    println!(
+        "{:20} | {:10} | {:20}",
+        "Factor".bold(),
+        "Worth".bold(),
+        "Best Use".bold()
+    );
+    println!(
+        "{}-+-{}-+-{}",
+        "-".repeat(20),
+        "-".repeat(10),
+        "-".repeat(20)
+    );
+    for &(factor, value) in values.iter() {
+        println!(
+            "{:20} | {:10} | {:20}",
+            factor_name(factor).green(),
+            value.value.to_string(),
+            match value.recipe {
+                Some(recipe) => sim.items().recipe_name(recipe),
+                None => "its yield".to_string(),
+            }
+        );
+    }
+    if let Some(wage) = sim.wages().latest(quote) {
+        println!();
+        println!(
+            "- an hour of labor last went for {} {} in wages",
+            wage.rate,
+            sim.items().name(quote)
+        );
+    }
+    println!();
+    println!(
+        "{:20} | {:50}",
+        "Actor".bold(),
+        "Factors, Most Valued First".bold()
+    );
+    println!("{}-+-{}", "-".repeat(20), "-".repeat(50));
+    for actor in sim.actors() {
+        let held = actor
+            .inventory
+            .iter()
+            .map(|(item, _)| Factor::Good(item))
+            .chain((!actor.labor.budget.is_zero()).then_some(Factor::Labor));
+        let ranked = imputation.rank(held);
+        if ranked.is_empty() {
+            continue;
+        }
+        println!(
+            "{:20} | {:50}",
+            actor.name.yellow(),
+            ranked
+                .iter()
+                .map(|&(factor, _)| factor_name(factor))
+                .collect::<Vec<_>>()
+                .join(" > ")
+        );
+    }
+    println!();
+}
 
-    let reader = Interface::new("microeconomics")?;
-    reader.set_completer(Arc::new(InterfaceCompleter(
-        (0..opts.actor_number)
-            .map(|i| format!("Actor#{}", i))
-            .collect(),
-    )));
-    reader.set_prompt(&"interaction> ".bold().blue().to_string())?;
+/// Show where every lot of money minted so far has got to: who's had some of
+/// it, in the order they first got it, how many hands it went through to get
+/// to them, and what they spent it on.
+fn show_minting(sim: &Simulation) {
+    let trace = sim.money_trace();
+    if trace.is_empty() {
+        return println!("no money's been minted yet");
+    }
+    for (n, injection) in trace.injections().iter().enumerate() {
+        println!(
+            "{} {} minted at {} on tick {}:",
+            injection.amount,
+            sim.items().name(injection.item).green(),
+            sim.actor_name(injection.actor).yellow(),
+            injection.tick
+        );
+        println!();
+        println!(
+            "{:20} | {:7} | {:5} | {:8} | {:8} | {:30}",
+            "Actor".bold(),
+            "Reached".bold(),
+            "Hands".bold(),
+            "Received".bold(),
+            "Holds".bold(),
+            "Spent On".bold()
+        );
+        println!(
+            "{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+            "-".repeat(20),
+            "-".repeat(7),
+            "-".repeat(5),
+            "-".repeat(8),
+            "-".repeat(8),
+            "-".repeat(30)
+        );
+        for holding in trace.holdings(n) {
+            println!(
+                "{:20} | {:7} | {:5} | {:8} | {:8} | {:30}",
+                sim.actor_name(holding.actor).yellow(),
+                holding.reached,
+                holding.hands,
+                holding.received.to_string(),
+                holding.held.to_string(),
+                if holding.spent_on.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    render_bundle(sim, &holding.spent_on)
+                }
+            );
+        }
+        println!();
+    }
+}
+
+/// Show the savings rate over the last 20 ticks anyone saved, and what
+/// everyone's saving now, and for what.
+fn show_savings(sim: &Simulation) {
+    let rates = sim.savings_rates();
+    if rates.is_empty() {
+        return println!("nobody's saved anything yet");
+    }
+    println!("{:6} | {:12}", "Tick".bold(), "Savings Rate".bold());
+    println!("{}-+-{}", "-".repeat(6), "-".repeat(12));
+    for (tick, rate) in rates.iter().skip(rates.len().saturating_sub(20)) {
+        println!(
+            "{:6} | {:12}",
+            tick,
+            format!("{:.1}%", 100.0 * rate.as_f64())
+        );
+    }
+    println!();
+    println!(
+        "{:20} | {:20} | {:41}",
+        "Actor".bold(),
+        "Saving For".bold(),
+        "Saved".bold()
+    );
+    println!(
+        "{}-+-{}-+-{}",
+        "-".repeat(20),
+        "-".repeat(20),
+        "-".repeat(41)
+    );
+    for actor in sim.actors() {
+        for (goal, goods) in actor.inventory.reservations() {
+            if actor.values.is_in_play(goal) {
+                continue;
+            }
+            println!(
+                "{:20} | {:20} | {:41}",
+                actor.name.yellow(),
+                sim.goals().name(goal).blue(),
+                render_plain_bundle(sim, goods)
+            );
+        }
+    }
+    println!();
+}
+
+/// Show every price control in force, and how trade in the goods it covers
+/// and everyone's goals have fared since it was imposed, against as many
+/// ticks before it.
+fn show_price_controls(sim: &Simulation) {
+    if sim.price_controls().is_empty() {
+        return println!("there are no price controls in force");
+    }
+    println!(
+        "{:20} | {:20} | {:10} | {:10} | {:15} | {:15} | {:10}",
+        "Price Of".bold(),
+        "In".bold(),
+        "Limit".bold(),
+        "Period".bold(),
+        "Trades A Tick".bold(),
+        "Goals Met/Tick".bold(),
+        "Avg Wait".bold()
+    );
+    let (ten, fifteen, twenty) = ("-".repeat(10), "-".repeat(15), "-".repeat(20));
+    println!(
+        "{}-+-{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+        twenty, twenty, ten, ten, fifteen, fifteen, ten
+    );
+    let now = sim.ticks();
+    for &(since, control) in sim.price_controls() {
+        let span = (now - since).min(since);
+        let periods = [("before", since - span, since), ("since", since, now)];
+        for (n, &(period, from, to)) in periods.iter().enumerate() {
+            let ticks = (to - from).max(1) as f64;
+            let trades = sim
+                .prices()
+                .points()
+                .iter()
+                .filter(|p| p.tick >= from && p.tick < to)
+                .filter(|p| {
+                    (p.base, p.quote) == (control.item, control.quote)
+                        || (p.base, p.quote) == (control.quote, control.item)
+                })
+                .count();
+            let (met, wait) = welfare(sim, from, to);
+            // (Only name the control on its first row)
+            let (item, quote, limit) = if n == 0 {
+                (
+                    sim.items().name(control.item).to_string(),
+                    sim.items().name(control.quote).to_string(),
+                    format!(
+                        "{} {}",
+                        if control.ceiling { "<=" } else { ">=" },
+                        control.ratio
+                    ),
+                )
+            } else {
+                (String::new(), String::new(), String::new())
+            };
+            println!(
+                "{:20} | {:20} | {:10} | {:10} | {:15} | {:15} | {:10}",
+                item,
+                quote,
+                limit,
+                format!("{} {}", period, since),
+                format!("{:.2}", trades as f64 / ticks),
+                format!("{:.2}", met),
+                wait.map_or("-".to_string(), |wait| format!("{:.1}", wait))
+            );
+        }
+    }
+    println!();
+}
+
+/// Show every legal tender law in force, and how much of each of the two
+/// moneys has been spent in trade since it was imposed, against as many
+/// ticks before it, along with how much of each everyone's holding now:
+/// whichever's overvalued by law should be doing the circulating, and the
+/// other sitting where it is.
+fn show_legal_tender(sim: &Simulation) {
+    if sim.legal_tender().is_empty() {
+        return println!("there are no legal tender laws in force");
+    }
+    println!(
+        "{:20} | {:20} | {:10} | {:15} | {:15} | {:15}",
+        "Money".bold(),
+        "Taken As".bold(),
+        "Period".bold(),
+        "Spent A Tick".bold(),
+        "Trades A Tick".bold(),
+        "Held Now".bold()
+    );
+    let (ten, fifteen, twenty) = ("-".repeat(10), "-".repeat(15), "-".repeat(20));
+    println!(
+        "{}-+-{}-+-{}-+-{}-+-{}-+-{}",
+        twenty, twenty, ten, fifteen, fifteen, fifteen
+    );
+    let now = sim.ticks();
+    for &(since, law) in sim.legal_tender() {
+        let span = (now - since).min(since);
+        let periods = [("before", since - span, since), ("since", since, now)];
+        let sides = [
+            (
+                law.item,
+                format!("{} {}", law.ratio, sim.items().name(law.quote)),
+            ),
+            (
+                law.quote,
+                format!(
+                    "{} {}",
+                    Quantity::ONE.divided_by(law.ratio),
+                    sim.items().name(law.item)
+                ),
+            ),
+        ];
+        for (money, taken_as) in sides.iter() {
+            let money = *money;
+            for (n, &(period, from, to)) in periods.iter().enumerate() {
+                let ticks = (to - from).max(1) as f64;
+                let spent: Vec<Quantity> = sim
+                    .actors()
+                    .flat_map(|actor| actor.accounts.entries().iter())
+                    .filter(|entry| entry.tick >= from && entry.tick < to)
+                    .filter(|entry| entry.account == Account::Trading)
+                    .map(|entry| entry.spent.count(money))
+                    .filter(|amount| !amount.is_zero())
+                    .collect();
+                let total: Quantity = spent.iter().copied().sum();
+                // (Only name the money, and what it's held, on its first row)
+                let (name, taken_as, held) = if n == 0 {
+                    (
+                        sim.items().name(money).to_string(),
+                        taken_as.clone(),
+                        sim.actors()
+                            .map(|actor| actor.inventory.count(money))
+                            .sum::<Quantity>()
+                            .to_string(),
+                    )
+                } else {
+                    (String::new(), String::new(), String::new())
+                };
+                println!(
+                    "{:20} | {:20} | {:10} | {:15} | {:15} | {:15}",
+                    name,
+                    taken_as,
+                    format!("{} {}", period, since),
+                    format!("{:.2}", total.as_f64() / ticks),
+                    format!("{:.2}", spent.len() as f64 / ticks),
+                    held
+                );
+            }
+        }
+    }
+    println!();
+}
+
+/// How everyone's goals fared from tick `from` up to tick `to`: how many got
+/// met a tick, and how long they'd waited on average, if any were.
+fn welfare(sim: &Simulation, from: u64, to: u64) -> (f64, Option<f64>) {
+    let met: Vec<_> = sim
+        .actors()
+        .flat_map(|actor| actor.ledger.records().iter())
+        .filter(|record| record.tick >= from && record.tick < to)
+        .collect();
+    let ticks = (to - from).max(1) as f64;
+    let wait = if met.is_empty() {
+        None
+    } else {
+        Some(met.iter().map(|r| r.waited).sum::<u64>() as f64 / met.len() as f64)
+    };
+    (met.len() as f64 / ticks, wait)
+}
+
+/// Show what the tax collector's taken and done with it, and how trade and
+/// everyone's goals have fared since it started, against as many ticks
+/// before it.
+fn show_tax(sim: &Simulation) {
+    let collector = match sim.collector() {
+        Some(collector) => collector,
+        None => return println!("nobody's collecting taxes"),
+    };
+    println!(
+        "taking {}% of {} since tick {}, and {}",
+        collector.rate.times(Quantity::units(100)),
+        match collector.base {
+            TaxBase::Trades => "what's traded",
+            TaxBase::Output => "what's made",
+            TaxBase::Both => "what's traded and made",
+        },
+        collector.since,
+        match collector.disposal {
+            Disposal::Redistribute => "handing it out evenly",
+            Disposal::Destroy => "destroying it",
+        }
+    );
+    println!();
+    println!(
+        "- collected {}",
+        render_plain_bundle(sim, collector.collected())
+    );
+    println!(
+        "- {} {}",
+        match collector.disposal {
+            Disposal::Redistribute => "handed out",
+            Disposal::Destroy => "destroyed",
+        },
+        render_plain_bundle(sim, collector.disposed())
+    );
+    println!();
+    println!(
+        "{:10} | {:15} | {:15} | {:10}",
+        "Period".bold(),
+        "Trades A Tick".bold(),
+        "Goals Met/Tick".bold(),
+        "Avg Wait".bold()
+    );
+    println!(
+        "{}-+-{}-+-{}-+-{}",
+        "-".repeat(10),
+        "-".repeat(15),
+        "-".repeat(15),
+        "-".repeat(10)
+    );
+    let (now, since) = (sim.ticks(), collector.since);
+    let span = (now - since).min(since);
+    for &(period, from, to) in [("before", since - span, since), ("since", since, now)].iter() {
+        let trades = sim
+            .prices()
+            .points()
+            .iter()
+            .filter(|p| p.tick >= from && p.tick < to)
+            .count();
+        let (met, wait) = welfare(sim, from, to);
+        println!(
+            "{:10} | {:15} | {:15} | {:10}",
+            format!("{} {}", period, since),
+            format!("{:.2}", trades as f64 / (to - from).max(1) as f64),
+            format!("{:.2}", met),
+            wait.map_or("-".to_string(), |wait| format!("{:.1}", wait))
+        );
+    }
+    println!();
+}
+
+/// Show how much of every other good a unit of `money` has fetched, tick by
+/// tick (the last 20 ticks it was traded), and how that's changed since it
+/// was first traded for each.
+fn show_purchasing_power(sim: &Simulation, money: ItemId) {
+    let baskets = sim.prices().purchasing_power(money);
+    if baskets.is_empty() {
+        return println!(
+            "{} hasn't been traded for anything yet",
+            sim.items().name(money)
+        );
+    }
+    println!(
+        "what a unit of {} fetched, by tick:",
+        sim.items().name(money).green()
+    );
+    println!();
+    println!("{:6} | {:60}", "Tick".bold(), "Basket".bold());
+    println!("{}-+-{}", "-".repeat(6), "-".repeat(60));
+    for (tick, basket) in baskets.iter().skip(baskets.len().saturating_sub(20)) {
+        println!("{:6} | {:60}", tick, render_bundle(sim, basket));
+    }
+    println!();
+    let mut goods: Vec<_> = baskets
+        .iter()
+        .flat_map(|(_, basket)| basket.iter().map(|(good, _)| good))
+        .collect();
+    goods.sort();
+    goods.dedup();
+    for good in goods {
+        let mut fetched = baskets
+            .iter()
+            .filter(|(_, basket)| basket.contains(good))
+            .map(|(_, basket)| basket.count(good));
+        let first = fetched.next().unwrap();
+        let last = fetched.next_back().unwrap_or(first);
+        println!(
+            "- {} went from {} to {} a unit ({:+.0}%)",
+            sim.items().name(good),
+            first,
+            last,
+            100.0 * (last.as_f64() - first.as_f64()) / first.as_f64()
+        );
+    }
+    println!();
+}
+
+/// Write what a unit of `money` has fetched of every other good, tick by
+/// tick, to a CSV file.
+fn export_purchasing_power(sim: &Simulation, money: ItemId, file: &str) {
+    let csv = sim.prices().purchasing_power_csv(money, sim.items());
+    match std::fs::write(file, csv) {
+        Ok(()) => println!(
+            "wrote {}'s purchasing power over {} ticks to {}",
+            sim.items().name(money),
+            sim.prices().purchasing_power(money).len(),
+            file.green()
+        ),
+        Err(e) => println!("{} {}", "error:".red(), e),
+    }
+}
+
+/// Give an actor a goal from the goals file, which comes about `once`, on a
+/// `regular` timer, or at `random`, followed by how many units it takes and
+/// optionally where it goes in the actor's hierarchy (last if not given).
+/// A marginal goal can go by its own name, in which case its units are
+/// split up between the wants it was registered as (`Eat#1`, `Eat#2` and so
+/// on, a unit each; see `GoalRegistry::register`), ranked one after another.
+fn add_goal(sim: &mut Simulation, actor: &str, name: &str, how: &[&str]) {
+    let (id, wants) = match (sim.find_actor(actor), sim.goals().wants(name)) {
+        (None, _) => return println!("{}", "unrecognized actor".red()),
+        (_, wants) if wants.is_empty() => return unrecognized_goal(sim, name),
+        (Some(id), wants) => (id, wants),
+    };
+    let marginal = sim.goals().id(name).is_none();
+    let goal = wants[0];
+    let (data, position) = match how {
+        ["once", units, position @ ..] => (
+            units.parse::<Quantity>().ok().map(|units| GoalData::Satisfaction {
+                goal,
+                units_required: units,
+                units: Quantity::ZERO,
+                id: goal.0 as i32,
+            }),
+            position,
+        ),
+        ["regular", ticks, units, position @ ..] => (
+            match (ticks.parse::<i32>(), units.parse::<Quantity>()) {
+                (Ok(ticks), Ok(units)) if ticks > 0 => Some(GoalData::RegularSatisfaction {
+                    goal,
+                    time_required: ticks,
+                    time: 0,
+                    units_required: units,
+                    units: Quantity::ZERO,
+                    id: goal.0 as i32,
+                }),
+                _ => None,
+            },
+            position,
+        ),
+        ["random", probability, units, position @ ..] => (
+            match (probability.parse::<Quantity>(), units.parse::<Quantity>()) {
+                (Ok(probability), Ok(units))
+                    if !probability.is_zero() && probability <= Quantity::ONE =>
+                {
+                    Some(GoalData::Stochastic {
+                        goal,
+                        probability,
+                        units_required: units,
+                        units: Quantity::ZERO,
+                        id: goal.0 as i32,
+                    })
+                }
+                _ => None,
+            },
+            position,
+        ),
+        _ => {
+            return println!(
+                "{} add-goal <actor> <goal> (once <units> | regular <ticks> <units> | random <chance> <units>) [position]",
+                "usage:".red()
+            )
+        }
+    };
+    // A goal that takes nothing to satisfy is no goal at all
+    let data = match data {
+        Some(
+            data @ (GoalData::Satisfaction { units_required, .. }
+            | GoalData::RegularSatisfaction { units_required, .. }
+            | GoalData::Stochastic { units_required, .. }),
+        ) if !units_required.is_zero() => data,
+        _ => return println!("{} {}", "not a valid goal:".red(), how.join(" ")),
+    };
+    let position = match position {
+        [] => usize::MAX,
+        [position] => match position.parse::<usize>() {
+            Ok(position) => position,
+            Err(_) => return println!("{} {}", "not a valid position:".red(), position),
+        },
+        _ => return println!("{} {}", "too many arguments:".red(), how.join(" ")),
+    };
+    // Split the units up between the wants, most valued first
+    let (GoalData::Satisfaction { units_required, .. }
+    | GoalData::RegularSatisfaction { units_required, .. }
+    | GoalData::Stochastic { units_required, .. }) = data;
+    let mut left = units_required;
+    let mut split = vec![];
+    for &want in wants.iter() {
+        if left.is_zero() {
+            break;
+        }
+        let units = match sim.goals().get(want) {
+            Some(def) if marginal => left.min(def.units_required),
+            _ => left,
+        };
+        left -= units;
+        split.push(with_goal(data, want, units));
+    }
+    if !left.is_zero() {
+        let most = units_required - left;
+        return println!(
+            "{} {} only has wants for {} units",
+            "error:".red(),
+            name,
+            most
+        );
+    }
+    let mut added = vec![];
+    for (n, data) in split.into_iter().enumerate() {
+        let want = data.get_goal();
+        if sim.add_goal(id, data, position.saturating_add(n)) {
+            added.push(sim.goals().name(want).blue().to_string());
+        } else {
+            println!(
+                "{} {} already has the goal {}",
+                "error:".red(),
+                sim.actor_name(id),
+                sim.goals().name(want)
+            );
+        }
+    }
+    if !added.is_empty() {
+        println!(
+            "{} now has the goal {}",
+            sim.actor_name(id).yellow(),
+            added.join(", ")
+        );
+    }
+}
+
+/// `data` as it'd be for another goal, taking `units` units.
+fn with_goal(mut data: GoalData, goal: GoalId, units: Quantity) -> GoalData {
+    let (GoalData::Satisfaction {
+        goal: g,
+        units_required,
+        id,
+        ..
+    }
+    | GoalData::RegularSatisfaction {
+        goal: g,
+        units_required,
+        id,
+        ..
+    }
+    | GoalData::Stochastic {
+        goal: g,
+        units_required,
+        id,
+        ..
+    }) = &mut data;
+    *g = goal;
+    *units_required = units;
+    *id = goal.0 as i32;
+    data
+}
+
+/// Take a goal away from an actor (every want of a marginal goal it has,
+/// if it goes by its own name).
+fn remove_goal(sim: &mut Simulation, actor: &str, name: &str) {
+    let (id, wants) = match (sim.find_actor(actor), sim.goals().wants(name)) {
+        (None, _) => return println!("{}", "unrecognized actor".red()),
+        (_, wants) if wants.is_empty() => return unrecognized_goal(sim, name),
+        (Some(id), wants) => (id, wants),
+    };
+    let removed: Vec<GoalId> = wants
+        .into_iter()
+        .filter(|&want| sim.remove_goal(id, want))
+        .collect();
+    let removed: Vec<String> = removed
+        .into_iter()
+        .map(|want| sim.goals().name(want).blue().to_string())
+        .collect();
+    if removed.is_empty() {
+        println!(
+            "{} {} doesn't have that goal",
+            "error:".red(),
+            sim.actor_name(id)
+        );
+    } else {
+        println!(
+            "{} no longer has the goal {}",
+            sim.actor_name(id).yellow(),
+            removed.join(", ")
+        );
+    }
+}
+
+/// Say there's no goal by the name given, and if it's one of a marginal
+/// goal's wants that doesn't exist (`Eat#7`, say), which ones do.
+fn unrecognized_goal(sim: &Simulation, name: &str) {
+    println!("{} {}", "unrecognized goal:".red(), name);
+    if let Some((base, _)) = name.rsplit_once('#') {
+        if sim
+            .goals()
+            .marginal_names()
+            .any(|marginal| marginal == base)
+        {
+            let wants: Vec<String> = sim
+                .goals()
+                .wants(base)
+                .into_iter()
+                .map(|want| sim.goals().name(want))
+                .collect();
+            println!(
+                "{} is marginal, so it's split into {} (or use {} for all of them)",
+                base,
+                wants.join(", "),
+                base
+            );
+        }
+    }
+}
+
+/// A bundle of items in a human-readable way ("1 FoodUnit + 2 Silver").
+fn render_bundle(sim: &Simulation, bundle: &Bundle) -> String {
+    bundle
+        .iter()
+        .map(|(item, amount)| format!("{} {}", amount, sim.items().name(item).green()))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Like `render_bundle`, but without any color, so it can be padded out to
+/// fit a table column (and "nothing" if it's empty).
+fn render_plain_bundle(sim: &Simulation, bundle: &Bundle) -> String {
+    if bundle.is_empty() {
+        return "nothing".to_string();
+    }
+    bundle
+        .iter()
+        .map(|(item, amount)| format!("{} {}", amount, sim.items().name(item)))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
 
-    while let ReadResult::Input(input) = reader.read_line()? {
-        if !input.trim().is_empty() {
-            reader.add_history_unique(input.clone());
+/// The difference between what something cost and what it brought in, as a
+/// profit or a loss.
+fn render_profit(cost: Quantity, revenue: Quantity) -> ColoredString {
+    if revenue == cost {
+        "broke even".normal()
+    } else if revenue > cost {
+        format!("profit {}", revenue - cost).green()
+    } else {
+        format!("loss {}", cost - revenue).red()
+    }
+}
+
+/// Print an event from the simulation in a human-readable way.
+fn render_event(sim: &Simulation, event: &SimEvent) {
+    match event {
+        SimEvent::GoalReintroduced { actor, goal } => {
+            println!(
+                "{} reintroduces {}",
+                sim.actor_name(*actor).yellow(),
+                sim.goals().name(*goal).blue()
+            )
         }
-        let cmd: Vec<&str> = input.trim().split_ascii_whitespace().collect();
-        match &*cmd {
-            ["help"] => {
-                println!("actor interface commands:");
-                println!();
-                for &(cmd, help) in INT_COMMANDS {
-                    println!("  {:20} - {}", cmd.green(), help);
-                }
-                println!();
-            }
-            ["get-actor", property, actorid] => {
-                let actor_number = actorid
-                    .split("#")
-                    .nth(1)
-                    .and_then(|x| x.parse::<usize>().ok())
-                    .unwrap();
-                if let Some(actor) = actors.get(actor_number) {
-                    let actor = actor.borrow();
-                    match *property {
-                        "preference-list" => {
-                            println!("ordinal hierarchy of items for {}:", actorid.yellow());
-                            println!("");
-                            println!(
-                                "{:20} | {:20} | {:20}",
-                                "Item".bold(),
-                                "Highest-Valued Goal".bold(),
-                                "# Goals".bold()
-                            );
-                            let twenty = "-".to_string().repeat(20);
-                            println!("{}-+-{}-+-{}", twenty, twenty, twenty);
-                            for (item, bh) in actor.preference_list.iter() {
-                                println!(
-                                    "{:20} | {:20} | {:20}",
-                                    format!("{:?}", item).green(),
-                                    if let Some(g) = bh.peek() {
-                                        format!("{:?}", g.goal).blue()
-                                    } else {
-                                        "N/A".to_string().blue()
-                                    },
-                                    format!("{:?}", bh.capacity())
-                                );
-                            }
-                            println!("");
-                        }
-                        "state" => {
-                            println!("general AI state for {}:", actorid.yellow());
-                            println!("");
-                            println!("- ACTOR STATE");
-                            println!("  {}", format!("{:?}", actor.state).yellow());
-                            println!("");
-                            println!("- CURRENT GOALS IN PLAY");
-                            println!("  {:10} | {:10}", "Goal".bold(), "Index".bold());
-                            println!("  {:-^1$}", "+", 23);
-                            let mut sorted_goals: Vec<_> = actor
-                                .current_goals
-                                .iter()
-                                .map(|x| (x.goal, actor.goal_hierarchy.get(&x.goal).unwrap()))
-                                .collect();
-                            sorted_goals.sort_by_key(|f| f.1);
-                            for (goal, index) in sorted_goals {
-                                println!(
-                                    "  {:10} | {:10}",
-                                    format!("{:?}", goal).blue(),
-                                    format!("{:?}", index)
-                                );
-                            }
-                            println!("");
-                            println!("- INVENTORY");
-                            println!(
-                                "  {:20} | {:20} | {:20}",
-                                "Item".bold(),
-                                "Highest-Valued Goal".bold(),
-                                "# Goals".bold()
-                            );
-                            let twenty = "-".to_string().repeat(20);
-                            println!("  {}-+-{}-+-{}", twenty, twenty, twenty);
-                            for item in actor.inventory.iter() {
-                                let bh = actor.preference_list.get(&item);
-                                println!(
-                                    "  {:20} | {:20} | {:20}",
-                                    format!("{:?}", item).green(),
-                                    if let Some(g) = bh.and_then(|x| x.peek()) {
-                                        format!("{:?}", g.goal).blue()
-                                    } else {
-                                        "N/A".to_string().blue()
-                                    },
-                                    format!("{:?}", bh.map(|x| x.capacity()).unwrap_or(0))
-                                );
-                            }
-                            println!("");
-                        }
-                        "goal-registry" => {
-                            println!("goal details for {}:", actorid.yellow());
-                            let mut registry: Vec<(&Goal, &GoalData)> =
-                                actor.goal_registry.iter().collect();
-                            registry.sort_by_key(|(g, _)| actor.goal_hierarchy.get(g).unwrap());
-                            for (goal, goal_data) in registry {
-                                println!("");
-                                println!("- {}", format!("{:?}", goal).blue());
-                                println!("  {:?}", goal_data);
-                            }
-                            println!("");
-                        }
-                        "goal-hierarchy" => {
-                            println!("ordinal hierarchy of values for {}:", actorid.yellow());
-                            println!("");
-                            println!("{:10} | {:10}", "Goal".bold(), "Index".bold());
-                            println!("{:-^1$}", "+", 23);
-                            let mut sorted_goals: Vec<_> = actor.goal_hierarchy.iter().collect();
-                            sorted_goals.sort_by_key(|f| f.1);
-                            for (goal, index) in sorted_goals {
-                                println!(
-                                    "{:10} | {:10}",
-                                    format!("{:?}", goal).blue(),
-                                    format!("{:?}", index)
-                                );
-                            }
-                            println!("");
-                        }
-                        x => println!("{} {}", "unknown subcommand:".red(), x),
-                    }
-                } else {
-                    println!("cannot find actor: {}", actorid);
-                }
+        SimEvent::GoalActivated { actor, goal } => {
+            println!(
+                "{} can now pursue {}",
+                sim.actor_name(*actor).yellow(),
+                sim.goals().name(*goal).blue()
+            )
+        }
+        SimEvent::GoalDrifted {
+            actor,
+            goal,
+            from,
+            to,
+        } => println!(
+            "{}'s tastes change: {} moves from {} to {} in its hierarchy",
+            sim.actor_name(*actor).yellow(),
+            sim.goals().name(*goal).blue(),
+            from,
+            to
+        ),
+        SimEvent::GoalSelected { actor, goal } => {
+            println!();
+            println!(
+                "{} selects {} as a goal",
+                sim.actor_name(*actor).yellow(),
+                sim.goals().name(*goal).blue()
+            )
+        }
+        SimEvent::NoGoals { actor } => {
+            println!();
+            println!(
+                "{} does not pursue any goals",
+                sim.actor_name(*actor).yellow()
+            )
+        }
+        SimEvent::ItemUsed {
+            actor,
+            item,
+            amount,
+            goal,
+        } => println!(
+            "{} uses {} of item {} for goal {}",
+            sim.actor_name(*actor).yellow(),
+            amount,
+            sim.items().name(*item).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::PublicGoodUsed { actor, item, goal } => println!(
+            "{} uses the public {} for goal {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::ItemSpoiled {
+            actor,
+            item,
+            amount,
+        } => println!(
+            "{}'s {} {} has spoiled",
+            sim.actor_name(*actor).yellow(),
+            amount,
+            sim.items().name(*item).green()
+        ),
+        SimEvent::GoalAbandoned { actor, goal, ticks } => println!(
+            "{} gives up on goal {} after {} ticks getting nowhere",
+            sim.actor_name(*actor).yellow(),
+            sim.goals().name(*goal).blue(),
+            ticks
+        ),
+        SimEvent::Rested { actor, hours, goal } => println!(
+            "{} rests for {} hours towards goal {}",
+            sim.actor_name(*actor).yellow(),
+            hours,
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::LeisureForgone { actor, hours, goal } => println!(
+            "{} works {} hours this tick, giving up that much time towards goal {}",
+            sim.actor_name(*actor).yellow(),
+            hours,
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::OutOfLabor { actor, work } => println!(
+            "{} is too worn out for any more {:?} this tick",
+            sim.actor_name(*actor).yellow(),
+            work
+        ),
+        SimEvent::ItemYielded {
+            actor,
+            land,
+            item,
+            amount,
+        } => println!(
+            "{}'s {} yields {} {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*land).green(),
+            amount,
+            sim.items().name(*item).green()
+        ),
+        SimEvent::ResourceGathered {
+            actor,
+            node,
+            item,
+            amount,
+            goal,
+        } => println!(
+            "{} gathers {} {} from {} for goal {}",
+            sim.actor_name(*actor).yellow(),
+            amount,
+            sim.items().name(*item).green(),
+            sim.resources()[node.0].name,
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::ResourceExhausted { actor, node } => println!(
+            "{} finds {} {} for this tick",
+            sim.actor_name(*actor).yellow(),
+            sim.resources()[node.0].name,
+            "picked clean".red()
+        ),
+        SimEvent::RecipeDiscovered { actor, recipe } => println!(
+            "{} {} how to make things by {}",
+            sim.actor_name(*actor).yellow(),
+            "discovers".green(),
+            sim.items().recipe_name(*recipe).green()
+        ),
+        SimEvent::RecipeLearned {
+            actor,
+            from,
+            recipe,
+        } => println!(
+            "{} learns {} from watching {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().recipe_name(*recipe).green(),
+            sim.actor_name(*from).yellow()
+        ),
+        SimEvent::ProductionStarted {
+            actor,
+            recipe,
+            goal,
+        } => println!(
+            "{} starts {} for goal {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().recipe_name(*recipe).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::ItemsReserved { actor, goal, items } => println!(
+            "{} keeps {} for the rest of what it's making for goal {}",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, items),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::TaxCollected { actor, items } => println!(
+            "{} pays {} in tax",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, items)
+        ),
+        SimEvent::TaxPaidOut { actor, items } => println!(
+            "{} is handed {} out of what was collected in tax",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, items)
+        ),
+        SimEvent::TaxDestroyed { items } => {
+            println!(
+                "{} collected in tax is destroyed",
+                render_bundle(sim, items)
+            )
+        }
+        SimEvent::GoodsSaved { actor, goal, items } => println!(
+            "{} saves {} for goal {}, for when it comes back around",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, items),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::ItemsProduced {
+            actor,
+            recipe,
+            outputs,
+        } => println!(
+            "{} finishes {}, making {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().recipe_name(*recipe).green(),
+            render_bundle(sim, outputs)
+        ),
+        SimEvent::ItemServed { actor, item, goal } => println!(
+            "{}'s {} serves goal {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::ToolMaintained {
+            actor,
+            tool,
+            inputs,
+        } => println!(
+            "{} maintains its {} with {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*tool).green(),
+            render_bundle(sim, inputs)
+        ),
+        SimEvent::ItemWornOut { actor, item } => println!(
+            "{}'s {} has worn out",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green()
+        ),
+        SimEvent::ItemDiscarded {
+            actor,
+            item,
+            amount,
+        } => println!(
+            "{} has no room, and throws out {} {}",
+            sim.actor_name(*actor).yellow(),
+            amount,
+            sim.items().name(*item).green()
+        ),
+        SimEvent::ItemMissing { actor, item, goal } => println!(
+            "{} does not have item {} for goal {} in inventory",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::GoalSatisfied { actor, goal } => println!(
+            "{} has satisfied goal {}",
+            sim.actor_name(*actor).yellow(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::WillingToTrade { actor } => {
+            println!(
+                "{} is now willing to trade",
+                sim.actor_name(*actor).yellow()
+            )
+        }
+        SimEvent::PartnerOccupied { actor, partner } => println!(
+            "{}: {} is already occupied trading with another actor, skipping",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow()
+        ),
+        SimEvent::PartnersMatched { actor, partner } => println!(
+            "{} is matched with trade partner {}",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow()
+        ),
+        SimEvent::PartnerFound {
+            actor,
+            partner,
+            items,
+            engaged,
+        } => println!(
+            "{} finds trade partner {} with {} items it is interested in ({})",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            items,
+            if *engaged { "engaged" } else { "not engaged" }
+        ),
+        SimEvent::BidMade {
+            actor,
+            partner,
+            offer,
+            want,
+        } => println!(
+            "{}/{} makes bid: will give {} for {}",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            render_bundle(sim, offer),
+            render_bundle(sim, want)
+        ),
+        SimEvent::OfferPosted { actor, offer, want } => println!(
+            "{} posts an offer: will give {} for {}",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, offer),
+            render_bundle(sim, want)
+        ),
+        SimEvent::OrderPlaced {
+            actor,
+            item,
+            pay_with,
+            price,
+        } => println!(
+            "{} bids {} for {} on the market",
+            sim.actor_name(*actor).yellow(),
+            render_bundle(sim, &Bundle::single(*pay_with, *price)),
+            render_bundle(sim, &Bundle::single(*item, Quantity::ONE))
+        ),
+        SimEvent::CounterOfferMade {
+            actor,
+            partner,
+            offer,
+            want,
+        } => println!(
+            "{}/{} counters: will give {} for {} instead",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            render_bundle(sim, offer),
+            render_bundle(sim, want)
+        ),
+        SimEvent::BidHeld {
+            actor,
+            bidder,
+            offer,
+            outbid,
+        } => match outbid {
+            Some(outbid) => println!(
+                "{} holds on to {}'s bid of {}, beating {}'s",
+                sim.actor_name(*actor).yellow(),
+                sim.actor_name(*bidder).yellow(),
+                render_bundle(sim, offer),
+                sim.actor_name(*outbid).yellow()
+            ),
+            None => println!(
+                "{} holds on to {}'s bid of {} in case anyone beats it",
+                sim.actor_name(*actor).yellow(),
+                sim.actor_name(*bidder).yellow(),
+                render_bundle(sim, offer)
+            ),
+        },
+        SimEvent::BidRejected {
+            actor,
+            partner,
+            rejected_by,
+        } => {
+            println!(
+                "{}/{}: bid rejected by {}",
+                sim.actor_name(*actor).yellow(),
+                sim.actor_name(*partner).yellow(),
+                sim.actor_name(*rejected_by).yellow()
+            );
+            println!(
+                "{}/{}: bid is proceeding up",
+                sim.actor_name(*actor).yellow(),
+                sim.actor_name(*partner).yellow()
+            );
+        }
+        SimEvent::TradeCompleted {
+            actor,
+            partner,
+            gave,
+            received,
+        } => println!(
+            "{}/{}: {} ({} for {})",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            "Trade complete".green(),
+            render_bundle(sim, gave),
+            render_bundle(sim, received)
+        ),
+        SimEvent::TradeCycleCompleted { actors, gave } => println!(
+            "Trade around a circle: {}",
+            actors
+                .iter()
+                .zip(gave)
+                .enumerate()
+                .map(|(n, (giver, bundle))| format!(
+                    "{} gives {} to {}",
+                    sim.actor_name(*giver).yellow(),
+                    render_bundle(sim, bundle),
+                    sim.actor_name(actors[(n + 1) % actors.len()]).yellow()
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SimEvent::Searched { actor, looked_at } => println!(
+            "{} looks around to see what {} have",
+            sim.actor_name(*actor).yellow(),
+            looked_at
+                .iter()
+                .map(|&other| sim.actor_name(other).yellow().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SimEvent::TradeCostPaid { actor, cost } => match *cost {
+            TradeCost::Labor(hours) => println!(
+                "{} spends {} hours making the trade",
+                sim.actor_name(*actor).yellow(),
+                hours
+            ),
+            TradeCost::Item(item, amount) => println!(
+                "{} pays {} {} to make the trade",
+                sim.actor_name(*actor).yellow(),
+                amount,
+                sim.items().name(item).green()
+            ),
+        },
+        SimEvent::MediumAccepted {
+            actor, item, goal, ..
+        } => println!(
+            "{} takes {} as a medium of exchange, to trade on for {}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green(),
+            sim.goals().name(*goal).blue()
+        ),
+        SimEvent::IouIssued {
+            debtor,
+            creditor,
+            lent,
+            owed,
+            due,
+            interest,
+        } => println!(
+            "{} borrows {} from {}, to pay back {} on tick {} ({}% a tick interest)",
+            sim.actor_name(*debtor).yellow(),
+            render_bundle(sim, lent),
+            sim.actor_name(*creditor).yellow(),
+            render_bundle(sim, owed),
+            due,
+            interest.times(Quantity::from(100u32))
+        ),
+        SimEvent::DepositMade {
+            bank,
+            depositor,
+            amount,
+            due,
+        } => println!(
+            "{} puts {} in the bank with {}, to have back on tick {}",
+            sim.actor_name(*depositor).yellow(),
+            render_bundle(sim, amount),
+            sim.actor_name(*bank).yellow(),
+            due
+        ),
+        SimEvent::BankFailed { bank, owed, assets } => println!(
+            "{} has {}, owing its depositors {} against {} in hand and lent out",
+            sim.actor_name(*bank).yellow(),
+            "failed".red(),
+            owed,
+            assets
+        ),
+        SimEvent::IouRepaid {
+            debtor,
+            creditor,
+            owed,
+        } => println!(
+            "{} pays back the {} it owes {}",
+            sim.actor_name(*debtor).yellow(),
+            render_bundle(sim, owed),
+            sim.actor_name(*creditor).yellow()
+        ),
+        SimEvent::IouDefaulted {
+            debtor,
+            creditor,
+            paid,
+            unpaid,
+        } => println!(
+            "{} {} on its debt to {}, still owing {} (paid {})",
+            sim.actor_name(*debtor).yellow(),
+            "defaults".red(),
+            sim.actor_name(*creditor).yellow(),
+            render_bundle(sim, unpaid),
+            if paid.is_empty() {
+                "nothing".to_string()
+            } else {
+                render_bundle(sim, paid)
             }
-            ["compare-item-values", actor, item1, item2] => {
-                let actor_number = actor
-                    .split("#")
-                    .nth(1)
-                    .and_then(|x| x.parse::<usize>().ok())
-                    .unwrap();
-                use Item::*;
-                let i1 = match *item1 {
-                    "FoodUnit" => FoodUnit,
-                    "HouseUnit" => HouseUnit,
-                    "LeisureUnit1" => LeisureUnit1,
-                    "LeisureUnit2" => LeisureUnit2,
-                    _ => panic!("unrecognized item"),
-                };
-                let i2 = match *item2 {
-                    "FoodUnit" => FoodUnit,
-                    "HouseUnit" => HouseUnit,
-                    "LeisureUnit1" => LeisureUnit1,
-                    "LeisureUnit2" => LeisureUnit2,
-                    _ => panic!("unrecognized item"),
-                };
-                match actors
-                    .get(actor_number)
-                    .unwrap()
-                    .borrow()
-                    .compare_item_values(i1, i2)
-                {
-                    Ordering::Equal => println!("These items are valued the same!"),
-                    Ordering::Less => println!(
-                        "{} is valued less than {}",
-                        item1.to_string().green(),
-                        item2.to_string().green()
-                    ),
-                    Ordering::Greater => println!(
-                        "{} is valued more than {}",
-                        item1.to_string().green(),
-                        item2.to_string().green()
-                    ),
-                }
+        ),
+        SimEvent::ForwardAgreed {
+            buyer,
+            seller,
+            pays,
+            gets,
+            due,
+        } => println!(
+            "{}/{}: {} ({} for {}, on tick {})",
+            sim.actor_name(*buyer).yellow(),
+            sim.actor_name(*seller).yellow(),
+            "Forward contract agreed".green(),
+            render_bundle(sim, pays),
+            render_bundle(sim, gets),
+            due
+        ),
+        SimEvent::ForwardSettled { buyer, seller } => println!(
+            "{}/{}: forward contract delivered",
+            sim.actor_name(*buyer).yellow(),
+            sim.actor_name(*seller).yellow()
+        ),
+        SimEvent::ForwardDefaulted {
+            buyer,
+            seller,
+            defaulted,
+        } => println!(
+            "{}/{}: forward contract {} by {}",
+            sim.actor_name(*buyer).yellow(),
+            sim.actor_name(*seller).yellow(),
+            "defaulted on".red(),
+            defaulted
+                .iter()
+                .map(|&id| sim.actor_name(id))
+                .collect::<Vec<_>>()
+                .join(" and ")
+        ),
+        SimEvent::LeaseAgreed {
+            landlord,
+            tenant,
+            leased,
+            rent,
+            due,
+        } => println!(
+            "{}/{}: {} ({} for {} a tick, until tick {})",
+            sim.actor_name(*landlord).yellow(),
+            sim.actor_name(*tenant).yellow(),
+            "Lease agreed".green(),
+            render_bundle(sim, leased),
+            render_bundle(sim, rent),
+            due
+        ),
+        SimEvent::LeaseRefused {
+            landlord,
+            tenant,
+            refused_by,
+        } => println!(
+            "{}/{}: lease {} by {}",
+            sim.actor_name(*landlord).yellow(),
+            sim.actor_name(*tenant).yellow(),
+            "refused".red(),
+            sim.actor_name(*refused_by)
+        ),
+        SimEvent::RentPaid {
+            tenant,
+            landlord,
+            rent,
+        } => println!(
+            "{} pays {} {} in rent",
+            sim.actor_name(*tenant).yellow(),
+            sim.actor_name(*landlord).yellow(),
+            render_bundle(sim, rent)
+        ),
+        SimEvent::LeaseEnded {
+            landlord,
+            tenant,
+            leased,
+            returned,
+            defaulted,
+        } => println!(
+            "{}/{}: lease on {} {}, {} handed back",
+            sim.actor_name(*landlord).yellow(),
+            sim.actor_name(*tenant).yellow(),
+            render_bundle(sim, leased),
+            if *defaulted {
+                "defaulted on".red()
+            } else {
+                "up".normal()
+            },
+            if returned.is_empty() {
+                "nothing".to_string()
+            } else {
+                render_bundle(sim, returned)
             }
-            ["tick"] => {
-                println!("");
-                for actor in actors.iter() {
-                    actor.borrow_mut().tick(&actors);
-                }
+        ),
+        SimEvent::LaborHired {
+            employer,
+            worker,
+            recipe,
+            hours,
+            wage,
+        } => println!(
+            "{} {} {} for {} hours of {} for {}",
+            sim.actor_name(*employer).yellow(),
+            "hires".green(),
+            sim.actor_name(*worker).yellow(),
+            hours,
+            sim.items().recipe_name(*recipe).green(),
+            render_bundle(sim, wage)
+        ),
+        SimEvent::WageRejected {
+            employer,
+            worker,
+            offered,
+        } => println!(
+            "{} {} {}'s wage of {}",
+            sim.actor_name(*worker).yellow(),
+            "turns down".red(),
+            sim.actor_name(*employer).yellow(),
+            render_bundle(sim, offered)
+        ),
+        SimEvent::Resold {
+            actor,
+            partner,
+            item,
+            bought_with,
+            sold_for,
+        } => println!(
+            "{} sells on {} to {} for {}{}",
+            sim.actor_name(*actor).yellow(),
+            sim.items().name(*item).green(),
+            sim.actor_name(*partner).yellow(),
+            sim.items().name(*sold_for).green(),
+            match bought_with {
+                Some(paid) => format!(" (it paid {})", sim.items().name(*paid).green()),
+                None => String::new(),
             }
-            ["give-item", actor, item] => {
-                let actor_number = actor
-                    .split("#")
-                    .nth(1)
-                    .and_then(|x| x.parse::<usize>().ok())
-                    .unwrap();
-                use Item::*;
-                if let Some(actor) = actors.get(actor_number) {
-                    let mut actor = actor.borrow_mut();
-                    actor.add_item(match *item {
-                        "FoodUnit" => FoodUnit,
-                        "HouseUnit" => HouseUnit,
-                        "LeisureUnit1" => LeisureUnit1,
-                        "LeisureUnit2" => LeisureUnit2,
-                        _ => panic!("unrecognized item"),
-                    });
-                } else {
-                    println!("{}", "unrecognized actor".red())
-                }
+        ),
+        SimEvent::NoZoneOfAgreement {
+            actor,
+            partner,
+            want,
+            most_given,
+            least_taken,
+        } => println!(
+            "{}/{}: {} for {} (the most it would give is {}, the least they would take is {})",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            "No zone of agreement".red(),
+            render_bundle(sim, want),
+            most_given
+                .as_ref()
+                .map_or("nothing".to_string(), |b| render_bundle(sim, b)),
+            least_taken
+                .as_ref()
+                .map_or("nothing".to_string(), |b| render_bundle(sim, b))
+        ),
+        SimEvent::NegotiationExpired {
+            actor,
+            partner: Some(partner),
+            ticks,
+        } => println!(
+            "{}/{}: {} after {} ticks",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow(),
+            "Negotiation expired".red(),
+            ticks
+        ),
+        SimEvent::NegotiationExpired {
+            actor,
+            partner: None,
+            ..
+        } => println!(
+            "{}: {} (nobody's bidding any more)",
+            sim.actor_name(*actor).yellow(),
+            "Negotiation expired".red()
+        ),
+        SimEvent::MoneyMinted {
+            actor,
+            item,
+            amount,
+        } => println!(
+            "{} {} minted and handed to {}",
+            amount,
+            sim.items().name(*item).green(),
+            sim.actor_name(*actor).yellow()
+        ),
+        SimEvent::NewMoneySpent {
+            actor,
+            to,
+            item,
+            amount,
+            minted,
+            hands,
+            received,
+        } => println!(
+            "{} passes {} of the {} minted on tick {} on to {} (hand #{} from the mint), for {}",
+            sim.actor_name(*actor).yellow(),
+            amount,
+            sim.items().name(*item).green(),
+            minted,
+            sim.actor_name(*to).yellow(),
+            hands,
+            if received.is_empty() {
+                "nothing".to_string()
+            } else {
+                render_bundle(sim, received)
             }
-            ["quit"] => return Ok(()),
-            _ => println!("{} {}", "unrecognized command".red(), cmd.join(" ")),
+        ),
+        SimEvent::GiftGiven { actor, to, gift } => println!(
+            "{}/{}: {} ({}, for nothing)",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*to).yellow(),
+            "Gift given".green(),
+            render_bundle(sim, gift)
+        ),
+        SimEvent::TradeAbandoned { actor, partner } => println!(
+            "{}/{}: No more items to trade, going to next actor.",
+            sim.actor_name(*actor).yellow(),
+            sim.actor_name(*partner).yellow()
+        ),
+        SimEvent::WaitingInBid { actor } => {
+            println!("{} is waiting in bid", sim.actor_name(*actor).yellow())
         }
     }
-
-    println!("Exiting...");
-    Ok(())
 }
 
 #[derive(Debug, StructOpt)]
@@ -312,26 +4037,334 @@ fn main() -> io::Result<()> {
     about = "A simple Austrian microeconomic actor prototype."
 )]
 struct Cli {
-    /// Number of actors to use
-    actor_number: i32,
+    /// Number of actors to use (as many as the scenario says, if it says)
+    actor_number: Option<usize>,
+    /// Run this many ticks before handing over to the interactive interface (or, with --no-repl, before stopping)
+    #[structopt(long)]
+    ticks: Option<u64>,
+    /// Don't start the interactive interface: run the --ticks given and write out the results as JSON
+    #[structopt(long, requires = "ticks")]
+    no_repl: bool,
+    /// File to write the results of a --no-repl run to (standard output if not given)
+    #[structopt(long, parse(from_os_str), requires = "no-repl")]
+    output: Option<PathBuf>,
+    /// Event log file to record every event of the run to, to replay later
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+    /// How many ticks back the world can be rewound from the interactive interface (keeping a copy of it for each)
+    #[structopt(long, default_value = "20")]
+    history: usize,
+    /// How actors negotiate trades with each other
+    #[structopt(long, default_value = "bilateral", possible_values = trade::PROTOCOL_NAMES)]
+    market: String,
+    /// Seed for the random number generator (picked at random if not given)
+    #[structopt(long)]
+    seed: Option<u64>,
+    /// Have actors make their decisions in parallel each tick (for big populations)
+    #[structopt(long)]
+    parallel: bool,
+    /// TOML file describing who's in the world, what they start out with, and what they're after (uses the built-in scenario if not given)
+    #[structopt(long, parse(from_os_str))]
+    scenario: Option<PathBuf>,
+    /// TOML file describing the goals actors pursue (uses the scenario's, or the built-in goals, if not given)
+    #[structopt(long, parse(from_os_str))]
+    goals: Option<PathBuf>,
+    /// TOML file describing the goods in the world (uses the scenario's, or the built-in items, if not given)
+    #[structopt(long, parse(from_os_str))]
+    items: Option<PathBuf>,
+    /// Most each actor can carry (no limit if not given)
+    #[structopt(long)]
+    capacity: Option<Quantity>,
+    /// Hours of labor each actor has every tick
+    #[structopt(long, default_value = "8")]
+    labor: Quantity,
+    /// Chance, from 0 to 1, of each actor's preferences drifting on any given tick
+    #[structopt(long, conflicts_with = "drift-every")]
+    drift: Option<Quantity>,
+    /// Have each actor's preferences drift every so many ticks
+    #[structopt(long)]
+    drift_every: Option<u64>,
+    /// How far, as a fraction, each actor's goals can stray from the goals file's, either way
+    #[structopt(long, default_value = "0")]
+    jitter: Quantity,
+    /// Ticks an actor goes after a goal without getting anywhere before giving up on it (never gives up if not given)
+    #[structopt(long)]
+    patience: Option<u32>,
+    /// How many of the actors are middlemen, buying things to sell on (the first ones)
+    #[structopt(long, default_value = "0")]
+    middlemen: i32,
+    /// How many of the actors are arbitrageurs, buying where things go cheap to sell where they go dear (the ones after the middlemen)
+    #[structopt(long, default_value = "0")]
+    arbitrageurs: i32,
+    /// How many of the actors are banks, taking deposits of money and lending them out (the ones after the arbitrageurs)
+    #[structopt(long, default_value = "0")]
+    banks: i32,
+    /// Fraction of what they owe their depositors banks keep in reserve, instead of lending it out (0.2 if not given)
+    #[structopt(long)]
+    reserve_ratio: Option<Quantity>,
+    /// Most extra, as a fraction, each actor wants back per tick for lending something (each gets a random amount up to it)
+    #[structopt(long, default_value = "0")]
+    time_preference: Quantity,
+    /// Have actors remember who they've traded with, forgetting this fraction of it every tick
+    #[structopt(long)]
+    memory_decay: Option<Quantity>,
+    /// What each side pays to make a trade, burned when it goes through (in hours of labor, unless --trade-cost-in is given)
+    #[structopt(long)]
+    trade_cost: Option<Quantity>,
+    /// Item trade costs are paid in, instead of labor
+    #[structopt(long, requires = "trade-cost")]
+    trade_cost_in: Option<String>,
+    /// Have actors only know what others hold from having seen it, so they have to spend time looking
+    #[structopt(long)]
+    imperfect_information: bool,
+    /// Have actors with nothing left to go after give away what they've no use for to whoever wants it
+    #[structopt(long)]
+    charity: bool,
+    /// Have actors work out the worst trade they'd make before bidding, and break off as soon as there's no deal to be had
+    #[structopt(long)]
+    walk_away: bool,
+    /// Have actors learn what to expect goods to go for from everyone's trades, not just their own
+    #[structopt(long)]
+    watch_market: bool,
+    /// Have actors turn down trades more than this fraction worse than they expect, and wait for a better one
+    #[structopt(long)]
+    hold_out: Option<Quantity>,
+    /// Have actors with nothing to do stock up on what their recurring goals will need next, when it looks cheap
+    #[structopt(long)]
+    stockpile: bool,
+    /// Have actors hire other actors' labor, paying wages in goods, when they don't have enough of their own to make something
+    #[structopt(long)]
+    hire_labor: bool,
+    /// Have actors make something else to trade for what they want, when they expect that to get them more than making it themselves
+    #[structopt(long)]
+    specialize: bool,
+    /// Have actors weigh every hour of work against the leisure it costs them, taking whatever they don't work off
+    #[structopt(long)]
+    weigh_leisure: bool,
+    /// Have actors weigh bundles by how many of their wants they'd go towards, so enough lesser goods can outweigh a better one
+    #[structopt(long)]
+    weigh_bundles: bool,
+    /// Have actors set goods and money aside for the recurring goals they know are coming back, rather than use them up on lesser ones
+    #[structopt(long)]
+    save: bool,
+    /// Have the world pair up everyone looking to trade at once every tick, in random or priority order
+    #[structopt(long, possible_values = MATCHING_NAMES)]
+    matching: Option<Matching>,
+    /// Order actors act in each tick: by ID, shuffled fresh every tick, or by ID starting one further along every tick
+    #[structopt(long, default_value = "fixed", possible_values = TICK_ORDER_NAMES)]
+    tick_order: TickOrder,
+    /// Ticks a negotiation can go on without a deal before it's called off (no limit if not given)
+    #[structopt(long)]
+    negotiation_timeout: Option<u64>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Run the same world over many seeds at once, without the interactive interface, and sum up how each metric came out
+    Experiment(Experiment),
+}
+
+/// What to run an experiment over, and what to make of it (the world itself
+/// is set up by the options before the subcommand, as for any other run).
+#[derive(Debug, StructOpt)]
+struct Experiment {
+    /// How many seeds to run, counting up from --seed (or from 0, if it's not given)
+    #[structopt(long, default_value = "10")]
+    seeds: u64,
+    /// How many ticks to run each seed for
+    #[structopt(long, default_value = "100")]
+    ticks: u64,
+    /// Metrics to sum up, separated by commas
+    #[structopt(long, use_delimiter = true, default_value = "trades,satisfaction,prices", possible_values = METRIC_NAMES)]
+    metrics: Vec<String>,
+    /// Format to write the summary in
+    #[structopt(long, default_value = "json", possible_values = &["json", "csv"])]
+    format: String,
+    /// File to write the summary to (standard output if not given)
+    #[structopt(long, parse(from_os_str))]
+    output: Option<PathBuf>,
 }
 
 static INT_COMMANDS: &[(&str, &str)] = &[
     ("help", "You're looking at it"),
     (
         "get-actor",
-        "Get a prop (preference-list, goal-hierarchy, goal-registry, state)",
+        "Get a prop (preference-list, goal-hierarchy, goal-registry, state, provenance, satisfaction-history, trade-history, saleability, credit, trade-memory, expectations, pnl, bank-book)",
+    ),
+    (
+        "tick",
+        "Tick time forward and run simulation on its own (optionally so many ticks, and --quiet to only sum them up)",
+    ),
+    (
+        "run-until",
+        "Run quietly until something happens (no-trades for so many ticks, an actor's in a state, or a tick's reached), showing watches every tick unless --quiet",
+    ),
+    (
+        "give-item",
+        "Add an item to an actor's inventory (optionally how much)",
+    ),
+    (
+        "mint",
+        "Mint so much new money and hand it to an actor (optionally which money), tracing where it goes",
+    ),
+    (
+        "gift",
+        "Have one actor give another an item for nothing (optionally how much)",
+    ),
+    (
+        "impose-price-ceiling",
+        "Forbid trading a unit of one item for more than so much of another, from now on",
+    ),
+    (
+        "impose-price-floor",
+        "Forbid trading a unit of one item for less than so much of another, from now on",
+    ),
+    (
+        "impose-legal-tender",
+        "Make one money have to be taken as so much of another, from now on",
+    ),
+    (
+        "impose-tax",
+        "Take a fraction of what's traded (or output, or both) every tick from now on, and redistribute it (or destroy it)",
+    ),
+    (
+        "trade",
+        "Have one actor offer an item to another for one of theirs, swapping them if both would",
+    ),
+    (
+        "lease",
+        "Have one actor let an item to another for so many ticks, for rent each tick (optionally how much)",
+    ),
+    (
+        "add-public",
+        "Put a public good anyone can use in the commons (optionally how much)",
     ),
-    ("tick", "Tick time forward and run simulation on its own"),
-    ("give-item", "Add an item to an actor's inventory"),
     (
         "compare-item-values",
         "Have an actor compare two item's values",
     ),
+    (
+        "add-goal",
+        "Give an actor a goal (once, regular, or random), optionally where to rank it; a marginal goal's own name (Eat) splits its units between its wants (Eat#1, Eat#2...)",
+    ),
+    (
+        "remove-goal",
+        "Take a goal away from an actor (all of a marginal goal's wants, by its own name)",
+    ),
+    (
+        "prices",
+        "Show the last and average exchange ratio of every pair of items traded",
+    ),
+    (
+        "add-resource",
+        "Put somewhere in the world anyone can gather an item from, so much a tick (optionally named)",
+    ),
+    (
+        "resources",
+        "Show every resource node in the world, and what it yields",
+    ),
+    (
+        "wages",
+        "Show the last and average hourly wage labor's been paid in each good",
+    ),
+    (
+        "recipes",
+        "Show every way of making goods out of other goods, and what it takes",
+    ),
+    (
+        "stats",
+        "Show a report (specialization, optionally over the last so many ticks, imputation or purchasing-power, optionally of an item, mint, savings, price-controls, legal-tender, or tax)",
+    ),
+    (
+        "export-trades",
+        "Write who's traded with whom so far to a file (as dot)",
+    ),
+    (
+        "export-purchasing-power",
+        "Write what a unit of money has fetched of every other good, by tick, to a file (as csv, optionally for an item)",
+    ),
+    (
+        "save",
+        "Save the whole world to a snapshot file, to pick back up later",
+    ),
+    (
+        "load",
+        "Load the world from a snapshot file, just as it was saved",
+    ),
+    (
+        "rewind",
+        "Put the world back the way it was a tick ago (optionally so many)",
+    ),
+    (
+        "record",
+        "Record every event from now on to an event log file, to replay later (or stop)",
+    ),
+    (
+        "replay",
+        "Load the world from an event log file, to step through the run it recorded",
+    ),
+    (
+        "step",
+        "Replay the next tick of the event log (optionally so many)",
+    ),
+    (
+        "world",
+        "List the worlds in the session, create one as a copy of this one, switch to running another, or diff this one against another",
+    ),
+    (
+        "watch",
+        "Show a prop of an actor (as for get-actor) again, with what's changed marked, whenever the world's run on (or list what's watched)",
+    ),
+    (
+        "unwatch",
+        "Stop watching an actor's prop (or all of them, or everything)",
+    ),
+    (
+        "play",
+        "Run a tick every second (or so many milliseconds) on its own, showing what happens, until paused",
+    ),
+    ("pause", "Stop playing"),
     ("quit", "Quit the interactive interface"),
 ];
 
-struct InterfaceCompleter(Vec<String>);
+/// Every property of an actor `get-actor` can show.
+const ACTOR_PROPERTIES: &[&str] = &[
+    "preference-list",
+    "goal-hierarchy",
+    "goal-registry",
+    "state",
+    "provenance",
+    "satisfaction-history",
+    "trade-history",
+    "saleability",
+    "credit",
+    "trade-memory",
+    "expectations",
+    "pnl",
+    "bank-book",
+];
+
+/// A completer for the actors, items and goals in the world as it is.
+fn completer(sim: &Simulation) -> Arc<InterfaceCompleter> {
+    Arc::new(InterfaceCompleter(
+        sim.actors().map(|actor| actor.name.to_string()).collect(),
+        sim.items()
+            .iter()
+            .map(|(_, def)| def.name.clone())
+            .collect(),
+        sim.goals()
+            .iter()
+            .map(|(_, def)| def.name.clone())
+            .chain(sim.goals().marginal_names().map(String::from))
+            .collect(),
+    ))
+}
+
+/// Completes commands, plus actor names (first field), item names (second
+/// field), and goal names (third field) for the commands that take them.
+struct InterfaceCompleter(Vec<String>, Vec<String>, Vec<String>);
 
 impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
     fn complete(
@@ -364,12 +4397,7 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                 if wc == 0 {
                     let mut res = Vec::new();
 
-                    for subcmd in vec![
-                        "preference-list",
-                        "goal-hierarchy",
-                        "goal-registry",
-                        "state",
-                    ] {
+                    for &subcmd in ACTOR_PROPERTIES {
                         if subcmd.starts_with(word) {
                             res.push(Completion::simple(subcmd.to_owned()));
                         }
@@ -390,6 +4418,44 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                     None
                 }
             }
+            Some("watch") | Some("unwatch") => {
+                let names: Vec<&str> = match words.count() {
+                    0 => self.0.iter().map(|s| s.as_str()).collect(),
+                    1 => ACTOR_PROPERTIES.to_vec(),
+                    _ => return None,
+                };
+                let mut res = Vec::new();
+
+                for name in names {
+                    if name.starts_with(word) {
+                        res.push(Completion::simple(name.to_owned()));
+                    }
+                }
+
+                Some(res)
+            }
+            Some("stats") => {
+                if words.count() == 0 {
+                    Some(
+                        [
+                            "specialization",
+                            "imputation",
+                            "mint",
+                            "purchasing-power",
+                            "savings",
+                            "price-controls",
+                            "legal-tender",
+                            "tax",
+                        ]
+                        .iter()
+                        .filter(|report| report.starts_with(word))
+                        .map(|report| Completion::simple(report.to_string()))
+                        .collect(),
+                    )
+                } else {
+                    None
+                }
+            }
             Some("compare-item-values") => {
                 let wc = words.count();
                 if wc == 0 {
@@ -405,7 +4471,83 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                 } else if wc == 1 || wc == 2 {
                     let mut res = Vec::new();
 
-                    for item in vec!["FoodUnit", "HouseUnit", "LeisureUnit1", "LeisureUnit2"] {
+                    for item in self.1.iter() {
+                        if item.starts_with(word) {
+                            res.push(Completion::simple(item.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            Some("add-public") => {
+                if words.count() == 0 {
+                    let mut res = Vec::new();
+
+                    for item in self.1.iter() {
+                        if item.starts_with(word) {
+                            res.push(Completion::simple(item.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            Some("run-until") => {
+                let options: &[&str] = match words.count() {
+                    0 => &["no-trades", "actor", "tick"],
+                    2 => &["state"],
+                    3 => ACTOR_STATE_NAMES,
+                    _ => &[],
+                };
+                Some(
+                    options
+                        .iter()
+                        .filter(|option| option.starts_with(word))
+                        .map(|&option| Completion::simple(option.to_owned()))
+                        .collect(),
+                )
+            }
+            Some("impose-tax") => {
+                let options: &[&str] = match words.count() {
+                    1 => &["trades", "output", "both"],
+                    2 => &["redistribute", "destroy"],
+                    _ => &[],
+                };
+                Some(
+                    options
+                        .iter()
+                        .filter(|option| option.starts_with(word))
+                        .map(|&option| Completion::simple(option.to_owned()))
+                        .collect(),
+                )
+            }
+            Some("impose-price-ceiling")
+            | Some("impose-price-floor")
+            | Some("impose-legal-tender") => {
+                if words.count() < 2 {
+                    let mut res = Vec::new();
+
+                    for item in self.1.iter() {
+                        if item.starts_with(word) {
+                            res.push(Completion::simple(item.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            Some("add-resource") => {
+                if words.count() == 0 {
+                    let mut res = Vec::new();
+
+                    for item in self.1.iter() {
                         if item.starts_with(word) {
                             res.push(Completion::simple(item.to_owned()));
                         }
@@ -416,6 +4558,23 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                     None
                 }
             }
+            Some("mint") => {
+                let wc = words.count();
+                let names = if wc == 0 { &self.0 } else { &self.1 };
+                if wc == 0 || wc == 2 {
+                    let mut res = Vec::new();
+
+                    for name in names.iter() {
+                        if name.starts_with(word) {
+                            res.push(Completion::simple(name.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
             Some("give-item") => {
                 let wc = words.count();
                 if wc == 0 {
@@ -431,7 +4590,67 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                 } else if wc == 1 {
                     let mut res = Vec::new();
 
-                    for item in vec!["FoodUnit", "HouseUnit", "LeisureUnit1", "LeisureUnit2"] {
+                    for item in self.1.iter() {
+                        if item.starts_with(word) {
+                            res.push(Completion::simple(item.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            Some("trade") => {
+                let wc = words.count();
+                let names = if wc == 0 || wc == 2 { &self.0 } else { &self.1 };
+                if wc < 4 {
+                    let mut res = Vec::new();
+
+                    for name in names.iter() {
+                        if name.starts_with(word) {
+                            res.push(Completion::simple(name.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            Some("lease") => {
+                let wc = words.count();
+                let names = match wc {
+                    0 | 1 => &self.0,
+                    2 | 4 => &self.1,
+                    _ => return None,
+                };
+                let mut res = Vec::new();
+
+                for name in names.iter() {
+                    if name.starts_with(word) {
+                        res.push(Completion::simple(name.to_owned()));
+                    }
+                }
+
+                Some(res)
+            }
+            Some("gift") => {
+                let wc = words.count();
+                if wc == 0 || wc == 1 {
+                    let mut res = Vec::new();
+
+                    for actor_name in self.0.iter() {
+                        if actor_name.starts_with(word) {
+                            res.push(Completion::simple(actor_name.to_owned()));
+                        }
+                    }
+
+                    Some(res)
+                } else if wc == 2 {
+                    let mut res = Vec::new();
+
+                    for item in self.1.iter() {
                         if item.starts_with(word) {
                             res.push(Completion::simple(item.to_owned()));
                         }
@@ -442,6 +4661,31 @@ impl<Term: Terminal> Completer<Term> for InterfaceCompleter {
                     None
                 }
             }
+            Some("export-trades") => {
+                if words.count() == 0 && "dot".starts_with(word) {
+                    Some(vec![Completion::simple("dot".to_owned())])
+                } else {
+                    None
+                }
+            }
+            Some(cmd @ "add-goal") | Some(cmd @ "remove-goal") => {
+                let wc = words.count();
+                let names: Vec<&str> = match wc {
+                    0 => self.0.iter().map(|s| s.as_str()).collect(),
+                    1 => self.2.iter().map(|s| s.as_str()).collect(),
+                    2 if cmd == "add-goal" => vec!["once", "regular", "random"],
+                    _ => return None,
+                };
+                let mut res = Vec::new();
+
+                for name in names {
+                    if name.starts_with(word) {
+                        res.push(Completion::simple(name.to_owned()));
+                    }
+                }
+
+                Some(res)
+            }
             _ => None,
         }
     }
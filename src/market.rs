@@ -0,0 +1,271 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::events::SimEvent;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::preference_list::{ActorRef, ActorState};
+use crate::trade::{exchange, with_cost, SavedProtocol, TradeProtocol};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// A standing order to buy one unit of an order book's base item, paying for
+/// it in the book's quote item.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Order {
+    /// Who's buying
+    pub actor: ActorId,
+    /// The goal they want it for
+    pub goal: GoalId,
+    /// How much of the quote item they're bidding for it right now
+    pub price: Quantity,
+    /// The most of the quote item they'd ever give for it
+    pub limit: Quantity,
+}
+
+/// The market for one pair of items: everyone bidding some of the quote item
+/// for a unit of the base item, and the price the last unit went for.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OrderBook {
+    /// Standing bids, oldest first
+    bids: Vec<Order>,
+    /// What the last unit traded here went for, if anything ever has
+    last_price: Option<Quantity>,
+}
+
+impl OrderBook {
+    /// Every standing bid, oldest first.
+    pub fn bids(&self) -> &[Order] {
+        &self.bids
+    }
+
+    /// The highest standing bid (the oldest, of those that are tied), if
+    /// there are any.
+    pub fn best_bid(&self) -> Option<&Order> {
+        self.bids.iter().rev().max_by(|a, b| a.price.cmp(&b.price))
+    }
+
+    /// What the last unit traded here went for, if anything ever has.
+    pub fn last_price(&self) -> Option<Quantity> {
+        self.last_price
+    }
+}
+
+/// The most (in whole units) of `quote` that `actor` would give up for one
+/// unit of `base`, going by how it ranks the two bundles (counting what the
+/// trade costs it, see `TradeCost`) and how much it actually has. Zero if it
+/// wouldn't give up any at all.
+pub fn bid_limit(actor: ActorRef, base: ItemId, quote: ItemId) -> Quantity {
+    let want = Bundle::single(base, Quantity::ONE);
+    let held = actor.inventory.count(quote).whole();
+    let mut limit = Quantity::ZERO;
+    while limit < held {
+        let offer = with_cost(actor, &Bundle::single(quote, limit + Quantity::ONE));
+        if actor.compare_bundles(&offer, &want) == Ordering::Greater
+            || !actor.can_fit(&want, &offer)
+            || offer
+                .iter()
+                .any(|(i, amount)| actor.inventory.count(i) < amount)
+        {
+            break;
+        }
+        limit += Quantity::ONE;
+    }
+    limit
+}
+
+/// The least (in whole units, and no more than `cap`) of `quote` that
+/// `actor` would take for one unit of `base` it holds, going by how it ranks
+/// the two bundles (counting what the trade costs it). `None` if there's no
+/// such amount: it values the base item more than it could get for it, or
+/// doesn't have any (or can't pay for the trade).
+pub fn ask_price(actor: ActorRef, base: ItemId, quote: ItemId, cap: Quantity) -> Option<Quantity> {
+    if actor.inventory.count(base) < Quantity::ONE {
+        return None;
+    }
+    let give = with_cost(actor, &Bundle::single(base, Quantity::ONE));
+    if give
+        .iter()
+        .any(|(i, amount)| actor.inventory.count(i) < amount)
+    {
+        return None;
+    }
+    let mut price = Quantity::ONE;
+    while price <= cap {
+        let take = Bundle::single(quote, price);
+        if actor.compare_bundles(&take, &give) != Ordering::Less && actor.can_fit(&take, &give) {
+            return Some(price);
+        }
+        price += Quantity::ONE;
+    }
+    None
+}
+
+/// A continuous double auction: a market with an order book for every pair
+/// of items anyone's bid on. An actor that wants to trade puts in a bid for
+/// one unit of what it wants, in whatever it values least that it'd give up
+/// for it, starting at a single unit and going up by one every tick it isn't
+/// filled, as far as its valuations will let it (see `bid_limit`). Sellers
+/// don't have to be looking to trade themselves: once everyone's had their
+/// turn, the books are crossed, and any actor holding what's bid on that
+/// isn't busy negotiating something else will sell it, cheapest first, to
+/// whoever bids at least its asking price (see `ask_price`). Each unit goes
+/// for the price of the standing bid it fills, which is recorded as the
+/// book's last price, so prices emerge out of who wants what and how much.
+///
+/// # Notes
+///
+/// Values are ordinal, so there's usually not much between bids and asks:
+/// amounts only matter between goods valued the same (see
+/// `ValueScale::compare_bundles`). Like `PostedPrice`'s board, the books
+/// belong to the market, and are saved along with the protocol.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DoubleAuction {
+    /// Order books by base and quote item
+    #[serde(with = "crate::snapshot::pairs")]
+    books: BTreeMap<(ItemId, ItemId), OrderBook>,
+}
+
+impl DoubleAuction {
+    /// The order book for buying `base` with `quote`, if anyone's ever bid
+    /// there.
+    pub fn book(&self, base: ItemId, quote: ItemId) -> Option<&OrderBook> {
+        self.books.get(&(base, quote))
+    }
+
+    /// Every order book, by base and then quote item.
+    pub fn books(&self) -> impl Iterator<Item = (ItemId, ItemId, &OrderBook)> {
+        self.books
+            .iter()
+            .map(|(&(base, quote), book)| (base, quote, book))
+    }
+
+    /// The book `actor` has a bid standing in, and where in it, if any.
+    fn find_bid(&self, actor: ActorId) -> Option<((ItemId, ItemId), usize)> {
+        self.books.iter().find_map(|(&key, book)| {
+            book.bids
+                .iter()
+                .position(|bid| bid.actor == actor)
+                .map(|n| (key, n))
+        })
+    }
+}
+
+impl TradeProtocol for DoubleAuction {
+    fn name(&self) -> &'static str {
+        "auction"
+    }
+
+    fn save(&self) -> Option<SavedProtocol> {
+        Some(SavedProtocol::DoubleAuction(self.clone()))
+    }
+
+    fn step(&mut self, id: ActorId, goal: GoalId, actors: &mut ActorArena) -> Vec<SimEvent> {
+        let mut events = vec![];
+        match *actors.get(id).unwrap().state {
+            ActorState::WillingToTrade(_) => {}
+            _ => return events,
+        }
+        let me = actors.get(id).unwrap();
+        let wanted = me.wanted_for(goal);
+        let existing = self.find_bid(id);
+        let base = match wanted.first() {
+            Some(&base) => base,
+            None => {
+                if let Some((key, n)) = existing {
+                    self.books.get_mut(&key).unwrap().bids.remove(n);
+                }
+                *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+                return events;
+            }
+        };
+        // Stick with what we were paying in if we still can, or else bid in
+        // whatever we value least that we'd give up for it
+        let same_book = existing
+            .filter(|&((b, quote), _)| b == base && bid_limit(me, base, quote) > Quantity::ZERO);
+        let bid = match same_book {
+            Some((key, n)) => {
+                let limit = bid_limit(me, base, key.1);
+                let last = &self.books[&key].bids[n];
+                Some((key.1, (last.price + Quantity::ONE).min(limit), limit))
+            }
+            None => me
+                .ordered_inventory()
+                .into_iter()
+                .filter(|&i| !me.needs_for(goal, i) && !wanted.contains(&i))
+                .map(|quote| (quote, Quantity::ONE, bid_limit(me, base, quote)))
+                .find(|&(_, _, limit)| limit > Quantity::ZERO),
+        };
+        if same_book.is_none() {
+            if let Some((key, n)) = existing {
+                self.books.get_mut(&key).unwrap().bids.remove(n);
+            }
+        }
+        if let Some((quote, price, limit)) = bid {
+            events.push(SimEvent::OrderPlaced {
+                actor: id,
+                item: base,
+                pay_with: quote,
+                price,
+            });
+            let order = Order {
+                actor: id,
+                goal,
+                price,
+                limit,
+            };
+            let book = self.books.entry((base, quote)).or_default();
+            match same_book {
+                Some((_, n)) => book.bids[n] = order,
+                None => book.bids.push(order),
+            }
+        }
+        *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+        events
+    }
+
+    fn settle(&mut self, actors: &mut ActorArena) -> Vec<SimEvent> {
+        let mut events = vec![];
+        for (&(base, quote), book) in self.books.iter_mut() {
+            // Drop bids nobody can (or wants to) make good on anymore
+            book.bids.retain(|bid| {
+                let bidder = actors.get(bid.actor).unwrap();
+                bidder.wanted_for(bid.goal).contains(&base)
+                    && bid.price <= bid_limit(bidder, base, quote)
+            });
+            while let Some(bid) = book.best_bid().cloned() {
+                // Whoever'd sell for the least, out of everyone not busy
+                // with some other negotiation
+                let seller = actors
+                    .iter()
+                    .filter(|seller| seller.id != bid.actor)
+                    .filter(|seller| {
+                        matches!(
+                            seller.state,
+                            ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+                        )
+                    })
+                    .filter_map(|seller| {
+                        ask_price(seller, base, quote, bid.price).map(|ask| (ask, seller.id))
+                    })
+                    .min();
+                let seller = match seller {
+                    Some((_, seller)) => seller,
+                    // Nobody will sell at the best price, so nobody will at
+                    // any of the others either
+                    None => break,
+                };
+                book.bids.retain(|other| other.actor != bid.actor);
+                let (mut buyer, mut seller) = actors.pair_mut(bid.actor, seller).unwrap();
+                let pay = Bundle::single(quote, bid.price);
+                let unit = Bundle::single(base, Quantity::ONE);
+                if exchange(&mut buyer, &pay, &mut seller, &unit, &mut events) {
+                    book.last_price = Some(bid.price);
+                    *buyer.state = ActorState::SearchingForGoal;
+                }
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,98 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::goals::GoalId;
+use crate::preference_list::ActorRef;
+use crate::simulation::SimRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// How the world pairs up the actors looking to trade each tick, when it
+/// does it for everyone at once (see `Simulation::set_matching`) instead of
+/// leaving each of them to go looking on its own. Going looking one at a
+/// time means whoever's first in line gets the pick of the partners, and
+/// everyone else has to take what's left, a tick at a time; matching
+/// everyone in one go is fairer, and quicker about it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Matching {
+    /// Everyone gets matched in a random order, shuffled fresh every tick
+    Random,
+    /// Whoever's trading for the goal it values most gets matched first
+    /// (ties broken by ID)
+    Priority,
+}
+
+/// Names of all of the ways to match, for help text and completion.
+pub const MATCHING_NAMES: &[&str] = &["random", "priority"];
+
+impl FromStr for Matching {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Matching::Random),
+            "priority" => Ok(Matching::Priority),
+            _ => Err(format!("unknown matching: {}", s)),
+        }
+    }
+}
+
+/// Whether `a` and `b` each have something the other wants for the goal
+/// it's trading for (a double coincidence of wants), so a trade between
+/// them could go either way.
+fn complementary(a: ActorRef, a_goal: GoalId, b: ActorRef, b_goal: GoalId) -> bool {
+    !b.has_item_of(&a.wanted_for(a_goal)).is_empty()
+        && !a.has_item_of(&b.wanted_for(b_goal)).is_empty()
+}
+
+/// Pair up `traders` (everyone looking to trade this tick, and the goal
+/// each is trading for) in one pass: in the order `matching` says, each
+/// actor that isn't matched yet gets the first one after it in that order
+/// that isn't either and wants something of its own in return. Returns each
+/// pair with the actor that gets to bid first.
+///
+/// # Arguments
+///
+/// * `traders` - every actor looking to trade, with the goal it's after
+/// * `actors` - every actor in the world
+/// * `matching` - what order to match them in
+/// * `rng` - for shuffling them, if they're matched at random
+///
+/// # Notes
+///
+/// Matching is done by the world, not the actors, so it goes by what
+/// everyone actually holds, even for actors that only know what they've
+/// seen (see `TradeMemory::imperfect`). Anyone left unmatched goes looking
+/// for a partner on its own, the same as without matching.
+pub fn match_traders(
+    traders: &[(ActorId, GoalId)],
+    actors: &ActorArena,
+    matching: Matching,
+    rng: &mut SimRng,
+) -> Vec<(ActorId, ActorId)> {
+    let mut order = traders.to_vec();
+    match matching {
+        Matching::Random => order.shuffle(rng),
+        Matching::Priority => {
+            order.sort_by_key(|&(id, goal)| (actors.get(id).unwrap().values.rank(goal), id))
+        }
+    }
+    let mut matched = vec![false; order.len()];
+    let mut pairs = vec![];
+    for n in 0..order.len() {
+        if matched[n] {
+            continue;
+        }
+        let (a, a_goal) = order[n];
+        let a_ref = actors.get(a).unwrap();
+        let partner = (n + 1..order.len()).find(|&m| {
+            let (b, b_goal) = order[m];
+            !matched[m] && complementary(a_ref, a_goal, actors.get(b).unwrap(), b_goal)
+        });
+        if let Some(m) = partner {
+            matched[n] = true;
+            matched[m] = true;
+            pairs.push((a, order[m].0));
+        }
+    }
+    pairs
+}
@@ -0,0 +1,129 @@
+use crate::arena::ActorId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// How one partner has treated an actor's bids, as the actor remembers it.
+/// Both counts fade over time (see `TradeMemory::fade`), so they're amounts
+/// rather than whole numbers.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PartnerRecord {
+    /// How many trades with them went through
+    pub accepted: Quantity,
+    /// How many bids they turned down
+    pub rejected: Quantity,
+}
+
+/// What an actor saw another one holding, the last time it looked.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Sighting {
+    /// The tick it looked
+    pub tick: u64,
+    /// Every kind of item the other actor had then
+    pub items: Vec<ItemId>,
+}
+
+/// Who an actor has traded with before and how it went, so it can go back to
+/// partners that have dealt with it and put off ones that haven't (see
+/// `trade::find_next_partner`). The simulation keeps it up to date from the
+/// events each tick produces, but only if actors are set to remember at all
+/// (see `Simulation::set_memory_decay`).
+///
+/// It's also where an actor with imperfect information keeps what it's seen
+/// everyone else holding: such an actor doesn't just know what's in other
+/// actors' inventories, but only what it saw there when it last dealt with
+/// them or went looking (see `trade::search`).
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TradeMemory {
+    /// Everyone the actor remembers dealing with
+    partners: HashMap<ActorId, PartnerRecord>,
+    /// Whether the actor only knows what others hold from having seen it
+    #[serde(default)]
+    imperfect: bool,
+    /// What the actor last saw everyone it's looked at holding
+    #[serde(default)]
+    sightings: HashMap<ActorId, Sighting>,
+}
+
+impl TradeMemory {
+    /// An empty memory.
+    pub fn new() -> Self {
+        TradeMemory::default()
+    }
+
+    /// An empty memory for an actor with imperfect information, which only
+    /// knows what it's seen.
+    pub fn imperfect() -> Self {
+        TradeMemory {
+            imperfect: true,
+            ..TradeMemory::default()
+        }
+    }
+
+    /// Whether the actor only knows what others hold from having seen it.
+    pub fn is_imperfect(&self) -> bool {
+        self.imperfect
+    }
+
+    /// Notes what `other` was holding on tick `tick`.
+    pub fn see(&mut self, other: ActorId, items: Vec<ItemId>, tick: u64) {
+        self.sightings.insert(other, Sighting { tick, items });
+    }
+
+    /// What the actor last saw `other` holding, if it's ever looked.
+    pub fn seen(&self, other: ActorId) -> Option<&Sighting> {
+        self.sightings.get(&other)
+    }
+
+    /// Everyone the actor's seen, and what they had, in ID order.
+    pub fn sightings(&self) -> Vec<(ActorId, &Sighting)> {
+        let mut sightings: Vec<_> = self.sightings.iter().map(|(&id, s)| (id, s)).collect();
+        sightings.sort_by_key(|&(id, _)| id);
+        sightings
+    }
+
+    /// Notes that a trade with `partner` went through.
+    pub fn note_accepted(&mut self, partner: ActorId) {
+        self.partners.entry(partner).or_default().accepted += Quantity::ONE;
+    }
+
+    /// Notes that `partner` turned down a bid.
+    pub fn note_rejected(&mut self, partner: ActorId) {
+        self.partners.entry(partner).or_default().rejected += Quantity::ONE;
+    }
+
+    /// Fades everything by `decay` (a fraction), forgetting anyone there's
+    /// nothing left to remember about.
+    pub fn fade(&mut self, decay: Quantity) {
+        let keep = Quantity::ONE.saturating_sub(decay);
+        self.partners.retain(|_, record| {
+            record.accepted = record.accepted.times(keep);
+            record.rejected = record.rejected.times(keep);
+            !record.accepted.is_zero() || !record.rejected.is_zero()
+        });
+    }
+
+    /// What the actor remembers about `partner`, if anything.
+    pub fn get(&self, partner: ActorId) -> Option<PartnerRecord> {
+        self.partners.get(&partner).copied()
+    }
+
+    /// Whether the actor thinks better of `a` than `b`, going by how many
+    /// more trades with each went through than bids were turned down.
+    /// Anyone it doesn't remember stands at nothing either way.
+    pub fn compare(&self, a: ActorId, b: ActorId) -> Ordering {
+        let a = self.get(a).unwrap_or_default();
+        let b = self.get(b).unwrap_or_default();
+        (a.accepted + b.rejected).cmp(&(b.accepted + a.rejected))
+    }
+
+    /// Everyone the actor remembers, best thought of first (ties broken by
+    /// ID).
+    pub fn partners(&self) -> Vec<(ActorId, PartnerRecord)> {
+        let mut partners: Vec<_> = self.partners.iter().map(|(&id, &r)| (id, r)).collect();
+        partners.sort_by(|a, b| self.compare(b.0, a.0).then(a.0.cmp(&b.0)));
+        partners
+    }
+}
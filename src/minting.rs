@@ -0,0 +1,299 @@
+use crate::arena::ActorId;
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+
+/// New money put into the world at one actor (see `Simulation::mint`).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Injection {
+    /// The actor it was handed to
+    pub actor: ActorId,
+    /// The money it was
+    pub item: ItemId,
+    /// How much of it there was
+    pub amount: Quantity,
+    /// The tick it was minted on
+    pub tick: u64,
+}
+
+/// Where some newly minted money has got to: one actor that's had some of
+/// it, when it first did, and what it's done with it since.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Holding {
+    /// The injection the money came from (its place in
+    /// `MoneyTrace::injections`)
+    pub injection: usize,
+    /// The actor that's had it
+    pub actor: ActorId,
+    /// The tick it first got any of it
+    pub reached: u64,
+    /// How many hands it went through to get here, the first time it did (the
+    /// actor it was minted at is 0, whoever that one spends it with 1, and
+    /// so on)
+    pub hands: u32,
+    /// How much of it the actor has got, all told
+    pub received: Quantity,
+    /// How much of it the actor still has
+    pub held: Quantity,
+    /// Everything the actor got back for the new money it passed on (gifts,
+    /// loans, and the like get it nothing back)
+    pub spent_on: Bundle,
+}
+
+/// Where newly minted money goes, Cantillon-style. New money doesn't reach
+/// everyone at once: whoever it's minted at gets to spend it first, at
+/// prices that haven't yet risen to account for it, and the actors they spend
+/// it with after them, and so on out, with the last to get it having paid the
+/// higher prices all along. This keeps track of every actor some of it has
+/// reached, in the order it did, and the simulation keeps it up to date from
+/// the events each tick produces (see `record`), like the price history.
+///
+/// Money's all alike, so there's no telling which units an actor spends are
+/// the new ones: actors are taken to spend new money before anything they
+/// had already (the oldest injection first), which is the whole point of
+/// being a first receiver. Money changes hands in trades, gifts, wages,
+/// loans and deposits and their repayment, and rent and leases; a forward
+/// contract settling moves money too, but its event doesn't say how much,
+/// so it isn't traced.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct MoneyTrace {
+    /// Every injection so far, oldest first
+    injections: Vec<Injection>,
+    /// Every actor the new money's reached, in the order it did
+    holdings: Vec<Holding>,
+}
+
+impl MoneyTrace {
+    /// An empty trace.
+    pub fn new() -> Self {
+        MoneyTrace::default()
+    }
+
+    /// Starts tracing `amount` of `item` that's just been minted at `actor`,
+    /// on tick `tick`.
+    pub fn mint(&mut self, actor: ActorId, item: ItemId, amount: Quantity, tick: u64) {
+        self.injections.push(Injection {
+            actor,
+            item,
+            amount,
+            tick,
+        });
+        self.holdings.push(Holding {
+            injection: self.injections.len() - 1,
+            actor,
+            reached: tick,
+            hands: 0,
+            received: amount,
+            held: amount,
+            spent_on: Bundle::new(),
+        });
+    }
+
+    /// Whether any money's been minted to trace at all.
+    pub fn is_empty(&self) -> bool {
+        self.injections.is_empty()
+    }
+
+    /// Follows any new money that changed hands in something that happened
+    /// on tick `tick`, returning a `NewMoneySpent` for each bit of it that
+    /// did.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - what happened
+    /// * `tick` - when it happened
+    ///
+    pub fn record(&mut self, event: &SimEvent, tick: u64) -> Vec<SimEvent> {
+        let none = Bundle::new();
+        let transfers: Vec<(ActorId, ActorId, &Bundle, &Bundle)> = match *event {
+            SimEvent::TradeCompleted {
+                actor,
+                partner,
+                ref gave,
+                ref received,
+            } => vec![
+                (actor, partner, gave, received),
+                (partner, actor, received, gave),
+            ],
+            SimEvent::TradeCycleCompleted {
+                ref actors,
+                ref gave,
+            } => (0..actors.len())
+                .map(|n| {
+                    let next = (n + 1) % actors.len();
+                    let last = (n + actors.len() - 1) % actors.len();
+                    (actors[n], actors[next], &gave[n], &gave[last])
+                })
+                .collect(),
+            SimEvent::GiftGiven {
+                actor,
+                to,
+                ref gift,
+            } => vec![(actor, to, gift, &none)],
+            SimEvent::LaborHired {
+                employer,
+                worker,
+                ref wage,
+                ..
+            } => vec![(employer, worker, wage, &none)],
+            SimEvent::IouIssued {
+                debtor,
+                creditor,
+                ref lent,
+                ..
+            } => vec![(creditor, debtor, lent, &none)],
+            SimEvent::DepositMade {
+                bank,
+                depositor,
+                ref amount,
+                ..
+            } => vec![(depositor, bank, amount, &none)],
+            SimEvent::IouRepaid {
+                debtor,
+                creditor,
+                ref owed,
+            } => vec![(debtor, creditor, owed, &none)],
+            SimEvent::IouDefaulted {
+                debtor,
+                creditor,
+                ref paid,
+                ..
+            } => vec![(debtor, creditor, paid, &none)],
+            SimEvent::RentPaid {
+                tenant,
+                landlord,
+                ref rent,
+            } => vec![(tenant, landlord, rent, &none)],
+            SimEvent::LeaseAgreed {
+                landlord,
+                tenant,
+                ref leased,
+                ..
+            } => vec![(landlord, tenant, leased, &none)],
+            SimEvent::LeaseEnded {
+                landlord,
+                tenant,
+                ref returned,
+                ..
+            } => vec![(tenant, landlord, returned, &none)],
+            _ => return vec![],
+        };
+        let mut events = vec![];
+        for (from, to, given, got) in transfers {
+            for (item, amount) in given.iter() {
+                events.extend(self.pass(from, to, item, amount, got, tick));
+            }
+        }
+        events
+    }
+
+    /// Moves whatever of `amount` of `item` that `from` gave `to` was new
+    /// money (all of it, as far as `from` has any) from one to the other,
+    /// oldest injection first.
+    fn pass(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        item: ItemId,
+        amount: Quantity,
+        got: &Bundle,
+        tick: u64,
+    ) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let mut left = amount;
+        for injection in 0..self.injections.len() {
+            if left.is_zero() {
+                break;
+            }
+            if self.injections[injection].item != item {
+                continue;
+            }
+            let n = match self.holding(injection, from) {
+                Some(n) if !self.holdings[n].held.is_zero() => n,
+                _ => continue,
+            };
+            let passed = left.min(self.holdings[n].held);
+            left -= passed;
+            let hands = self.holdings[n].hands + 1;
+            let spender = &mut self.holdings[n];
+            spender.held -= passed;
+            for (bought, count) in got.iter() {
+                spender.spent_on.add(bought, count);
+            }
+            match self.holding(injection, to) {
+                Some(m) => {
+                    let receiver = &mut self.holdings[m];
+                    receiver.received += passed;
+                    receiver.held += passed;
+                }
+                None => self.holdings.push(Holding {
+                    injection,
+                    actor: to,
+                    reached: tick,
+                    hands,
+                    received: passed,
+                    held: passed,
+                    spent_on: Bundle::new(),
+                }),
+            }
+            events.push(SimEvent::NewMoneySpent {
+                actor: from,
+                to,
+                item,
+                amount: passed,
+                minted: self.injections[injection].tick,
+                hands,
+                received: got.clone(),
+            });
+        }
+        events
+    }
+
+    /// Makes sure nobody's taken to hold more new `item` than the `held` of
+    /// it they actually have, if some of it went some way that isn't traced.
+    pub fn settle(&mut self, actor: ActorId, item: ItemId, held: Quantity) {
+        let mut left = held;
+        for holding in self.holdings.iter_mut().filter(|h| h.actor == actor) {
+            if self.injections[holding.injection].item == item {
+                holding.held = holding.held.min(left);
+                left -= holding.held;
+            }
+        }
+    }
+
+    /// The holding of `actor` in injection `injection`, if the money's
+    /// reached it.
+    fn holding(&self, injection: usize, actor: ActorId) -> Option<usize> {
+        self.holdings
+            .iter()
+            .position(|h| h.injection == injection && h.actor == actor)
+    }
+
+    /// Every injection so far, oldest first.
+    pub fn injections(&self) -> &[Injection] {
+        &self.injections
+    }
+
+    /// Every actor the money from injection `injection` has reached, in the
+    /// order it did.
+    pub fn holdings(&self, injection: usize) -> impl Iterator<Item = &Holding> {
+        self.holdings
+            .iter()
+            .filter(move |h| h.injection == injection)
+    }
+
+    /// Every actor holding any new money, and which money it is.
+    pub fn holders(&self) -> Vec<(ActorId, ItemId)> {
+        let mut holders: Vec<_> = self
+            .holdings
+            .iter()
+            .filter(|h| !h.held.is_zero())
+            .map(|h| (h.actor, self.injections[h.injection].item))
+            .collect();
+        holders.sort();
+        holders.dedup();
+        holders
+    }
+}
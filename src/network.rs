@@ -0,0 +1,137 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::registry::{ItemId, ItemRegistry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Everything that's passed between two actors over a run, whichever of them
+/// started it.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct TradeLink {
+    /// The actor with the lower ID
+    pub a: ActorId,
+    /// The actor with the higher ID
+    pub b: ActorId,
+    /// How many times they've traded
+    pub trades: u32,
+    /// Every kind of item that's gone either way between them
+    pub items: BTreeSet<ItemId>,
+}
+
+/// Who's traded with whom over a run, and in what, so you can see the
+/// exchange network that emerges. The simulation keeps it up to date from the
+/// events each tick produces (see `record`), like the price history.
+///
+/// Links don't have a direction: a trade is two things changing hands, so
+/// each trade counts for both actors in it. A trade around a circle counts
+/// once for each pair of actors next to each other in it, since those are
+/// the ones who actually handed each other something.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TradeNetwork {
+    /// Every pair of actors that's ever traded, in the order they first did
+    links: Vec<TradeLink>,
+}
+
+impl TradeNetwork {
+    /// An empty network.
+    pub fn new() -> Self {
+        TradeNetwork::default()
+    }
+
+    /// Updates the network with something that happened. Only completed
+    /// trades count; bids and offers that came to nothing don't.
+    pub fn record(&mut self, event: &SimEvent) {
+        match *event {
+            SimEvent::TradeCompleted {
+                actor,
+                partner,
+                ref gave,
+                ref received,
+            } => self.link(actor, partner, &[gave, received]),
+            SimEvent::TradeCycleCompleted {
+                ref actors,
+                ref gave,
+            } => {
+                for (n, (&actor, given)) in actors.iter().zip(gave.iter()).enumerate() {
+                    let next = actors[(n + 1) % actors.len()];
+                    self.link(actor, next, &[given]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts a trade between `x` and `y` of everything in `bundles`.
+    fn link(&mut self, x: ActorId, y: ActorId, bundles: &[&Bundle]) {
+        let (a, b) = if x <= y { (x, y) } else { (y, x) };
+        let n = match self.links.iter().position(|l| l.a == a && l.b == b) {
+            Some(n) => n,
+            None => {
+                self.links.push(TradeLink {
+                    a,
+                    b,
+                    trades: 0,
+                    items: BTreeSet::new(),
+                });
+                self.links.len() - 1
+            }
+        };
+        let link = &mut self.links[n];
+        link.trades += 1;
+        link.items.extend(
+            bundles
+                .iter()
+                .flat_map(|bundle| bundle.iter().map(|(i, _)| i)),
+        );
+    }
+
+    /// Every pair of actors that's ever traded, in the order they first did.
+    pub fn links(&self) -> &[TradeLink] {
+        &self.links
+    }
+
+    /// Whatever's passed between `x` and `y`, if they've ever traded.
+    pub fn between(&self, x: ActorId, y: ActorId) -> Option<&TradeLink> {
+        let (a, b) = if x <= y { (x, y) } else { (y, x) };
+        self.links.iter().find(|l| l.a == a && l.b == b)
+    }
+
+    /// The network as a Graphviz DOT graph: a node for every actor (traded
+    /// or not), and an edge between every pair that's traded, weighted by how
+    /// many times they have and labeled with what in.
+    ///
+    /// # Arguments
+    ///
+    /// * `actors` - the actors, for their names
+    /// * `items` - the item registry, for item names
+    ///
+    pub fn to_dot(&self, actors: &ActorArena, items: &ItemRegistry) -> String {
+        let names = actors.names();
+        let mut dot = String::from("graph trades {\n");
+        for name in names {
+            writeln!(dot, "    {};", quote(name)).unwrap();
+        }
+        for link in self.links.iter() {
+            let traded: Vec<_> = link.items.iter().map(|&i| items.name(i)).collect();
+            writeln!(
+                dot,
+                "    {} -- {} [weight={}, penwidth={}, label={}];",
+                quote(&names[link.a.0]),
+                quote(&names[link.b.0]),
+                link.trades,
+                link.trades,
+                quote(&format!("{} ({})", traded.join(", "), link.trades))
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// `s` as a DOT string literal.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
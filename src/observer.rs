@@ -0,0 +1,51 @@
+use crate::arena::ActorId;
+use crate::events::SimEvent;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::preference_list::ActorState;
+
+/// Hooks for watching a simulation as it runs, for collecting statistics or
+/// driving visualizations without having to touch the actors themselves.
+/// Register one with `Simulation::add_observer`; every callback has a default
+/// no-op implementation, so only implement the ones you care about.
+pub trait SimObserver {
+    /// Called before any actor acts in a tick.
+    fn on_tick_start(&mut self, _tick: u64) {}
+
+    /// Called for every event produced during a tick, in order, after the
+    /// more specific callbacks below.
+    fn on_event(&mut self, _tick: u64, _event: &SimEvent) {}
+
+    /// Called whenever two actors complete a trade.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - the actor that initiated the trade
+    /// * `partner` - the actor it traded with
+    /// * `gave` - everything the initiator gave up
+    /// * `received` - everything the initiator got in return
+    ///
+    fn on_trade(
+        &mut self,
+        _tick: u64,
+        _actor: ActorId,
+        _partner: ActorId,
+        _gave: &Bundle,
+        _received: &Bundle,
+    ) {
+    }
+
+    /// Called whenever an actor puts enough units towards a goal to satisfy it.
+    fn on_goal_satisfied(&mut self, _tick: u64, _actor: ActorId, _goal: GoalId) {}
+
+    /// Called at the end of each tick for every actor whose state is different
+    /// from what it was at the start of the tick.
+    fn on_state_change(
+        &mut self,
+        _tick: u64,
+        _actor: ActorId,
+        _old: &ActorState,
+        _new: &ActorState,
+    ) {
+    }
+}
@@ -0,0 +1,75 @@
+use crate::arena::ActorId;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The order actors get to act in each tick (see `Simulation::set_order`).
+/// Whoever acts first gets first go at everyone else: the first to go looking
+/// for a partner gets the pick of them, and the first to take something from
+/// the commons gets it before it runs out. Always going in the same order
+/// gives the same actors that head start every tick, so the others can be
+/// shuffled, or taken in turn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum TickOrder {
+    /// By ID, every tick
+    #[default]
+    Fixed,
+    /// In a random order, shuffled fresh every tick
+    Shuffled,
+    /// By ID, but starting one further along every tick, so everyone gets
+    /// a turn at going first
+    RoundRobin,
+}
+
+/// Names of all of the orders, for help text and completion.
+pub const TICK_ORDER_NAMES: &[&str] = &["fixed", "shuffled", "round-robin"];
+
+impl FromStr for TickOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(TickOrder::Fixed),
+            "shuffled" => Ok(TickOrder::Shuffled),
+            "round-robin" => Ok(TickOrder::RoundRobin),
+            _ => Err(format!("unknown tick order: {}", s)),
+        }
+    }
+}
+
+impl TickOrder {
+    /// Put everyone acting on tick `tick`, in ID order, in the order they
+    /// get to act in.
+    ///
+    /// # Arguments
+    ///
+    /// * `acting` - everyone acting, by ID, with whatever it's doing
+    /// * `tick` - the tick it is
+    /// * `actors` - how many actors there are in the world, acting or not
+    /// * `rng` - the world's random number generator (only used to shuffle)
+    ///
+    pub fn arrange<T, R: Rng + ?Sized>(
+        self,
+        acting: &mut [(ActorId, T)],
+        tick: u64,
+        actors: usize,
+        rng: &mut R,
+    ) {
+        match self {
+            TickOrder::Fixed => {}
+            TickOrder::Shuffled => acting.shuffle(rng),
+            TickOrder::RoundRobin if actors > 0 => {
+                // Whoever's turn it is, or the next one along acting after
+                // them, goes first, and the ones before them go last
+                let first = (tick % actors as u64) as usize;
+                let split = acting
+                    .iter()
+                    .position(|(id, _)| id.0 >= first)
+                    .unwrap_or(acting.len());
+                acting.rotate_left(split);
+            }
+            TickOrder::RoundRobin => {}
+        }
+    }
+}
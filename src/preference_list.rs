@@ -1,11 +1,29 @@
-use crate::items::discretes::Goal;
-use crate::items::discretes::Item;
-use colored::*;
-use std::cell::RefCell;
-use std::cmp::{Ord, Ordering};
+use crate::arena::{ActorArena, ActorId};
+use crate::behavior::{Action, Behavior, DefaultBehavior};
+use crate::builder::ActorBuilder;
+use crate::credit::CreditBook;
+use crate::events::SimEvent;
+use crate::expectations::Expectations;
+use crate::goals::{GoalId, GoalRegistry};
+use crate::items::bundle::Bundle;
+use crate::items::inventory::{Acquisition, Inventory};
+use crate::items::production::{ProductionPlan, Recipe, RecipeId, MAX_STAGES};
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use crate::labor::{Labor, Work};
+use crate::ledger::{Accounts, SatisfactionLedger, TradeLedger};
+use crate::memory::TradeMemory;
+use crate::resources::{ResourceId, ResourceNode};
+use crate::technology::KnowHow;
+use crate::trade::{LegalTender, PriceControl, TradeCost};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ord, Ordering, Reverse};
+use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 
 /// Contains all of the metadata required to satisfy a goal properly. This data
 /// is stored only in the preference list of the actor and the recurrance list
@@ -14,31 +32,51 @@ use std::rc::Rc;
 /// place where the metadata about recurrance time intervals matter. I could
 /// have designed separate data structures for those two peices of information,
 /// but that would've been unweildy in my opinion.
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum GoalData {
-    /// A goal that either occurs at random times or only once.
+    /// A goal that only occurs once.
     Satisfaction {
         /// The goal to be satisfied
-        goal: Goal,
-        /// Amount of acceptable units needed to satisfy this goal
-        units_required: i32,
-        /// Current units diverted to this goal
-        units: i32,
+        goal: GoalId,
+        /// Amount of acceptable items needed to satisfy this goal (can be
+        /// fractional, for goals satisfied with divisible goods)
+        units_required: Quantity,
+        /// Current amount diverted to this goal
+        units: Quantity,
         /// Unique id
         id: i32,
     },
     /// A regularly recurring goal.
     RegularSatisfaction {
         /// The goal to be satisfied
-        goal: Goal,
+        goal: GoalId,
         /// Time required for this goal to reoccur
         time_required: i32,
-        /// Time since this goal was dismissed
+        /// Ticks already on the clock towards the goal coming due when it was
+        /// added (the simulation schedules it `time_required - time` ticks
+        /// later, and every `time_required` ticks after that)
         time: i32,
-        /// Amount of acceptable units needed to satisfy this goal
-        units_required: i32,
-        /// Current units diverted to this goal
-        units: i32,
+        /// Amount of acceptable items needed to satisfy this goal (can be
+        /// fractional, for goals satisfied with divisible goods)
+        units_required: Quantity,
+        /// Current amount diverted to this goal
+        units: Quantity,
+        /// Unique id
+        id: i32,
+    },
+    /// A goal that comes up at random (something breaks and needs
+    /// repairing), and again at random after it's satisfied. It isn't in play
+    /// until it first comes up.
+    Stochastic {
+        /// The goal to be satisfied
+        goal: GoalId,
+        /// Chance of the goal coming up on any given tick, from 0 to 1
+        probability: Quantity,
+        /// Amount of acceptable items needed to satisfy this goal (can be
+        /// fractional, for goals satisfied with divisible goods)
+        units_required: Quantity,
+        /// Current amount diverted to this goal
+        units: Quantity,
         /// Unique id
         id: i32,
     },
@@ -46,32 +84,34 @@ pub enum GoalData {
 
 impl GoalData {
     /// Get the goal this metadata might satisfy
-    pub fn get_goal(&self) -> Goal {
+    pub fn get_goal(&self) -> GoalId {
         match self {
-            &GoalData::Satisfaction { goal, .. } | &GoalData::RegularSatisfaction { goal, .. } => {
-                goal
-            }
+            &GoalData::Satisfaction { goal, .. }
+            | &GoalData::RegularSatisfaction { goal, .. }
+            | &GoalData::Stochastic { goal, .. } => goal,
         }
     }
 
     /// Check if this goal should be in the recurrance list
     pub fn is_recurring(&self) -> bool {
-        match self {
-            &GoalData::Satisfaction { .. } => false,
-            _ => true,
-        }
+        !matches!(self, GoalData::Satisfaction { .. })
     }
 }
 
 /// This is necessary to take advantage of the automatic sorting abilities of
 /// the BinaryHeap that we use in the preference list. This only exists because
 /// of that, there's nothing special about this otherwise.
+///
+/// The goal's position in the hierarchy is copied in here when the wrapper is
+/// made, so ordering is plain data (and the whole preference list can be
+/// serialized). That does mean that if the hierarchy changes, the heaps holding
+/// these have to be rebuilt.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct GoalWrapper {
-    /// Closure that encloses a reference-counted pointer to the goal hierarchy
-    /// of the containing actor so it can do comparasons.
-    comparator: Box<dyn Fn(&Goal, &Goal) -> Ordering>,
+    /// Position of the goal in the actor's goal hierarchy (0 is most valued)
+    pub rank: usize,
     /// The actual interesting data that we want the BinaryHeap to sort
-    pub goal: Goal,
+    pub goal: GoalId,
 }
 
 impl PartialOrd for GoalWrapper {
@@ -80,424 +120,517 @@ impl PartialOrd for GoalWrapper {
     }
 }
 
-impl PartialEq for GoalWrapper {
-    fn eq(&self, other: &Self) -> bool {
-        self.goal == other.goal
+impl Ord for GoalWrapper {
+    /// Lower ranks are valued more, so they compare greater and end up at the
+    /// top of the max-heap.
+    ///
+    /// Goals in the same indifference class (see `ValueScale::make_indifferent`)
+    /// share a rank, but the actor still has to pick one of them to pursue
+    /// first, so ties go to whichever goal was registered first (the lower
+    /// `GoalId`, which is the one listed first in the goals file). This is
+    /// purely a tie-breaker: items are still valued the same for either.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .rank
+            .cmp(&self.rank)
+            .then_with(|| other.goal.cmp(&self.goal))
     }
 }
 
-impl Eq for GoalWrapper {}
+/// Why a goal hierarchy isn't strictly ordinal. Every goal has to be in the
+/// hierarchy once, and the ranks have to run from 0 with no gaps. Goals only
+/// share a rank when they've been made indifferent on purpose (see
+/// `ValueScale::make_indifferent`).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum HierarchyError {
+    /// The same goal was put in the hierarchy more than once
+    DuplicateGoal(GoalId),
+    /// No goal has this rank, though some goal is ranked below it
+    MissingRank(usize),
+}
 
-impl Ord for GoalWrapper {
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.comparator)(&self.goal, &other.goal)
+impl fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HierarchyError::DuplicateGoal(goal) => {
+                write!(f, "{} appears in the hierarchy more than once", goal)
+            }
+            HierarchyError::MissingRank(rank) => {
+                write!(f, "no goal is ranked {}, but goals below it are", rank)
+            }
+        }
     }
 }
 
+impl Error for HierarchyError {}
+
 /// A map of the item that must be valued or used to the max-heap containing the
 /// goals that can be satisfied with the item. Since the most highly-valued goal
 /// is the one that will always be referenced for both use and valuing, those
 /// operations need only ever deal with the root of the heap, making this very
 /// performant.
-pub type PreferenceList = HashMap<Item, BinaryHeap<Rc<GoalWrapper>>>;
+pub type PreferenceList = HashMap<ItemId, BinaryHeap<GoalWrapper>>;
 
-/// Individual acting, valuing, satisfying Austrian microeconomic actor
-pub struct Actor {
-    /// Name for printouts
-    pub name: String,
+/// An actor's whole scale of values: which goals it has, how it ranks them,
+/// which of them are in play right now, and what items can satisfy them. This
+/// is one of the components the arena stores for every actor (see
+/// `ActorArena`), and everything to do with valuing items lives here.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ValueScale {
     /// Registry of data for goals (to avoid needing interior mutability, etc)
-    pub goal_registry: HashMap<Goal, GoalData>,
+    pub goal_registry: HashMap<GoalId, GoalData>,
     /// Absolute list of goals to use for actions
-    pub current_goals: BinaryHeap<Rc<GoalWrapper>>,
+    pub current_goals: BinaryHeap<GoalWrapper>,
     /// Mapping of items to their goals
     pub preference_list: PreferenceList,
     /// Mapping of goals to the items that can satisfy them
-    pub satisfactions: HashMap<Goal, Vec<Item>>,
-    // TODO: Make sure that goal heirarchy is strictly ordinal.
+    pub satisfactions: HashMap<GoalId, Vec<ItemId>>,
+    /// Mapping of goals to the items they need *as well as* one of their
+    /// satisfactions (the fuel to cook the food with), all at the same time
+    #[serde(default)]
+    pub complements: HashMap<GoalId, Vec<ItemId>>,
     /// How much goals are valued. This could easily be stored as a list, and in
     /// fact is constructed from one, but is more performant for our purposes as
-    /// a map from a goal to how much it is valued.
-    pub goal_hierarchy: HashMap<Goal, usize>,
-    /// Items the actor has already (for trade or use)
-    pub inventory: Vec<Item>,
-    /// Actor internal AI state
-    pub state: ActorState,
+    /// a map from a goal to how much it is valued. Ranks run from 0 with no
+    /// gaps (see `validate_hierarchy`); goals that share one are an
+    /// indifference class, valued exactly the same.
+    pub goal_hierarchy: HashMap<GoalId, usize>,
+    /// Mapping of producer's goods to the lower-order goods they help
+    /// produce, which is where their value comes from
+    #[serde(default)]
+    pub imputations: HashMap<ItemId, Vec<ItemId>>,
+    /// How much a unit of each item counts for, for items that aren't
+    /// standard quality (see `ItemRegistry::quality`)
+    #[serde(default)]
+    pub qualities: HashMap<ItemId, Quantity>,
+    /// Mapping of items to the other items that can stand in for them, which
+    /// satisfy whatever they do
+    #[serde(default)]
+    pub substitutes: HashMap<ItemId, Vec<ItemId>>,
+    /// Which items are money, wanted only to trade away again
+    #[serde(default)]
+    pub money: HashSet<ItemId>,
+    /// Which goals time off goes towards
+    #[serde(default)]
+    pub leisure: HashSet<GoalId>,
+    /// Mapping of goals to the goals that have to be seen to before they can
+    /// come into play (eating and finding shelter before thinking about
+    /// leisure)
+    #[serde(default)]
+    pub prerequisites: HashMap<GoalId, Vec<GoalId>>,
+    /// Goals that would be in play, but are waiting on their prerequisites
+    #[serde(default)]
+    pub dormant: BTreeSet<GoalId>,
+    /// How many ticks in a row the actor will go after a goal without getting
+    /// any closer to it before it gives up (never, if not set)
+    #[serde(default)]
+    pub patience: Option<u32>,
+    /// For each goal being gone after, how much it still needed when the
+    /// actor last went after it, and how many ticks in a row that hasn't gone
+    /// down
+    #[serde(default)]
+    pub stalled: HashMap<GoalId, (Quantity, u32)>,
+    /// Goals the actor has given up on (see `shelve_goal`)
+    #[serde(default)]
+    pub shelved: BTreeSet<GoalId>,
+    /// How many times the actor has seen each item accepted in a trade,
+    /// which is how saleable it takes each one to be
+    #[serde(default)]
+    pub saleability: HashMap<ItemId, u32>,
+    /// Goods the actor is only holding to trade on again, and the goal it
+    /// means to get with them (see `ActorRef::would_take_as_medium`)
+    #[serde(default)]
+    pub media: HashMap<ItemId, GoalId>,
+    /// How much more of something the actor wants back, for every tick it
+    /// waits, to give up a unit of it now (nothing, by default: it doesn't
+    /// care when it gets things). This is what lenders charge interest out
+    /// of (see `credit::repayment`).
+    #[serde(default)]
+    pub time_preference: Quantity,
+    /// What it costs the actor to make a trade, on top of whatever it gives
+    /// up in it (nothing, by default)
+    #[serde(default)]
+    pub trade_cost: Option<TradeCost>,
+    /// Whether the actor gives away what it has no use for once it's got no
+    /// goals in play (see `DefaultBehavior`)
+    #[serde(default)]
+    pub charitable: bool,
+    /// Whether the actor works out the worst trade it'd make before bidding
+    /// starts, and breaks off negotiations that can't meet it straight away
+    /// (see `trade::AscendingBid`)
+    #[serde(default)]
+    pub walks_away: bool,
+    /// Whether the actor stocks up on things for recurring goals before they
+    /// come back around, when it has nothing in play and they look cheap
+    /// (see `ActorRef::stockpile_goal`)
+    #[serde(default)]
+    pub stockpiles: bool,
+    /// Whether the actor hires other actors' labor, paying wages in goods,
+    /// when it doesn't have enough of its own for something it's making
+    /// (see `labor::hire`)
+    #[serde(default)]
+    pub hires_labor: bool,
+    /// Whether the actor makes something else to trade for what it wants,
+    /// when it expects that to get it more than making it itself would (see
+    /// `ActorRef::recipe_to_trade_with`)
+    #[serde(default)]
+    pub specializes: bool,
+    /// Whether the actor weighs every hour of work against the leisure it
+    /// could be taking instead, working only for goals it ranks above its
+    /// leisure and taking whatever hours it doesn't work off (see
+    /// `DefaultBehavior` and `Simulation::tick`)
+    #[serde(default)]
+    pub weighs_leisure: bool,
+    /// Whether the actor weighs bundles by how many of its wants they'd go
+    /// towards before how its best goods rank, so that enough goods valued
+    /// less can outweigh one valued more (see `ValueScale::compare_bundles`)
+    #[serde(default)]
+    pub weighs_bundles: bool,
+    /// Whether the actor sets goods and money aside for the recurring goals
+    /// it knows are coming back around, rather than using them up on lesser
+    /// goals in the meantime (see `ActorRef::savings`)
+    #[serde(default)]
+    pub saves: bool,
+    /// The price controls in force, which the actor won't trade against
+    /// (see `Simulation::impose_price_control`)
+    #[serde(default)]
+    pub price_controls: Vec<PriceControl>,
+    /// The legal tender laws in force, which the actor has to keep to when
+    /// it's paid (see `Simulation::impose_legal_tender`)
+    #[serde(default)]
+    pub legal_tender: Vec<LegalTender>,
 }
 
-/// The state the actor is in for one tick (reset at the start of every tick)
-#[derive(PartialEq, Debug)]
-pub enum ActorState {
-    /// Needs a goal (previous goals satisfied)
-    SearchingForGoal,
-    /// Needs to trade to get an item, needs to do that on next tick
-    WillingToTrade(i32),
-    /// Found an actor to try bidding with, begin bidding on next tick
-    FoundTradePartner(usize),
-    /// Current bid, this is used for actor on the initiating side
-    Bidding(usize),
-    /// A state for the actor waiting on the other side of a bid, so that it doesn't consume items needed for the trade.
-    BidRecipiant(Option<Item>, Option<(usize, Item)>),
-}
+/// How many orders of goods up from consumers' goods value gets imputed
+/// through before giving up (so that a loop of items producing each other
+/// can't go on forever).
+const MAX_IMPUTATION_DEPTH: usize = 16;
 
-impl Actor {
-    /// Construct a new actor. Does some housekeeping to make construction easier.
+impl ValueScale {
+    /// Construct a value scale from a goal hierarchy.
     ///
     /// # Arguments
     ///
-    /// * `name` - actor's name, for printout results
-    /// * `hierarchy` - list of actor's valued ends as `GoalData` so that they can also be added to other places.
+    /// * `hierarchy` - list of valued ends as `GoalData`, most valued first
+    /// * `satisfactions` - which items can be used for each goal
     ///
     pub fn new(
-        name: String,
         hierarchy: Vec<GoalData>,
-        satisfactions: Vec<(Goal, Vec<Item>)>,
-    ) -> Self {
-        let mut this = Actor {
-            name: name,
-            current_goals: BinaryHeap::new(),
-            goal_registry: HashMap::new(),
-            preference_list: HashMap::new(),
+        satisfactions: Vec<(GoalId, Vec<ItemId>)>,
+    ) -> Result<Self, HierarchyError> {
+        let mut this = ValueScale {
             satisfactions: satisfactions.into_iter().collect(),
-            goal_hierarchy: HashMap::new(),
-            inventory: vec![],
-            state: ActorState::SearchingForGoal,
+            ..ValueScale::default()
         };
         for (i, goal) in hierarchy.into_iter().enumerate() {
-            this.add_new_goal(goal, i);
+            this.add_new_goal(goal, i)?;
+        }
+        Ok(this)
+    }
+
+    /// Checks that the goal hierarchy is ordinal: the ranks run from 0 with
+    /// no gaps (goals sharing a rank are an indifference class, not a gap).
+    /// Everything that changes the hierarchy here keeps it that way, so this
+    /// is for hierarchies that have been put together (or loaded) some other
+    /// way.
+    pub fn validate_hierarchy(&self) -> Result<(), HierarchyError> {
+        let mut ranks: Vec<usize> = self.goal_hierarchy.values().copied().collect();
+        ranks.sort_unstable();
+        ranks.dedup();
+        match ranks.iter().enumerate().find(|&(i, &rank)| rank != i) {
+            Some((missing, _)) => Err(HierarchyError::MissingRank(missing)),
+            None => Ok(()),
         }
-        this
     }
 
-    /// This function runs the actor's simplified-praxeology choice-AI for one
-    /// tick, where ticks are the time unit of the recurring goals, and consist
-    /// of one action (see README).
+    /// Every goal in the hierarchy, grouped into indifference classes, most
+    /// valued class first. Goals in a class are in tie-breaking order (see
+    /// `GoalWrapper::cmp`), and most classes are just the one goal.
+    pub fn indifference_classes(&self) -> Vec<Vec<GoalId>> {
+        let mut ranked: Vec<(usize, GoalId)> = self
+            .goal_hierarchy
+            .iter()
+            .map(|(&g, &rank)| (rank, g))
+            .collect();
+        ranked.sort();
+        let mut classes: Vec<Vec<GoalId>> = vec![];
+        let mut last = None;
+        for (rank, goal) in ranked {
+            match classes.last_mut() {
+                Some(class) if last == Some(rank) => class.push(goal),
+                _ => classes.push(vec![goal]),
+            }
+            last = Some(rank);
+        }
+        classes
+    }
+
+    /// How a goal is ranked (0 is most valued). Goals that somehow aren't in
+    /// the hierarchy are valued least of all.
+    pub fn rank(&self, goal: GoalId) -> usize {
+        self.goal_hierarchy
+            .get(&goal)
+            .copied()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The highest-valued goal of the ones that are in play, if any are.
+    pub fn top_goal(&self) -> Option<GoalId> {
+        self.current_goals.peek().map(|g| g.goal)
+    }
+
+    /// Whether a goal is currently in play.
+    pub fn is_in_play(&self, goal: GoalId) -> bool {
+        self.current_goals.iter().any(|g| g.goal == goal)
+    }
+
+    /// Puts a recurring goal back into play now that its timer has run out
+    /// (or a random one now that it's come up), starting it over from no units. Returns whether anything happened:
+    /// nothing does if the goal has been removed from the registry in the
+    /// meantime, or if it's still in play from last time.
     ///
     /// # Arguments
     ///
-    /// * `other_actors` - list of the other actors available to trade with
+    /// * `goal` - the goal that's come due
     ///
-    pub fn tick(&mut self, other_actors: &Vec<RefCell<Actor>>) {
-        let mut reintroduce_goals = vec![];
-        for (goal, goal_data) in self.goal_registry.iter_mut() {
-            if let GoalData::RegularSatisfaction {
-                time,
-                time_required,
-                ..
-            } = goal_data
-            {
-                *time += 1;
-                if *time >= *time_required {
-                    *time = 0;
-                    reintroduce_goals.push(goal.clone());
-                }
-            }
+    pub fn reintroduce_goal(&mut self, goal: GoalId) -> bool {
+        if self.is_in_play(goal) || self.dormant.contains(&goal) {
+            return false;
         }
-        for goal in reintroduce_goals {
-            self.add_goal(goal);
+        // Whatever was given up on last time gets another go
+        self.shelved.remove(&goal);
+        match self.goal_registry.get_mut(&goal) {
+            Some(GoalData::RegularSatisfaction { units, .. })
+            | Some(GoalData::Stochastic { units, .. }) => *units = Quantity::ZERO,
+            _ => return false,
         }
+        if !self.prerequisites_met(goal) {
+            self.dormant.insert(goal);
+            return false;
+        }
+        self.add_goal(goal);
+        true
+    }
 
-        // Get the highest-valued goal of the ones that are in play
-        let goal = self.current_goals.peek().map(|x| x.goal);
-        println!(
-            "{} selects {} as a goal",
-            self.name.yellow(),
-            format!("{:?}", goal).blue()
-        );
+    /// Notes that the actor went after a goal this tick, returning how many
+    /// ticks in a row it's done that now without the goal needing any less
+    /// than it did (0 if it's getting somewhere, or just started on it).
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal being gone after
+    ///
+    pub fn note_progress(&mut self, goal: GoalId) -> u32 {
+        let needed = match self.units_needed(goal) {
+            Some(needed) if self.is_in_play(goal) => needed,
+            _ => {
+                self.stalled.remove(&goal);
+                return 0;
+            }
+        };
+        match self.stalled.get_mut(&goal) {
+            Some(stalled) if needed >= stalled.0 => {
+                stalled.1 += 1;
+                stalled.1
+            }
+            _ => {
+                self.stalled.insert(goal, (needed, 0));
+                0
+            }
+        }
+    }
 
-        // If it exists...
-        if let Some(goal) = goal {
-            match self.state {
-                ActorState::SearchingForGoal => {
-                    // ...try to find all of the items that *might* be able to satisfy this goal
-                    let possibilities = self.find_item_for_goal(goal);
-                    println!(
-                        "{} finds {} items for use on goal {}",
-                        self.name.yellow(),
-                        possibilities.len(),
-                        format!("{:?}", goal).blue()
-                    );
-                    if possibilities.len() >= 1 {
-                        // We ended up finding a viable item, so use it
-
-                        // TODO: Add time-preference so that agents will wait if an item
-                        // has a higher-valued goal, so they can use it for that goal,
-                        // and decide to trade instead for their current goal (if the
-                        // situation isn't too dire)
-                        self.use_item_for_goal(*possibilities.last().unwrap(), goal);
-                        self.state = ActorState::SearchingForGoal;
-                    } else if possibilities.len() == 0 {
-                        // We need an item
-                        if self.inventory.len() > 0 {
-                            println!("{} is now willing to trade", self.name.yellow(),);
-                            self.state = ActorState::WillingToTrade(-1);
-                        } else {
-                            self.state = ActorState::SearchingForGoal;
-                        }
-                    }
-                }
-                ActorState::WillingToTrade(idx) => {
-                    // Find trade partner
-                    if let Some(first_idx) =
-                        self.find_next_actor_for_trade(goal, &other_actors, idx)
-                    {
-                        self.state = ActorState::FoundTradePartner(first_idx);
-                        let mut oa = other_actors[first_idx].borrow_mut();
-                        match oa.state {
-                            ActorState::BidRecipiant(..) => {}
-                            _ => {
-                                oa.state = ActorState::BidRecipiant(None, None);
-                            }
-                        }
-                    } else {
-                        self.state = ActorState::SearchingForGoal;
-                    }
-                }
-                ActorState::FoundTradePartner(idx) => {
-                    // Check if transaction is viable at all
-                    let mut other_actor = other_actors[idx].borrow_mut();
-                    let actors_items =
-                        other_actor.has_item_of(self.satisfactions.get(&goal).unwrap());
-                    // prepare to bid
-                    self.state = ActorState::Bidding(idx);
-                    other_actor.state =
-                        ActorState::BidRecipiant(None, Some(*actors_items.first().unwrap()));
-                }
-                ActorState::Bidding(idx) => {
-                    let mut other_actor = other_actors[idx].borrow_mut();
-                    // if there's no more items for this actor, find another to
-                    // trade with
-                    match other_actor.state {
-                        ActorState::BidRecipiant(previous_bid, Some(goal_item)) => {
-                            let my_item = self
-                                .inventory
-                                .iter()
-                                .enumerate()
-                                .filter(|(_idx, i)| {
-                                    if previous_bid.is_some() {
-                                        other_actor.compare_item_values(**i, previous_bid.unwrap())
-                                            == Ordering::Greater
-                                    } else {
-                                        other_actor.compare_item_values(**i, goal_item.1)
-                                            == Ordering::Greater
-                                    }
-                                })
-                                .last();
-                            if let Some(possible_item) = my_item {
-                                println!(
-                                    "{}/{} makes bid: will give {} for {}",
-                                    self.name.yellow(),
-                                    other_actor.name.yellow(),
-                                    format!("{:?}", possible_item.1).green(),
-                                    format!("{:?}", goal_item.1).green(),
-                                );
-                                let other = other_actor
-                                    .compare_item_values(*possible_item.1, goal_item.1)
-                                    != Ordering::Less;
-                                let me = self.compare_item_values(*possible_item.1, goal_item.1)
-                                    != Ordering::Greater;
-                                if other && me {
-                                    println!("\n-----");
-                                    println!("Inventories before:");
-                                    println!(
-                                        "- {}: {}",
-                                        self.name.yellow(),
-                                        format!("{:?}", self.inventory).red()
-                                    );
-                                    println!(
-                                        "- {}: {}",
-                                        other_actor.name.yellow(),
-                                        format!("{:?}", other_actor.inventory).red()
-                                    );
-
-                                    // add other's item to inventory, remove it from theirs
-                                    self.add_item(goal_item.1);
-                                    self.inventory.remove(possible_item.0);
-                                    // remove my item from my inventory, add it to theirs
-                                    other_actor.add_item(*possible_item.1);
-                                    other_actor.inventory.remove(goal_item.0);
-                                    println!("-----");
-
-                                    println!("Inventories after:");
-                                    println!(
-                                        "+ {}: {}",
-                                        self.name.yellow(),
-                                        format!("{:?}", self.inventory).green()
-                                    );
-                                    println!(
-                                        "+ {}: {}",
-                                        other_actor.name.yellow(),
-                                        format!("{:?}", other_actor.inventory).green()
-                                    );
-                                    println!("-----\n");
-
-                                    self.state = ActorState::SearchingForGoal;
-                                    other_actor.state = ActorState::SearchingForGoal;
-                                    println!(
-                                        "{}/{}: {}",
-                                        self.name.yellow(),
-                                        other_actor.name.yellow(),
-                                        "Trade complete".green()
-                                    );
-                                } else {
-                                    println!(
-                                        "{}/{}: bid rejected by {}",
-                                        self.name.yellow(),
-                                        other_actor.name.yellow(),
-                                        if !other && me {
-                                            other_actor.name.yellow()
-                                        } else {
-                                            self.name.yellow()
-                                        }
-                                    );
-                                    println!(
-                                        "{}/{}: bid is proceeding up",
-                                        self.name.yellow(),
-                                        other_actor.name.yellow()
-                                    );
-                                    match other_actor.state {
-                                        ActorState::BidRecipiant(..) => {
-                                            other_actor.state = ActorState::BidRecipiant(
-                                                Some(*possible_item.1),
-                                                Some(goal_item),
-                                            );
-                                        }
-                                        _ => unreachable!(),
-                                    }
-                                }
-                                other_actor.state = ActorState::BidRecipiant(
-                                    Some(*possible_item.1),
-                                    Some(goal_item),
-                                );
-                            } else {
-                                println!(
-                                    "{}/{}: No more items to trade, going to next actor.",
-                                    self.name.yellow(),
-                                    other_actor.name.yellow()
-                                );
-                                other_actor.state = ActorState::SearchingForGoal;
-                                self.state = ActorState::WillingToTrade(idx as i32);
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-                ActorState::BidRecipiant(_i1, _i2) => {
-                    println!("{} is waiting in bid", self.name.yellow());
+    /// Gives up on a goal: it's taken out of play, so the actor can get on
+    /// with the goals below it. Recurring and random goals get another go
+    /// when they next come due (see `reintroduce_goal`); anything else stays
+    /// shelved for good.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal to give up on
+    ///
+    pub fn shelve_goal(&mut self, goal: GoalId) {
+        self.take_goal_out_of_play(goal);
+        self.stalled.remove(&goal);
+        self.shelved.insert(goal);
+    }
+
+    /// Whether every one of a goal's prerequisites has been seen to: none of
+    /// them are in play or waiting on prerequisites of their own. (Recurring
+    /// goals count as seen to until they come back around, so a goal that
+    /// requires them has to wait until they've been satisfied this time
+    /// round.)
+    pub fn prerequisites_met(&self, goal: GoalId) -> bool {
+        self.prerequisites
+            .get(&goal)
+            .into_iter()
+            .flatten()
+            .all(|&p| !self.is_in_play(p) && !self.dormant.contains(&p))
+    }
+
+    /// Puts every dormant goal whose prerequisites have now been met into
+    /// play, returning them in the order they were put in play.
+    pub fn activate_goals(&mut self) -> Vec<GoalId> {
+        let mut activated = vec![];
+        loop {
+            let ready = self
+                .dormant
+                .iter()
+                .copied()
+                .find(|&g| self.prerequisites_met(g));
+            match ready {
+                Some(goal) => {
+                    self.dormant.remove(&goal);
+                    self.add_goal(goal);
+                    activated.push(goal);
                 }
+                None => return activated,
             }
-        } else {
-            println!("{} does not pursue any goals", self.name.yellow());
         }
-        println!("");
     }
 
-    fn find_next_actor_for_trade(
-        &self,
-        goal: Goal,
-        other_actors: &Vec<RefCell<Actor>>,
-        idx: i32,
-    ) -> Option<usize> {
-        for (idx, actor) in other_actors.iter().skip((idx + 1) as usize).enumerate() {
-            if format!("Actor#{}", idx) == self.name {
-                continue;
-            }
-            if let Ok(actor) = actor.try_borrow() {
-                match actor.state {
-                    ActorState::Bidding(..) | ActorState::FoundTradePartner(..) => {
-                        println!(
-                            "{}: {} is already occupied trading with another actor, skipping",
-                            self.name.yellow(),
-                            format!("Actor#{}", idx).yellow()
-                        );
-                    }
-                    ActorState::BidRecipiant(..) | _ => {
-                        let actors_items =
-                            actor.has_item_of(self.satisfactions.get(&goal).unwrap());
-                        if actors_items.len() > 0 {
-                            println!(
-                                "{} finds trade partner {} with {} items it is interested in ({})",
-                                self.name.yellow(),
-                                format!("Actor#{}", idx).yellow(),
-                                actors_items.len(),
-                                match actor.state {
-                                    ActorState::BidRecipiant(..) => "engaged",
-                                    _ => "not engaged",
-                                }
-                            );
-                            return Some(idx);
-                        }
-                    }
-                }
-            } else {
-                /*println!(
-                    "{}: {} is already occupied trading with another actor, skipping (EO1)",
-                    self.name.yellow(),
-                    format!("Actor#{}", idx).yellow()
-                );*/
+    /// How many ticks a recurring goal takes to come back around, or `None`
+    /// if it isn't a recurring goal on this scale.
+    pub fn recurrence(&self, goal: GoalId) -> Option<u64> {
+        match self.goal_registry.get(&goal) {
+            Some(GoalData::RegularSatisfaction { time_required, .. }) => {
+                Some((*time_required).max(1) as u64)
             }
+            _ => None,
         }
-        None
     }
 
-    pub fn has_item_of(&self, items: &Vec<Item>) -> Vec<(usize, Item)> {
-        self.inventory
-            .iter()
-            .enumerate()
-            .filter(|(_, x)| items.contains(x))
-            .map(|(i, x)| (i, x.clone()))
-            .collect()
+    /// The chance of a goal that comes up at random coming up on any given
+    /// tick, or `None` if it isn't a random goal on this scale.
+    pub fn arrival_probability(&self, goal: GoalId) -> Option<Quantity> {
+        match self.goal_registry.get(&goal) {
+            Some(GoalData::Stochastic { probability, .. }) => Some(*probability),
+            _ => None,
+        }
     }
 
-    /// Adds item to inventory in a sorted manner
-    pub fn add_item(&mut self, item: Item) {
-        let loc = self
-            .inventory
-            .binary_search_by(|probe| self.compare_item_values(*probe, item))
-            .unwrap_or_else(|e| e);
-        self.inventory.insert(loc, item);
+    /// Every goal on this scale that comes up at random, along with the
+    /// chance of it coming up on any given tick, in a fixed order.
+    pub fn stochastic_goals(&self) -> Vec<(GoalId, Quantity)> {
+        let mut goals: Vec<_> = self
+            .goal_registry
+            .values()
+            .filter_map(|g| match *g {
+                GoalData::Stochastic {
+                    goal, probability, ..
+                } => Some((goal, probability)),
+                _ => None,
+            })
+            .collect();
+        goals.sort();
+        goals
     }
 
-    /// Finds any items in the inventory that might satisfy a goal.
+    /// Every recurring goal on this scale, along with how many ticks from now
+    /// it first comes due (taking into account any `time` it already had on
+    /// the clock), in a fixed order.
+    pub fn recurring_goals(&self) -> Vec<(GoalId, u64)> {
+        let mut goals: Vec<_> = self
+            .goal_registry
+            .values()
+            .filter_map(|g| match *g {
+                GoalData::RegularSatisfaction {
+                    goal,
+                    time_required,
+                    time,
+                    ..
+                } => Some((goal, (time_required - time).max(1) as u64)),
+                _ => None,
+            })
+            .collect();
+        goals.sort();
+        goals
+    }
+
+    /// Moves a goal to a new place in the hierarchy (0 being the most valued),
+    /// shifting the goals in between down or up a place to make room. Returns
+    /// whether the goal is in the hierarchy to be moved at all.
     ///
     /// # Arguments
     ///
-    /// * `goal` - goal to satisfy
+    /// * `goal` - the goal to move
+    /// * `new_index` - where to put it (anything past the end puts it last)
     ///
     /// # Notes
     ///
-    ///  If it finds an item whose highest-valued goal is that goal, it returns
-    ///  a list of just that. Also, the list is sorted greatest-valued item to
-    ///  least using an insertion sort (best I can do without adding a binheap
-    ///  wrapper).
-    fn find_item_for_goal(&self, goal: Goal) -> Vec<Item> {
-        let mut possibilities = vec![];
-        let opts = self.satisfactions.get(&goal).unwrap();
-        for item in self.inventory.iter() {
-            if opts.contains(&item) {
-                // If we have an item whose best use is for this goal...
-                if self
-                    .preference_list
-                    .get(&item)
-                    .unwrap()
-                    .peek()
-                    .unwrap()
-                    .goal
-                    == goal
-                {
-                    // ...jackpot, use it!
-                    possibilities.push(item.clone());
-                    break;
-                } else {
-                    // ...otherwise, We want to use the least-valued
-                    // item that can satisfy our need, so insert
-                    // sortedly into vector (slow-ass, but I'm lazy and
-                    // don't want to make another binary heap wrapper
-                    // (argh!))
-                    possibilities.push(item.clone());
-                }
-            }
+    /// Every goal's rank is copied into the `GoalWrapper`s in the preference
+    /// list and the current goals when they're put in play, so they'd all go
+    /// stale; every goal in play is taken out and put back in again once the
+    /// hierarchy's been changed, which rebuilds them all. Ranks come out
+    /// contiguous from 0, whatever they were before.
+    ///
+    /// A goal that was indifferent to others is taken out of their class and
+    /// ranked on its own; places count indifference classes, not goals.
+    pub fn rerank_goal(&mut self, goal: GoalId, new_index: usize) -> bool {
+        if !self.goal_hierarchy.contains_key(&goal) {
+            return false;
+        }
+        let mut classes = self.classes_without(goal);
+        classes.insert(new_index.min(classes.len()), vec![goal]);
+        self.set_hierarchy(classes);
+        true
+    }
+
+    /// Puts a goal in the same indifference class as another, so the actor
+    /// values the two exactly the same (items best used for one are worth as
+    /// much as items best used for the other). Returns whether both goals are
+    /// in the hierarchy.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal to move
+    /// * `to` - the goal whose class it joins
+    ///
+    /// # Notes
+    ///
+    /// This is the only way goals come to share a rank, so ties are always
+    /// meant. Rebuilds everything in play just like `rerank_goal`.
+    pub fn make_indifferent(&mut self, goal: GoalId, to: GoalId) -> bool {
+        if goal == to {
+            return self.goal_hierarchy.contains_key(&goal);
+        }
+        if !self.goal_hierarchy.contains_key(&goal) || !self.goal_hierarchy.contains_key(&to) {
+            return false;
+        }
+        let mut classes = self.classes_without(goal);
+        if let Some(class) = classes.iter_mut().find(|c| c.contains(&to)) {
+            class.push(goal);
+        }
+        self.set_hierarchy(classes);
+        true
+    }
+
+    /// The indifference classes with a goal taken out, dropping its class if
+    /// that leaves it empty.
+    fn classes_without(&self, goal: GoalId) -> Vec<Vec<GoalId>> {
+        self.indifference_classes()
+            .into_iter()
+            .map(|class| class.into_iter().filter(|&g| g != goal).collect())
+            .filter(|class: &Vec<GoalId>| !class.is_empty())
+            .collect()
+    }
+
+    /// Ranks goals by the indifference classes given, most valued first,
+    /// rebuilding everything in play around the new ranks (see
+    /// `rerank_goal`).
+    fn set_hierarchy(&mut self, classes: Vec<Vec<GoalId>>) {
+        let in_play: Vec<GoalId> = self.current_goals.iter().map(|g| g.goal).collect();
+        for &g in in_play.iter() {
+            self.take_goal_out_of_play(g);
+        }
+        self.goal_hierarchy = classes
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, class)| class.into_iter().map(move |g| (g, i)))
+            .collect();
+        for g in in_play {
+            self.add_goal(g);
         }
-        possibilities
     }
 
     /// Adds a *new* goal (not already in registry) to all of the BinaryHeaps
@@ -508,10 +641,35 @@ impl Actor {
     /// * `goal` - `GoalData` of what's to be added
     /// * `location` - the location for it to be inserted into the hierarchy of ends/values
     ///
-    pub fn add_new_goal(&mut self, goal: GoalData, location: usize) {
-        self.add_goal(goal.get_goal());
-        self.goal_hierarchy.insert(goal.get_goal(), location);
-        self.goal_registry.insert(goal.get_goal(), goal);
+    /// # Notes
+    ///
+    /// Goals already at `location` or below it are moved down a place to make
+    /// room, so the hierarchy stays ordinal (the new goal isn't indifferent to
+    /// anything). It's an error to add a goal the actor already has, or to put
+    /// one further down than right after the last goal.
+    pub fn add_new_goal(&mut self, goal: GoalData, location: usize) -> Result<(), HierarchyError> {
+        let g = goal.get_goal();
+        if self.goal_registry.contains_key(&g) || self.goal_hierarchy.contains_key(&g) {
+            return Err(HierarchyError::DuplicateGoal(g));
+        }
+        let last = self.indifference_classes().len();
+        if location > last {
+            return Err(HierarchyError::MissingRank(last));
+        }
+        self.goal_hierarchy.insert(g, last);
+        self.goal_registry.insert(g, goal);
+        // Random goals wait to come up before they're in play
+        if !matches!(goal, GoalData::Stochastic { .. }) {
+            if self.prerequisites_met(g) {
+                self.add_goal(g);
+            } else {
+                self.dormant.insert(g);
+            }
+        }
+        if location < last {
+            self.rerank_goal(g, location);
+        }
+        Ok(())
     }
 
     /// Adds a goal (already in registry and hierarchy) to all of the
@@ -519,36 +677,74 @@ impl Actor {
     ///
     /// # Arguments
     ///
-    /// * `goal` - `Goal` of what's to be added, acts as ID into registry to get `GoalData`.
-    /// * `location` - the location for it to be inserted into the hierarchy of ends/values
+    /// * `goal` - `GoalId` of what's to be added, acts as ID into registry to get `GoalData`.
     ///
-    pub fn add_goal(&mut self, goal: Goal) {
-        println!(
-            "{} reintroduces {}",
-            self.name.yellow(),
-            format!("{:?}", goal).blue()
-        );
-        let gh = self.goal_hierarchy.clone();
-        let ordered_goal = Rc::new(GoalWrapper {
-            comparator: Box::new(move |x: &Goal, y: &Goal| {
-                let xval = gh.get(&x);
-                let yval = gh.get(&y);
-                xval.and_then(|x| yval.map(|y| x.cmp(y)))
-                    .unwrap_or(Ordering::Equal)
-            }),
-            goal: goal,
-        });
-        if let Some(effected_entries) = self.satisfactions.get(&goal) {
-            for item in effected_entries.iter() {
-                self.preference_list
-                    .entry(*item)
-                    .or_insert(BinaryHeap::new())
-                    .push(ordered_goal.clone());
-            }
+    pub fn add_goal(&mut self, goal: GoalId) {
+        let ordered_goal = GoalWrapper {
+            rank: self.rank(goal),
+            goal,
+        };
+        for item in self.goal_items(goal) {
+            self.preference_list
+                .entry(item)
+                .or_default()
+                .push(ordered_goal);
         }
         self.current_goals.push(ordered_goal);
     }
 
+    /// Every item that goes towards a goal, whether as one of the
+    /// alternatives that satisfy it or as a complement, each listed once.
+    pub fn goal_items(&self, goal: GoalId) -> Vec<ItemId> {
+        let mut items = self.satisfiers(goal);
+        for &item in self.complements.get(&goal).into_iter().flatten() {
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+        items
+    }
+
+    /// Every item that satisfies a goal: its satisfactions, and anything that
+    /// can stand in for one of them, each listed once.
+    pub fn satisfiers(&self, goal: GoalId) -> Vec<ItemId> {
+        let mut items: Vec<ItemId> = vec![];
+        for item in self.satisfactions.get(&goal).into_iter().flatten() {
+            let substitutes = self.substitutes.get(item).into_iter().flatten();
+            for &item in std::iter::once(item).chain(substitutes) {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+        items
+    }
+
+    /// Say which items a goal needs on top of one of its satisfactions (see
+    /// `complements`), replacing any it needed before.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal that needs them
+    /// * `items` - every item it needs one unit of, all together
+    ///
+    pub fn set_complements(&mut self, goal: GoalId, items: Vec<ItemId>) {
+        // Complements are valued for the goal they go towards, so the
+        // preference list has to be rebuilt around the new ones
+        let in_play = self.is_in_play(goal);
+        if in_play {
+            self.take_goal_out_of_play(goal);
+        }
+        if items.is_empty() {
+            self.complements.remove(&goal);
+        } else {
+            self.complements.insert(goal, items);
+        }
+        if in_play {
+            self.add_goal(goal);
+        }
+    }
+
     /// Removes any goal in the entire list of goals this actor has.
     ///
     /// # Arguments
@@ -566,96 +762,128 @@ impl Actor {
     /// performance basket-case and should basically never be used unless
     /// absolutely totally necessary
     ///
-    pub fn remove_goal(&mut self, actual_goal: Goal) {
-        if let Some(effected_entries) = self.satisfactions.get(&actual_goal) {
-            for item in effected_entries.iter() {
-                {
-                    if self.preference_list.contains_key(&item) {
-                        let mut new = BinaryHeap::new();
-                        self.preference_list.get(&item).map(
-                            |goals: &BinaryHeap<Rc<GoalWrapper>>| {
-                                for og in goals.into_iter() {
-                                    if og.goal != actual_goal {
-                                        new.push(og.clone());
-                                    }
-                                }
-                            },
-                        );
-                        *self.preference_list.get_mut(&item).unwrap() = new;
+    /// Goals that were ranked below it move up a place, so the hierarchy
+    /// stays strictly ordinal, which makes this slower still.
+    ///
+    pub fn remove_goal(&mut self, actual_goal: GoalId) {
+        self.take_goal_out_of_play(actual_goal);
+        self.dormant.remove(&actual_goal);
+        self.stalled.remove(&actual_goal);
+        self.shelved.remove(&actual_goal);
+        self.goal_registry.remove(&actual_goal);
+        if let Some(rank) = self.goal_hierarchy.remove(&actual_goal) {
+            // Only a goal that was alone in its class leaves a gap
+            if self.goal_hierarchy.values().all(|&r| r != rank)
+                && self.goal_hierarchy.values().any(|&r| r > rank)
+            {
+                self.set_hierarchy(self.indifference_classes());
+            }
+        }
+    }
+
+    /// Takes a goal out of the current goal list and preference list, but
+    /// leaves it in the registry and hierarchy, so it can come back into play
+    /// later (this is what happens to recurring goals once they're
+    /// satisfied). Just as slow as `remove_goal`.
+    ///
+    /// # Arguments
+    ///
+    /// * `actual_goal` - The goal to take out of play
+    ///
+    pub fn take_goal_out_of_play(&mut self, actual_goal: GoalId) {
+        for item in self.goal_items(actual_goal) {
+            if let Some(goals) = self.preference_list.get_mut(&item) {
+                let mut new = BinaryHeap::new();
+                for og in goals.iter() {
+                    if og.goal != actual_goal {
+                        new.push(*og);
                     }
                 }
+                *goals = new;
             }
         }
 
         let mut new = BinaryHeap::new();
         for og in self.current_goals.iter() {
             if og.goal != actual_goal {
-                new.push(og.clone());
+                new.push(*og);
             }
         }
         self.current_goals = new;
+    }
 
-        self.goal_registry.remove(&actual_goal);
-        self.goal_hierarchy.remove(&actual_goal);
+    /// How much more has to be put towards a goal before it's satisfied, or
+    /// `None` if the goal isn't on this scale.
+    pub fn units_needed(&self, goal: GoalId) -> Option<Quantity> {
+        match self.goal_registry.get(&goal)? {
+            GoalData::Satisfaction {
+                units,
+                units_required,
+                ..
+            }
+            | GoalData::RegularSatisfaction {
+                units,
+                units_required,
+                ..
+            }
+            | GoalData::Stochastic {
+                units,
+                units_required,
+                ..
+            } => Some(units_required.saturating_sub(*units)),
+        }
     }
 
-    /// Uses an item to satisfy the goal selected
+    /// How much has to be put towards a goal to satisfy it from scratch, or
+    /// `None` if the goal isn't on this scale.
+    pub fn units_required(&self, goal: GoalId) -> Option<Quantity> {
+        match self.goal_registry.get(&goal)? {
+            GoalData::Satisfaction { units_required, .. }
+            | GoalData::RegularSatisfaction { units_required, .. }
+            | GoalData::Stochastic { units_required, .. } => Some(*units_required),
+        }
+    }
+
+    /// Puts some amount of an item towards a goal, returning whether that was
+    /// enough to satisfy it. Satisfied recurring goals stay in the registry,
+    /// waiting to come due again (see `reintroduce_goal`); anything else is
+    /// removed for good.
     ///
     /// # Arguments
     ///
-    /// * `item` - `Item` to use
-    /// * `goal` - `Goal` to satisfy
-    ///
-    /// # Notes
-    ///
-    /// Doesn't update recurring goals. See `tick`.
+    /// * `goal` - `GoalId` to put the amount towards
+    /// * `amount` - how much is being put towards it
     ///
-    pub fn use_item_for_goal(&mut self, item: Item, goal: Goal) {
-        if let Some(idx) = self.inventory.iter().position(|&r| r == item) {
-            self.inventory.remove(idx);
-            let mut should_remove = false;
-            {
-                let highest_valued_goal: &mut GoalData = self.goal_registry.get_mut(&goal).unwrap();
-                println!(
-                    "{} uses item {} for goal {}",
-                    self.name.yellow(),
-                    format!("{:?}", item).green(),
-                    format!("{:?}", goal).blue()
-                );
-                match highest_valued_goal {
-                    GoalData::Satisfaction {
-                        units_required,
-                        units,
-                        ..
-                    } => {
-                        *units += 1;
-                        if *units >= *units_required {
-                            should_remove = true;
-                        }
-                    }
-                    GoalData::RegularSatisfaction {
-                        units_required,
-                        units,
-                        ..
-                    } => {
-                        *units += 1;
-                        if *units >= *units_required {
-                            should_remove = true;
-                        }
-                    }
-                }
-            }
-            if should_remove {
-                self.remove_goal(goal);
-            }
+    pub fn put_towards(&mut self, goal: GoalId, amount: Quantity) -> bool {
+        let (units, units_required, recurring) = match self.goal_registry.get_mut(&goal) {
+            Some(GoalData::Satisfaction {
+                units,
+                units_required,
+                ..
+            }) => (units, *units_required, false),
+            Some(GoalData::RegularSatisfaction {
+                units,
+                units_required,
+                ..
+            })
+            | Some(GoalData::Stochastic {
+                units,
+                units_required,
+                ..
+            }) => (units, *units_required, true),
+            None => return false,
+        };
+        *units += amount;
+        if *units < units_required {
+            return false;
+        }
+        if recurring {
+            self.take_goal_out_of_play(goal);
+            self.stalled.remove(&goal);
         } else {
-            println!(
-                "{} does not have item {} for goal {} in inventory",
-                self.name.yellow(),
-                format!("{:?}", item).green(),
-                format!("{:?}", goal).blue()
-            );
+            self.remove_goal(goal);
         }
+        true
     }
 
     /// Get the highest-valued goal which can be satisfied with this item
@@ -664,32 +892,1996 @@ impl Actor {
     ///
     /// * `item` - the item
     ///
-    pub fn get_best_goal(&self, item: Item) -> Option<Goal> {
-        self.preference_list
+    /// # Notes
+    ///
+    /// Producer's goods can't satisfy any goal directly, so the goal they're
+    /// valued for is the best one served by any of the lower-order goods they
+    /// help produce (see `set_imputations`).
+    ///
+    pub fn get_best_goal(&self, item: ItemId) -> Option<GoalId> {
+        let best = self.impute_best_goal(item, MAX_IMPUTATION_DEPTH);
+        // A good held to trade on is worth as much as what it's meant to get
+        let medium = self
+            .media
+            .get(&item)
+            .copied()
+            .filter(|&goal| self.is_in_play(goal));
+        match (best, medium) {
+            (Some(best), Some(medium)) => {
+                Some(std::cmp::min_by_key(best, medium, |&g| (self.rank(g), g)))
+            }
+            (best, medium) => best.or(medium),
+        }
+    }
+
+    fn impute_best_goal(&self, item: ItemId, depth: usize) -> Option<GoalId> {
+        let direct = self
+            .preference_list
             .get(&item)
             .and_then(|goals| goals.peek())
-            .map(|og| og.goal)
+            .map(|og| og.goal);
+        if direct.is_some() || depth == 0 {
+            return direct;
+        }
+        self.imputations
+            .get(&item)?
+            .iter()
+            .filter_map(|&product| self.impute_best_goal(product, depth - 1))
+            .min_by_key(|&g| (self.rank(g), g))
     }
 
-    /// Compare two items to see which is more valuable based on the goals it can satisfy
-    ///
-    /// # Arguments
-    ///
-    /// * `a` - first item
-    /// * `b` - second item
-    ///
-    pub fn compare_item_values(&self, a: Item, b: Item) -> Ordering {
-        let gh = self.goal_hierarchy.clone();
-        let a_val = self.get_best_goal(a).and_then(|a_g| gh.get(&a_g));
-        let b_val = self.get_best_goal(b).and_then(|b_g| gh.get(&b_g));
-        if a_val.is_none() && b_val.is_some() {
-            Ordering::Less
-        } else if a_val.is_some() && b_val.is_none() {
-            Ordering::Greater
-        } else if a_val.is_none() && b_val.is_none() {
-            Ordering::Equal
-        } else {
-            b_val.unwrap().cmp(a_val.unwrap())
+    /// Tell the scale which producer's goods help produce which lower-order
+    /// goods (usually `ItemRegistry::imputations`), so that it can value them.
+    pub fn set_imputations(&mut self, imputations: HashMap<ItemId, Vec<ItemId>>) {
+        self.imputations = imputations;
+    }
+
+    /// Pick up everything the scale needs to know about the world's items to
+    /// value them: which producer's goods make what, the quality of each, and
+    /// which are money.
+    pub fn learn_items(&mut self, items: &ItemRegistry) {
+        // Substitutes go towards the same goals, so every goal in play has to
+        // be rebuilt around them
+        let in_play: Vec<GoalId> = self.current_goals.iter().map(|g| g.goal).collect();
+        for &goal in in_play.iter() {
+            self.take_goal_out_of_play(goal);
+        }
+        self.imputations = items.imputations();
+        self.qualities = items.qualities();
+        self.money = items.money();
+        self.substitutes = items.substitutions();
+        for goal in in_play {
+            self.add_goal(goal);
+        }
+    }
+
+    /// Pick up everything the scale needs to know about the world's goals:
+    /// which of them are leisure, and which have prerequisites (on top of any
+    /// already set on the scale).
+    pub fn learn_goals(&mut self, goals: &GoalRegistry) {
+        self.leisure = goals
+            .iter()
+            .filter(|(_, def)| def.leisure)
+            .map(|(id, _)| id)
+            .collect();
+        for (id, _) in goals.iter() {
+            let prerequisites = goals.prerequisites(id);
+            if !prerequisites.is_empty() {
+                self.prerequisites.insert(id, prerequisites);
+            }
+        }
+        // Anything already in play that turns out to be waiting on something
+        // has to wait after all
+        let waiting: Vec<GoalId> = self
+            .current_goals
+            .iter()
+            .map(|g| g.goal)
+            .filter(|&g| !self.prerequisites_met(g))
+            .collect();
+        for goal in waiting {
+            self.take_goal_out_of_play(goal);
+            self.dormant.insert(goal);
         }
     }
+
+    /// Whether time off goes towards a goal.
+    pub fn is_leisure(&self, goal: GoalId) -> bool {
+        self.leisure.contains(&goal)
+    }
+
+    /// The highest-valued leisure goal in play, if there is one.
+    pub fn leisure_in_play(&self) -> Option<GoalId> {
+        self.current_goals
+            .iter()
+            .filter(|g| self.is_leisure(g.goal))
+            .max()
+            .map(|g| g.goal)
+    }
+
+    /// Whether an item is any use to the actor at all, now or later: whether
+    /// it goes towards any goal on the scale (in play or not, since recurring
+    /// goals come back around), helps produce something that does, or is
+    /// money or a good it's holding to trade on.
+    pub fn has_use_for(&self, item: ItemId) -> bool {
+        self.is_money(item)
+            || self.media.contains_key(&item)
+            || self.serves_any(item, MAX_IMPUTATION_DEPTH)
+    }
+
+    fn serves_any(&self, item: ItemId, depth: usize) -> bool {
+        let direct = self
+            .goal_registry
+            .keys()
+            .any(|&goal| self.goal_items(goal).contains(&item));
+        if direct || depth == 0 {
+            return direct;
+        }
+        self.imputations
+            .get(&item)
+            .into_iter()
+            .flatten()
+            .any(|&product| self.serves_any(product, depth - 1))
+    }
+
+    /// Whether an item is money to this actor.
+    pub fn is_money(&self, item: ItemId) -> bool {
+        self.money.contains(&item)
+    }
+
+    /// How many times the actor has seen an item accepted in a trade.
+    pub fn saleability(&self, item: ItemId) -> u32 {
+        self.saleability.get(&item).copied().unwrap_or(0)
+    }
+
+    /// Notes that the actor saw an item accepted in a trade, making it that
+    /// much more saleable as far as it's concerned.
+    pub fn note_accepted(&mut self, item: ItemId) {
+        *self.saleability.entry(item).or_insert(0) += 1;
+    }
+
+    /// The items the actor has seen accepted in trades, most saleable first
+    /// (ties broken by ID).
+    pub fn most_saleable(&self) -> Vec<(ItemId, u32)> {
+        let mut items: Vec<_> = self.saleability.iter().map(|(&i, &n)| (i, n)).collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        items
+    }
+
+    /// How much a unit of an item counts for towards a goal.
+    pub fn quality(&self, item: ItemId) -> Quantity {
+        self.qualities.get(&item).copied().unwrap_or(Quantity::ONE)
+    }
+
+    /// Compare two items to see which is more valuable based on the goals it can satisfy
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - first item
+    /// * `b` - second item
+    ///
+    /// # Notes
+    ///
+    /// Items that are best used for the same goal (like different grades of
+    /// the same good), or for goals in the same indifference class, are
+    /// ranked by their quality, and only if that's the same too are they
+    /// valued equally. Unlike `GoalWrapper::cmp`, there's no breaking the tie
+    /// by which goal came first: the actor really doesn't mind which it has.
+    /// An item that's best used for a goal that somehow isn't in the hierarchy
+    /// is valued below every goal that is, but still above an item that's no
+    /// use at all.
+    ///
+    pub fn compare_item_values(&self, a: ItemId, b: ItemId) -> Ordering {
+        let a_val = self.get_best_goal(a).map(|g| self.rank(g));
+        let b_val = self.get_best_goal(b).map(|g| self.rank(g));
+        match (a_val, b_val) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a_val), Some(b_val)) => b_val
+                .cmp(&a_val)
+                .then_with(|| self.quality(a).cmp(&self.quality(b))),
+        }
+    }
+
+    /// Compare two bundles of items to see which is more valuable. Values are
+    /// ordinal, so there's no adding them up: instead the most valued items
+    /// in each bundle are compared first, and only if they're valued the same
+    /// (and there's as much of them) does it come down to the next most
+    /// valued, and so on. Items valued the same are lumped together, so two
+    /// units of one grade of food count the same as one each of two grades
+    /// valued the same, and a bundle with more in it beats one that's the same
+    /// as it but for missing something.
+    ///
+    /// That way no amount of goods valued less ever outweighs a good valued
+    /// more, unless the actor weighs bundles (see `weighs_bundles`): then a
+    /// bundle that goes towards more of its wants (see `wants_served`) is
+    /// worth more, whatever they are, and the best goods only decide
+    /// between bundles that go towards as many.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - first bundle
+    /// * `b` - second bundle
+    ///
+    pub fn compare_bundles(&self, a: &Bundle, b: &Bundle) -> Ordering {
+        let ranked = |bundle: &Bundle| {
+            let mut entries: Vec<_> = bundle.iter().collect();
+            entries.sort_by(|x, y| self.compare_item_values(y.0, x.0));
+            let mut ranks: Vec<(ItemId, Quantity)> = vec![];
+            for (item, amount) in entries {
+                match ranks.last_mut() {
+                    Some((rep, total))
+                        if self.compare_item_values(*rep, item) == Ordering::Equal =>
+                    {
+                        *total += amount
+                    }
+                    _ => ranks.push((item, amount)),
+                }
+            }
+            ranks
+        };
+        if self.weighs_bundles {
+            let served = self.wants_served(a).cmp(&self.wants_served(b));
+            if served != Ordering::Equal {
+                return served;
+            }
+        }
+        let (a, b) = (ranked(a), ranked(b));
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| self.compare_item_values(x.0, y.0).then(x.1.cmp(&y.1)))
+            .find(|&ord| ord != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    }
+
+    /// How many of the actor's wants a bundle would go towards: every goal on
+    /// the scale, most valued first, takes as much as it needs of whatever in
+    /// the bundle satisfies it, and counts if it gets anything at all.
+    pub fn wants_served(&self, bundle: &Bundle) -> usize {
+        let mut left: HashMap<ItemId, Quantity> = bundle.iter().collect();
+        let mut goals: Vec<GoalId> = self.goal_hierarchy.keys().copied().collect();
+        goals.sort_by_key(|&goal| (self.rank(goal), goal));
+        goals
+            .into_iter()
+            .filter(|&goal| {
+                let mut needed = self.units_required(goal).unwrap_or(Quantity::ZERO);
+                let mut served = false;
+                for item in self.satisfiers(goal) {
+                    if let Some(held) = left.get_mut(&item) {
+                        let used = (*held).min(needed);
+                        if !used.is_zero() {
+                            *held -= used;
+                            needed -= used;
+                            served = true;
+                        }
+                    }
+                }
+                served
+            })
+            .count()
+    }
+
+    /// Every kind of item in an inventory, least valued first according to
+    /// this scale.
+    pub fn order_inventory(&self, inventory: &Inventory) -> Vec<ItemId> {
+        inventory.sorted_by(|a, b| self.compare_item_values(a, b))
+    }
+}
+
+/// Individual acting, valuing, satisfying Austrian microeconomic actor.
+///
+/// Inside a simulation, actors aren't stored as whole structs like this: each
+/// of their components (value scale, inventory, state...) lives in its own
+/// column in the `ActorArena`, and is looked at through an `ActorRef` or
+/// changed through an `ActorMut`. This is the bundle of components used to
+/// make an actor in the first place and hand it to the arena.
+#[derive(Serialize, Deserialize)]
+pub struct Actor {
+    /// Handle to this actor in its arena (assigned when it's added to one)
+    pub id: ActorId,
+    /// Name for printouts
+    pub name: String,
+    /// Goals and how the actor values them
+    pub values: ValueScale,
+    /// Items the actor has already (for trade or use)
+    pub inventory: Inventory,
+    /// Actor internal AI state
+    pub state: ActorState,
+    /// Hours the actor has to work (or rest) each tick
+    #[serde(default)]
+    pub labor: Labor,
+    /// History of the goals the actor has seen to
+    #[serde(default)]
+    pub ledger: SatisfactionLedger,
+    /// IOUs the actor has written and not yet settled
+    #[serde(default)]
+    pub credit: CreditBook,
+    /// Who the actor remembers trading with, and how it went
+    #[serde(default)]
+    pub memory: TradeMemory,
+    /// Every bid and trade the actor has been part of
+    #[serde(default)]
+    pub trades: TradeLedger,
+    /// What the actor's given up and got, for working out its profit and
+    /// loss
+    #[serde(default)]
+    pub accounts: Accounts,
+    /// The recipes the actor's found out about that not everyone knows
+    #[serde(default)]
+    pub know_how: KnowHow,
+    /// What the actor expects goods to go for, from the trades it's seen
+    #[serde(default)]
+    pub expectations: Expectations,
+    /// Decision rule used to choose an action each tick. Only the built-in
+    /// behaviors are serialized (see `Behavior::save`), so deserialized
+    /// actors with any other come back with `DefaultBehavior`.
+    #[serde(with = "crate::behavior::saved", default = "default_behavior")]
+    pub behavior: Box<dyn Behavior>,
+}
+
+/// The state the actor is in for one tick (reset at the start of every tick)
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum ActorState {
+    /// Needs a goal (previous goals satisfied)
+    #[default]
+    SearchingForGoal,
+    /// Needs to trade to get an item, needs to do that on next tick. Holds the
+    /// last actor tried, so the search can pick up after it.
+    WillingToTrade(Option<ActorId>),
+    /// Found an actor to try bidding with, begin bidding on next tick
+    FoundTradePartner(ActorId),
+    /// Current bid, this is used for actor on the initiating side. Holds the
+    /// last bundle offered (empty before the first bid), which the next bid
+    /// has to improve on.
+    Bidding(ActorId, Bundle),
+    /// A state for the actor waiting on the other side of a bid, so that it doesn't consume items needed for the trade.
+    /// Holds the last item it asked for in a counter-offer that the bidder
+    /// turned down, if any, the item being bid on, and the best bid it's
+    /// holding open for anyone else bidding on it to beat, if any (who made
+    /// it, what they offered, and what they wanted for it).
+    BidRecipiant(
+        Option<ItemId>,
+        Option<ItemId>,
+        Option<(ActorId, Bundle, Bundle)>,
+    ),
+    /// Making something out of what it had (see `ActorMut::produce`). Holds
+    /// the recipe, the goal it's being made for, and how many more ticks of
+    /// work it'll take. Nobody can trade with an actor while it's busy
+    /// producing.
+    Producing(RecipeId, GoalId, u32),
+}
+
+/// Names of all of the states, the way they're shown (without whatever
+/// they're holding on to), for checking and completion.
+pub const ACTOR_STATE_NAMES: &[&str] = &[
+    "SearchingForGoal",
+    "WillingToTrade",
+    "FoundTradePartner",
+    "Bidding",
+    "BidRecipiant",
+    "Producing",
+];
+
+impl Actor {
+    /// Start building an actor step by step. See `ActorBuilder`.
+    pub fn builder() -> ActorBuilder {
+        ActorBuilder::new()
+    }
+
+    /// Construct a new actor. Does some housekeeping to make construction easier.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - actor's name, for printout results
+    /// * `hierarchy` - list of actor's valued ends as `GoalData` so that they can also be added to other places.
+    /// * `satisfactions` - which items can be used for each goal
+    /// * `behavior` - decision rule the actor uses to pick an action each tick (usually `DefaultBehavior`)
+    ///
+    /// # Notes
+    ///
+    /// Fails if the same goal is in `hierarchy` more than once, since then it
+    /// wouldn't be strictly ordinal.
+    pub fn new(
+        name: String,
+        hierarchy: Vec<GoalData>,
+        satisfactions: Vec<(GoalId, Vec<ItemId>)>,
+        behavior: Box<dyn Behavior>,
+    ) -> Result<Self, HierarchyError> {
+        Ok(Actor {
+            id: ActorId::default(),
+            name,
+            values: ValueScale::new(hierarchy, satisfactions)?,
+            inventory: Inventory::new(),
+            state: ActorState::SearchingForGoal,
+            labor: Labor::default(),
+            ledger: SatisfactionLedger::new(),
+            credit: CreditBook::new(),
+            memory: TradeMemory::new(),
+            trades: TradeLedger::new(),
+            accounts: Accounts::new(),
+            know_how: KnowHow::new(),
+            expectations: Expectations::new(),
+            behavior,
+        })
+    }
+
+    /// Adds a unit of an item to the inventory
+    pub fn add_item(&mut self, item: ItemId) {
+        self.inventory.add(item, Quantity::ONE);
+    }
+
+    /// See `ValueScale::rerank_goal`.
+    pub fn rerank_goal(&mut self, goal: GoalId, new_index: usize) -> bool {
+        self.values.rerank_goal(goal, new_index)
+    }
+
+    /// See `ValueScale::make_indifferent`.
+    pub fn make_indifferent(&mut self, goal: GoalId, to: GoalId) -> bool {
+        self.values.make_indifferent(goal, to)
+    }
+}
+
+/// A read-only look at one actor's components, as handed out by the arena.
+#[derive(Clone, Copy)]
+pub struct ActorRef<'a> {
+    /// Handle to this actor in its arena
+    pub id: ActorId,
+    /// Name for printouts
+    pub name: &'a str,
+    /// Goals and how the actor values them
+    pub values: &'a ValueScale,
+    /// Items the actor has
+    pub inventory: &'a Inventory,
+    /// Actor internal AI state
+    pub state: &'a ActorState,
+    /// Hours the actor has to work (or rest) each tick
+    pub labor: &'a Labor,
+    /// History of the goals the actor has seen to
+    pub ledger: &'a SatisfactionLedger,
+    /// IOUs the actor has written and not yet settled
+    pub credit: &'a CreditBook,
+    /// Who the actor remembers trading with, and how it went
+    pub memory: &'a TradeMemory,
+    /// Every bid and trade the actor has been part of
+    pub trades: &'a TradeLedger,
+    /// What the actor's given up and got, for working out its profit and
+    /// loss
+    pub accounts: &'a Accounts,
+    /// The recipes the actor's found out about that not everyone knows
+    pub know_how: &'a KnowHow,
+    /// What the actor expects goods to go for, from the trades it's seen
+    pub expectations: &'a Expectations,
+}
+
+impl<'a> ActorRef<'a> {
+    /// Finds any items in the inventory that might satisfy a goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - goal to satisfy
+    ///
+    /// # Notes
+    ///
+    ///  If it finds an item whose highest-valued goal is that goal, it returns
+    ///  a list of just that. Also, the list is sorted greatest-valued item to
+    ///  least using an insertion sort (best I can do without adding a binheap
+    ///  wrapper).
+    ///
+    ///  If the goal needs complements the actor doesn't have, nothing it has
+    ///  can satisfy the goal yet, so the list is empty.
+    pub fn find_item_for_goal(&self, goal: GoalId) -> Vec<ItemId> {
+        if !self.missing_complements(goal).is_empty() {
+            return vec![];
+        }
+        self.satisfactions_held(goal)
+    }
+
+    /// The public good in `commons` that the actor would most like to use for
+    /// a goal, if any of them will do (and the actor has the goal's
+    /// complements to go with it).
+    pub fn find_public_good(&self, goal: GoalId, commons: &Inventory) -> Option<ItemId> {
+        if commons.is_empty() || !self.missing_complements(goal).is_empty() {
+            return None;
+        }
+        let opts = self.values.satisfiers(goal);
+        self.values
+            .order_inventory(commons)
+            .into_iter()
+            .rfind(|item| opts.contains(item))
+    }
+
+    /// A resource node the actor could gather something for a goal from
+    /// this tick, if there's one with anything left (see `ResourceNode`):
+    /// something that would do for the goal itself, or failing that the one
+    /// thing it's short of to make something that would by a recipe. It
+    /// only goes if it has the labor left to, and nodes are tried in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal it's after
+    /// * `resources` - every resource node in the world
+    /// * `items` - the world's items, for looking up recipes
+    /// * `now` - the tick being run
+    ///
+    pub fn find_resource_for_goal(
+        &self,
+        goal: GoalId,
+        resources: &[ResourceNode],
+        items: &ItemRegistry,
+        now: u64,
+    ) -> Option<ResourceId> {
+        if self.labor.left(now) < Work::Gathering.hours() {
+            return None;
+        }
+        let wanted = self.wanted_for(goal);
+        let short_one: Vec<ItemId> = items
+            .recipes()
+            .filter(|&(id, recipe)| self.know_how.knows(id, recipe))
+            .map(|(_, recipe)| recipe)
+            .filter(|recipe| {
+                recipe
+                    .outputs
+                    .iter()
+                    .any(|(item, _)| wanted.contains(&item))
+            })
+            .filter_map(|recipe| {
+                let mut short = recipe
+                    .needs()
+                    .filter(|&(item, amount)| self.inventory.count(item) < amount);
+                match (short.next(), short.next()) {
+                    (Some((item, _)), None) => Some(item),
+                    _ => None,
+                }
+            })
+            .collect();
+        let open = |item: ItemId| {
+            resources
+                .iter()
+                .position(|node| node.item == item && !node.left(now).is_zero())
+        };
+        wanted
+            .iter()
+            .chain(short_one.iter())
+            .find_map(|&item| open(item))
+            .map(ResourceId)
+    }
+
+    /// A tool the actor would spend some time and goods maintaining now, if
+    /// there's one (see `ItemDef::maintenance`). It only bothers with one
+    /// that's at least half worn out, and only if it has what it takes to
+    /// hand, and the labor.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - the world's items, for how long tools last and what they
+    ///   take to maintain
+    /// * `now` - the tick being run
+    ///
+    /// # Notes
+    ///
+    /// Maintaining a tool is giving something up now to get more out of the
+    /// tool later, so it's weighed like any other production plan (see
+    /// `ProductionPlan::present_value`): what it gets back is every tick of
+    /// work the wear has cost it, each making whatever the best recipe it's
+    /// used in makes. Those ticks only come once the ticks it has left are
+    /// up, so that's discounted at the actor's time preference for all of
+    /// them. It maintains the tool if that's worth at least what the upkeep
+    /// uses up (see `ValueScale::compare_bundles`). An impatient actor would
+    /// rather keep the goods, let the tool wear out, and make or trade for a
+    /// new one when it needs one.
+    pub fn tool_to_maintain(&self, items: &ItemRegistry, now: u64) -> Option<ItemId> {
+        if self.labor.left(now) < Work::Maintenance.hours() {
+            return None;
+        }
+        self.ordered_inventory().into_iter().rev().find(|&tool| {
+            let (durability, upkeep) = match items.durability(tool) {
+                Some(durability) => (durability, items.maintenance(tool)),
+                None => return false,
+            };
+            let wear = self.inventory.tool_wear(tool);
+            if upkeep.is_empty()
+                || wear * 2 < durability
+                || upkeep
+                    .iter()
+                    .any(|(item, amount)| item == tool || self.inventory.count(item) < amount)
+            {
+                return false;
+            }
+            let best = items
+                .recipes()
+                .filter(|&(id, recipe)| self.know_how.knows(id, recipe))
+                .map(|(_, recipe)| recipe)
+                .filter(|recipe| recipe.tools.contains(tool))
+                .max_by_key(|recipe| recipe.outputs.total());
+            let best = match best {
+                Some(best) => best,
+                None => return false,
+            };
+            let discount = Quantity::ONE
+                + self
+                    .values
+                    .time_preference
+                    .times(Quantity::from(durability - wear));
+            let mut returns = Bundle::new();
+            for (item, amount) in best.outputs.iter() {
+                returns.add(
+                    item,
+                    amount.times(Quantity::from(wear)).divided_by(discount),
+                );
+            }
+            self.compare_bundles(&returns, &upkeep) != Ordering::Less
+        })
+    }
+
+    /// A recipe the actor doesn't know yet (see `KnowHow`) for making
+    /// something that would do for a goal, that it has everything to hand to
+    /// try out, if there's one and it has the labor left to spend the tick
+    /// experimenting. Recipes are tried in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal it's after
+    /// * `items` - the world's items, for looking up recipes
+    /// * `now` - the tick being run
+    ///
+    pub fn recipe_to_discover(
+        &self,
+        goal: GoalId,
+        items: &ItemRegistry,
+        now: u64,
+    ) -> Option<RecipeId> {
+        if self.labor.left(now) < Work::Experimenting.hours() {
+            return None;
+        }
+        let wanted = self.wanted_for(goal);
+        items
+            .recipes()
+            .find(|&(id, recipe)| {
+                !self.know_how.knows(id, recipe)
+                    && recipe
+                        .outputs
+                        .iter()
+                        .any(|(item, _)| wanted.contains(&item))
+                    && recipe
+                        .needs()
+                        .all(|(item, amount)| self.inventory.count(item) >= amount)
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// A recipe for making something other than what a goal wants, to trade
+    /// for it instead, if the actor specializes (see
+    /// `ValueScale::specializes`) and there's one it would rather do that
+    /// by. That's one it knows and has everything to hand for, whose outputs
+    /// it expects to fetch more of what it wants (see
+    /// `Expectations::expected`) for every tick of work, going by its time
+    /// preference, than the best plan it has for making that itself would
+    /// (see `best_production_plan`), or than nothing, if it has none. The
+    /// recipe that fetches the most wins, which is comparative advantage:
+    /// what an actor makes comes down to what it's best at next to what
+    /// that goes for, not to what it wants. Something's only worth making
+    /// to trade while someone else is after it, though.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal it's after
+    /// * `items` - the world's items, for looking up recipes
+    /// * `actors` - everyone, for who's after what
+    ///
+    pub fn recipe_to_trade_with(
+        &self,
+        goal: GoalId,
+        items: &ItemRegistry,
+        actors: &ActorArena,
+    ) -> Option<RecipeId> {
+        if !self.values.specializes {
+            return None;
+        }
+        let wanted = self.wanted_for(goal);
+        let rate = self.values.time_preference;
+        let per_tick = |plan: &ProductionPlan| {
+            plan.present_value(rate)
+                .divided_by(Quantity::from(plan.ticks))
+        };
+        let autarky = self
+            .best_production_plan(goal, items)
+            .map(|plan| per_tick(&plan))
+            .unwrap_or(Quantity::ZERO);
+        let customer = |item: ItemId| {
+            actors.iter().any(|other| {
+                other.id != self.id
+                    && other
+                        .values
+                        .top_goal()
+                        .is_some_and(|goal| other.wanted_for(goal).contains(&item))
+            })
+        };
+        items
+            .recipes()
+            .filter(|&(id, recipe)| {
+                self.know_how.knows(id, recipe)
+                    && self.labor.budget >= recipe.labor
+                    && recipe
+                        .outputs
+                        .iter()
+                        .all(|(item, _)| !wanted.contains(&item))
+                    && recipe.outputs.iter().any(|(item, _)| customer(item))
+                    && recipe
+                        .needs()
+                        .all(|(item, amount)| self.inventory.count(item) >= amount)
+                    && self.worth_making(recipe)
+            })
+            .filter_map(|(id, recipe)| {
+                let fetches = wanted
+                    .iter()
+                    .map(|&want| {
+                        recipe
+                            .outputs
+                            .iter()
+                            .filter_map(|(item, amount)| {
+                                self.expectations
+                                    .expected(item, want)
+                                    .map(|ratio| amount.times(ratio))
+                            })
+                            .sum()
+                    })
+                    .max()?;
+                let plan = ProductionPlan {
+                    stages: vec![id],
+                    ticks: recipe.duration,
+                    output: fetches,
+                    byproducts: Bundle::new(),
+                };
+                Some((id, per_tick(&plan)))
+            })
+            .filter(|&(_, fetches)| fetches > autarky)
+            .max_by_key(|&(id, fetches)| (fetches, Reverse(id)))
+            .map(|(id, _)| id)
+    }
+
+    /// The first recipe of the best plan the actor has for making something
+    /// that would do for a goal (see `best_production_plan`), if it has one.
+    pub fn find_recipe_for_goal(&self, goal: GoalId, items: &ItemRegistry) -> Option<RecipeId> {
+        self.best_production_plan(goal, items)
+            .map(|plan| plan.stages[0])
+    }
+
+    /// Of all the ways the actor could make something that would do for a
+    /// goal out of what it has (see `production_plans`), the one whose
+    /// output it values most now, going by its time preference (see
+    /// `ProductionPlan::present_value`). An impatient actor makes do with
+    /// whatever's quickest, and a patient one takes the long way round if
+    /// it makes more. Ties go to the plan whose byproducts the actor values
+    /// more (see `compare_bundles`), and then to the quicker plan.
+    pub fn best_production_plan(
+        &self,
+        goal: GoalId,
+        items: &ItemRegistry,
+    ) -> Option<ProductionPlan> {
+        let rate = self.values.time_preference;
+        self.production_plans(goal, items).into_iter().fold(
+            None,
+            |best: Option<ProductionPlan>, plan| match best {
+                Some(best) => {
+                    let order = best
+                        .present_value(rate)
+                        .cmp(&plan.present_value(rate))
+                        .then_with(|| self.compare_bundles(&best.byproducts, &plan.byproducts))
+                        .then(plan.ticks.cmp(&best.ticks));
+                    Some(if order == Ordering::Less { plan } else { best })
+                }
+                None => Some(plan),
+            },
+        )
+    }
+
+    /// Every way the actor could make something that would do for a goal,
+    /// starting from what it has now, in up to `MAX_STAGES` stages.
+    ///
+    /// # Notes
+    ///
+    /// The actor only goes for a recipe it has enough labor each tick to do
+    /// the work of (unless it hires labor, see `ValueScale::hires_labor`),
+    /// and whose outputs it values at least as much as
+    /// whatever of its inputs it values for its own sake (producer's goods
+    /// are only worth anything for what they make, see
+    /// `ValueScale::set_imputations`, so using them to make it is no loss).
+    /// A recipe it's short one thing for (an input or a tool) can still be
+    /// the last stage of a plan, if there's another plan for making enough
+    /// of that thing first; it only ever plans one thing at a time that
+    /// way, so a recipe it's short two things for is out.
+    pub fn production_plans(&self, goal: GoalId, items: &ItemRegistry) -> Vec<ProductionPlan> {
+        self.plans_for(&self.wanted_for(goal), items, MAX_STAGES)
+    }
+
+    /// Every plan of up to `stages` stages for making any of `wanted`.
+    fn plans_for(
+        &self,
+        wanted: &[ItemId],
+        items: &ItemRegistry,
+        stages: usize,
+    ) -> Vec<ProductionPlan> {
+        let mut plans = vec![];
+        if stages == 0 {
+            return plans;
+        }
+        for (id, recipe) in items.recipes() {
+            let output: Quantity = recipe
+                .outputs
+                .iter()
+                .filter(|(item, _)| wanted.contains(item))
+                .map(|(_, amount)| amount)
+                .sum();
+            let enough_labor = self.labor.budget >= recipe.labor || self.values.hires_labor;
+            if output.is_zero()
+                || !enough_labor
+                || !self.know_how.knows(id, recipe)
+                || !self.worth_making(recipe)
+            {
+                continue;
+            }
+            let short: Vec<(ItemId, Quantity)> = recipe
+                .needs()
+                .filter(|&(item, amount)| self.inventory.count(item) < amount)
+                .map(|(item, amount)| (item, amount - self.inventory.count(item)))
+                .collect();
+            let byproducts = recipe.outputs.filter(|item| !wanted.contains(&item));
+            match short[..] {
+                [] => plans.push(ProductionPlan {
+                    stages: vec![id],
+                    ticks: recipe.duration,
+                    output,
+                    byproducts,
+                }),
+                // It'll have to make it first
+                [(item, amount)] => {
+                    for before in self.plans_for(&[item], items, stages - 1) {
+                        if before.output >= amount && !before.stages.contains(&id) {
+                            let ticks = before.ticks + recipe.duration;
+                            let mut stages = before.stages;
+                            stages.push(id);
+                            let mut byproducts = before.byproducts;
+                            for (item, amount) in recipe
+                                .outputs
+                                .iter()
+                                .filter(|(item, _)| !wanted.contains(item))
+                            {
+                                byproducts.add(item, amount);
+                            }
+                            plans.push(ProductionPlan {
+                                stages,
+                                ticks,
+                                output,
+                                byproducts,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        plans
+    }
+
+    /// Whether the actor values what a recipe makes at least as much as
+    /// whatever it uses up that the actor values for its own sake.
+    fn worth_making(&self, recipe: &Recipe) -> bool {
+        let consumed = recipe
+            .inputs
+            .filter(|item| !self.values.imputations.contains_key(&item));
+        consumed.is_empty() || self.compare_bundles(&recipe.outputs, &consumed) != Ordering::Less
+    }
+
+    /// Like `find_item_for_goal`, but without caring about complements.
+    fn satisfactions_held(&self, goal: GoalId) -> Vec<ItemId> {
+        let mut possibilities = vec![];
+        let opts = self.values.satisfiers(goal);
+        for item in self.ordered_inventory() {
+            // Producer's goods are for making things, not for consuming
+            if opts.contains(&item)
+                && !self.values.imputations.contains_key(&item)
+                && !self.inventory.spare_for(item, goal).is_zero()
+            {
+                // If we have an item whose best use is for this goal...
+                if self.values.get_best_goal(item) == Some(goal) {
+                    // ...jackpot, use it!
+                    possibilities.push(item);
+                    break;
+                } else {
+                    // ...otherwise, We want to use the least-valued
+                    // item that can satisfy our need, so insert
+                    // sortedly into vector (slow-ass, but I'm lazy and
+                    // don't want to make another binary heap wrapper
+                    // (argh!))
+                    possibilities.push(item);
+                }
+            }
+        }
+        possibilities
+    }
+
+    /// The complements a goal needs that the actor doesn't have any of.
+    pub fn missing_complements(&self, goal: GoalId) -> Vec<ItemId> {
+        self.values
+            .complements
+            .get(&goal)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&item| !self.inventory.contains(item))
+            .collect()
+    }
+
+    /// The items worth trading for to satisfy a goal: any of its
+    /// satisfactions, or, once the actor has one of those, whichever of its
+    /// complements are still missing.
+    pub fn wanted_for(&self, goal: GoalId) -> Vec<ItemId> {
+        let missing = self.missing_complements(goal);
+        if !missing.is_empty() && !self.satisfactions_held(goal).is_empty() {
+            missing
+        } else {
+            self.values.satisfiers(goal)
+        }
+    }
+
+    /// The goal the actor would take `offer` in exchange for `give` to trade
+    /// on towards, even though it has no use for `offer` itself, if any. This
+    /// is indirect exchange, the way Menger had money come about: the actor
+    /// takes `offer` if it's seen it accepted in more trades than `give`
+    /// (so it should be easier to get rid of), and it has a goal it can't
+    /// satisfy with what it has that it values more than `give`, which it
+    /// can hope to trade `offer` on for.
+    ///
+    /// # Arguments
+    ///
+    /// * `offer` - the good on offer
+    /// * `give` - the good the actor would be giving up for it
+    ///
+    pub fn would_take_as_medium(&self, offer: ItemId, give: ItemId) -> Option<GoalId> {
+        if offer == give || self.values.saleability(offer) <= self.values.saleability(give) {
+            return None;
+        }
+        let give_rank = self
+            .values
+            .get_best_goal(give)
+            .map_or(usize::MAX, |g| self.values.rank(g));
+        self.values
+            .current_goals
+            .clone()
+            .into_sorted_vec()
+            .into_iter()
+            .rev()
+            .map(|g| g.goal)
+            .take_while(|&goal| self.values.rank(goal) < give_rank)
+            .find(|&goal| {
+                !self.values.is_leisure(goal)
+                    && self.find_item_for_goal(goal).is_empty()
+                    && !self.needs_for(goal, offer)
+            })
+    }
+
+    /// Whether an item goes towards a goal at all (as a satisfaction or a
+    /// complement), in which case it shouldn't be traded away for that goal.
+    /// Nor should anything that's all set aside for a production plan (see
+    /// `Inventory::reserve`).
+    pub fn needs_for(&self, goal: GoalId, item: ItemId) -> bool {
+        self.values.goal_items(goal).contains(&item)
+            || (self.inventory.contains(item) && self.inventory.spare(item).is_zero())
+    }
+
+    /// Every kind of item in the inventory, least valued first.
+    pub fn ordered_inventory(&self) -> Vec<ItemId> {
+        self.values.order_inventory(self.inventory)
+    }
+
+    /// Every kind of item in the inventory that's one of `items`, least
+    /// valued first.
+    pub fn has_item_of(&self, items: &[ItemId]) -> Vec<ItemId> {
+        self.ordered_inventory()
+            .into_iter()
+            .filter(|x| items.contains(x))
+            .collect()
+    }
+
+    /// Compare two items to see which this actor values more. See
+    /// `ValueScale::compare_item_values`.
+    pub fn compare_item_values(&self, a: ItemId, b: ItemId) -> Ordering {
+        self.values.compare_item_values(a, b)
+    }
+
+    /// Compare two bundles to see which this actor values more. See
+    /// `ValueScale::compare_bundles`.
+    pub fn compare_bundles(&self, a: &Bundle, b: &Bundle) -> Ordering {
+        self.values.compare_bundles(a, b)
+    }
+
+    /// The money the actor would spend first, and how much of it, if it has
+    /// any at all (see `monies_held`).
+    pub fn money_held(&self) -> Option<(ItemId, Quantity)> {
+        self.monies_held().first().copied()
+    }
+
+    /// Every money the actor holds, and how much of each, in the order it'd
+    /// spend them: whichever it values least first, since it'd sooner keep
+    /// any it values more for itself, and of monies it values the same,
+    /// whichever it holds the most of.
+    pub fn monies_held(&self) -> Vec<(ItemId, Quantity)> {
+        let mut monies: Vec<_> = self
+            .inventory
+            .iter()
+            .filter(|(item, _)| self.values.is_money(*item))
+            .collect();
+        monies.sort_by(|(a, a_amount), (b, b_amount)| {
+            self.compare_item_values(*a, *b)
+                .then(b_amount.cmp(a_amount))
+                .then(a.cmp(b))
+        });
+        monies
+    }
+
+    /// Whether the actor has room for everything it'd be `taking`, counting
+    /// whatever it'd be `giving` up in return and anything it values less
+    /// than all of what it's taking, which it'd be willing to throw out to
+    /// make room.
+    pub fn can_fit(&self, taking: &Bundle, giving: &Bundle) -> bool {
+        let capacity = match self.inventory.capacity() {
+            Some(capacity) => capacity,
+            None => return true,
+        };
+        let least_taken = match taking
+            .iter()
+            .map(|(i, _)| i)
+            .min_by(|&a, &b| self.compare_item_values(a, b))
+        {
+            Some(item) => item,
+            None => return true,
+        };
+        let held = |i: ItemId| self.inventory.count(i).saturating_sub(giving.count(i));
+        let ordered = self.ordered_inventory();
+        let total: Quantity = ordered.iter().map(|&i| held(i)).sum();
+        let mut over = (total + taking.total()).saturating_sub(capacity);
+        for i in ordered {
+            if over.is_zero() {
+                break;
+            }
+            if !taking.contains(i) && self.compare_item_values(i, least_taken) == Ordering::Less {
+                over = over.saturating_sub(held(i));
+            }
+        }
+        over.is_zero()
+    }
+
+    /// Whether a durable good the actor has in use is already taking care of
+    /// a goal, so there's no need to use or trade for anything else for it.
+    pub fn is_served(&self, goal: GoalId) -> bool {
+        self.inventory.is_serving(goal)
+    }
+
+    /// Whether the actor has anything to do next tick: goals in play, a
+    /// trade in progress, or something to stock up on. Actors with nothing to
+    /// do are left alone until their next goal comes due.
+    pub fn is_busy(&self) -> bool {
+        !self.values.current_goals.is_empty()
+            || *self.state != ActorState::SearchingForGoal
+            || self.stockpile_goal().is_some()
+    }
+
+    /// The recurring goal the actor would stock up for ahead of time, if it
+    /// stockpiles at all (see `ValueScale::stockpiles`) and has nothing in
+    /// play right now: the most valued one that isn't in play, that it
+    /// doesn't already have anything for, and whose items look cheap. An
+    /// item looks cheap if the actor expects a unit of something else it has
+    /// to fetch at least a unit of it (see `Expectations::expected`), so it
+    /// has to have seen it traded first.
+    ///
+    /// # Notes
+    ///
+    /// Only goals that come back around like clockwork count: there's no
+    /// telling when one that comes up at random will be back, so no telling
+    /// whether anything got for it now would keep till then.
+    pub fn stockpile_goal(&self) -> Option<GoalId> {
+        if !self.values.stockpiles || !self.values.current_goals.is_empty() {
+            return None;
+        }
+        self.values
+            .indifference_classes()
+            .into_iter()
+            .flatten()
+            .find(|&goal| {
+                self.values.recurrence(goal).is_some()
+                    && !self.values.dormant.contains(&goal)
+                    && !self.values.shelved.contains(&goal)
+                    && !self.is_served(goal)
+                    && self.find_item_for_goal(goal).is_empty()
+                    && self.looks_cheap(goal)
+            })
+    }
+
+    /// What the actor would set aside now for the recurring goals it knows
+    /// are coming back around, if it saves at all (see `ValueScale::saves`),
+    /// most valued goal first: for every one it ranks above everything it
+    /// has in play, whatever it has that would do for it, up to all it
+    /// takes, and if that's not enough, the money it expects to buy the rest
+    /// with (see `Expectations::expected`). Saved goods aren't used up on
+    /// anything else or traded away (see `Inventory::reserve`), so an actor
+    /// with only lesser wants right now puts off seeing to them, or trades
+    /// for them instead.
+    ///
+    /// # Notes
+    ///
+    /// How far ahead the actor looks depends on its time preference: it
+    /// only saves for a goal that comes back within one over its time
+    /// preference ticks, so an impatient actor consumes now what a patient
+    /// one would put by, and one with no time preference at all looks as
+    /// far ahead as it likes. Like stockpiling, only goals that come back
+    /// around like clockwork count.
+    pub fn savings(&self) -> Vec<(GoalId, Bundle)> {
+        if !self.values.saves {
+            return vec![];
+        }
+        let rate = self.values.time_preference;
+        let top = self.values.top_goal().map(|goal| self.values.rank(goal));
+        let money = self.money_held();
+        self.values
+            .indifference_classes()
+            .into_iter()
+            .flatten()
+            .filter(|&goal| {
+                top.is_none_or(|top| self.values.rank(goal) < top)
+                    && !self.values.is_in_play(goal)
+                    && !self.values.dormant.contains(&goal)
+                    && !self.values.shelved.contains(&goal)
+                    && !self.is_served(goal)
+            })
+            .filter_map(|goal| {
+                let wait = Quantity::from(self.values.recurrence(goal)? as u32);
+                if !rate.is_zero() && wait.times(rate) > Quantity::ONE {
+                    return None;
+                }
+                let mut short = self.values.units_required(goal)?;
+                let mut saved = Bundle::new();
+                let satisfiers = self.values.satisfiers(goal);
+                for &item in satisfiers.iter() {
+                    if self.values.imputations.contains_key(&item) {
+                        continue;
+                    }
+                    let amount = self.inventory.count(item).min(short);
+                    if !amount.is_zero() {
+                        saved.add(item, amount);
+                        short -= amount;
+                    }
+                }
+                if let Some((money, held)) = money.filter(|_| !short.is_zero()) {
+                    let price = satisfiers
+                        .iter()
+                        .filter_map(|&item| self.expectations.expected(item, money))
+                        .min();
+                    if let Some(price) = price {
+                        saved.add(money, short.times(price).min(held));
+                    }
+                }
+                (!saved.is_empty()).then_some((goal, saved))
+            })
+            .collect()
+    }
+
+    /// Whether the actor expects to get something towards `goal` for no
+    /// more than a unit of something it has that won't do for it.
+    fn looks_cheap(&self, goal: GoalId) -> bool {
+        let wanted = self.wanted_for(goal);
+        wanted.iter().any(|&item| {
+            self.inventory
+                .iter()
+                .map(|(i, _)| i)
+                .filter(|held| !wanted.contains(held))
+                .any(|held| {
+                    self.expectations
+                        .expected(item, held)
+                        .is_some_and(|price| price <= Quantity::ONE)
+                })
+        })
+    }
+}
+
+/// Mutable access to one actor's components, as handed out by the arena.
+/// Different components of the same actor (or the same component of two
+/// different actors) can be changed at once, since they're all just separate
+/// borrows of separate columns.
+pub struct ActorMut<'a> {
+    /// Handle to this actor in its arena
+    pub id: ActorId,
+    /// Name for printouts
+    pub name: &'a str,
+    /// Goals and how the actor values them
+    pub values: &'a mut ValueScale,
+    /// Items the actor has
+    pub inventory: &'a mut Inventory,
+    /// Actor internal AI state
+    pub state: &'a mut ActorState,
+    /// Hours the actor has to work (or rest) each tick
+    pub labor: &'a mut Labor,
+    /// History of the goals the actor has seen to
+    pub ledger: &'a mut SatisfactionLedger,
+    /// IOUs the actor has written and not yet settled
+    pub credit: &'a mut CreditBook,
+    /// Who the actor remembers trading with, and how it went
+    pub memory: &'a mut TradeMemory,
+    /// Every bid and trade the actor has been part of
+    pub trades: &'a mut TradeLedger,
+    /// What the actor's given up and got, for working out its profit and
+    /// loss
+    pub accounts: &'a mut Accounts,
+    /// The recipes the actor's found out about that not everyone knows
+    pub know_how: &'a mut KnowHow,
+    /// What the actor expects goods to go for, from the trades it's seen
+    pub expectations: &'a mut Expectations,
+    /// The tick it is, so whatever the actor gets can be stamped with when
+    pub now: u64,
+}
+
+impl<'a> ActorMut<'a> {
+    /// A read-only look at the same actor.
+    pub fn as_ref(&self) -> ActorRef<'_> {
+        ActorRef {
+            id: self.id,
+            name: self.name,
+            values: self.values,
+            inventory: self.inventory,
+            state: self.state,
+            labor: self.labor,
+            ledger: self.ledger,
+            credit: self.credit,
+            memory: self.memory,
+            trades: self.trades,
+            accounts: self.accounts,
+            know_how: self.know_how,
+            expectations: self.expectations,
+        }
+    }
+
+    /// See `ActorRef::ordered_inventory`.
+    pub fn ordered_inventory(&self) -> Vec<ItemId> {
+        self.as_ref().ordered_inventory()
+    }
+
+    /// See `ActorRef::has_item_of`.
+    pub fn has_item_of(&self, items: &[ItemId]) -> Vec<ItemId> {
+        self.as_ref().has_item_of(items)
+    }
+
+    /// See `ValueScale::rerank_goal`.
+    pub fn rerank_goal(&mut self, goal: GoalId, new_index: usize) -> bool {
+        self.values.rerank_goal(goal, new_index)
+    }
+
+    /// See `ValueScale::make_indifferent`.
+    pub fn make_indifferent(&mut self, goal: GoalId, to: GoalId) -> bool {
+        self.values.make_indifferent(goal, to)
+    }
+
+    /// See `ValueScale::compare_item_values`.
+    pub fn compare_item_values(&self, a: ItemId, b: ItemId) -> Ordering {
+        self.values.compare_item_values(a, b)
+    }
+
+    /// See `ValueScale::compare_bundles`.
+    pub fn compare_bundles(&self, a: &Bundle, b: &Bundle) -> Ordering {
+        self.values.compare_bundles(a, b)
+    }
+
+    /// Adds a unit of an item to the inventory
+    pub fn add_item(&mut self, item: ItemId) {
+        self.inventory.add_at(
+            item,
+            Quantity::ONE,
+            Acquisition {
+                from: None,
+                tick: self.now,
+            },
+        );
+    }
+
+    /// Takes some of an item into the inventory, keeping within the actor's
+    /// carrying capacity: the least valued things it has are thrown out to
+    /// make room, as long as they're valued less than what's coming in, and
+    /// whatever there still isn't room for is left behind.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - what's being received
+    /// * `amount` - how much of it
+    ///
+    pub fn receive(&mut self, item: ItemId, amount: Quantity) -> Vec<SimEvent> {
+        self.inventory.add_at(
+            item,
+            amount,
+            Acquisition {
+                from: None,
+                tick: self.now,
+            },
+        );
+        let mut events = self.discard_overflow(item);
+        let left_behind = self.inventory.overflow();
+        if !left_behind.is_zero() {
+            self.inventory.remove(item, left_behind);
+            events.push(SimEvent::ItemDiscarded {
+                actor: self.id,
+                item,
+                amount: left_behind,
+            });
+        }
+        events
+    }
+
+    /// Throws out whatever's valued least until the inventory is back within
+    /// its capacity, but never anything valued as much as `keep` (or `keep`
+    /// itself).
+    pub fn discard_overflow(&mut self, keep: ItemId) -> Vec<SimEvent> {
+        let mut events = vec![];
+        for item in self.ordered_inventory() {
+            let over = self.inventory.overflow();
+            if over.is_zero() {
+                break;
+            }
+            if item == keep || self.compare_item_values(item, keep) != Ordering::Less {
+                continue;
+            }
+            let amount = self.inventory.count(item).min(over);
+            self.inventory.remove(item, amount);
+            events.push(SimEvent::ItemDiscarded {
+                actor: self.id,
+                item,
+                amount,
+            });
+        }
+        events
+    }
+
+    /// Puts any goals whose prerequisites have now been met into play. See
+    /// `ValueScale::activate_goals`.
+    pub fn activate_goals(&mut self) -> Vec<SimEvent> {
+        self.values
+            .activate_goals()
+            .into_iter()
+            .map(|goal| SimEvent::GoalActivated {
+                actor: self.id,
+                goal,
+            })
+            .collect()
+    }
+
+    /// Puts a recurring goal back into play now that its timer has run out.
+    /// See `ValueScale::reintroduce_goal`.
+    pub fn goal_due(&mut self, goal: GoalId) -> Vec<SimEvent> {
+        if self.values.reintroduce_goal(goal) {
+            vec![SimEvent::GoalReintroduced {
+                actor: self.id,
+                goal,
+            }]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Uses an item to satisfy the goal selected
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - `ItemId` to use
+    /// * `goal` - `GoalId` to satisfy
+    /// * `items` - the world's items, for how long the item lasts if it's
+    ///   durable (see `ItemDef::durability`) and how much it counts for (see
+    ///   `ItemDef::quality`)
+    ///
+    /// # Notes
+    ///
+    /// At most one unit is used at a time, and never more than the goal still
+    /// needs, so an actor with 0.5 litres of water left over uses just that,
+    /// and an actor that only needs half a unit more only uses half a unit.
+    ///
+    /// Durable goods aren't used up: the unit is put in service instead, and
+    /// keeps putting a unit towards the goal every tick (see `serve`) until
+    /// it's worn out.
+    ///
+    /// If the goal has complements, the same amount of each of them is used
+    /// up along with the item.
+    ///
+    pub fn use_item_for_goal(
+        &mut self,
+        item: ItemId,
+        goal: GoalId,
+        items: &ItemRegistry,
+    ) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let durability = items.durability(item);
+        let quality = items.quality(item);
+        let held = self.inventory.count(item).min(Quantity::ONE);
+        let amount = match durability {
+            Some(_) => held,
+            None => held.min(
+                self.values
+                    .units_needed(goal)
+                    .map(|needed| needed.divided_by(quality))
+                    .unwrap_or(Quantity::ONE),
+            ),
+        };
+        // Complements all have to be there to go along with it, or none of
+        // them get used
+        if !amount.is_zero() {
+            if let Some(missing) = self.complement_short(goal, amount) {
+                events.push(SimEvent::ItemMissing {
+                    actor: self.id,
+                    item: missing,
+                    goal,
+                });
+                return events;
+            }
+        }
+        let used = !amount.is_zero()
+            && match durability {
+                // This tick's service is the one it's being used for now
+                Some(ticks) => {
+                    self.inventory
+                        .put_in_service(item, amount, goal, ticks.saturating_sub(1))
+                }
+                None => self.inventory.remove(item, amount),
+            };
+        if used {
+            events.push(SimEvent::ItemUsed {
+                actor: self.id,
+                item,
+                amount,
+                goal,
+            });
+            events.extend(self.use_complements(goal, amount));
+            if self.values.put_towards(goal, amount.times(quality)) {
+                events.push(SimEvent::GoalSatisfied {
+                    actor: self.id,
+                    goal,
+                });
+            }
+        } else {
+            events.push(SimEvent::ItemMissing {
+                actor: self.id,
+                item,
+                goal,
+            });
+        }
+        events
+    }
+
+    /// Uses a public good from the commons towards a goal. It isn't used up,
+    /// and doesn't have to be held, so this only takes up any of the goal's
+    /// complements (from the actor's own inventory).
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - `ItemId` of the public good to use
+    /// * `goal` - `GoalId` to satisfy
+    /// * `items` - the world's items, for how much the good counts for (see
+    ///   `ItemDef::quality`)
+    ///
+    pub fn use_public_good(
+        &mut self,
+        item: ItemId,
+        goal: GoalId,
+        items: &ItemRegistry,
+    ) -> Vec<SimEvent> {
+        let quality = items.quality(item);
+        let amount = self
+            .values
+            .units_needed(goal)
+            .map(|needed| needed.divided_by(quality))
+            .unwrap_or(Quantity::ONE)
+            .min(Quantity::ONE);
+        if let Some(missing) = self.complement_short(goal, amount) {
+            return vec![SimEvent::ItemMissing {
+                actor: self.id,
+                item: missing,
+                goal,
+            }];
+        }
+        let mut events = vec![SimEvent::PublicGoodUsed {
+            actor: self.id,
+            item,
+            goal,
+        }];
+        events.extend(self.use_complements(goal, amount));
+        if self.values.put_towards(goal, amount.times(quality)) {
+            events.push(SimEvent::GoalSatisfied {
+                actor: self.id,
+                goal,
+            });
+        }
+        events
+    }
+
+    /// Spends whatever labor the actor has left this tick resting, putting it
+    /// towards a goal (leisure, usually). A whole tick of rest is worth a unit
+    /// towards the goal, and part of a tick part of a unit. Nothing happens if
+    /// the goal isn't in play or there's no labor left.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal rest goes towards
+    ///
+    pub fn rest(&mut self, goal: GoalId) -> Vec<SimEvent> {
+        if !self.values.is_in_play(goal) || self.labor.left(self.now).is_zero() {
+            return vec![];
+        }
+        let hours = self.labor.spend_rest(self.now);
+        let mut events = vec![SimEvent::Rested {
+            actor: self.id,
+            hours,
+            goal,
+        }];
+        if self
+            .values
+            .put_towards(goal, hours.divided_by(self.labor.budget))
+        {
+            events.push(SimEvent::GoalSatisfied {
+                actor: self.id,
+                goal,
+            });
+        }
+        events
+    }
+
+    /// Spends a tick's work gathering a unit of whatever a resource node
+    /// yields (or what's left of it this tick, if that's less), towards a
+    /// goal. If somebody else got there first and there's nothing left, the
+    /// tick's wasted.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the node's ID, for reporting
+    /// * `node` - the node to gather from
+    /// * `goal` - the goal it's being gathered for
+    /// * `items` - the world's items, for whether what it yields is divisible
+    ///
+    pub fn gather(
+        &mut self,
+        id: ResourceId,
+        node: &mut ResourceNode,
+        goal: GoalId,
+        items: &ItemRegistry,
+    ) -> Vec<SimEvent> {
+        if !self.labor.spend(Work::Gathering.hours(), self.now) {
+            return vec![SimEvent::OutOfLabor {
+                actor: self.id,
+                work: Work::Gathering,
+            }];
+        }
+        let amount = node.take(Quantity::ONE, items.is_divisible(node.item), self.now);
+        if amount.is_zero() {
+            return vec![SimEvent::ResourceExhausted {
+                actor: self.id,
+                node: id,
+            }];
+        }
+        let mut events = vec![SimEvent::ResourceGathered {
+            actor: self.id,
+            node: id,
+            item: node.item,
+            amount,
+            goal,
+        }];
+        events.extend(self.receive(node.item, amount));
+        events
+    }
+
+    /// Spends a tick's work experimenting with what the actor has, to find
+    /// out how to make things by a recipe it doesn't know yet (see
+    /// `KnowHow::experiment`). Nothing gets used up, but if nothing comes of
+    /// it the tick's wasted.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipe` - the recipe it's feeling its way towards
+    /// * `roll` - a random number from 0 to 1, for whether it finds it
+    /// * `items` - the world's items, for looking up the recipe
+    ///
+    pub fn experiment(
+        &mut self,
+        recipe: RecipeId,
+        roll: f64,
+        items: &ItemRegistry,
+    ) -> Vec<SimEvent> {
+        let def = match items.recipe(recipe) {
+            Some(def) if !self.know_how.knows(recipe, def) => def,
+            _ => return vec![],
+        };
+        let hours = Work::Experimenting.hours();
+        if !self.labor.spend(hours, self.now) {
+            return vec![SimEvent::OutOfLabor {
+                actor: self.id,
+                work: Work::Experimenting,
+            }];
+        }
+        if !self.know_how.experiment(recipe, def, hours, roll) {
+            return vec![];
+        }
+        vec![SimEvent::RecipeDiscovered {
+            actor: self.id,
+            recipe,
+        }]
+    }
+
+    /// Spends a couple of hours maintaining a tool, using up whatever it
+    /// takes (see `ItemDef::maintenance`) to put it back as good as new.
+    /// Nothing happens if the actor doesn't have all of that, or the labor.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool` - the tool to maintain
+    /// * `items` - the world's items, for what it takes
+    ///
+    pub fn maintain(&mut self, tool: ItemId, items: &ItemRegistry) -> Vec<SimEvent> {
+        let inputs = items.maintenance(tool);
+        if inputs.is_empty()
+            || !self.inventory.contains(tool)
+            || inputs
+                .iter()
+                .any(|(item, amount)| self.inventory.count(item) < amount)
+        {
+            return vec![];
+        }
+        if !self.labor.spend(Work::Maintenance.hours(), self.now) {
+            return vec![SimEvent::OutOfLabor {
+                actor: self.id,
+                work: Work::Maintenance,
+            }];
+        }
+        for (item, amount) in inputs.iter() {
+            self.inventory.remove(item, amount);
+        }
+        self.inventory.repair_tool(tool);
+        vec![SimEvent::ToolMaintained {
+            actor: self.id,
+            tool,
+            inputs,
+        }]
+    }
+
+    /// Puts a tick's work into making something by a recipe, for a goal.
+    /// On the first tick the inputs go in (if the actor still has them all,
+    /// and the tools), and the actor's busy producing until it's put in as
+    /// many ticks of work as the recipe takes, when the outputs come out into
+    /// its inventory. A tick it hasn't got the labor for doesn't count, and
+    /// has to be made up later. Nothing happens if the actor doesn't know
+    /// the recipe (see `KnowHow`). If this is only one stage of a longer plan
+    /// (see `ActorRef::production_plans`), the actor plans again once it's
+    /// done, and carries on from what it's made.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipe` - what's being made, and how
+    /// * `goal` - the goal it's being made for
+    /// * `items` - the world's items, for looking up the recipe
+    ///
+    pub fn produce(
+        &mut self,
+        recipe: RecipeId,
+        goal: GoalId,
+        items: &ItemRegistry,
+    ) -> Vec<SimEvent> {
+        let def = match items.recipe(recipe) {
+            Some(def) if self.know_how.knows(recipe, def) => def,
+            _ => return vec![],
+        };
+        let mut events = vec![];
+        let started = matches!(*self.state, ActorState::Producing(r, _, _) if r == recipe);
+        if !started {
+            if let Some((item, _)) = def
+                .needs()
+                .find(|&(item, amount)| self.inventory.count(item) < amount)
+            {
+                events.push(SimEvent::ItemMissing {
+                    actor: self.id,
+                    item,
+                    goal,
+                });
+                // The plan's off, so there's nothing to keep for it
+                self.inventory.release(goal);
+                *self.state = ActorState::SearchingForGoal;
+                return events;
+            }
+        }
+        if !self.labor.spend(def.labor, self.now) {
+            events.push(SimEvent::OutOfLabor {
+                actor: self.id,
+                work: Work::Production,
+            });
+            return events;
+        }
+        let ticks_left = match *self.state {
+            ActorState::Producing(_, _, ticks_left) if started => ticks_left,
+            _ => {
+                // If this is the first stage of a longer plan, whatever the
+                // rest of it takes has to be kept for it
+                let later = self
+                    .as_ref()
+                    .best_production_plan(goal, items)
+                    .filter(|plan| plan.stages[0] == recipe)
+                    .map(|plan| plan.later_needs(items))
+                    .unwrap_or_default();
+                self.inventory.release(goal);
+                for (item, amount) in def.inputs.iter() {
+                    self.inventory.remove(item, amount);
+                }
+                events.push(SimEvent::ProductionStarted {
+                    actor: self.id,
+                    recipe,
+                    goal,
+                });
+                let reserved = self.inventory.reserve(goal, &later);
+                if !reserved.is_empty() {
+                    events.push(SimEvent::ItemsReserved {
+                        actor: self.id,
+                        goal,
+                        items: reserved,
+                    });
+                }
+                def.duration
+            }
+        } - 1;
+        // Tools that wear out put another tick in
+        for (tool, _) in def.tools.iter() {
+            let worn_out = match items.durability(tool) {
+                Some(durability) => self.inventory.use_tool(tool, durability),
+                None => false,
+            };
+            if worn_out {
+                events.push(SimEvent::ItemWornOut {
+                    actor: self.id,
+                    item: tool,
+                });
+            }
+        }
+        if ticks_left > 0 {
+            *self.state = ActorState::Producing(recipe, goal, ticks_left);
+            return events;
+        }
+        let made = Acquisition {
+            from: None,
+            tick: self.now,
+        };
+        for (item, amount) in def.outputs.iter() {
+            self.inventory.add_at(item, amount, made);
+        }
+        events.push(SimEvent::ItemsProduced {
+            actor: self.id,
+            recipe,
+            outputs: def.outputs.clone(),
+        });
+        for (item, _) in def.outputs.iter() {
+            events.extend(self.discard_overflow(item));
+        }
+        *self.state = ActorState::SearchingForGoal;
+        events
+    }
+
+    /// The first of a goal's complements there isn't `amount` of, if any.
+    fn complement_short(&self, goal: GoalId, amount: Quantity) -> Option<ItemId> {
+        self.values
+            .complements
+            .get(&goal)?
+            .iter()
+            .copied()
+            .find(|&c| self.inventory.count(c) < amount)
+    }
+
+    /// Uses up `amount` of each of a goal's complements.
+    fn use_complements(&mut self, goal: GoalId, amount: Quantity) -> Vec<SimEvent> {
+        let complements = self.values.complements.get(&goal).cloned();
+        let mut events = vec![];
+        for complement in complements.unwrap_or_default() {
+            self.inventory.remove(complement, amount);
+            events.push(SimEvent::ItemUsed {
+                actor: self.id,
+                item: complement,
+                amount,
+                goal,
+            });
+        }
+        events
+    }
+
+    /// Lets a tick go by for the durable goods this actor has in use: each one
+    /// puts another unit towards its goal (if the goal is in play), and any
+    /// that have served their time wear out.
+    pub fn serve(&mut self, items: &ItemRegistry) -> Vec<SimEvent> {
+        let mut events = vec![];
+        for service in self.inventory.wear() {
+            if self.values.is_in_play(service.goal) {
+                events.push(SimEvent::ItemServed {
+                    actor: self.id,
+                    item: service.item,
+                    goal: service.goal,
+                });
+                let amount = service.amount.times(items.quality(service.item));
+                if self.values.put_towards(service.goal, amount) {
+                    events.push(SimEvent::GoalSatisfied {
+                        actor: self.id,
+                        goal: service.goal,
+                    });
+                }
+            }
+            if service.ticks_left == 0 {
+                events.push(SimEvent::ItemWornOut {
+                    actor: self.id,
+                    item: service.item,
+                });
+            }
+        }
+        events
+    }
+
+    /// Carries out the parts of an action chosen by this actor's behavior
+    /// that only involve this actor. When the action is to keep trading, the
+    /// actual negotiation is left to the world's `TradeProtocol`, which the
+    /// simulation runs straight after this (it needs the other actors too).
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - what the actor has decided to do
+    /// * `items` - the world's items, for looking up how they get used
+    ///
+    /// # Notes
+    ///
+    /// Every actor decides what to do at the start of the tick, before anyone
+    /// acts, so by the time this runs someone may have started bidding on this
+    /// actor's items. In that case it holds still instead, so as not to pull
+    /// the rug out from under the bidder.
+    ///
+    pub fn act(&mut self, action: Action, items: &ItemRegistry) -> Vec<SimEvent> {
+        let action = match (action, &*self.state) {
+            (Action::Use { goal, .. }, ActorState::BidRecipiant(..))
+            | (Action::UsePublic { goal, .. }, ActorState::BidRecipiant(..))
+            | (Action::Rest { goal }, ActorState::BidRecipiant(..))
+            | (Action::Trade { goal }, ActorState::BidRecipiant(..))
+            | (Action::Produce { goal, .. }, ActorState::BidRecipiant(..))
+            | (Action::Gather { goal, .. }, ActorState::BidRecipiant(..))
+            | (Action::Maintain { goal, .. }, ActorState::BidRecipiant(..))
+            | (Action::Experiment { goal, .. }, ActorState::BidRecipiant(..)) => {
+                Action::Wait { goal }
+            }
+            _ => action,
+        };
+        let mut events = vec![];
+        match action {
+            Action::Idle => {
+                events.push(SimEvent::NoGoals { actor: self.id });
+            }
+            Action::Use { goal, item } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                events.extend(self.use_item_for_goal(item, goal, items));
+                *self.state = ActorState::SearchingForGoal;
+            }
+            Action::UsePublic { goal, item } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                events.extend(self.use_public_good(item, goal, items));
+                *self.state = ActorState::SearchingForGoal;
+            }
+            Action::Rest { goal } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                events.extend(self.rest(goal));
+                // Taking a tick off making something doesn't mean giving up
+                // on it
+                if !matches!(self.state, ActorState::Producing(..)) {
+                    *self.state = ActorState::SearchingForGoal;
+                }
+            }
+            Action::Trade { goal } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                if *self.state == ActorState::SearchingForGoal {
+                    events.push(SimEvent::WillingToTrade { actor: self.id });
+                    *self.state = ActorState::WillingToTrade(None);
+                }
+            }
+            Action::Produce { goal, recipe } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                events.extend(self.produce(recipe, goal, items));
+            }
+            Action::Maintain { goal, tool } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                events.extend(self.maintain(tool, items));
+                *self.state = ActorState::SearchingForGoal;
+            }
+            // Gathering takes a resource node to gather from, and
+            // experimenting takes luck, so they're up to the simulation to
+            // carry out
+            Action::Gather { goal, .. } | Action::Experiment { goal, .. } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+            }
+            Action::Wait { goal } => {
+                events.push(SimEvent::GoalSelected {
+                    actor: self.id,
+                    goal,
+                });
+                if let ActorState::BidRecipiant(..) = self.state {
+                    events.push(SimEvent::WaitingInBid { actor: self.id });
+                } else if let Some(leisure) = self.values.leisure_in_play() {
+                    // Time with nothing to do is time off
+                    events.extend(self.rest(leisure));
+                }
+            }
+            // Dealing, giving, and banking take someone to deal with, give to,
+            // or bank with, so they're up to the simulation to carry out
+            Action::Buy { .. }
+            | Action::Sell { .. }
+            | Action::Arbitrage { .. }
+            | Action::Give { .. }
+            | Action::TakeDeposit { .. }
+            | Action::Lend { .. } => {}
+        }
+        // Production always gets somewhere, even if it takes a while, and so
+        // does seeing to the tools for it
+        let producing = matches!(action, Action::Produce { .. } | Action::Maintain { .. });
+        if let (Some(goal), false) = (action.goal(), producing) {
+            events.extend(self.keep_at(goal));
+        }
+        events
+    }
+
+    /// Notes that the actor went after a goal this tick, and gives up on it
+    /// if it's run out of patience (see `ValueScale::patience`), so it can
+    /// move on down its hierarchy instead of trying to trade for it forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - the goal gone after this tick
+    ///
+    /// # Notes
+    ///
+    /// An actor in the middle of negotiating with a trade partner always sees
+    /// the negotiation through first, so nobody's left hanging; it gives up as
+    /// soon as it's back to looking for one.
+    ///
+    fn keep_at(&mut self, goal: GoalId) -> Vec<SimEvent> {
+        let ticks = self.values.note_progress(goal);
+        match self.values.patience {
+            Some(patience)
+                if ticks >= patience
+                    && matches!(
+                        *self.state,
+                        ActorState::SearchingForGoal | ActorState::WillingToTrade(..)
+                    ) =>
+            {
+                self.values.shelve_goal(goal);
+                *self.state = ActorState::SearchingForGoal;
+                vec![SimEvent::GoalAbandoned {
+                    actor: self.id,
+                    goal,
+                    ticks,
+                }]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+fn default_behavior() -> Box<dyn Behavior> {
+    Box::new(DefaultBehavior)
 }
@@ -0,0 +1,349 @@
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// One trade of one good for another, as an exchange ratio.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PricePoint {
+    /// The tick the trade happened on
+    pub tick: u64,
+    /// The item the ratio is a price of
+    pub base: ItemId,
+    /// The item the ratio is priced in
+    pub quote: ItemId,
+    /// How much of `quote` went for each unit of `base`
+    pub ratio: Quantity,
+}
+
+impl PricePoint {
+    /// The exchange ratio of something that happened on tick `tick`, if it
+    /// was a trade of one kind of item for another: there's no single ratio
+    /// between bundles of several, and nothing else is a trade at all.
+    pub fn of(event: &SimEvent, tick: u64) -> Option<PricePoint> {
+        if let SimEvent::TradeCompleted {
+            ref gave,
+            ref received,
+            ..
+        } = *event
+        {
+            if gave.len() != 1 || received.len() != 1 {
+                return None;
+            }
+            let ((a, a_amount), (b, b_amount)) = (gave.first().unwrap(), received.first().unwrap());
+            let ((base, base_amount), (quote, quote_amount)) = if a <= b {
+                ((a, a_amount), (b, b_amount))
+            } else {
+                ((b, b_amount), (a, a_amount))
+            };
+            if base == quote || base_amount.is_zero() {
+                return None;
+            }
+            return Some(PricePoint {
+                tick,
+                base,
+                quote,
+                ratio: quote_amount.divided_by(base_amount),
+            });
+        }
+        None
+    }
+}
+
+/// Every exchange ratio there's been between two goods over a run, oldest
+/// first, so you can see what things went for and how that changed. The
+/// simulation keeps it up to date from the events each tick produces (see
+/// `record`), like actors' satisfaction ledgers.
+///
+/// Ratios are always kept for a pair of items the same way round (the item
+/// with the lower ID is the base), so trades in either direction land in the
+/// same place.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct PriceHistory {
+    /// Every trade so far, oldest first
+    points: Vec<PricePoint>,
+}
+
+impl PriceHistory {
+    /// An empty history.
+    pub fn new() -> Self {
+        PriceHistory::default()
+    }
+
+    /// Updates the history with something that happened on tick `tick`.
+    /// Only trades of one kind of item for another count (see
+    /// `PricePoint::of`).
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - what happened
+    /// * `tick` - when it happened
+    ///
+    pub fn record(&mut self, event: &SimEvent, tick: u64) {
+        if let Some(point) = PricePoint::of(event, tick) {
+            self.points.push(point);
+        }
+    }
+
+    /// Every trade so far, oldest first.
+    pub fn points(&self) -> &[PricePoint] {
+        &self.points
+    }
+
+    /// Every pair of items that's ever been traded for each other, as base
+    /// and quote items, in order.
+    pub fn pairs(&self) -> Vec<(ItemId, ItemId)> {
+        let mut pairs: Vec<_> = self.points.iter().map(|p| (p.base, p.quote)).collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Every trade of `a` and `b` for each other, oldest first.
+    fn between(&self, a: ItemId, b: ItemId) -> impl Iterator<Item = &PricePoint> {
+        let (base, quote) = if a <= b { (a, b) } else { (b, a) };
+        self.points
+            .iter()
+            .filter(move |p| p.base == base && p.quote == quote)
+    }
+
+    /// The last time `a` and `b` were traded for each other, if they ever
+    /// have been.
+    pub fn latest(&self, a: ItemId, b: ItemId) -> Option<PricePoint> {
+        self.between(a, b).last().copied()
+    }
+
+    /// The lowest and highest ratios `a` and `b` have gone for each other at
+    /// over the last `last` trades of them, if they've ever been traded. The
+    /// ratios are of the pair's base item, like the points themselves.
+    pub fn range(&self, a: ItemId, b: ItemId, last: usize) -> Option<(Quantity, Quantity)> {
+        let points: Vec<_> = self.between(a, b).collect();
+        let recent = &points[points.len().saturating_sub(last)..];
+        let low = recent.iter().map(|p| p.ratio).min()?;
+        let high = recent.iter().map(|p| p.ratio).max()?;
+        Some((low, high))
+    }
+
+    /// How much of `quote` a unit of `base` last went for, if they've ever
+    /// been traded for each other (a unit of anything goes for a unit of
+    /// itself).
+    pub fn price_of(&self, base: ItemId, quote: ItemId) -> Option<Quantity> {
+        if base == quote {
+            return Some(Quantity::ONE);
+        }
+        let point = self.latest(base, quote)?;
+        if point.base == base {
+            Some(point.ratio)
+        } else if point.ratio.is_zero() {
+            None
+        } else {
+            Some(Quantity::ONE.divided_by(point.ratio))
+        }
+    }
+
+    /// What a bundle's worth in `quote`, going by what each of its items
+    /// last went for in it (see `price_of`), along with whatever's in it
+    /// that's never been traded for `quote`, and so can't be valued in it.
+    pub fn value_in(&self, bundle: &Bundle, quote: ItemId) -> (Quantity, Bundle) {
+        let mut value = Quantity::ZERO;
+        let mut unpriced = Bundle::new();
+        for (item, amount) in bundle.iter() {
+            match self.price_of(item, quote) {
+                Some(price) => value += amount.times(price),
+                None => unpriced.add(item, amount),
+            }
+        }
+        (value, unpriced)
+    }
+
+    /// The item that's been part of the most trades, and so has the most
+    /// prices in terms of it: the closest thing there is to money, for
+    /// valuing things in, if anything's been traded at all. Ties go to the
+    /// lower ID.
+    pub fn most_traded(&self) -> Option<ItemId> {
+        let mut counts: BTreeMap<ItemId, u32> = BTreeMap::new();
+        for point in self.points.iter() {
+            *counts.entry(point.base).or_insert(0) += 1;
+            *counts.entry(point.quote).or_insert(0) += 1;
+        }
+        counts
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+            .map(|(item, _)| item)
+    }
+
+    /// How much of every other good a unit of `money` fetched, tick by tick,
+    /// oldest first: its purchasing power, as a basket of what it'd buy on
+    /// each tick it was traded at all (averaged over that tick's trades, if
+    /// there were several for the same good). Goods it wasn't traded for on
+    /// a tick are left out of that tick's basket.
+    pub fn purchasing_power(&self, money: ItemId) -> Vec<(u64, Bundle)> {
+        let mut ticks: BTreeMap<u64, BTreeMap<ItemId, (Quantity, u32)>> = BTreeMap::new();
+        for point in self.points.iter() {
+            let (good, fetched) = if point.base == money {
+                (point.quote, point.ratio)
+            } else if point.quote == money && !point.ratio.is_zero() {
+                (point.base, Quantity::ONE.divided_by(point.ratio))
+            } else {
+                continue;
+            };
+            let (total, count) = ticks
+                .entry(point.tick)
+                .or_default()
+                .entry(good)
+                .or_insert((Quantity::ZERO, 0));
+            *total += fetched;
+            *count += 1;
+        }
+        ticks
+            .into_iter()
+            .map(|(tick, goods)| {
+                let basket =
+                    goods
+                        .into_iter()
+                        .fold(Bundle::new(), |basket, (good, (total, count))| {
+                            basket.with(good, total.divided_by(Quantity::from(count)))
+                        });
+                (tick, basket)
+            })
+            .collect()
+    }
+
+    /// `money`'s purchasing power (see `purchasing_power`) as CSV: a row for
+    /// every tick it was traded, with a column for every good it's ever
+    /// fetched, left empty on ticks it wasn't traded for that good.
+    ///
+    /// # Arguments
+    ///
+    /// * `money` - the good to measure the purchasing power of
+    /// * `items` - the item registry, for item names
+    ///
+    pub fn purchasing_power_csv(&self, money: ItemId, items: &ItemRegistry) -> String {
+        let baskets = self.purchasing_power(money);
+        let mut goods: Vec<_> = baskets
+            .iter()
+            .flat_map(|(_, basket)| basket.iter().map(|(good, _)| good))
+            .collect();
+        goods.sort();
+        goods.dedup();
+        let mut csv = String::from("tick");
+        for &good in goods.iter() {
+            write!(csv, ",{}", items.name(good)).unwrap();
+        }
+        csv.push('\n');
+        for (tick, basket) in baskets {
+            write!(csv, "{}", tick).unwrap();
+            for &good in goods.iter() {
+                if basket.contains(good) {
+                    write!(csv, ",{}", basket.count(good)).unwrap();
+                } else {
+                    csv.push(',');
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// The average ratio `a` and `b` have gone for each other at, and over
+    /// how many trades, if they ever have been. The ratio is of the pair's
+    /// base item, like the points themselves.
+    pub fn average(&self, a: ItemId, b: ItemId) -> Option<(Quantity, u32)> {
+        let (total, count) = self
+            .between(a, b)
+            .fold((Quantity::ZERO, 0), |(total, count), p| {
+                (total + p.ratio, count + 1)
+            });
+        if count == 0 {
+            None
+        } else {
+            Some((total.divided_by(Quantity::from(count)), count))
+        }
+    }
+}
+
+/// One hire of one actor's labor by another, as a wage rate.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct WagePoint {
+    /// The tick the labor was hired on
+    pub tick: u64,
+    /// The item the wage was paid in
+    pub item: ItemId,
+    /// How much of it went for each hour of labor
+    pub rate: Quantity,
+}
+
+/// Every wage there's been for an hour of labor over a run, oldest first:
+/// the price of labor, in terms of whatever goods it was paid in (see
+/// `labor::hire`). Like the price history, the simulation keeps it up to
+/// date from the events each tick produces.
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct WageHistory {
+    /// Every hire so far, oldest first
+    points: Vec<WagePoint>,
+}
+
+impl WageHistory {
+    /// An empty history.
+    pub fn new() -> Self {
+        WageHistory::default()
+    }
+
+    /// Updates the history with something that happened on tick `tick`.
+    /// Only hires paid in one kind of item count, since there's no single
+    /// rate for a wage of several.
+    pub fn record(&mut self, event: &SimEvent, tick: u64) {
+        if let SimEvent::LaborHired {
+            hours, ref wage, ..
+        } = *event
+        {
+            if wage.len() != 1 || hours.is_zero() {
+                return;
+            }
+            let (item, amount) = wage.first().unwrap();
+            self.points.push(WagePoint {
+                tick,
+                item,
+                rate: amount.divided_by(hours),
+            });
+        }
+    }
+
+    /// Every hire so far, oldest first.
+    pub fn points(&self) -> &[WagePoint] {
+        &self.points
+    }
+
+    /// Every item labor's ever been paid in, in order.
+    pub fn items(&self) -> Vec<ItemId> {
+        let mut items: Vec<_> = self.points.iter().map(|p| p.item).collect();
+        items.sort();
+        items.dedup();
+        items
+    }
+
+    /// The last time labor was paid in `item`, if it ever has been.
+    pub fn latest(&self, item: ItemId) -> Option<WagePoint> {
+        self.points.iter().rev().find(|p| p.item == item).copied()
+    }
+
+    /// The average hourly rate labor's been paid in `item`, and over how
+    /// many hires, if it ever has been.
+    pub fn average(&self, item: ItemId) -> Option<(Quantity, u32)> {
+        let (total, count) = self
+            .points
+            .iter()
+            .filter(|p| p.item == item)
+            .fold((Quantity::ZERO, 0), |(total, count), p| {
+                (total + p.rate, count + 1)
+            });
+        if count == 0 {
+            None
+        } else {
+            Some((total.divided_by(Quantity::from(count)), count))
+        }
+    }
+}
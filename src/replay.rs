@@ -0,0 +1,238 @@
+use crate::events::SimEvent;
+use crate::simulation::{SimRng, Simulation};
+use crate::snapshot::{SnapshotError, SNAPSHOT_VERSION};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// One line of an event log (see `Recorder`).
+#[derive(Serialize, Deserialize)]
+pub(crate) enum LogEntry<W> {
+    /// The whole world as it was going into tick `tick`, written when
+    /// recording starts and again whenever anything's been done to the world
+    /// from outside since the last tick, so replaying it picks up whatever
+    /// was done
+    World { tick: u64, version: u32, world: W },
+    /// Something done to the world from outside before tick `tick` (a
+    /// command typed into the REPL, say), for the record
+    Intervention { tick: u64, what: String },
+    /// Everything that happened on tick `tick`, and where the random number
+    /// generator had got to by the end of it
+    Tick {
+        tick: u64,
+        events: Vec<SimEvent>,
+        rng: SimRng,
+    },
+}
+
+/// Records a run to an append-only event log, one JSON entry a line: the
+/// world as it was when recording started, every event of every tick, and
+/// where the random number generator had got to after each one, so the run
+/// can be replayed tick by tick and checked against what happened the first
+/// time round (see `Replay`). The simulation writes to it as it runs (see
+/// `Simulation::start_recording`).
+///
+/// Anything done to the world between ticks from outside (see
+/// `Simulation::record_intervention`) is noted down, and the whole world
+/// written out again before the next tick, since there's no telling what it
+/// changed. Every entry's written out as soon as it's made, so a log's good
+/// up to the last tick even if the program stops partway through one. If
+/// writing to the log ever fails, it stops there (see `error`).
+pub struct Recorder {
+    /// The log file
+    file: File,
+    /// Whether the world's to be written out again before the next tick
+    changed: bool,
+    /// What went wrong writing to the log, if anything has
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    /// Start a new log at `path`, replacing whatever's there.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Recorder {
+            file: File::create(path)?,
+            changed: true,
+            error: None,
+        })
+    }
+
+    /// What went wrong writing to the log, if anything has (nothing more's
+    /// written to it after that).
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Have the world written out again before the next tick.
+    pub(crate) fn changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// Whether the world's to be written out again before the next tick.
+    pub(crate) fn has_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Write one entry to the end of the log.
+    pub(crate) fn write<W: Serialize>(&mut self, entry: &LogEntry<W>) {
+        if self.error.is_some() {
+            return;
+        }
+        if let LogEntry::World { .. } = entry {
+            self.changed = false;
+        }
+        let written = serde_json::to_string(entry)
+            .map_err(io::Error::from)
+            .and_then(|line| self.file.write_all(format!("{}\n", line).as_bytes()));
+        if let Err(e) = written {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// What happened on one tick of a replay (see `Replay::step`).
+pub struct ReplayedTick {
+    /// The tick it was
+    pub tick: u64,
+    /// Everything done to the world from outside before it
+    pub interventions: Vec<String>,
+    /// Everything that happened on it this time round
+    pub events: Vec<SimEvent>,
+    /// Whether that (or where the random number generator got to) is any
+    /// different from what happened the first time round
+    pub diverged: bool,
+}
+
+/// A run recorded to an event log (see `Recorder`), being replayed a tick at
+/// a time. The world starts out as the log has it, and every tick replayed
+/// is run again for real and checked against the log, so any difference
+/// between the two (a bug that depends on something other than the world
+/// and its random number generator, say) shows up on the tick it first
+/// crops up on.
+pub struct Replay {
+    /// Everything in the log still to come, oldest first
+    entries: VecDeque<LogEntry<Simulation>>,
+}
+
+/// Parse line `line` of the event log at `path`. Worlds in a format we
+/// can't read are turned away before they get as far as being parsed, like
+/// snapshots are.
+fn parse_entry(
+    path: &Path,
+    line: usize,
+    text: &str,
+) -> Result<LogEntry<Simulation>, SnapshotError> {
+    let parse_error = |error| SnapshotError::LogParse {
+        path: path.to_path_buf(),
+        line,
+        error,
+    };
+    let outline: LogEntry<IgnoredAny> = serde_json::from_str(text).map_err(parse_error)?;
+    match outline {
+        LogEntry::World { version, .. } if version != SNAPSHOT_VERSION => {
+            Err(SnapshotError::LogVersion {
+                path: path.to_path_buf(),
+                version,
+            })
+        }
+        _ => serde_json::from_str(text).map_err(parse_error),
+    }
+}
+
+impl Replay {
+    /// Read in the event log at `path`, returning the replay and the world as
+    /// it was when recording started.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<(Self, Simulation), SnapshotError> {
+        let path = path.as_ref();
+        let mut entries = fs::read_to_string(path)
+            .map_err(|error| SnapshotError::Log {
+                path: path.to_path_buf(),
+                error,
+            })?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| parse_entry(path, i + 1, line))
+            .collect::<Result<VecDeque<LogEntry<Simulation>>, _>>()?;
+        // Anything noted down before the world first was can't be replayed
+        while let Some(entry) = entries.pop_front() {
+            if let LogEntry::World { world, .. } = entry {
+                world.validate_hierarchies()?;
+                return Ok((Replay { entries }, world));
+            }
+        }
+        Err(SnapshotError::Empty(path.to_path_buf()))
+    }
+
+    /// Replay the next tick in the log on `sim`, which should be the world
+    /// as `open` gave it and every tick since, returning what happened, or
+    /// `None` if there's nothing left in the log.
+    pub fn step(&mut self, sim: &mut Simulation) -> Result<Option<ReplayedTick>, SnapshotError> {
+        let mut interventions = vec![];
+        while let Some(entry) = self.entries.pop_front() {
+            match entry {
+                LogEntry::Intervention { what, .. } => interventions.push(what),
+                LogEntry::World { world, .. } => {
+                    world.validate_hierarchies()?;
+                    *sim = world;
+                }
+                LogEntry::Tick { tick, events, rng } => {
+                    let diverged = sim.ticks() != tick;
+                    let replayed = sim.tick();
+                    // The generator can't be compared directly, only what it
+                    // saves as
+                    let diverged = diverged
+                        || replayed != events
+                        || serde_json::to_string(sim.rng())? != serde_json::to_string(&rng)?;
+                    return Ok(Some(ReplayedTick {
+                        tick,
+                        interventions,
+                        events: replayed,
+                        diverged,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_logs_say_where() {
+        let path = std::env::temp_dir().join("microeconomics-malformed.log");
+        fs::write(&path, "\n{\"World\": 3}\n").unwrap();
+        let error = Replay::open(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        match &error {
+            SnapshotError::LogParse { path: p, line, .. } => {
+                assert_eq!(p, &path);
+                assert_eq!(*line, 2);
+            }
+            e => panic!("wrong error: {}", e),
+        }
+        assert!(error.to_string().contains(&*path.to_string_lossy()));
+    }
+
+    #[test]
+    fn logs_in_another_format_say_where() {
+        let path = std::env::temp_dir().join("microeconomics-old-format.log");
+        let world = "{\"World\":{\"tick\":0,\"version\":99,\"world\":null}}";
+        fs::write(&path, world).unwrap();
+        let error = Replay::open(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        match &error {
+            SnapshotError::LogVersion { path: p, version } => {
+                assert_eq!(p, &path);
+                assert_eq!(*version, 99);
+            }
+            e => panic!("wrong error: {}", e),
+        }
+    }
+}
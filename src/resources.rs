@@ -0,0 +1,78 @@
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Copyable handle to a resource node in a simulation (see
+/// `Simulation::add_resource`).
+#[derive(
+    PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug, Default, Serialize, Deserialize,
+)]
+pub struct ResourceId(pub usize);
+
+impl fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "resource#{}", self.0)
+    }
+}
+
+/// Somewhere out in the world that nobody owns where an item can be had for
+/// the work of gathering it, like a field of wild grain or a forest. Unlike
+/// land (see `ItemRegistry::is_land`), which yields to whoever holds it,
+/// anyone can spend a tick gathering from a resource node, but it only
+/// yields so much a tick, so whoever gets there first gets it, and anyone
+/// after that goes without until the next tick.
+///
+/// Like labor, it doesn't need visiting at the start of every tick to grow
+/// back: it remembers which tick it was last gathered from, and starts over
+/// whenever it's gathered from on a new one.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceNode {
+    /// Name for printouts
+    pub name: String,
+    /// What it yields
+    pub item: ItemId,
+    /// How much of it can be gathered every tick, all told
+    pub yield_per_tick: Quantity,
+    /// How much has been gathered on tick `tick`
+    taken: Quantity,
+    /// The tick `taken` counts for
+    tick: u64,
+}
+
+impl ResourceNode {
+    /// A node yielding so much of an item every tick, none of it gathered.
+    pub fn new<S: Into<String>>(name: S, item: ItemId, yield_per_tick: Quantity) -> Self {
+        ResourceNode {
+            name: name.into(),
+            item,
+            yield_per_tick,
+            taken: Quantity::ZERO,
+            tick: 0,
+        }
+    }
+
+    /// How much is left to gather on tick `now`.
+    pub fn left(&self, now: u64) -> Quantity {
+        if self.tick == now {
+            self.yield_per_tick.saturating_sub(self.taken)
+        } else {
+            self.yield_per_tick
+        }
+    }
+
+    /// Gather up to `amount` on tick `now` (only whole units, unless
+    /// `divisible`), returning how much that came to.
+    pub fn take(&mut self, amount: Quantity, divisible: bool, now: u64) -> Quantity {
+        let mut taken = std::cmp::min(amount, self.left(now));
+        if !divisible {
+            taken = taken.whole();
+        }
+        if self.tick != now {
+            self.tick = now;
+            self.taken = Quantity::ZERO;
+        }
+        self.taken += taken;
+        taken
+    }
+}
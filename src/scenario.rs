@@ -0,0 +1,411 @@
+use crate::builder::{ActorBuilder, BuildError};
+use crate::goals::{GoalRegistry, GoalRegistryError, GoalTemplate};
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry, RegistryError};
+use crate::preference_list::Actor;
+use crate::simulation::Simulation;
+use rand::seq::IteratorRandom;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The scenario every world gets unless it's given its own scenario file.
+pub const DEFAULT_SCENARIO: &str = include_str!("../data/scenarios/default.toml");
+
+/// Why a scenario file couldn't be loaded, or its actors set up.
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML, or doesn't describe a scenario
+    Parse(toml::de::Error),
+    /// The goals it brings with it couldn't be loaded
+    Goals(GoalRegistryError),
+    /// The items it brings with it couldn't be loaded
+    Items(RegistryError),
+    /// Actors are said to start out with, or use, an item that doesn't exist
+    UnknownItem(String),
+    /// Actors are given a goal to rank that doesn't exist
+    UnknownGoal(String),
+    /// More than one group of actors leaves out how many of them there are
+    Uncounted,
+    /// A group of actors leaves out how many of them there are, and nobody
+    /// said how many actors to use all told
+    NoActorCount(String),
+    /// One of the actors couldn't be set up as described
+    BadActor { name: String, error: BuildError },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "couldn't read scenario file: {}", e),
+            ScenarioError::Parse(e) => write!(f, "couldn't parse scenario file: {}", e),
+            ScenarioError::Goals(e) => write!(f, "scenario's goals: {}", e),
+            ScenarioError::Items(e) => write!(f, "scenario's items: {}", e),
+            ScenarioError::UnknownItem(name) => {
+                write!(f, "scenario uses item {}, which isn't defined", name)
+            }
+            ScenarioError::UnknownGoal(name) => {
+                write!(f, "scenario ranks goal {}, which isn't defined", name)
+            }
+            ScenarioError::Uncounted => write!(
+                f,
+                "only one group of actors can leave out how many of them there are"
+            ),
+            ScenarioError::NoActorCount(name) => write!(
+                f,
+                "the {} group of actors makes up the numbers, but nobody said how many actors to use",
+                name
+            ),
+            ScenarioError::BadActor { name, error } => {
+                write!(f, "couldn't set up actor {}: {}", name, error)
+            }
+        }
+    }
+}
+
+impl Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(e: io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ScenarioError {
+    fn from(e: toml::de::Error) -> Self {
+        ScenarioError::Parse(e)
+    }
+}
+
+impl From<GoalRegistryError> for ScenarioError {
+    fn from(e: GoalRegistryError) -> Self {
+        ScenarioError::Goals(e)
+    }
+}
+
+impl From<RegistryError> for ScenarioError {
+    fn from(e: RegistryError) -> Self {
+        ScenarioError::Items(e)
+    }
+}
+
+/// Shape of a scenario file. Goals and items can be given inline, as
+/// `[[goal]]` and `[[item]]` tables just like the ones in a goals or items
+/// file, or in files of their own, by path. Anything else in it is a mistake
+/// (a misspelt table, say), so it's turned away rather than ignored.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    /// Goals file to use, relative to the scenario file
+    goals: Option<String>,
+    /// Items file to use, relative to the scenario file
+    items: Option<String>,
+    /// Goals given inline (only looked at to see whether there are any)
+    #[serde(default)]
+    goal: Vec<toml::Value>,
+    /// Items given inline (only looked at to see whether there are any)
+    #[serde(default)]
+    item: Vec<toml::Value>,
+    #[serde(default)]
+    actor: Vec<ActorGroup>,
+}
+
+/// So many actors alike, as a scenario describes them.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActorGroup {
+    /// What they're called: each is named this and a number (`Actor#3`),
+    /// counting up from 0 across every group of the same name
+    #[serde(default = "default_group_name")]
+    pub name: String,
+    /// How many of them there are. A group without one makes up the numbers
+    /// (see `Scenario::populate`)
+    #[serde(default)]
+    pub count: Option<usize>,
+    /// The goals they rank, by name, most valued first (every goal in the
+    /// world, in the order it's defined, if not given)
+    #[serde(default)]
+    pub goals: Option<Vec<String>>,
+    /// What each of them might start out with, by item name: each gets one
+    /// of these, picked at random (nothing, if there are none)
+    #[serde(default)]
+    pub endowments: Vec<BTreeMap<String, Quantity>>,
+    /// What they know satisfies which goals
+    #[serde(default)]
+    pub satisfies: Vec<Satisfaction>,
+}
+
+fn default_group_name() -> String {
+    "Actor".to_string()
+}
+
+/// Items a group of actors knows satisfy a goal.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Satisfaction {
+    /// The goal, by name. If the world has no such goal, this is skipped, so
+    /// a scenario can be run with a goals file that leaves some out
+    pub goal: String,
+    /// The items that satisfy it, by name
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Items one of which (picked at random for each actor) satisfies it
+    /// too, on top of `items`
+    #[serde(default)]
+    pub one_of: Vec<String>,
+    /// Whether to skip this, rather than give up, if the world doesn't have
+    /// all the items
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Everything a world starts out with: the goals its actors pursue, the
+/// items there are, and who the actors are, what they start out with, and
+/// what they know satisfies what. Like goals and items, this is data, not
+/// code, described in a TOML file like this one:
+///
+/// ```toml
+/// items = "items.toml"
+///
+/// [[goal]]
+/// name = "Eat"
+/// units_required = 2
+/// recurrence = 10
+///
+/// [[actor]]
+/// name = "Farmer"
+/// count = 10
+/// endowments = [{ FoodUnit = 3 }, { FoodUnit = 1, HouseUnit = 1 }]
+///
+/// [[actor.satisfies]]
+/// goal = "Eat"
+/// items = ["FoodUnit"]
+/// ```
+///
+/// Goals and items are given inline, as here, or in a file of their own
+/// (`goals = "..."`, `items = "..."`, relative to the scenario file), or
+/// left out to go with whatever the world already has.
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    /// The goals its actors pursue, if it brings its own
+    pub goals: Option<GoalRegistry>,
+    /// The items there are, if it brings its own
+    pub items: Option<ItemRegistry>,
+    /// Every group of actors in it, in the order they're added
+    pub actors: Vec<ActorGroup>,
+}
+
+impl Scenario {
+    /// The built-in scenario (see `DEFAULT_SCENARIO`).
+    pub fn default_scenario() -> Self {
+        Scenario::from_toml(DEFAULT_SCENARIO).expect("built-in scenario file is broken")
+    }
+
+    /// Parse a scenario out of the contents of a scenario file, with any
+    /// goals or items files it names taken relative to the current directory.
+    pub fn from_toml(source: &str) -> Result<Self, ScenarioError> {
+        Scenario::parse(source, Path::new(""))
+    }
+
+    /// Load a scenario from a scenario file on disk.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        Scenario::parse(
+            &fs::read_to_string(path)?,
+            path.parent().unwrap_or_else(|| Path::new("")),
+        )
+    }
+
+    /// Parse a scenario, with any files it names taken relative to `base`.
+    fn parse(source: &str, base: &Path) -> Result<Self, ScenarioError> {
+        let file: ScenarioFile = toml::from_str(source)?;
+        let goals = match file.goals {
+            Some(path) => Some(GoalRegistry::load(base.join(path))?),
+            None if !file.goal.is_empty() => Some(GoalRegistry::from_toml(source)?),
+            None => None,
+        };
+        let items = match file.items {
+            Some(path) => Some(ItemRegistry::load(base.join(path))?),
+            None if !file.item.is_empty() => Some(ItemRegistry::from_toml(source)?),
+            None => None,
+        };
+        if file.actor.iter().filter(|g| g.count.is_none()).count() > 1 {
+            return Err(ScenarioError::Uncounted);
+        }
+        Ok(Scenario {
+            goals,
+            items,
+            actors: file.actor,
+        })
+    }
+
+    /// Add the scenario's actors to `sim`, which should already have its
+    /// goals and items. A group that doesn't say how many of it there are
+    /// gets however many it takes to bring the total up to `actors` (none,
+    /// if the others come to that many already).
+    ///
+    /// For each actor, anything picked at random for it is picked in the
+    /// order its group lists it (its satisfactions' `one_of`s, then its
+    /// endowment) before its goals are drawn up, so the same seed always
+    /// deals the same actors out.
+    ///
+    /// # Arguments
+    ///
+    /// * `sim` - the world to add them to
+    /// * `actors` - how many actors to use all told, if anyone said
+    /// * `jitter` - how far, as a fraction, each actor's goals can stray from
+    ///   the world's, either way (see `GoalTemplate::jitter`)
+    /// * `setup` - anything else to do to each actor before it's built, given
+    ///   its place in line, counting every actor added so far
+    ///
+    pub fn populate<F>(
+        &self,
+        sim: &mut Simulation,
+        actors: Option<usize>,
+        jitter: Quantity,
+        mut setup: F,
+    ) -> Result<(), ScenarioError>
+    where
+        F: FnMut(usize, ActorBuilder, &mut Simulation) -> ActorBuilder,
+    {
+        let counted: usize = self.actors.iter().filter_map(|g| g.count).sum();
+        let mut numbered: HashMap<&str, usize> = HashMap::new();
+        let mut added = 0;
+        for group in self.actors.iter() {
+            let count = match (group.count, actors) {
+                (Some(count), _) => count,
+                (None, Some(actors)) => actors.saturating_sub(counted),
+                (None, None) => return Err(ScenarioError::NoActorCount(group.name.clone())),
+            };
+            let templates = self.templates(sim, group, jitter)?;
+            let endowments = group
+                .endowments
+                .iter()
+                .map(|endowment| {
+                    endowment
+                        .iter()
+                        .map(|(name, &amount)| item(sim, name).map(|item| (item, amount)))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for _ in 0..count {
+                let mut satisfactions = vec![];
+                for satisfaction in group.satisfies.iter() {
+                    let wants = sim.goals().wants(&satisfaction.goal);
+                    let items = satisfaction
+                        .items
+                        .iter()
+                        .map(|name| item(sim, name))
+                        .collect::<Result<Vec<_>, _>>();
+                    let picked = satisfaction
+                        .one_of
+                        .iter()
+                        .choose(sim.rng())
+                        .map(|name| item(sim, name))
+                        .transpose();
+                    match (items, picked) {
+                        (Ok(mut items), Ok(picked)) => {
+                            items.extend(picked);
+                            satisfactions
+                                .extend(wants.into_iter().map(|goal| (goal, items.clone())));
+                        }
+                        (Err(_), _) | (_, Err(_)) if satisfaction.optional => {}
+                        (Err(e), _) | (_, Err(e)) => return Err(e),
+                    }
+                }
+                let endowment = endowments
+                    .iter()
+                    .choose(sim.rng())
+                    .cloned()
+                    .unwrap_or_default();
+                let number = numbered.entry(&group.name).or_insert(0);
+                let name = format!("{}#{}", group.name, number);
+                *number += 1;
+                let builder = templates
+                    .iter()
+                    .fold(Actor::builder(), |b, t| b.template(t, sim.rng()))
+                    .name(name.clone());
+                let builder = endowment
+                    .into_iter()
+                    .fold(builder, |b, (item, amount)| b.starting_amount(item, amount));
+                let builder = setup(added, builder, sim);
+                let actor = satisfactions
+                    .into_iter()
+                    .fold(builder, |b, (goal, items)| b.satisfaction(goal, items))
+                    .build()
+                    .map_err(|error| ScenarioError::BadActor { name, error })?;
+                sim.add_actor(actor);
+                added += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The goals every actor in `group` ranks, most valued first, as
+    /// templates for its own version of them.
+    fn templates(
+        &self,
+        sim: &Simulation,
+        group: &ActorGroup,
+        jitter: Quantity,
+    ) -> Result<Vec<GoalTemplate>, ScenarioError> {
+        let hierarchy = sim.goals().hierarchy();
+        let ranked = match &group.goals {
+            None => hierarchy,
+            Some(names) => {
+                let mut ranked = vec![];
+                for name in names {
+                    let wants = sim.goals().wants(name);
+                    if wants.is_empty() {
+                        return Err(ScenarioError::UnknownGoal(name.clone()));
+                    }
+                    ranked.extend(
+                        hierarchy
+                            .iter()
+                            .filter(|goal| wants.contains(&goal.get_goal()))
+                            .cloned(),
+                    );
+                }
+                ranked
+            }
+        };
+        Ok(ranked
+            .into_iter()
+            .map(|goal| GoalTemplate::from_data(goal, vec![]).jitter(jitter))
+            .collect())
+    }
+}
+
+/// Look up an item by name in `sim`.
+fn item(sim: &Simulation, name: &str) -> Result<ItemId, ScenarioError> {
+    sim.items()
+        .id(name)
+        .ok_or_else(|| ScenarioError::UnknownItem(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_tables_and_keys_are_turned_away() {
+        assert!(Scenario::from_toml(DEFAULT_SCENARIO).is_ok());
+        for source in [
+            "[[trader]]\ncount = 4\n",
+            "[[actor]]\ncount = 4\nwant = [\"Eat\"]\n",
+        ]
+        .iter()
+        {
+            match Scenario::from_toml(source) {
+                Err(ScenarioError::Parse(_)) => {}
+                Err(e) => panic!("wrong error: {}", e),
+                Ok(_) => panic!("accepted {:?}", source),
+            }
+        }
+    }
+}
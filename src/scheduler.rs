@@ -0,0 +1,126 @@
+use crate::arena::ActorId;
+use crate::goals::GoalId;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Something that has to happen to an actor at a particular tick.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Wakeup {
+    /// A recurring goal's timer has run out, so it comes back into play
+    GoalDue(GoalId),
+    /// The actor gets to decide on and carry out an action
+    Act,
+    /// One of the actor's goals moves a place up or down its hierarchy (see
+    /// `Simulation::set_drift`)
+    Drift,
+}
+
+/// A wakeup along with when it's for. The sequence number breaks ties, so
+/// wakeups for the same tick come out in the order they were scheduled.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+struct Scheduled {
+    at: u64,
+    seq: u64,
+    actor: ActorId,
+    wakeup: Wakeup,
+}
+
+// Reversed, so the earliest wakeup sits at the top of the (max-)heap
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .at
+            .cmp(&self.at)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Queue of everything that's going to happen in a simulation, ordered by
+/// time. Instead of visiting every actor every tick and counting down every
+/// recurring goal's timer by one, the simulation puts wakeups in here for the
+/// exact tick they're needed, and each tick only touches the actors that have
+/// something due. Actors that are idle cost nothing until their next goal
+/// comes due.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Scheduler {
+    /// Pending wakeups, earliest first
+    queue: BinaryHeap<Scheduled>,
+    /// Sequence number to give the next wakeup scheduled
+    next_seq: u64,
+}
+
+impl Scheduler {
+    /// Construct an empty schedule.
+    pub fn new() -> Self {
+        Scheduler {
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Arrange for something to happen to an actor.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - the tick it should happen on
+    /// * `actor` - who it happens to
+    /// * `wakeup` - what happens
+    ///
+    pub fn schedule(&mut self, at: u64, actor: ActorId, wakeup: Wakeup) {
+        self.queue.push(Scheduled {
+            at,
+            seq: self.next_seq,
+            actor,
+            wakeup,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Drop every pending wakeup of one kind for an actor (say, a goal it no
+    /// longer has coming due).
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - who the wakeups are for
+    /// * `wakeup` - which wakeups to drop
+    ///
+    pub fn cancel(&mut self, actor: ActorId, wakeup: Wakeup) {
+        self.queue
+            .retain(|s| !(s.actor == actor && s.wakeup == wakeup));
+    }
+
+    /// The tick the next wakeup is due on, if anything is scheduled at all.
+    pub fn next_due(&self) -> Option<u64> {
+        self.queue.peek().map(|s| s.at)
+    }
+
+    /// Take every wakeup due on or before `now` off the queue, in order.
+    pub fn pop_due(&mut self, now: u64) -> Vec<(ActorId, Wakeup)> {
+        let mut due = vec![];
+        while let Some(s) = self.queue.peek() {
+            if s.at > now {
+                break;
+            }
+            let s = self.queue.pop().unwrap();
+            due.push((s.actor, s.wakeup));
+        }
+        due
+    }
+
+    /// Number of wakeups waiting
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Whether nothing is scheduled
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
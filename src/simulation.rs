@@ -0,0 +1,2184 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::behavior::{Action, Behavior, WorldView};
+use crate::credit::{self, BankBook};
+use crate::events::SimEvent;
+use crate::goals::{GoalId, GoalRegistry};
+use crate::imputation::Imputation;
+use crate::items::bundle::Bundle;
+use crate::items::inventory::Inventory;
+use crate::items::production::RecipeId;
+use crate::items::quantity::Quantity;
+use crate::items::registry::{ItemId, ItemRegistry};
+use crate::labor::{self, Work};
+use crate::matching::{self, Matching};
+use crate::minting::MoneyTrace;
+use crate::network::TradeNetwork;
+use crate::observer::SimObserver;
+use crate::ordering::TickOrder;
+use crate::preference_list::{Actor, ActorMut, ActorRef, ActorState, GoalData};
+use crate::prices::{PriceHistory, PricePoint, WageHistory};
+use crate::replay::{LogEntry, Recorder};
+use crate::resources::{ResourceId, ResourceNode};
+use crate::scheduler::{Scheduler, Wakeup};
+use crate::snapshot::{History, Snapshot, SnapshotError, SnapshotVersion, SNAPSHOT_VERSION};
+use crate::taxation::Collector;
+use crate::trade::{self, AscendingBid, LegalTender, PriceControl, TradeProtocol};
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// The random number generator used for everything stochastic in a
+/// simulation. It's seedable (so the same seed always gives the same run) and
+/// serializable (so its state can be saved along with everything else).
+pub type SimRng = rand_pcg::Pcg64;
+
+/// How actors' preferences shift over a long run (see
+/// `Simulation::set_drift`). Every time an actor's preferences drift, one of
+/// its goals, picked at random, swaps places with the goal just above or just
+/// below it.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Drift {
+    /// Preferences drift every so many ticks, like clockwork
+    Every(u64),
+    /// Preferences drift at random, with this chance (from 0 to 1) on any
+    /// given tick
+    Random(Quantity),
+}
+
+impl Drift {
+    /// How many ticks from now an actor's preferences next drift, if they
+    /// ever do.
+    fn delay(self, rng: &mut SimRng) -> Option<u64> {
+        match self {
+            Drift::Every(ticks) => Some(ticks.max(1)),
+            Drift::Random(probability) => arrival_delay(rng, probability),
+        }
+    }
+}
+
+/// Owns every actor in the world and drives them forward in time. This is the
+/// entry point for anyone embedding the model in their own programs: build
+/// some actors, add them, and call `tick` as many times as you like.
+///
+/// The whole world can be serialized, and saved to a snapshot file to be
+/// picked back up later (see `save`). Only the built-in behaviors and trade
+/// protocols come back just as they were, though (see `Behavior::save` and
+/// `TradeProtocol::save`).
+#[derive(Serialize, Deserialize)]
+pub struct Simulation {
+    /// Every kind of item that exists in the world
+    items: ItemRegistry,
+    /// Every goal actors in the world might pursue
+    #[serde(default = "GoalRegistry::default_goals")]
+    goals: GoalRegistry,
+    /// All of the actors in the world
+    actors: ActorArena,
+    /// Public goods that belong to nobody in particular, and that anyone can
+    /// use
+    #[serde(default)]
+    commons: Inventory,
+    /// Places anyone can gather things from, a limited amount a tick
+    #[serde(default)]
+    resources: Vec<ResourceNode>,
+    /// How actors negotiate trades with each other
+    #[serde(with = "crate::trade::saved")]
+    protocol: Box<dyn TradeProtocol>,
+    /// Source of all randomness in the simulation
+    rng: SimRng,
+    /// Number of ticks run so far
+    ticks: u64,
+    /// Whether actors make their decisions in parallel
+    #[serde(default)]
+    parallel: bool,
+    /// Everything that's going to happen, and when
+    schedule: Scheduler,
+    /// How actors' preferences shift over time, if they do
+    #[serde(default)]
+    drift: Option<Drift>,
+    /// How much of what actors remember about their trading partners fades
+    /// every tick, if they remember anything at all
+    #[serde(default)]
+    memory_decay: Option<Quantity>,
+    /// How actors looking to trade are paired up each tick, if the world
+    /// does it for them
+    #[serde(default)]
+    matching: Option<Matching>,
+    /// The order actors act in each tick
+    #[serde(default)]
+    order: TickOrder,
+    /// How many ticks a negotiation can go on before it's called off, if
+    /// there's any limit
+    #[serde(default)]
+    negotiation_timeout: Option<u64>,
+    /// Every negotiation going on: who started it, who with, and on what
+    /// tick
+    #[serde(default)]
+    negotiations: Vec<(ActorId, ActorId, u64)>,
+    /// Every exchange ratio there's been between two goods
+    #[serde(default)]
+    prices: PriceHistory,
+    /// Every wage there's been for an hour of labor
+    #[serde(default)]
+    wages: WageHistory,
+    /// Who's traded with whom
+    #[serde(default)]
+    trades: TradeNetwork,
+    /// Where any newly minted money has gone
+    #[serde(default)]
+    money_trace: MoneyTrace,
+    /// The share of everything held that could be used up or spent that
+    /// was being saved instead, every tick anyone saved at all
+    #[serde(default)]
+    savings_rates: Vec<(u64, Quantity)>,
+    /// Every bank that's failed (see `BankBook::has_failed`)
+    #[serde(default)]
+    failed_banks: BTreeSet<ActorId>,
+    /// The price controls in force, and the tick each was imposed on
+    #[serde(default)]
+    price_controls: Vec<(u64, PriceControl)>,
+    /// The legal tender laws in force, and the tick each was imposed on
+    #[serde(default)]
+    legal_tender: Vec<(u64, LegalTender)>,
+    /// Whoever's collecting taxes, if anyone is
+    #[serde(default)]
+    collector: Option<Collector>,
+    /// Hooks to notify as the simulation runs (not serialized)
+    #[serde(skip)]
+    observers: Vec<Box<dyn SimObserver>>,
+    /// Where the run's being recorded to, if it is (not serialized)
+    #[serde(skip)]
+    recorder: Option<Recorder>,
+    /// The world going into each of the last so many ticks, to rewind to
+    /// (not serialized)
+    #[serde(skip)]
+    history: History,
+}
+
+impl Simulation {
+    /// Construct an empty simulation with no actors in it, where trades are
+    /// negotiated by ascending bids, seeded from system entropy.
+    pub fn new() -> Self {
+        Simulation::with_rng(SimRng::from_entropy())
+    }
+
+    /// Construct an empty simulation whose randomness is entirely determined
+    /// by `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Simulation::with_rng(SimRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: SimRng) -> Self {
+        Simulation {
+            items: ItemRegistry::default_items(),
+            goals: GoalRegistry::default_goals(),
+            actors: ActorArena::new(),
+            commons: Inventory::new(),
+            resources: vec![],
+            protocol: Box::new(AscendingBid::default()),
+            rng,
+            ticks: 0,
+            parallel: false,
+            schedule: Scheduler::new(),
+            drift: None,
+            memory_decay: None,
+            matching: None,
+            order: TickOrder::Fixed,
+            negotiation_timeout: None,
+            negotiations: vec![],
+            prices: PriceHistory::new(),
+            wages: WageHistory::new(),
+            trades: TradeNetwork::new(),
+            money_trace: MoneyTrace::new(),
+            savings_rates: vec![],
+            failed_banks: BTreeSet::new(),
+            price_controls: vec![],
+            legal_tender: vec![],
+            collector: None,
+            observers: vec![],
+            recorder: None,
+            history: History::default(),
+        }
+    }
+
+    /// Save the whole world to a snapshot file: every actor and everything
+    /// it has and is after, the tick count, the random number generator, and
+    /// everything else, so it can be loaded back exactly as it is (see
+    /// `load`) and run on from there the same as if it never stopped.
+    /// Observers aren't saved.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            world: self,
+        };
+        fs::write(path, serde_json::to_string(&snapshot)?)?;
+        Ok(())
+    }
+
+    /// Load a world from a snapshot file written by `save`, just as it was
+    /// when it was saved. Snapshots in any format but the current one (see
+    /// `SNAPSHOT_VERSION`), or with an actor whose goal hierarchy isn't
+    /// strictly ordinal, are turned away.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        let json = fs::read_to_string(path)?;
+        let SnapshotVersion { version } = serde_json::from_str(&json)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let snapshot: Snapshot<Simulation> = serde_json::from_str(&json)?;
+        snapshot.world.validate_hierarchies()?;
+        Ok(snapshot.world)
+    }
+
+    /// Check every actor's goal hierarchy is strictly ordinal (see
+    /// `ValueScale::validate_hierarchy`). Worlds built here always are, but
+    /// ones loaded from a file could have been edited by hand.
+    pub(crate) fn validate_hierarchies(&self) -> Result<(), SnapshotError> {
+        let actors = &self.actors;
+        for (name, values) in actors.names().iter().zip(actors.values()) {
+            values
+                .validate_hierarchy()
+                .map_err(|error| SnapshotError::Hierarchy {
+                    actor: name.clone(),
+                    error,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// The simulation's random number generator. Use this for any randomness
+    /// in setting the world up (random endowments, etc) so that it's covered
+    /// by the seed too.
+    pub fn rng(&mut self) -> &mut SimRng {
+        &mut self.rng
+    }
+
+    /// The kinds of item that exist in this world.
+    pub fn items(&self) -> &ItemRegistry {
+        &self.items
+    }
+
+    /// Replace the kinds of item that exist in this world (by default, the
+    /// built-in ones). Do this before adding any actors, since they refer to
+    /// items by ID.
+    pub fn set_items(&mut self, items: ItemRegistry) {
+        self.items = items;
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            actor.values.learn_items(&self.items);
+        }
+    }
+
+    /// The goals actors in this world might pursue.
+    pub fn goals(&self) -> &GoalRegistry {
+        &self.goals
+    }
+
+    /// Replace the goals actors in this world might pursue (by default, the
+    /// built-in ones). Do this before adding any actors, since they refer to
+    /// goals by ID.
+    pub fn set_goals(&mut self, goals: GoalRegistry) {
+        self.goals = goals;
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            actor.values.learn_goals(&self.goals);
+        }
+    }
+
+    /// Every exchange ratio there's been between two goods so far.
+    pub fn prices(&self) -> &PriceHistory {
+        &self.prices
+    }
+
+    /// Every wage there's been for an hour of labor so far.
+    pub fn wages(&self) -> &WageHistory {
+        &self.wages
+    }
+
+    /// What the factors of production are worth in `quote`, imputed back
+    /// from what consumers' goods last went for through the recipes anybody
+    /// knows (see `Imputation`).
+    pub fn imputation(&self, quote: ItemId) -> Imputation {
+        Imputation::new(&self.items, &self.prices, quote, |id| {
+            let recipe = self.items.recipe(id).unwrap();
+            self.actors().any(|actor| actor.know_how.knows(id, recipe))
+        })
+    }
+
+    /// Who's traded with whom so far, and in what.
+    pub fn trades(&self) -> &TradeNetwork {
+        &self.trades
+    }
+
+    /// The savings rate on every tick anyone saved at all, oldest first: the
+    /// share of all the consumers' goods and money held that was set aside
+    /// for later rather than used up or spent (see `ActorRef::savings`).
+    /// Units of different goods are all counted alike.
+    pub fn savings_rates(&self) -> &[(u64, Quantity)] {
+        &self.savings_rates
+    }
+
+    /// Where any money minted so far has gone (see `mint`).
+    pub fn money_trace(&self) -> &MoneyTrace {
+        &self.money_trace
+    }
+
+    /// The price controls in force, and the tick each was imposed on.
+    pub fn price_controls(&self) -> &[(u64, PriceControl)] {
+        &self.price_controls
+    }
+
+    /// Put a price control in force from now on, in place of any control
+    /// of the same kind there was on the same pair of goods, and let every
+    /// actor know about it so they keep to it (see `PriceControl`).
+    pub fn impose_price_control(&mut self, control: PriceControl) {
+        self.price_controls.retain(|(_, c)| {
+            (c.item, c.quote, c.ceiling) != (control.item, control.quote, control.ceiling)
+        });
+        self.price_controls.push((self.ticks, control));
+        let controls: Vec<PriceControl> = self.price_controls.iter().map(|&(_, c)| c).collect();
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            actor.values.price_controls = controls.clone();
+            self.wake(id);
+        }
+    }
+
+    /// The legal tender laws in force, and the tick each was imposed on.
+    pub fn legal_tender(&self) -> &[(u64, LegalTender)] {
+        &self.legal_tender
+    }
+
+    /// Put a legal tender law in force from now on, in place of any law
+    /// there was between the same two moneys, and let every actor know about
+    /// it so they keep to it (see `LegalTender`).
+    pub fn impose_legal_tender(&mut self, law: LegalTender) {
+        self.legal_tender.retain(|(_, l)| {
+            [l.item, l.quote] != [law.item, law.quote] && [l.item, l.quote] != [law.quote, law.item]
+        });
+        self.legal_tender.push((self.ticks, law));
+        let laws: Vec<LegalTender> = self.legal_tender.iter().map(|&(_, l)| l).collect();
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            actor.values.legal_tender = laws.clone();
+            self.wake(id);
+        }
+    }
+
+    /// Whoever's collecting taxes, if anyone is.
+    pub fn collector(&self) -> Option<&Collector> {
+        self.collector.as_ref()
+    }
+
+    /// Have `collector` collect taxes from the end of this tick on, in place
+    /// of whoever was before (or nobody, if it's `None`).
+    pub fn set_collector(&mut self, collector: Option<Collector>) {
+        self.collector = collector;
+    }
+
+    /// Mint `amount` of new money and hand it to `actor`, then follow it as
+    /// it's spent on from there (see `MoneyTrace`). Returns nothing if
+    /// there's no such actor, or `item` isn't money.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - the actor the new money goes to first
+    /// * `item` - the money to mint
+    /// * `amount` - how much of it to mint
+    ///
+    pub fn mint(&mut self, actor: ActorId, item: ItemId, amount: Quantity) -> Vec<SimEvent> {
+        if !self.items.get(item).is_some_and(|def| def.class.is_money()) {
+            return vec![];
+        }
+        let mut events = match self.actors.get_mut(actor) {
+            Some(mut receiver) => receiver.receive(item, amount),
+            None => return vec![],
+        };
+        self.money_trace.mint(actor, item, amount, self.ticks);
+        events.insert(
+            0,
+            SimEvent::MoneyMinted {
+                actor,
+                item,
+                amount,
+            },
+        );
+        self.wake(actor);
+        events
+    }
+
+    /// The public goods that anyone in the world can use.
+    pub fn commons(&self) -> &Inventory {
+        &self.commons
+    }
+
+    /// Put some of a public good in the commons, for anyone to use, and wake
+    /// everyone up so they can. Returns whether it was put there: only
+    /// non-rival goods (see `ItemDef::public`) can be.
+    pub fn add_to_commons(&mut self, item: ItemId, amount: Quantity) -> bool {
+        if !self.items.is_public(item) {
+            return false;
+        }
+        self.commons.add(item, amount);
+        for id in self.actors.ids() {
+            self.wake(id);
+        }
+        true
+    }
+
+    /// Every resource node in the world, in order of ID.
+    pub fn resources(&self) -> &[ResourceNode] {
+        &self.resources
+    }
+
+    /// Put a resource node in the world for anyone to gather from, and wake
+    /// everyone up so they can. Returns its ID.
+    pub fn add_resource(&mut self, node: ResourceNode) -> ResourceId {
+        self.resources.push(node);
+        for id in self.actors.ids() {
+            self.wake(id);
+        }
+        ResourceId(self.resources.len() - 1)
+    }
+
+    /// Change how actors negotiate trades from now on.
+    pub fn set_trade_protocol(&mut self, protocol: Box<dyn TradeProtocol>) {
+        self.protocol = protocol;
+    }
+
+    /// The protocol actors are currently using to negotiate trades.
+    pub fn trade_protocol(&self) -> &dyn TradeProtocol {
+        &*self.protocol
+    }
+
+    /// Have actors make their decisions each tick concurrently, across all
+    /// available cores, which is worth it for big populations. Decisions are
+    /// still carried out one at a time in ID order afterwards, and every
+    /// actor gets its own random number generator seeded from the
+    /// simulation's, so a run comes out exactly the same either way.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Whether actors make their decisions in parallel.
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Keep the world as it was going into each of the last `ticks` ticks
+    /// from now on, so it can be rewound to any of them (see `rewind`). None,
+    /// unless this is set: the whole world's kept each time, so it takes as
+    /// much memory as that many snapshots (see `save`), and a little longer
+    /// to run every tick.
+    pub fn set_history(&mut self, ticks: usize) {
+        self.history.limit = ticks;
+        self.history.trim();
+    }
+
+    /// How many ticks back the simulation can be rewound right now.
+    pub fn history(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Put the world back the way it was going into the tick `ticks` ago,
+    /// with anything done to it before then, returning the tick it's at now,
+    /// or `None` if there isn't the history kept to go back that far (see
+    /// `set_history`). Observers, recording and what history there is from
+    /// before then are kept; the ticks rewound over are forgotten.
+    pub fn rewind(&mut self, ticks: usize) -> Option<u64> {
+        let snapshot = self.history.rewind(ticks)?;
+        let mut world: Simulation =
+            serde_json::from_str(&snapshot).expect("history always deserializes");
+        world.observers = std::mem::take(&mut self.observers);
+        world.recorder = self.recorder.take();
+        world.history = std::mem::take(&mut self.history);
+        if let Some(recorder) = world.recorder.as_mut() {
+            recorder.changed();
+        }
+        *self = world;
+        Some(self.ticks)
+    }
+
+    /// An independent copy of the world as it is now, to run on side by side
+    /// with this one (with and without some intervention, say). The copy has
+    /// the same random number generator, so the two keep in step until
+    /// something's done to one and not the other. Observers, recording and
+    /// history aren't copied, though how much history to keep is.
+    pub fn fork(&self) -> Self {
+        let json = serde_json::to_string(self).expect("worlds always serialize");
+        let mut world: Simulation = serde_json::from_str(&json).expect("worlds always deserialize");
+        world.history.limit = self.history.limit;
+        world
+    }
+
+    /// Register an observer to be notified of what happens from now on.
+    pub fn add_observer(&mut self, observer: Box<dyn SimObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Record everything that happens from now on to `recorder`'s event log,
+    /// starting with the world as it is going into the next tick (see
+    /// `Recorder`). Stops recording to whatever it was recording to before.
+    pub fn start_recording(&mut self, mut recorder: Recorder) {
+        recorder.changed();
+        self.recorder = Some(recorder);
+    }
+
+    /// Stop recording, handing back whatever it was recording to, if it was
+    /// (to carry on recording to in another world, say).
+    pub fn stop_recording(&mut self) -> Option<Recorder> {
+        self.recorder.take()
+    }
+
+    /// Whatever the run's being recorded to, if it is.
+    pub fn recorder(&self) -> Option<&Recorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Note down in the event log, if the run's being recorded, that
+    /// something's been done to the world from outside (described by
+    /// `what`), so the world's written out again before the next tick.
+    pub fn record_intervention(&mut self, what: &str) {
+        let tick = self.ticks;
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.write(&LogEntry::<()>::Intervention {
+                tick,
+                what: what.to_string(),
+            });
+            recorder.changed();
+        }
+    }
+
+    /// Have every actor's preferences drift over time (or stop drifting, with
+    /// `None`), so long runs show demand shifting around. Each drift moves
+    /// one of an actor's goals a place up or down its hierarchy; the
+    /// preference list is rebuilt around it (see `ValueScale::rerank_goal`),
+    /// so everything that depends on how the actor values things stays
+    /// consistent.
+    pub fn set_drift(&mut self, drift: Option<Drift>) {
+        self.drift = drift;
+        for id in self.actors.ids() {
+            self.schedule.cancel(id, Wakeup::Drift);
+            self.schedule_drift(id);
+        }
+    }
+
+    /// How actors' preferences shift over time, if they do.
+    pub fn drift(&self) -> Option<Drift> {
+        self.drift
+    }
+
+    /// Have actors remember who's dealt with them and who's turned them down
+    /// (or stop remembering, with `None`), so they go back to partners that
+    /// have traded with them before (see `trade::find_next_partner`). Every
+    /// tick, `decay` (a fraction) of what they remember fades, so old
+    /// dealings count for less than new ones: with 1, they only remember
+    /// last tick, and with 0 they never forget.
+    pub fn set_memory_decay(&mut self, decay: Option<Quantity>) {
+        self.memory_decay = decay;
+    }
+
+    /// How much of what actors remember about their trading partners fades
+    /// every tick, if they remember anything at all.
+    pub fn memory_decay(&self) -> Option<Quantity> {
+        self.memory_decay
+    }
+
+    /// Have the world pair up everyone looking to trade all at once, every
+    /// tick, before anyone negotiates (or leave them to go looking one at a
+    /// time, with `None`). See `matching::match_traders`. Only protocols that
+    /// can pick up from a partner found for them use it (see
+    /// `TradeProtocol::takes_matches`).
+    pub fn set_matching(&mut self, matching: Option<Matching>) {
+        self.matching = matching;
+    }
+
+    /// How actors looking to trade are paired up each tick, if the world
+    /// does it for them.
+    pub fn matching(&self) -> Option<Matching> {
+        self.matching
+    }
+
+    /// Have actors act in `order` every tick (by ID, unless this is set).
+    /// See `TickOrder`.
+    pub fn set_order(&mut self, order: TickOrder) {
+        self.order = order;
+    }
+
+    /// The order actors act in each tick.
+    pub fn order(&self) -> TickOrder {
+        self.order
+    }
+
+    /// Have negotiations called off once they've gone on for `ticks` ticks
+    /// without a deal (or let them go on as long as they like, with `None`).
+    /// Either way, anyone left waiting on bids from someone who's gone off
+    /// and done something else is let go (see `expire_negotiations`).
+    pub fn set_negotiation_timeout(&mut self, ticks: Option<u64>) {
+        self.negotiation_timeout = ticks;
+    }
+
+    /// How many ticks a negotiation can go on before it's called off, if
+    /// there's any limit.
+    pub fn negotiation_timeout(&self) -> Option<u64> {
+        self.negotiation_timeout
+    }
+
+    /// Schedule an actor's preferences to drift next, if they drift at all.
+    fn schedule_drift(&mut self, id: ActorId) {
+        if let Some(delay) = self.drift.and_then(|d| d.delay(&mut self.rng)) {
+            self.schedule
+                .schedule(self.ticks + delay, id, Wakeup::Drift);
+        }
+    }
+
+    /// Moves one of an actor's goals, picked at random, a place up or down
+    /// its hierarchy.
+    fn drift_goal(&mut self, id: ActorId) -> Vec<SimEvent> {
+        let mut actor = self.actors.get_mut(id).unwrap();
+        let classes = actor.values.indifference_classes();
+        if classes.len() < 2 {
+            return vec![];
+        }
+        let mut goals: Vec<GoalId> = classes.iter().flatten().copied().collect();
+        goals.sort();
+        let goal = goals[self.rng.gen_range(0, goals.len())];
+        let from = classes.iter().position(|c| c.contains(&goal)).unwrap();
+        let to = if from == 0 {
+            1
+        } else if from == classes.len() - 1 || self.rng.gen() {
+            from - 1
+        } else {
+            from + 1
+        };
+        actor.rerank_goal(goal, to);
+        vec![SimEvent::GoalDrifted {
+            actor: id,
+            goal,
+            from,
+            to,
+        }]
+    }
+
+    /// Adds an actor to the world, returning its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - the actor to add
+    ///
+    pub fn add_actor(&mut self, mut actor: Actor) -> ActorId {
+        actor.values.learn_items(&self.items);
+        actor.values.learn_goals(&self.goals);
+        actor.values.price_controls = self.price_controls.iter().map(|&(_, c)| c).collect();
+        actor.values.legal_tender = self.legal_tender.iter().map(|&(_, l)| l).collect();
+        for goal in actor.values.current_goals.iter() {
+            actor.ledger.open(goal.goal, self.ticks);
+        }
+        let id = self.actors.insert(actor);
+        let actor = self.actors.get(id).unwrap();
+        for (goal, delay) in actor.values.recurring_goals() {
+            self.schedule
+                .schedule(self.ticks + delay, id, Wakeup::GoalDue(goal));
+        }
+        for (goal, probability) in actor.values.stochastic_goals() {
+            if let Some(delay) = arrival_delay(&mut self.rng, probability) {
+                self.schedule
+                    .schedule(self.ticks + delay, id, Wakeup::GoalDue(goal));
+            }
+        }
+        self.schedule_drift(id);
+        self.wake(id);
+        id
+    }
+
+    /// Gives an actor a new goal while the simulation is running, and
+    /// schedules it to come due if it recurs or comes up at random. Returns
+    /// whether the actor took it: it doesn't if there's no such actor, or if
+    /// it already has the goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the actor to give it to
+    /// * `goal` - the goal, and how it comes about
+    /// * `position` - where it goes in the actor's hierarchy (0 is most
+    ///   valued, anything past the end puts it last)
+    ///
+    pub fn add_goal(&mut self, id: ActorId, goal: GoalData, position: usize) -> bool {
+        let actor = match self.actors.get_mut(id) {
+            Some(actor) => actor,
+            None => return false,
+        };
+        let g = goal.get_goal();
+        let position = position.min(actor.values.indifference_classes().len());
+        if actor.values.add_new_goal(goal, position).is_err() {
+            return false;
+        }
+        if actor.values.is_in_play(g) {
+            actor.ledger.open(g, self.ticks);
+        }
+        let next = match actor.values.arrival_probability(g) {
+            Some(probability) => arrival_delay(&mut self.rng, probability),
+            None => actor
+                .values
+                .recurring_goals()
+                .into_iter()
+                .find(|&(r, _)| r == g)
+                .map(|(_, delay)| delay),
+        };
+        if let Some(delay) = next {
+            self.schedule
+                .schedule(self.ticks + delay, id, Wakeup::GoalDue(g));
+        }
+        self.wake(id);
+        true
+    }
+
+    /// Takes a goal away from an actor altogether while the simulation is
+    /// running. Returns whether the actor had it to begin with.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the actor to take it from
+    /// * `goal` - the goal to take away
+    ///
+    pub fn remove_goal(&mut self, id: ActorId, goal: GoalId) -> bool {
+        let actor = match self.actors.get_mut(id) {
+            Some(actor) => actor,
+            None => return false,
+        };
+        if !actor.values.goal_registry.contains_key(&goal) {
+            return false;
+        }
+        actor.values.remove_goal(goal);
+        actor.ledger.close(goal);
+        // Otherwise it'd come due twice as often if it were ever given back
+        self.schedule.cancel(id, Wakeup::GoalDue(goal));
+        self.wake(id);
+        true
+    }
+
+    /// Make sure an actor gets to act on the next tick. Actors that have
+    /// nothing to do aren't visited until one of their goals comes due, so
+    /// call this after changing an actor from outside the simulation (giving
+    /// it items, goals, etc).
+    pub fn wake(&mut self, id: ActorId) {
+        self.schedule.schedule(self.ticks, id, Wakeup::Act);
+    }
+
+    /// The tick the next thing is scheduled to happen on, if anything is
+    /// scheduled at all. Ticks before then will be empty.
+    pub fn next_wakeup(&self) -> Option<u64> {
+        self.schedule.next_due()
+    }
+
+    /// Runs one tick of the simulation. Returns everything that happened, in
+    /// order.
+    ///
+    /// # Notes
+    ///
+    /// Only actors with something scheduled for this tick are visited (see
+    /// `Scheduler`). A tick happens in phases, so that no actor ever has to be
+    /// borrowed while another one is looking at it:
+    ///
+    /// 1. Perishable goods that have been held too long spoil, durable goods
+    ///    in use serve their goals for another tick, land yields its produce
+    ///    to whoever holds it, recurring goals that are due come back into
+    ///    play and are scheduled to come due again, and preferences that are
+    ///    due to drift do.
+    /// 2. Every actor that's awake decides what to do, all looking at the same
+    ///    (read-only) snapshot of the world.
+    /// 3. The decisions are carried out one at a time, in the order the actors
+    ///    were added, including any trade negotiation they involve.
+    /// 4. Anyone who still has something to do is woken again next tick.
+    ///
+    pub fn tick(&mut self) -> Vec<SimEvent> {
+        let tick = self.ticks;
+        if self.history.limit > 0 {
+            let world = serde_json::to_string(&*self).expect("worlds always serialize");
+            self.history.keep(world);
+        }
+        if let Some(mut recorder) = self.recorder.take() {
+            if recorder.has_changed() {
+                recorder.write(&LogEntry::World {
+                    tick,
+                    version: SNAPSHOT_VERSION,
+                    world: &*self,
+                });
+            }
+            self.recorder = Some(recorder);
+        }
+        self.actors.set_now(tick);
+        for observer in self.observers.iter_mut() {
+            observer.on_tick_start(tick);
+        }
+        // Only bother remembering everyone's state if someone's listening
+        let states_before: Vec<_> = if self.observers.is_empty() {
+            vec![]
+        } else {
+            self.actors().map(|a| a.state.clone()).collect()
+        };
+
+        let mut events = vec![];
+        if self.items.has_perishables() {
+            events.extend(self.spoil());
+        }
+        if self.items.has_durables() {
+            for id in self.actors.ids() {
+                events.extend(self.actors.get_mut(id).unwrap().serve(&self.items));
+            }
+        }
+        if self.items.has_land() {
+            events.extend(self.harvest());
+        }
+        events.extend(self.settle_debts(tick));
+        events.extend(self.check_banks());
+        let mut awake = BTreeSet::new();
+        for (id, wakeup) in self.schedule.pop_due(tick) {
+            if let Wakeup::GoalDue(goal) = wakeup {
+                let mut actor = self.actors.get_mut(id).unwrap();
+                let due = actor.goal_due(goal);
+                if due.contains(&SimEvent::GoalReintroduced { actor: id, goal }) {
+                    // Whatever was saved for it can go towards it now
+                    actor.inventory.release(goal);
+                }
+                events.extend(due);
+                let next = match actor.values.arrival_probability(goal) {
+                    Some(probability) => arrival_delay(&mut self.rng, probability),
+                    None => actor.values.recurrence(goal),
+                };
+                if let Some(delay) = next {
+                    self.schedule
+                        .schedule(tick + delay, id, Wakeup::GoalDue(goal));
+                }
+            }
+            if wakeup == Wakeup::Drift {
+                if self.drift.is_none() {
+                    continue;
+                }
+                events.extend(self.drift_goal(id));
+                self.schedule_drift(id);
+            }
+            awake.insert(id);
+        }
+
+        let awake: Vec<_> = awake.into_iter().collect();
+        if self.actors.iter().any(|actor| actor.values.saves) {
+            events.extend(self.save_for_later(tick));
+        }
+        let mut actions = self.decide_all(&awake);
+        self.order
+            .arrange(&mut actions, tick, self.actors.len(), &mut self.rng);
+        if let Some(matching) = self.matching.filter(|_| self.protocol.takes_matches()) {
+            events.extend(self.match_traders(&actions, matching));
+        }
+        for (id, action) in actions {
+            events.extend(self.commit(id, action));
+        }
+        events.extend(self.protocol.settle(&mut self.actors));
+        events.extend(self.expire_negotiations(tick));
+        let learned = self.spread_know_how(&events);
+        events.extend(learned);
+        events.extend(self.take_time_off(&awake));
+        let traced = self.trace_new_money(&events, tick);
+        events.extend(traced);
+        let taxed = self.collect_taxes(&events);
+        events.extend(taxed);
+        // Anything that was waiting on goals satisfied this tick can go ahead
+        for &id in awake.iter() {
+            events.extend(self.actors.get_mut(id).unwrap().activate_goals());
+        }
+        for id in awake {
+            if self.actors.get(id).unwrap().is_busy() || self.actors.behavior(id).stays_awake() {
+                self.schedule.schedule(tick + 1, id, Wakeup::Act);
+            }
+        }
+
+        self.record_satisfactions(&events, tick);
+        self.release_reservations(&events);
+        self.record_trades(&events, tick);
+        self.record_accounts(&events, tick);
+        self.record_saleability(&events);
+        if let Some(decay) = self.memory_decay {
+            self.remember_partners(&events, decay);
+        }
+        self.record_sightings(&events, tick);
+        self.record_expectations(&events, tick);
+        for event in events.iter() {
+            self.prices.record(event, tick);
+            self.wages.record(event, tick);
+            self.trades.record(event);
+        }
+
+        for observer in self.observers.iter_mut() {
+            for event in events.iter() {
+                match *event {
+                    SimEvent::TradeCompleted {
+                        actor,
+                        partner,
+                        ref gave,
+                        ref received,
+                    } => observer.on_trade(tick, actor, partner, gave, received),
+                    SimEvent::GoalSatisfied { actor, goal } => {
+                        observer.on_goal_satisfied(tick, actor, goal)
+                    }
+                    _ => {}
+                }
+                observer.on_event(tick, event);
+            }
+            for (actor, old) in self.actors.iter().zip(states_before.iter()) {
+                let new = actor.state;
+                if new != old {
+                    observer.on_state_change(tick, actor.id, old, new);
+                }
+            }
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.write(&LogEntry::<()>::Tick {
+                tick,
+                events: events.clone(),
+                rng: self.rng.clone(),
+            });
+        }
+
+        self.ticks += 1;
+        events
+    }
+
+    /// Have every actor that weighs its work against its leisure (see
+    /// `ValueScale::weighs_leisure`), and has a leisure goal in play, take
+    /// whatever hours it didn't work this tick off, towards that goal, and
+    /// note what the hours it did work cost it in leisure.
+    fn take_time_off(&mut self, awake: &[ActorId]) -> Vec<SimEvent> {
+        let mut events = vec![];
+        for &id in awake {
+            let mut actor = self.actors.get_mut(id).unwrap();
+            if !actor.values.weighs_leisure {
+                continue;
+            }
+            let leisure = match actor.values.leisure_in_play() {
+                Some(leisure) => leisure,
+                None => continue,
+            };
+            let hours = actor.labor.worked(actor.now);
+            if !hours.is_zero() {
+                events.push(SimEvent::LeisureForgone {
+                    actor: id,
+                    hours,
+                    goal: leisure,
+                });
+            }
+            events.extend(actor.rest(leisure));
+        }
+        events
+    }
+
+    /// Call off every negotiation that's run out of time (see
+    /// `set_negotiation_timeout`), and let go of anyone waiting on bids that
+    /// nobody's making any more: if whoever started bidding gave up, went
+    /// off to do something else, or had its negotiation called off, its
+    /// partner would otherwise wait on it forever, turning everyone else
+    /// away. Everyone let go goes back to looking for a goal, and is woken
+    /// next tick to get on with it.
+    fn expire_negotiations(&mut self, tick: u64) -> Vec<SimEvent> {
+        let previous = std::mem::take(&mut self.negotiations);
+        for actor in self.actors.iter() {
+            let partner = match *actor.state {
+                ActorState::FoundTradePartner(partner) | ActorState::Bidding(partner, _) => partner,
+                _ => continue,
+            };
+            let since = previous
+                .iter()
+                .find(|&&(a, b, _)| a == actor.id && b == partner)
+                .map_or(tick, |&(_, _, since)| since);
+            self.negotiations.push((actor.id, partner, since));
+        }
+        let mut events = vec![];
+        let mut released = vec![];
+        let mut expired = vec![];
+        if let Some(timeout) = self.negotiation_timeout {
+            self.negotiations.retain(|&(actor, partner, since)| {
+                if tick + 1 - since < timeout {
+                    return true;
+                }
+                events.push(SimEvent::NegotiationExpired {
+                    actor,
+                    partner: Some(partner),
+                    ticks: tick + 1 - since,
+                });
+                released.push(actor);
+                expired.push(partner);
+                false
+            });
+        }
+        for id in self.actors.ids() {
+            let waiting = matches!(
+                self.actors.get(id).unwrap().state,
+                ActorState::BidRecipiant(..)
+            );
+            if !waiting
+                || self
+                    .negotiations
+                    .iter()
+                    .any(|&(_, partner, _)| partner == id)
+            {
+                continue;
+            }
+            if !expired.contains(&id) {
+                let last = previous.iter().find(|&&(_, partner, _)| partner == id);
+                events.push(SimEvent::NegotiationExpired {
+                    actor: id,
+                    partner: last.map(|&(initiator, _, _)| initiator),
+                    ticks: last.map_or(0, |&(_, _, since)| tick + 1 - since),
+                });
+            }
+            released.push(id);
+        }
+        for id in released {
+            *self.actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+            self.schedule.schedule(tick + 1, id, Wakeup::Act);
+        }
+        events
+    }
+
+    /// Keep everyone's satisfaction ledger up to date with what happened to
+    /// them this tick (see `SatisfactionLedger::record`).
+    fn record_satisfactions(&mut self, events: &[SimEvent], tick: u64) {
+        for event in events {
+            let actor = match *event {
+                SimEvent::GoalReintroduced { actor, .. }
+                | SimEvent::GoalActivated { actor, .. }
+                | SimEvent::ItemUsed { actor, .. }
+                | SimEvent::GoalSatisfied { actor, .. }
+                | SimEvent::GoalAbandoned { actor, .. } => actor,
+                _ => continue,
+            };
+            if let Some(actor) = self.actors.get_mut(actor) {
+                actor.ledger.record(event, tick);
+            }
+        }
+    }
+
+    /// Have everyone who saves set aside what they'd keep for the recurring
+    /// goals they know are coming (see `ActorRef::savings`), in place of
+    /// whatever they'd set aside for them before, and note the savings rate
+    /// for the tick.
+    fn save_for_later(&mut self, tick: u64) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let (mut saved, mut held) = (Quantity::ZERO, Quantity::ZERO);
+        for id in self.actors.ids() {
+            let savings = self.actors.get(id).unwrap().savings();
+            let actor = self.actors.get_mut(id).unwrap();
+            // Anything set aside for a goal in play is for a plan under way
+            let before: Vec<(GoalId, Bundle)> = actor
+                .inventory
+                .reservations()
+                .filter(|&(goal, _)| !actor.values.is_in_play(goal))
+                .map(|(goal, goods)| (goal, goods.clone()))
+                .collect();
+            for &(goal, _) in before.iter() {
+                actor.inventory.release(goal);
+            }
+            for (goal, goods) in savings {
+                let kept = actor.inventory.reserve(goal, &goods);
+                let unchanged = before.iter().any(|(g, old)| *g == goal && *old == kept);
+                if !kept.is_empty() && !unchanged {
+                    events.push(SimEvent::GoodsSaved {
+                        actor: id,
+                        goal,
+                        items: kept,
+                    });
+                }
+            }
+            for (item, amount) in actor.inventory.iter() {
+                let def = self.items.get(item).unwrap();
+                if def.class.is_money() || (def.order.is_consumer_good() && !def.class.is_land()) {
+                    held += amount;
+                    saved += actor
+                        .inventory
+                        .reservations()
+                        .filter(|&(goal, _)| !actor.values.is_in_play(goal))
+                        .map(|(_, goods)| goods.count(item))
+                        .sum::<Quantity>();
+                }
+            }
+        }
+        if !held.is_zero() {
+            self.savings_rates.push((tick, saved.divided_by(held)));
+        }
+        events
+    }
+
+    /// Follow any newly minted money that changed hands this tick (see
+    /// `MoneyTrace::record`), returning where it went.
+    fn trace_new_money(&mut self, events: &[SimEvent], tick: u64) -> Vec<SimEvent> {
+        if self.money_trace.is_empty() {
+            return vec![];
+        }
+        let mut traced = vec![];
+        for event in events {
+            traced.extend(self.money_trace.record(event, tick));
+        }
+        for (id, item) in self.money_trace.holders() {
+            let held = self.actors.get(id).unwrap().inventory.count(item);
+            self.money_trace.settle(id, item, held);
+        }
+        traced
+    }
+
+    /// Have the collector, if there is one, take its cut of whatever was
+    /// traded or made this tick (see `Collector::assess`), out of whatever
+    /// the actors who owe it have spare, and then hand it all out or
+    /// destroy it (see `Collector::dispose`).
+    fn collect_taxes(&mut self, events: &[SimEvent]) -> Vec<SimEvent> {
+        let mut collector = match self.collector.take() {
+            Some(collector) => collector,
+            None => return vec![],
+        };
+        let mut taxed = vec![];
+        for event in events {
+            collector.assess(event);
+        }
+        let mut paid: BTreeMap<ActorId, Bundle> = BTreeMap::new();
+        for (id, item, owed) in collector.due(|item| self.items.is_divisible(item)) {
+            let actor = match self.actors.get_mut(id) {
+                Some(actor) => actor,
+                None => continue,
+            };
+            let mut amount = owed.min(actor.inventory.spare(item));
+            if !self.items.is_divisible(item) {
+                amount = amount.whole();
+            }
+            if !amount.is_zero() {
+                actor.inventory.remove(item, amount);
+                collector.pay(item, amount);
+                paid.entry(id).or_default().add(item, amount);
+            }
+        }
+        for (actor, items) in paid {
+            taxed.push(SimEvent::TaxCollected { actor, items });
+        }
+        let ids: Vec<ActorId> = self.actors.ids().collect();
+        for (n, items) in collector.dispose(ids.len(), |item| self.items.is_divisible(item)) {
+            match n {
+                Some(n) => {
+                    let mut actor = self.actors.get_mut(ids[n]).unwrap();
+                    taxed.push(SimEvent::TaxPaidOut {
+                        actor: ids[n],
+                        items: items.clone(),
+                    });
+                    for (item, amount) in items.iter() {
+                        taxed.extend(actor.receive(item, amount));
+                    }
+                    self.wake(ids[n]);
+                }
+                None => taxed.push(SimEvent::TaxDestroyed { items }),
+            }
+        }
+        self.collector = Some(collector);
+        taxed
+    }
+
+    /// Stop keeping anything for goals that were satisfied or given up on
+    /// this tick (see `Inventory::reserve`): whatever plan it was being kept
+    /// for is done with.
+    fn release_reservations(&mut self, events: &[SimEvent]) {
+        for event in events {
+            if let SimEvent::GoalSatisfied { actor, goal }
+            | SimEvent::GoalAbandoned { actor, goal, .. } = *event
+            {
+                if let Some(actor) = self.actors.get_mut(actor) {
+                    actor.inventory.release(goal);
+                }
+            }
+        }
+    }
+
+    /// Keep the trade ledgers of everyone who bid or traded this tick up to
+    /// date (see `TradeLedger::record`).
+    fn record_trades(&mut self, events: &[SimEvent], tick: u64) {
+        for event in events {
+            let parties = match *event {
+                SimEvent::BidMade { actor, partner, .. }
+                | SimEvent::CounterOfferMade { actor, partner, .. }
+                | SimEvent::BidRejected { actor, partner, .. }
+                | SimEvent::TradeCompleted { actor, partner, .. }
+                | SimEvent::TradeAbandoned { actor, partner } => vec![actor, partner],
+                SimEvent::TradeCycleCompleted { ref actors, .. } => actors.clone(),
+                _ => continue,
+            };
+            for id in parties {
+                if let Some(actor) = self.actors.get_mut(id) {
+                    actor.trades.record(id, event, tick);
+                }
+            }
+        }
+    }
+
+    /// Let everyone who traded this tick see how their partners made what
+    /// they got from them, and learn any recipe for it the partners know
+    /// that they don't (see `KnowHow`). Other than finding them out for
+    /// themselves, that's the only way actors learn recipes that aren't
+    /// common knowledge.
+    fn spread_know_how(&mut self, events: &[SimEvent]) -> Vec<SimEvent> {
+        if self
+            .items
+            .recipes()
+            .all(|(_, recipe)| recipe.discovery.is_none())
+        {
+            return vec![];
+        }
+        // Who got what from whom
+        let mut handed = vec![];
+        for event in events {
+            match *event {
+                SimEvent::TradeCompleted {
+                    actor,
+                    partner,
+                    ref gave,
+                    ref received,
+                } => {
+                    handed.push((actor, partner, received));
+                    handed.push((partner, actor, gave));
+                }
+                SimEvent::TradeCycleCompleted {
+                    ref actors,
+                    ref gave,
+                } => {
+                    for (i, bundle) in gave.iter().enumerate() {
+                        handed.push((actors[(i + 1) % actors.len()], actors[i], bundle));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut learned = vec![];
+        for (actor, from, got) in handed {
+            let teacher = match self.actors.get(from) {
+                Some(teacher) => teacher,
+                None => continue,
+            };
+            let recipes: Vec<RecipeId> = teacher
+                .know_how
+                .known()
+                .filter(|&recipe| {
+                    self.items
+                        .recipe(recipe)
+                        .is_some_and(|def| def.outputs.iter().any(|(item, _)| got.contains(item)))
+                })
+                .collect();
+            if let Some(learner) = self.actors.get_mut(actor) {
+                for recipe in recipes {
+                    if learner.know_how.learn(recipe) {
+                        learned.push(SimEvent::RecipeLearned {
+                            actor,
+                            from,
+                            recipe,
+                        });
+                    }
+                }
+            }
+        }
+        learned
+    }
+
+    /// Put whatever goods changed hands or were made this tick on the books
+    /// of everyone who gave them up or got them (see `Accounts::record`).
+    fn record_accounts(&mut self, events: &[SimEvent], tick: u64) {
+        for event in events {
+            let parties = match *event {
+                SimEvent::TradeCompleted { actor, partner, .. } => vec![actor, partner],
+                SimEvent::TradeCycleCompleted { ref actors, .. } => actors.clone(),
+                SimEvent::TradeCostPaid { actor, .. }
+                | SimEvent::ProductionStarted { actor, .. }
+                | SimEvent::ItemsProduced { actor, .. }
+                | SimEvent::ToolMaintained { actor, .. } => vec![actor],
+                SimEvent::LaborHired {
+                    employer, worker, ..
+                } => vec![employer, worker],
+                SimEvent::RentPaid {
+                    tenant, landlord, ..
+                } => vec![tenant, landlord],
+                _ => continue,
+            };
+            for id in parties {
+                if let Some(actor) = self.actors.get_mut(id) {
+                    actor.accounts.record(id, event, tick, &self.items);
+                }
+            }
+        }
+    }
+
+    /// Let everyone know what was accepted in the trades that happened this
+    /// tick, so they can keep track of how saleable each good is (see
+    /// `ValueScale::saleability`). Trades are out in the open, so everyone
+    /// sees all of them. A trade of one medium of exchange for another
+    /// doesn't count, since nobody took either for what it's good for, and
+    /// counting it would only make both look more saleable each time they
+    /// were swapped back and forth.
+    fn record_saleability(&mut self, events: &[SimEvent]) {
+        let mut accepted: Vec<ItemId> = vec![];
+        // Where the last trade's goods start in `accepted`, so they can be
+        // taken back out if it turns out to have been a swap of media
+        let mut last = 0;
+        for event in events {
+            match event {
+                SimEvent::TradeCompleted { gave, received, .. } => {
+                    last = accepted.len();
+                    accepted.extend(gave.iter().chain(received.iter()).map(|(i, _)| i));
+                }
+                SimEvent::TradeCycleCompleted { gave, .. } => {
+                    last = accepted.len();
+                    accepted.extend(gave.iter().flat_map(|bundle| bundle.iter().map(|(i, _)| i)));
+                }
+                SimEvent::MediumAccepted {
+                    swapped: Some(_), ..
+                } => accepted.truncate(last),
+                _ => {}
+            }
+        }
+        if accepted.is_empty() {
+            return;
+        }
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            for &item in accepted.iter() {
+                actor.values.note_accepted(item);
+            }
+        }
+    }
+
+    /// Fade what everyone remembers about their trading partners by `decay`,
+    /// then have them remember what happened this tick: both sides of every
+    /// trade that went through, and whoever had a bid turned down, who
+    /// turned it down (see `TradeMemory`).
+    fn remember_partners(&mut self, events: &[SimEvent], decay: Quantity) {
+        for id in self.actors.ids() {
+            self.actors.get_mut(id).unwrap().memory.fade(decay);
+        }
+        for event in events {
+            match *event {
+                SimEvent::TradeCompleted { actor, partner, .. } => {
+                    if let Some((a, b)) = self.actors.pair_mut(actor, partner) {
+                        a.memory.note_accepted(partner);
+                        b.memory.note_accepted(actor);
+                    }
+                }
+                SimEvent::TradeCycleCompleted { ref actors, .. } => {
+                    for (n, &actor) in actors.iter().enumerate() {
+                        let next = actors[(n + 1) % actors.len()];
+                        if let Some((a, b)) = self.actors.pair_mut(actor, next) {
+                            a.memory.note_accepted(next);
+                            b.memory.note_accepted(actor);
+                        }
+                    }
+                }
+                SimEvent::BidRejected {
+                    actor,
+                    partner,
+                    rejected_by,
+                } => {
+                    let (by, of) = if rejected_by == actor {
+                        (actor, partner)
+                    } else {
+                        (partner, actor)
+                    };
+                    if let Some(rejected) = self.actors.get_mut(of) {
+                        rejected.memory.note_rejected(by);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Have everyone learn what to expect goods to go for from the trades
+    /// that happened this tick: both sides of each trade from their own, and
+    /// anyone watching the market from every one (see `Expectations`).
+    /// Anyone who's learned enough to want to stock up on something now
+    /// (see `ActorRef::stockpile_goal`) is woken next tick to go and do it.
+    fn record_expectations(&mut self, events: &[SimEvent], tick: u64) {
+        let mut learned = BTreeSet::new();
+        let watching: Vec<_> = self
+            .actors
+            .iter()
+            .filter(|a| a.expectations.watches_market())
+            .map(|a| a.id)
+            .collect();
+        for event in events {
+            let point = match PricePoint::of(event, tick) {
+                Some(point) => point,
+                None => continue,
+            };
+            let (actor, partner) = match *event {
+                SimEvent::TradeCompleted { actor, partner, .. } => (actor, partner),
+                _ => continue,
+            };
+            let others = watching
+                .iter()
+                .copied()
+                .filter(|&id| id != actor && id != partner);
+            for id in [actor, partner].iter().copied().chain(others) {
+                if let Some(actor) = self.actors.get_mut(id) {
+                    actor.expectations.observe(&point);
+                    learned.insert(id);
+                }
+            }
+        }
+        for id in learned {
+            if self.actors.get(id).unwrap().stockpile_goal().is_some() {
+                self.schedule.schedule(tick + 1, id, Wakeup::Act);
+            }
+        }
+    }
+
+    /// Have everyone with imperfect information that saw someone else this
+    /// tick remember what they had by the end of it: whoever they looked at
+    /// searching (see `trade::search`), and both sides of any bid or trade
+    /// (see `TradeMemory::see`).
+    fn record_sightings(&mut self, events: &[SimEvent], tick: u64) {
+        let mut seen: Vec<(ActorId, ActorId)> = vec![];
+        for event in events {
+            match *event {
+                SimEvent::Searched {
+                    actor,
+                    ref looked_at,
+                } => seen.extend(looked_at.iter().map(|&other| (actor, other))),
+                SimEvent::BidMade { actor, partner, .. }
+                | SimEvent::CounterOfferMade { actor, partner, .. }
+                | SimEvent::TradeCompleted { actor, partner, .. } => {
+                    seen.push((actor, partner));
+                    seen.push((partner, actor));
+                }
+                SimEvent::TradeCycleCompleted { ref actors, .. } => {
+                    for (n, &actor) in actors.iter().enumerate() {
+                        let next = actors[(n + 1) % actors.len()];
+                        seen.push((actor, next));
+                        seen.push((next, actor));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (by, of) in seen {
+            if !self.actors.get(by).unwrap().memory.is_imperfect() {
+                continue;
+            }
+            if let Some((looker, other)) = self.actors.pair_mut(by, of) {
+                let items = other.inventory.iter().map(|(i, _)| i).collect();
+                looker.memory.see(of, items, tick);
+            }
+        }
+    }
+
+    /// Age everyone's inventory by a tick, throwing out whatever's gone off
+    /// (public goods in the commons go off too). Unlike everything else this
+    /// has to visit every actor, but it's skipped entirely when there aren't
+    /// any perishable items.
+    fn spoil(&mut self) -> Vec<SimEvent> {
+        let items = &self.items;
+        let mut events = vec![];
+        self.commons.age(|i| items.shelf_life(i));
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            for (item, amount) in actor.inventory.age(|i| items.shelf_life(i)) {
+                events.push(SimEvent::ItemSpoiled {
+                    actor: id,
+                    item,
+                    amount,
+                });
+            }
+        }
+        events
+    }
+
+    /// Give everyone who holds land what it yields this tick (rounded down to
+    /// whole units for goods that only come in those), and wake them up so
+    /// they can do something with it. Skipped entirely when there isn't any
+    /// land.
+    fn harvest(&mut self) -> Vec<SimEvent> {
+        let items = &self.items;
+        let mut events = vec![];
+        let mut harvesters = vec![];
+        for id in self.actors.ids() {
+            let mut actor = self.actors.get_mut(id).unwrap();
+            let mut land: Vec<_> = actor
+                .inventory
+                .iter()
+                .filter(|&(item, _)| items.is_land(item))
+                .collect();
+            land.sort();
+            for (land, held) in land {
+                for (item, per_unit) in items.yields(land) {
+                    let mut amount = per_unit.times(held);
+                    if !items.is_divisible(item) {
+                        amount = amount.whole();
+                    }
+                    if amount.is_zero() {
+                        continue;
+                    }
+                    events.push(SimEvent::ItemYielded {
+                        actor: id,
+                        land,
+                        item,
+                        amount,
+                    });
+                    events.extend(actor.receive(item, amount));
+                    harvesters.push(id);
+                }
+            }
+        }
+        for id in harvesters {
+            self.wake(id);
+        }
+        events
+    }
+
+    /// Settle every debt and forward contract that's come due on tick
+    /// `tick`: each debtor pays back what it can (see `credit::repay`), and
+    /// the parties to each contract swap what they agreed to if they both
+    /// can (see `credit::deliver`). Whoever got anything is woken up to do
+    /// something with it.
+    fn settle_debts(&mut self, tick: u64) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let mut paid = vec![];
+        for id in self.actors.ids() {
+            let actor = self.actors.get_mut(id).unwrap();
+            let due = actor.credit.take_due(tick);
+            let forwards = actor.credit.take_due_forwards(tick);
+            let leases = actor.credit.leases().to_vec();
+            for iou in due {
+                let (mut debtor, mut creditor) =
+                    self.actors.pair_mut(iou.debtor, iou.creditor).unwrap();
+                credit::repay(&iou, &mut debtor, &mut creditor, &mut events);
+                paid.push(iou.creditor);
+            }
+            // Both parties have a copy of each contract, but it only needs
+            // settling once
+            for forward in forwards.into_iter().filter(|f| f.buyer == id) {
+                let (mut buyer, mut seller) =
+                    self.actors.pair_mut(forward.buyer, forward.seller).unwrap();
+                credit::deliver(&forward, &mut buyer, &mut seller, &mut events);
+                paid.push(forward.buyer);
+                paid.push(forward.seller);
+            }
+            // Same goes for leases, which the tenant sees to
+            for lease in leases.into_iter().filter(|l| l.tenant == id) {
+                let (mut tenant, mut landlord) =
+                    self.actors.pair_mut(lease.tenant, lease.landlord).unwrap();
+                if credit::collect_rent(&lease, &mut tenant, &mut landlord, &mut events) {
+                    paid.push(lease.tenant);
+                    paid.push(lease.landlord);
+                }
+            }
+        }
+        for id in paid {
+            self.wake(id);
+        }
+        events
+    }
+
+    /// Note every bank that's failed since it was last looked at: one that
+    /// owes its depositors more than it holds and is owed put together, or
+    /// couldn't pay a deposit back (see `BankBook::has_failed`).
+    fn check_banks(&mut self) -> Vec<SimEvent> {
+        let mut events = vec![];
+        for bank in self.actors.iter() {
+            if self.failed_banks.contains(&bank.id) {
+                continue;
+            }
+            match self.bank_book(bank.id) {
+                Some(book) if book.has_failed() => events.push(SimEvent::BankFailed {
+                    bank: bank.id,
+                    owed: book.owed(),
+                    assets: book.reserves + book.lent(),
+                }),
+                _ => {}
+            }
+        }
+        for event in events.iter() {
+            if let SimEvent::BankFailed { bank, .. } = *event {
+                self.failed_banks.insert(bank);
+            }
+        }
+        events
+    }
+
+    /// The balance sheet of `bank`, drawn up in whatever money it's taken
+    /// deposits of (or failing that, the money a `Bank` would deal in), if
+    /// it's taken deposits or lent that money at all.
+    pub fn bank_book(&self, bank: ActorId) -> Option<BankBook> {
+        let actor = self.actors.get(bank)?;
+        let money = match actor.credit.ious().iter().find(|iou| iou.deposit) {
+            Some(iou) => iou.owed.first()?.0,
+            None => self.items.money().into_iter().min()?,
+        };
+        let book = BankBook::of(actor, &self.actors, money);
+        if book.loans.is_empty() && book.deposits.is_empty() && book.defaulted.is_empty() {
+            None
+        } else {
+            Some(book)
+        }
+    }
+
+    /// Whether a bank has failed (see `BankBook::has_failed`).
+    pub fn has_failed(&self, bank: ActorId) -> bool {
+        self.failed_banks.contains(&bank)
+    }
+
+    /// The read phase of a tick: ask the behavior of each actor in `ids`
+    /// what it wants to do, without changing anything.
+    fn decide_all(&mut self, ids: &[ActorId]) -> Vec<(ActorId, Action)> {
+        // Behaviors live inside the actors they decide for, so pull them all
+        // out first to leave the arena free to be looked at. Everyone also
+        // gets their own generator, seeded in ID order, so it doesn't matter
+        // what order (or on what thread) the decisions are made in
+        let mut deciders: Vec<(ActorId, Box<dyn Behavior>, u64)> = ids
+            .iter()
+            .map(|&id| (id, self.actors.take_behavior(id), self.rng.gen()))
+            .collect();
+        let actors = &self.actors;
+        let commons = &self.commons;
+        let items = &self.items;
+        let resources = &self.resources;
+        let prices = &self.prices;
+        let decide = |(id, behavior, seed): &mut (ActorId, Box<dyn Behavior>, u64)| {
+            let action = behavior.decide(WorldView {
+                me: actors.get(*id).unwrap(),
+                actors,
+                commons,
+                items,
+                resources,
+                prices,
+                rng: &mut SimRng::seed_from_u64(*seed),
+            });
+            (*id, action)
+        };
+        let actions = if self.parallel {
+            deciders.par_iter_mut().map(decide).collect()
+        } else {
+            deciders.iter_mut().map(decide).collect()
+        };
+        for (id, behavior, _) in deciders {
+            self.actors.put_behavior(id, behavior);
+        }
+        actions
+    }
+
+    /// The matching phase of a tick: pair up everyone who's decided to trade
+    /// and isn't in the middle of negotiating already (see
+    /// `matching::match_traders`), and get each pair ready to bid.
+    fn match_traders(
+        &mut self,
+        actions: &[(ActorId, Action)],
+        matching: Matching,
+    ) -> Vec<SimEvent> {
+        let actors = &self.actors;
+        let traders: Vec<(ActorId, GoalId)> = actions
+            .iter()
+            .filter_map(|&(id, action)| match action {
+                Action::Trade { goal } => Some((id, goal)),
+                _ => None,
+            })
+            .filter(|&(id, goal)| {
+                let actor = actors.get(id).unwrap();
+                matches!(
+                    actor.state,
+                    ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+                ) && !actor.credit.expecting(id, &actor.wanted_for(goal))
+            })
+            .collect();
+        let pairs = matching::match_traders(&traders, actors, matching, &mut self.rng);
+        let mut events = vec![];
+        for (id, partner) in pairs {
+            let (actor, other) = self.actors.pair_mut(id, partner).unwrap();
+            *actor.state = ActorState::FoundTradePartner(partner);
+            *other.state = ActorState::BidRecipiant(None, None, None);
+            events.push(SimEvent::PartnersMatched { actor: id, partner });
+        }
+        events
+    }
+
+    /// The commit phase of a tick for one actor: carry out what it decided,
+    /// handing over to the trade protocol if it's in the middle of a trade.
+    /// Searching for a partner and haggling with one both take labor (only
+    /// the initiator's), and if there isn't enough left the trade has to wait
+    /// for next tick.
+    fn commit(&mut self, id: ActorId, action: Action) -> Vec<SimEvent> {
+        if let Action::Buy { .. } | Action::Sell { .. } | Action::Arbitrage { .. } = action {
+            return self.deal(id, action);
+        }
+        if let Action::Give { item, to } = action {
+            return self.gift(id, to, &Bundle::single(item, Quantity::ONE));
+        }
+        if let Action::TakeDeposit { .. } | Action::Lend { .. } = action {
+            return self.bank(id, action);
+        }
+        let mut events = vec![];
+        if let Action::Produce { recipe, .. } = action {
+            events.extend(self.hire_for(id, recipe));
+        }
+        let mut actor = self.actors.get_mut(id).unwrap();
+        let work = match *actor.state {
+            ActorState::WillingToTrade(..) => Some(Work::Search),
+            ActorState::FoundTradePartner(..) | ActorState::Bidding(..) => Some(Work::Negotiation),
+            _ => None,
+        };
+        events.extend(actor.act(action, &self.items));
+        if let Action::Gather { goal, node } = action {
+            let gathering = !matches!(*actor.state, ActorState::BidRecipiant(..));
+            if let (true, Some(resource)) = (gathering, self.resources.get_mut(node.0)) {
+                events.extend(actor.gather(node, resource, goal, &self.items));
+            }
+        }
+        if let Action::Experiment { recipe, .. } = action {
+            if !matches!(*actor.state, ActorState::BidRecipiant(..)) {
+                let roll = self.rng.gen();
+                events.extend(actor.experiment(recipe, roll, &self.items));
+            }
+        }
+        if let (Action::Trade { goal }, Some(work)) = (action, work) {
+            let now = actor.now;
+            if actor.labor.spend(work.hours(), now) {
+                events.extend(self.protocol.step(id, goal, &mut self.actors));
+            } else {
+                events.push(SimEvent::OutOfLabor { actor: id, work });
+            }
+        }
+        events
+    }
+
+    /// Hire whatever labor an actor that hires it (see
+    /// `ValueScale::hires_labor`) is short of to put a tick's work into
+    /// making something by `recipe`, from the first actor that's free and
+    /// will take a wage it'll pay (see `labor::hire`). Nobody's hired for a
+    /// recipe the actor couldn't start anyway, or while it's being bid on.
+    fn hire_for(&mut self, id: ActorId, recipe: RecipeId) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let def = match self.items.recipe(recipe) {
+            Some(def) => def.clone(),
+            None => return events,
+        };
+        let now = self.actors.now();
+        let employer = self.actors.get(id).unwrap();
+        let short = def.labor.saturating_sub(employer.labor.left(now));
+        let ready = match *employer.state {
+            ActorState::Producing(r, _, _) => r == recipe,
+            ActorState::BidRecipiant(..) => false,
+            _ => def
+                .needs()
+                .all(|(item, amount)| employer.inventory.count(item) >= amount),
+        };
+        if !employer.values.hires_labor || short.is_zero() || !ready {
+            return events;
+        }
+        let workers: Vec<ActorId> = self
+            .actors
+            .iter()
+            .filter(|worker| {
+                worker.id != id
+                    && matches!(
+                        *worker.state,
+                        ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+                    )
+                    && worker.labor.left(now) >= short
+            })
+            .map(|worker| worker.id)
+            .collect();
+        for worker in workers {
+            let (mut employer, mut other) = self.actors.pair_mut(id, worker).unwrap();
+            if labor::hire(&mut employer, &mut other, short, recipe, &def, &mut events) {
+                self.wake(worker);
+                break;
+            }
+        }
+        events
+    }
+
+    /// Carry out a middleman's purchase or sale (see `behavior::Middleman`),
+    /// or an arbitrageur's (see `behavior::Arbitrageur`), if the other side
+    /// is still free and willing. It takes the same labor as a tick's
+    /// negotiating.
+    fn deal(&mut self, id: ActorId, action: Action) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let unit = |item| Bundle::single(item, Quantity::ONE);
+        let (partner, give, take) = match action {
+            Action::Buy { item, from, pay } => (from, unit(pay), unit(item)),
+            Action::Sell {
+                item, to, price, ..
+            } => (to, unit(item), unit(price)),
+            Action::Arbitrage {
+                partner,
+                give,
+                give_amount,
+                take,
+                take_amount,
+            } => (
+                partner,
+                Bundle::single(give, give_amount),
+                Bundle::single(take, take_amount),
+            ),
+            _ => return events,
+        };
+        let (mut actor, mut other) = match self.actors.pair_mut(id, partner) {
+            Some(pair) => pair,
+            None => return events,
+        };
+        let now = actor.now;
+        if !actor.labor.spend(Work::Negotiation.hours(), now) {
+            events.push(SimEvent::OutOfLabor {
+                actor: id,
+                work: Work::Negotiation,
+            });
+            return events;
+        }
+        let free = matches!(
+            *other.state,
+            ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+        );
+        if free
+            && trade::accepts(other.as_ref(), &give, &take)
+            && trade::exchange(&mut actor, &give, &mut other, &take, &mut events)
+        {
+            *other.state = ActorState::SearchingForGoal;
+            if let Action::Sell {
+                item,
+                to,
+                price,
+                bought_with,
+            } = action
+            {
+                events.push(SimEvent::Resold {
+                    actor: id,
+                    partner: to,
+                    item,
+                    bought_with,
+                    sold_for: price,
+                });
+            }
+        }
+        events
+    }
+
+    /// Carry out a bank's business (see `Bank`): take a deposit from someone,
+    /// or lend to someone, as long as the bank has the labor for it and
+    /// they're still free and would still go for it.
+    fn bank(&mut self, id: ActorId, action: Action) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let (partner, amount) = match action {
+            Action::TakeDeposit {
+                from,
+                money,
+                amount,
+            } => (from, Bundle::single(money, amount)),
+            Action::Lend {
+                to, money, amount, ..
+            } => (to, Bundle::single(money, amount)),
+            _ => return events,
+        };
+        let (mut bank, mut other) = match self.actors.pair_mut(id, partner) {
+            Some(pair) => pair,
+            None => return events,
+        };
+        if self.failed_banks.contains(&id) {
+            return events;
+        }
+        let now = bank.now;
+        if !bank.labor.spend(Work::Negotiation.hours(), now) {
+            events.push(SimEvent::OutOfLabor {
+                actor: id,
+                work: Work::Negotiation,
+            });
+            return events;
+        }
+        match (action, &*other.state) {
+            (Action::TakeDeposit { .. }, ActorState::SearchingForGoal)
+                if credit::would_deposit(other.as_ref(), &amount) =>
+            {
+                credit::deposit(&mut other, &mut bank, &amount, &mut events);
+            }
+            (Action::Lend { interest, .. }, ActorState::WillingToTrade(_))
+                if credit::would_lend(bank.as_ref(), other.as_ref(), &amount) =>
+            {
+                credit::borrow_at(&mut other, &mut bank, &amount, interest, &mut events);
+            }
+            _ => {}
+        }
+        events
+    }
+
+    /// Have `from` give `gift` to `to` for nothing in return (see
+    /// `trade::give`), if it has it all. Whoever gets it is woken up to make
+    /// use of it, and so is the giver, in case it has more to give away.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - the actor giving it
+    /// * `to` - the actor getting it
+    /// * `gift` - what's being given
+    ///
+    pub fn gift(&mut self, from: ActorId, to: ActorId, gift: &Bundle) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let (mut actor, mut other) = match self.actors.pair_mut(from, to) {
+            Some(pair) => pair,
+            None => return events,
+        };
+        if trade::give(&mut actor, &mut other, gift, &mut events) {
+            // Anyone who was only looking to trade for it doesn't need to now
+            if let ActorState::WillingToTrade(_) = *other.state {
+                *other.state = ActorState::SearchingForGoal;
+            }
+            self.wake(from);
+            self.wake(to);
+        }
+        let traced = self.trace_new_money(&events, self.ticks);
+        events.extend(traced);
+        events
+    }
+
+    /// Have `actor` offer `give` to `partner` for `take`, and swap them if
+    /// they'd both go for it: the actor has to value what it'd get at least
+    /// as much as what it'd give up (see `trade::would_give`), and so does
+    /// the partner (see `trade::accepts`), and both have to still have what
+    /// they're giving. If either turns it down, the bid's rejected by them
+    /// instead. It all counts the same as a trade made during a tick (in
+    /// prices, trade histories, expectations, and the trading network), and
+    /// both sides are woken up to make use of what they got.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - the actor making the offer
+    /// * `give` - what it offers
+    /// * `partner` - the actor it's offered to
+    /// * `take` - what it wants for it
+    ///
+    pub fn trade(
+        &mut self,
+        actor: ActorId,
+        give: &Bundle,
+        partner: ActorId,
+        take: &Bundle,
+    ) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let (mut a, mut b) = match self.actors.pair_mut(actor, partner) {
+            Some(pair) => pair,
+            None => return events,
+        };
+        events.push(SimEvent::BidMade {
+            actor,
+            partner,
+            offer: give.clone(),
+            want: take.clone(),
+        });
+        let rejected_by = if !trade::would_give(a.as_ref(), give, take) {
+            Some(actor)
+        } else if !trade::accepts(b.as_ref(), give, take) {
+            Some(partner)
+        } else {
+            None
+        };
+        let traded =
+            rejected_by.is_none() && trade::exchange(&mut a, give, &mut b, take, &mut events);
+        if !traded {
+            events.push(SimEvent::BidRejected {
+                actor,
+                partner,
+                rejected_by: rejected_by.unwrap_or(actor),
+            });
+        }
+        let learned = self.spread_know_how(&events);
+        events.extend(learned);
+        let tick = self.ticks;
+        let traced = self.trace_new_money(&events, tick);
+        events.extend(traced);
+        self.record_trades(&events, tick);
+        self.record_accounts(&events, tick);
+        self.record_expectations(&events, tick);
+        for event in events.iter() {
+            self.prices.record(event, tick);
+            self.trades.record(event);
+        }
+        if traded {
+            self.wake(actor);
+            self.wake(partner);
+        }
+        events
+    }
+
+    /// Have `landlord` let `tenant` have the use of `leased` for `term`
+    /// ticks, for `rent` every tick until then, if they'd both go for it
+    /// (see `credit::refuses_lease`). The leased goods go back to the
+    /// landlord on their own once the lease is up, or as soon as the tenant
+    /// can't pay the rent, and until then both sides keep the lease in
+    /// their credit books. Both are woken up to make use of what they got.
+    ///
+    /// # Arguments
+    ///
+    /// * `landlord` - the actor letting the goods out
+    /// * `tenant` - the actor taking them on
+    /// * `leased` - what's let out (usually a durable good)
+    /// * `rent` - what the tenant pays for it each tick
+    /// * `term` - how many ticks it's let out for
+    ///
+    pub fn lease(
+        &mut self,
+        landlord: ActorId,
+        tenant: ActorId,
+        leased: &Bundle,
+        rent: &Bundle,
+        term: u64,
+    ) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let (mut l, mut t) = match self.actors.pair_mut(landlord, tenant) {
+            Some(pair) => pair,
+            None => return events,
+        };
+        let refused_by = credit::refuses_lease(l.as_ref(), t.as_ref(), leased, rent);
+        let signed = refused_by.is_none()
+            && term > 0
+            && credit::sign_lease(&mut l, &mut t, leased, rent, term, &mut events);
+        if !signed {
+            events.push(SimEvent::LeaseRefused {
+                landlord,
+                tenant,
+                refused_by: refused_by.unwrap_or(landlord),
+            });
+        } else {
+            self.wake(landlord);
+            self.wake(tenant);
+        }
+        let traced = self.trace_new_money(&events, self.ticks);
+        events.extend(traced);
+        events
+    }
+
+    /// Number of ticks that have been run so far
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Number of actors in the world
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    /// Whether there are no actors in the world at all
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    /// The arena holding every actor in the world.
+    pub fn arena(&self) -> &ActorArena {
+        &self.actors
+    }
+
+    /// Look at an actor by ID.
+    pub fn actor(&self, id: ActorId) -> Option<ActorRef<'_>> {
+        self.actors.get(id)
+    }
+
+    /// Change an actor by ID, for instance to hand it items.
+    pub fn actor_mut(&mut self, id: ActorId) -> Option<ActorMut<'_>> {
+        self.actors.get_mut(id)
+    }
+
+    /// Find an actor by name (`Actor#3`) or by raw ID (`3`, `#3`).
+    pub fn find_actor(&self, name_or_id: &str) -> Option<ActorId> {
+        self.actors.resolve(name_or_id)
+    }
+
+    /// The name of an actor, or its raw ID if it doesn't exist.
+    pub fn actor_name(&self, id: ActorId) -> String {
+        self.actor(id)
+            .map(|a| a.name.to_string())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Iterate over every actor in the world, in ID order.
+    pub fn actors(&self) -> impl Iterator<Item = ActorRef<'_>> {
+        self.actors.iter()
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation::new()
+    }
+}
+
+/// How many ticks from now something that has a `probability` chance of
+/// happening on any given tick next happens, or `None` if it never will.
+/// Rather than rolling the dice for every actor's random goals every tick,
+/// this works out when the first success will be in one go (it's
+/// geometrically distributed), so the goal can be scheduled like any other.
+fn arrival_delay(rng: &mut SimRng, probability: Quantity) -> Option<u64> {
+    let p = probability.as_f64();
+    if p <= 0.0 {
+        return None;
+    }
+    if p >= 1.0 {
+        return Some(1);
+    }
+    let roll: f64 = rng.gen();
+    let ticks = ((1.0 - roll).ln() / (1.0 - p).ln()).ceil();
+    Some((ticks as u64).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+    use crate::HierarchyError;
+
+    #[test]
+    fn loaded_worlds_run_on_as_if_they_never_stopped() {
+        let mut sim = Simulation::with_seed(3);
+        Scenario::default_scenario()
+            .populate(&mut sim, Some(8), Quantity::ZERO, |_, a, _| {
+                a.labor(Quantity::from(8u32))
+            })
+            .unwrap();
+        for _ in 0..20 {
+            sim.tick();
+        }
+        let path = std::env::temp_dir().join("microeconomics-resume.json");
+        sim.save(&path).unwrap();
+        let mut loaded = Simulation::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.ticks(), sim.ticks());
+        for _ in 0..20 {
+            assert_eq!(loaded.tick(), sim.tick());
+            // The generator can't be compared directly, only what it saves as
+            assert_eq!(
+                serde_json::to_string(loaded.rng()).unwrap(),
+                serde_json::to_string(sim.rng()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn loading_a_broken_hierarchy_says_whose_it_is() {
+        let mut sim = Simulation::with_seed(0);
+        Scenario::default_scenario()
+            .populate(&mut sim, Some(2), Quantity::ZERO, |_, a, _| a)
+            .unwrap();
+        // Knock every goal down a rank, so nothing's ranked first
+        let actor = sim.actors.get_mut(ActorId(1)).unwrap();
+        for rank in actor.values.goal_hierarchy.values_mut() {
+            *rank += 1;
+        }
+        let path = std::env::temp_dir().join("microeconomics-broken-hierarchy.json");
+        sim.save(&path).unwrap();
+        let loaded = Simulation::load(&path);
+        fs::remove_file(&path).unwrap();
+        match loaded {
+            Err(SnapshotError::Hierarchy { actor, error }) => {
+                assert_eq!(actor, sim.arena().names()[1]);
+                assert_eq!(error, HierarchyError::MissingRank(0));
+            }
+            Err(e) => panic!("wrong error: {}", e),
+            Ok(_) => panic!("loaded a broken hierarchy"),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::HierarchyError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// The version of the format `Simulation::save` writes snapshots in. Bump
+/// this whenever a change to the world means older snapshots can't be
+/// loaded back exactly as they were, so they're turned away instead.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The outside of a snapshot file: the version of the format it's in, and
+/// the world itself.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Snapshot<W> {
+    /// The format it's in (see `SNAPSHOT_VERSION`)
+    pub version: u32,
+    /// The world, if it's in a format we can read
+    pub world: W,
+}
+
+/// Just the version of a snapshot file, read first so a world in a format we
+/// can't read doesn't get as far as being parsed.
+#[derive(Deserialize)]
+pub(crate) struct SnapshotVersion {
+    pub version: u32,
+}
+
+/// Why a snapshot couldn't be saved or loaded.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The file couldn't be read or written
+    Io(io::Error),
+    /// The file isn't a snapshot, or the world in it doesn't make sense
+    Parse(serde_json::Error),
+    /// The snapshot is in a format this version can't read
+    Version(u32),
+    /// The event log at this path has no world in it to start replaying from
+    Empty(PathBuf),
+    /// The event log at `path` couldn't be read
+    Log { path: PathBuf, error: io::Error },
+    /// Line `line` of the event log at `path` isn't a log entry, or the
+    /// world in it doesn't make sense
+    LogParse {
+        path: PathBuf,
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// The event log at `path` has a world in a format this version can't
+    /// read
+    LogVersion { path: PathBuf, version: u32 },
+    /// An actor's goal hierarchy in the world loaded isn't strictly ordinal
+    Hierarchy {
+        actor: String,
+        error: HierarchyError,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "couldn't access snapshot file: {}", e),
+            SnapshotError::Parse(e) => write!(f, "couldn't parse snapshot file: {}", e),
+            SnapshotError::Version(version) => write!(
+                f,
+                "snapshot is in format version {}, but only version {} can be loaded",
+                version, SNAPSHOT_VERSION
+            ),
+            SnapshotError::Empty(path) => {
+                write!(
+                    f,
+                    "event log {} has no world recorded in it",
+                    path.display()
+                )
+            }
+            SnapshotError::Log { path, error } => {
+                write!(f, "couldn't read event log {}: {}", path.display(), error)
+            }
+            SnapshotError::LogParse { path, line, error } => write!(
+                f,
+                "couldn't parse line {} of event log {}: {}",
+                line,
+                path.display(),
+                error
+            ),
+            SnapshotError::LogVersion { path, version } => write!(
+                f,
+                "event log {} is in format version {}, but only version {} can be replayed",
+                path.display(),
+                version,
+                SNAPSHOT_VERSION
+            ),
+            SnapshotError::Hierarchy { actor, error } => {
+                write!(f, "{}'s goal hierarchy is broken: {}", actor, error)
+            }
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> Self {
+        SnapshotError::Parse(e)
+    }
+}
+
+/// The world as it was going into each of the last so many ticks, kept in
+/// memory so the simulation can be rewound to any of them (see
+/// `Simulation::rewind`). Once it's full, the oldest is let go to make room
+/// for each new one.
+#[derive(Default)]
+pub(crate) struct History {
+    /// The most ticks back it keeps
+    pub limit: usize,
+    /// The world going into each tick it's kept, serialized, oldest first
+    snapshots: VecDeque<String>,
+}
+
+impl History {
+    /// Keep the world as it's about to go into another tick (serialized).
+    pub fn keep(&mut self, world: String) {
+        if self.limit == 0 {
+            return;
+        }
+        while self.snapshots.len() >= self.limit {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(world);
+    }
+
+    /// How many ticks back it goes.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Let go of everything older than the last `limit` ticks.
+    pub fn trim(&mut self) {
+        while self.snapshots.len() > self.limit {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// The world going into the tick `ticks` back, letting go of it and
+    /// everything after it, if it goes back that far.
+    pub fn rewind(&mut self, ticks: usize) -> Option<String> {
+        if ticks == 0 || ticks > self.snapshots.len() {
+            return None;
+        }
+        let at = self.snapshots.len() - ticks;
+        self.snapshots.truncate(at + 1);
+        self.snapshots.pop_back()
+    }
+}
+
+/// Serializes a map as a list of key and value pairs, for maps keyed by
+/// something that can't be a key in a snapshot (anything but a string or a
+/// number, like a pair of items). Use with `#[serde(with =
+/// "crate::snapshot::pairs")]`.
+pub(crate) mod pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::iter::FromIterator;
+
+    pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
@@ -0,0 +1,221 @@
+use crate::arena::ActorId;
+use crate::events::SimEvent;
+use crate::items::bundle::Bundle;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a collector takes its cut of.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TaxBase {
+    /// Whatever each side gets in a trade
+    Trades,
+    /// Whatever comes out of making, gathering, or land
+    Output,
+    /// Both
+    Both,
+}
+
+impl TaxBase {
+    /// Whether trades are taxed.
+    pub fn trades(self) -> bool {
+        self != TaxBase::Output
+    }
+
+    /// Whether output is taxed.
+    pub fn output(self) -> bool {
+        self != TaxBase::Trades
+    }
+}
+
+/// What a collector does with what it takes.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Disposal {
+    /// Hand it out evenly to everyone
+    Redistribute,
+    /// Throw it away
+    Destroy,
+}
+
+/// A tax collector: every tick it takes a fraction of what everyone got in
+/// trade, or made, or both (see `TaxBase`), and either hands it all back out
+/// evenly to everyone or destroys it (see `Disposal`). The simulation runs
+/// it at the end of every tick (see `Simulation::set_collector`), working
+/// out what's owed from the tick's events (see `assess`).
+///
+/// Nobody's made to pay what they haven't got to spare (see
+/// `Inventory::spare`): whatever they can't pay is let go. Goods that only
+/// come in whole units can't be taxed a fraction of a unit at a time, so a
+/// fraction owed on them is carried over until it adds up to a unit, and
+/// they're handed out a unit at a time, to each actor in turn.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Collector {
+    /// The fraction it takes
+    pub rate: Quantity,
+    /// What it takes it from
+    pub base: TaxBase,
+    /// What it does with it
+    pub disposal: Disposal,
+    /// The tick it started collecting on
+    pub since: u64,
+    /// What each actor owes on each item and hasn't paid yet
+    #[serde(with = "crate::snapshot::pairs")]
+    owed: BTreeMap<(ActorId, ItemId), Quantity>,
+    /// What it's collected and not yet handed out
+    pot: Bundle,
+    /// Everything it's collected so far
+    collected: Bundle,
+    /// Everything it's handed out or destroyed so far
+    disposed: Bundle,
+    /// Whose turn it is for the next whole unit handed out
+    next: usize,
+}
+
+impl Collector {
+    /// A collector taking `rate` of `base` from tick `since` on, and doing
+    /// `disposal` with it.
+    pub fn new(rate: Quantity, base: TaxBase, disposal: Disposal, since: u64) -> Self {
+        Collector {
+            rate,
+            base,
+            disposal,
+            since,
+            owed: BTreeMap::new(),
+            pot: Bundle::new(),
+            collected: Bundle::new(),
+            disposed: Bundle::new(),
+            next: 0,
+        }
+    }
+
+    /// Works out the tax owed on something that happened, if it's taxed.
+    pub fn assess(&mut self, event: &SimEvent) {
+        let taxed: Vec<(ActorId, &Bundle)> = match *event {
+            SimEvent::TradeCompleted {
+                actor,
+                partner,
+                ref gave,
+                ref received,
+            } if self.base.trades() => vec![(actor, received), (partner, gave)],
+            SimEvent::TradeCycleCompleted {
+                ref actors,
+                ref gave,
+            } if self.base.trades() => (0..actors.len())
+                .map(|n| (actors[n], &gave[(n + actors.len() - 1) % actors.len()]))
+                .collect(),
+            SimEvent::ItemsProduced {
+                actor, ref outputs, ..
+            } if self.base.output() => vec![(actor, outputs)],
+            SimEvent::ItemYielded {
+                actor,
+                item,
+                amount,
+                ..
+            }
+            | SimEvent::ResourceGathered {
+                actor,
+                item,
+                amount,
+                ..
+            } if self.base.output() => {
+                return self.owe(actor, item, amount.times(self.rate));
+            }
+            _ => return,
+        };
+        for (actor, items) in taxed {
+            for (item, amount) in items.iter() {
+                self.owe(actor, item, amount.times(self.rate));
+            }
+        }
+    }
+
+    /// Adds `amount` of `item` to what `actor` owes.
+    fn owe(&mut self, actor: ActorId, item: ItemId, amount: Quantity) {
+        *self.owed.entry((actor, item)).or_insert(Quantity::ZERO) += amount;
+    }
+
+    /// Everything owed that's due to be paid now: each actor and item, and
+    /// how much of it (whole units of anything `divisible` says only comes
+    /// whole). Everything else owed is let go, but fractions of a unit,
+    /// which are carried over.
+    pub fn due<F: Fn(ItemId) -> bool>(&mut self, divisible: F) -> Vec<(ActorId, ItemId, Quantity)> {
+        let mut due = vec![];
+        for (&(actor, item), owed) in self.owed.iter_mut() {
+            let amount = if divisible(item) { *owed } else { owed.whole() };
+            *owed -= amount;
+            if !amount.is_zero() {
+                due.push((actor, item, amount));
+            }
+        }
+        self.owed.retain(|_, owed| !owed.is_zero());
+        due
+    }
+
+    /// Takes in `amount` of `item` paid in tax.
+    pub fn pay(&mut self, item: ItemId, amount: Quantity) {
+        self.pot.add(item, amount);
+        self.collected.add(item, amount);
+    }
+
+    /// Empties out everything collected that can be handed out to `actors`
+    /// actors (or destroyed), returning how much of each item goes to each
+    /// of them by their place in line (or `None`, all together, if it's
+    /// destroyed). Goods that only come whole go out a unit at a time, to
+    /// each actor in turn, and whatever's left of them is kept for next time.
+    pub fn dispose<F: Fn(ItemId) -> bool>(
+        &mut self,
+        actors: usize,
+        divisible: F,
+    ) -> Vec<(Option<usize>, Bundle)> {
+        let pot = std::mem::take(&mut self.pot);
+        if self.disposal == Disposal::Destroy || actors == 0 {
+            for (item, amount) in pot.iter() {
+                self.disposed.add(item, amount);
+            }
+            return if pot.is_empty() {
+                vec![]
+            } else {
+                vec![(None, pot)]
+            };
+        }
+        let mut shares = vec![Bundle::new(); actors];
+        for (item, amount) in pot.iter() {
+            if divisible(item) {
+                let share = amount.divided_by(Quantity::units(actors as u32));
+                for bundle in shares.iter_mut() {
+                    bundle.add(item, share);
+                }
+                self.disposed
+                    .add(item, share.times(Quantity::units(actors as u32)));
+                self.pot
+                    .add(item, amount - share.times(Quantity::units(actors as u32)));
+            } else {
+                let mut left = amount.whole();
+                while !left.is_zero() {
+                    shares[self.next % actors].add(item, Quantity::ONE);
+                    self.next = (self.next + 1) % actors;
+                    left -= Quantity::ONE;
+                }
+                self.disposed.add(item, amount.whole());
+                self.pot.add(item, amount - amount.whole());
+            }
+        }
+        shares
+            .into_iter()
+            .enumerate()
+            .filter(|(_, share)| !share.is_empty())
+            .map(|(n, share)| (Some(n), share))
+            .collect()
+    }
+
+    /// Everything it's collected so far.
+    pub fn collected(&self) -> &Bundle {
+        &self.collected
+    }
+
+    /// Everything it's handed out or destroyed so far.
+    pub fn disposed(&self) -> &Bundle {
+        &self.disposed
+    }
+}
@@ -0,0 +1,71 @@
+use crate::items::production::{Recipe, RecipeId};
+use crate::items::quantity::Quantity;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// What an actor knows about making things. Most recipes are common
+/// knowledge, but one with a `discovery` chance (see `Recipe::discovery`)
+/// has to be found out first, either by experimenting (see
+/// `ActorMut::experiment`) or by watching a trade partner who already knows
+/// it (see `Simulation::tick`), so some actors end up able to make more out
+/// of the same goods than others.
+///
+/// The more hours an actor has put into experimenting since it last found
+/// something out, the better its chances of finding something out next.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KnowHow {
+    /// Recipes it's found out about that not everyone knows
+    known: BTreeSet<RecipeId>,
+    /// Hours spent experimenting since it last discovered anything
+    experimented: Quantity,
+}
+
+impl KnowHow {
+    /// Knowing only what everyone knows.
+    pub fn new() -> Self {
+        KnowHow::default()
+    }
+
+    /// Whether the actor knows how to make things by a recipe.
+    pub fn knows(&self, id: RecipeId, recipe: &Recipe) -> bool {
+        recipe.discovery.is_none() || self.known.contains(&id)
+    }
+
+    /// The recipes it's found out about that not everyone knows.
+    pub fn known(&self) -> impl Iterator<Item = RecipeId> + '_ {
+        self.known.iter().copied()
+    }
+
+    /// Hours it's spent experimenting since it last discovered anything.
+    pub fn experimented(&self) -> Quantity {
+        self.experimented
+    }
+
+    /// Find out how to make things by a recipe. Returns whether it's news.
+    pub fn learn(&mut self, id: RecipeId) -> bool {
+        self.known.insert(id)
+    }
+
+    /// Put so many hours into experimenting towards a recipe, returning
+    /// whether that discovered it. The chance of that is the recipe's
+    /// discovery chance for every hour put in since the last discovery, so
+    /// `roll` (from 0 to 1) has to come in under that.
+    pub fn experiment(
+        &mut self,
+        id: RecipeId,
+        recipe: &Recipe,
+        hours: Quantity,
+        roll: f64,
+    ) -> bool {
+        self.experimented += hours;
+        let chance = recipe
+            .discovery
+            .unwrap_or(Quantity::ONE)
+            .times(self.experimented);
+        if roll >= chance.as_f64() {
+            return false;
+        }
+        self.experimented = Quantity::ZERO;
+        self.learn(id)
+    }
+}
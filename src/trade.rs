@@ -0,0 +1,1803 @@
+use crate::arena::{ActorArena, ActorId};
+use crate::credit::{agree_forward, borrow, would_lend, FORWARD_TERM};
+use crate::events::SimEvent;
+use crate::goals::GoalId;
+use crate::items::bundle::Bundle;
+use crate::items::inventory::Inventory;
+use crate::items::quantity::Quantity;
+use crate::items::registry::ItemId;
+use crate::market::DoubleAuction;
+use crate::preference_list::{ActorMut, ActorRef, ActorState};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// A negotiation scheme actors use to exchange items. The simulation holds one
+/// of these, and whenever an actor decides it wants to trade for a goal, the
+/// protocol is asked to take the next step of the negotiation on its behalf.
+///
+/// Protocols drive the `WillingToTrade`, `FoundTradePartner`, `Bidding`, and
+/// `BidRecipiant` states of the actors involved however they see fit, but
+/// should always leave both sides back in `SearchingForGoal` once a trade is
+/// done.
+pub trait TradeProtocol {
+    /// Short name used to select this protocol (e.g. from the command line)
+    fn name(&self) -> &'static str;
+
+    /// Take one step of negotiation for an actor that wants to trade.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - the actor trying to trade (already `WillingToTrade` or further along)
+    /// * `goal` - the goal the actor wants an item for
+    /// * `actors` - every actor in the world, including the one trading
+    ///
+    fn step(&mut self, actor: ActorId, goal: GoalId, actors: &mut ActorArena) -> Vec<SimEvent>;
+
+    /// Anything the protocol does once a tick, after everyone's had their
+    /// turn, like matching up orders that have come in. Does nothing unless
+    /// a protocol needs it to.
+    ///
+    /// # Arguments
+    ///
+    /// * `actors` - every actor in the world
+    ///
+    fn settle(&mut self, _actors: &mut ActorArena) -> Vec<SimEvent> {
+        vec![]
+    }
+
+    /// Whether the protocol can negotiate with a partner the world found for
+    /// an actor (see `Simulation::set_matching`), picking up from
+    /// `FoundTradePartner` instead of going looking itself. Not unless a
+    /// protocol goes through that state.
+    fn takes_matches(&self) -> bool {
+        false
+    }
+
+    /// The protocol as it's saved along with the simulation, if it can be.
+    /// Protocols that can't be (anything that isn't built in) are saved by
+    /// name, and come back however `protocol_by_name` makes them.
+    fn save(&self) -> Option<SavedProtocol> {
+        None
+    }
+}
+
+/// One of the built-in protocols, along with whatever it's kept track of so
+/// far (a board of offers, order books), as it's saved along with the
+/// simulation (see `TradeProtocol::save`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SavedProtocol {
+    AscendingBid(AscendingBid),
+    TakeItOrLeaveIt(TakeItOrLeaveIt),
+    PostedPrice(PostedPrice),
+    DoubleAuction(DoubleAuction),
+}
+
+impl SavedProtocol {
+    /// The protocol back again, just as it was saved.
+    pub fn restore(self) -> Box<dyn TradeProtocol> {
+        match self {
+            SavedProtocol::AscendingBid(protocol) => Box::new(protocol),
+            SavedProtocol::TakeItOrLeaveIt(protocol) => Box::new(protocol),
+            SavedProtocol::PostedPrice(protocol) => Box::new(protocol),
+            SavedProtocol::DoubleAuction(protocol) => Box::new(protocol),
+        }
+    }
+}
+
+/// Names of all of the built-in protocols, for help text and completion.
+pub const PROTOCOL_NAMES: &[&str] = &["bilateral", "forward", "offer", "posted", "auction"];
+
+/// Look up one of the built-in protocols by its name.
+pub fn protocol_by_name(name: &str) -> Option<Box<dyn TradeProtocol>> {
+    match name {
+        "bilateral" => Some(Box::new(AscendingBid::default())),
+        "forward" => Some(Box::new(AscendingBid::forward(FORWARD_TERM))),
+        "offer" => Some(Box::new(TakeItOrLeaveIt)),
+        "posted" => Some(Box::new(PostedPrice::default())),
+        "auction" => Some(Box::new(DoubleAuction::default())),
+        _ => None,
+    }
+}
+
+/// Serializes a simulation's trade protocol as it's saved (see
+/// `TradeProtocol::save`), or as just its name if it can't be, so that the
+/// protocol can be looked back up with `protocol_by_name` when deserializing.
+/// Use with `#[serde(with = "crate::trade::saved")]`.
+pub mod saved {
+    use super::{protocol_by_name, SavedProtocol, TradeProtocol};
+    use serde::de::value::MapAccessDeserializer;
+    use serde::de::{Error, MapAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    /// Reads a protocol saved either way: as it was, or by name.
+    struct Saved;
+
+    impl<'de> Visitor<'de> for Saved {
+        type Value = Box<dyn TradeProtocol>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a saved trade protocol, or the name of one")
+        }
+
+        fn visit_str<E: Error>(self, name: &str) -> Result<Self::Value, E> {
+            protocol_by_name(name)
+                .ok_or_else(|| E::custom(format!("unknown trade protocol: {}", name)))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            Ok(SavedProtocol::deserialize(MapAccessDeserializer::new(map))?.restore())
+        }
+    }
+
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        protocol: &Box<dyn TradeProtocol>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match protocol.save() {
+            Some(saved) => saved.serialize(serializer),
+            None => serializer.serialize_str(protocol.name()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn TradeProtocol>, D::Error> {
+        deserializer.deserialize_any(Saved)
+    }
+}
+
+/// Look for the next actor after `last_tried` that has an item that can
+/// satisfy `goal` and isn't busy initiating its own trade. Actors are tried
+/// in the order `actor` thinks of them (see `TradeMemory::compare`), so
+/// partners that have dealt with it before come first and ones that have
+/// turned it down come last; anyone it doesn't remember is tried in ID order.
+/// An actor with imperfect information only goes by what it's seen others
+/// holding (see `search`), which might not be what they hold now.
+///
+/// # Notes
+///
+/// Being turned down by someone puts them further down the order, so the
+/// search picks up after wherever they are now; anyone that skips over gets
+/// another chance the next time the actor goes looking.
+pub fn find_next_partner(
+    actor: ActorRef,
+    goal: GoalId,
+    other_actors: &ActorArena,
+    last_tried: Option<ActorId>,
+    events: &mut Vec<SimEvent>,
+) -> Option<ActorId> {
+    let wanted = actor.wanted_for(goal);
+    if wanted.is_empty() {
+        return None;
+    }
+    let mut order: Vec<_> = other_actors.iter().filter(|o| o.id != actor.id).collect();
+    order.sort_by(|a, b| actor.memory.compare(b.id, a.id));
+    let start = last_tried
+        .and_then(|last| order.iter().position(|o| o.id == last))
+        .map(|n| n + 1)
+        .unwrap_or(0);
+    for other in order.into_iter().skip(start) {
+        let id = other.id;
+        match other.state {
+            ActorState::Bidding(..)
+            | ActorState::FoundTradePartner(..)
+            | ActorState::Producing(..) => {
+                events.push(SimEvent::PartnerOccupied {
+                    actor: actor.id,
+                    partner: id,
+                });
+            }
+            _ => {
+                let others_items = if actor.memory.is_imperfect() {
+                    match actor.memory.seen(id) {
+                        Some(sighting) => sighting
+                            .items
+                            .iter()
+                            .copied()
+                            .filter(|i| wanted.contains(i))
+                            .collect(),
+                        None => vec![],
+                    }
+                } else {
+                    other.has_item_of(&wanted)
+                };
+                if !others_items.is_empty() {
+                    events.push(SimEvent::PartnerFound {
+                        actor: actor.id,
+                        partner: id,
+                        items: others_items.iter().map(|&i| other.inventory.count(i)).sum(),
+                        engaged: matches!(other.state, ActorState::BidRecipiant(..)),
+                    });
+                    return Some(id);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// How many other actors an actor with imperfect information gets a look at
+/// in a tick spent searching
+pub const SEARCH_BREADTH: usize = 3;
+
+/// How many ticks what an actor's seen someone holding is good for before
+/// it'd go and look again
+pub const SIGHTING_LIFE: u64 = 10;
+
+/// Spend a tick looking around, for an actor with imperfect information that
+/// doesn't know of anyone with what it wants: it gets a look at what the
+/// `SEARCH_BREADTH` actors it's gone longest without seeing hold (anyone
+/// it's never seen first), and remembers it from the end of the tick on.
+/// Anyone seen in the last `SIGHTING_LIFE` ticks isn't worth looking at
+/// again yet. Returns whether it looked at anyone at all: actors with perfect
+/// information never need to, and there's no point once everyone's been seen
+/// lately.
+pub fn search(actor: ActorRef, actors: &ActorArena, events: &mut Vec<SimEvent>) -> bool {
+    if !actor.memory.is_imperfect() {
+        return false;
+    }
+    let now = actors.now();
+    let mut stale: Vec<(Option<u64>, ActorId)> = actors
+        .ids()
+        .filter(|&id| id != actor.id)
+        .map(|id| (actor.memory.seen(id).map(|s| s.tick), id))
+        .filter(|&(seen, _)| seen.is_none_or(|tick| tick + SIGHTING_LIFE <= now))
+        .collect();
+    if stale.is_empty() {
+        return false;
+    }
+    stale.sort();
+    events.push(SimEvent::Searched {
+        actor: actor.id,
+        looked_at: stale
+            .into_iter()
+            .take(SEARCH_BREADTH)
+            .map(|(_, id)| id)
+            .collect(),
+    });
+    true
+}
+
+/// How much of `want` to ask a partner for in one trade: a single unit, or
+/// whatever they have if it's less than that.
+pub fn asking_amount(partner: ActorRef, want: ItemId) -> Quantity {
+    partner.inventory.count(want).min(Quantity::ONE)
+}
+
+/// How much of `offer` to put up for `want_amount` of something: as much as
+/// is being asked for, or everything spare (see `Inventory::spare`) if it's
+/// less than that.
+pub fn offer_amount(actor: ActorRef, offer: ItemId, want_amount: Quantity) -> Quantity {
+    actor.inventory.spare(offer).min(want_amount)
+}
+
+/// Everything `actor` asks `partner` for in one go when trading for `goal`:
+/// `goal_item`, along with any of the goal's complements the actor is
+/// missing that the partner happens to have too, so it can get them all in
+/// the same trade.
+pub fn asking_bundle(
+    actor: ActorRef,
+    partner: ActorRef,
+    goal: GoalId,
+    goal_item: ItemId,
+) -> Bundle {
+    let mut want = Bundle::single(goal_item, asking_amount(partner, goal_item));
+    for complement in actor.missing_complements(goal) {
+        if complement != goal_item {
+            want.add(complement, asking_amount(partner, complement));
+        }
+    }
+    want
+}
+
+/// Whether `partner` would give up the `want` bundle for the `offer` bundle.
+/// Values are ordinal, so this is down to how the partner ranks the two
+/// bundles (see `ValueScale::compare_bundles`): amounts only come into it
+/// when the best items on each side serve the same goal, so an item they
+/// value more outright is worth taking even if there's less of it, which is
+/// how exchange ratios other than 1:1 come about. Failing that, they might
+/// still take it as a medium of exchange, to trade on later (see
+/// `takes_as_medium`). They won't give up a good they're only holding to
+/// trade on for one that's harder to get rid of, unless they'd rather have
+/// it outright (see `gives_up_medium`). Either way, they have to have room
+/// for it (see `ActorRef::can_fit`), and whatever the trade costs them comes
+/// on top of what they're giving up (see `TradeCost`). If they expect to do
+/// better by waiting, they won't take it at all (see
+/// `Expectations::holds_out`), and they won't break a price control to (see
+/// `PriceControl`). Any money on offer that's legal tender for another
+/// counts at face value (see `LegalTender`).
+pub fn accepts(partner: ActorRef, offer: &Bundle, want: &Bundle) -> bool {
+    let giving = with_cost(partner, want);
+    let valued = partner.compare_bundles(&at_face_value(partner, offer), &giving);
+    !offer.is_empty()
+        && (valued == Ordering::Greater
+            || (valued == Ordering::Equal && !gives_up_medium(partner, offer, want))
+            || takes_as_medium(partner, offer, want).is_some())
+        && partner.can_fit(offer, &giving)
+        && affords(partner, &giving)
+        && !partner.expectations.holds_out(want, offer)
+        && within_controls(partner, offer, want)
+}
+
+/// Whether `actor` would give up `give` for `take`, and has room for it: the
+/// other side of `accepts`, for whoever's making the offer. Like there,
+/// whatever the trade costs comes on top of what it's giving up, and it
+/// won't if it expects to do better by waiting, or it'd break a price
+/// control.
+pub fn would_give(actor: ActorRef, give: &Bundle, take: &Bundle) -> bool {
+    let giving = with_cost(actor, give);
+    actor.compare_bundles(&giving, take) != Ordering::Greater
+        && actor.can_fit(take, &giving)
+        && affords(actor, &giving)
+        && !actor.expectations.holds_out(give, take)
+        && within_controls(actor, give, take)
+}
+
+/// What it costs an actor to trade, on top of whatever it gives up for what
+/// it gets: the frictions of exchange, like carting goods about and making
+/// sure of what you're getting. Each side of a trade pays its own (see
+/// `ValueScale::trade_cost`) when the trade goes through, and it's burned
+/// rather than going to anyone.
+///
+/// # Notes
+///
+/// Paying in an item makes trades that were only just worth making not worth
+/// it any more, since giving up the cost on top tips the balance (see
+/// `ValueScale::compare_bundles`). Paying in labor doesn't come into how
+/// anyone values a trade, but it does take time, so fewer trades fit in a
+/// tick.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TradeCost {
+    /// So many hours of labor
+    Labor(Quantity),
+    /// So much of an item
+    Item(ItemId, Quantity),
+}
+
+/// `give`, plus whatever items it costs `actor` to trade at all.
+pub fn with_cost(actor: ActorRef, give: &Bundle) -> Bundle {
+    match actor.values.trade_cost {
+        Some(TradeCost::Item(item, amount)) => give.clone().with(item, amount),
+        _ => give.clone(),
+    }
+}
+
+/// Whether `actor` holds everything in `bundle`, without touching anything
+/// it's set aside for a production plan.
+fn affords(actor: ActorRef, bundle: &Bundle) -> bool {
+    bundle
+        .iter()
+        .all(|(item, amount)| actor.inventory.spare(item) >= amount)
+}
+
+/// Whether `actor` can give up `give` in a trade on tick `now`, and pay for
+/// the trade on top.
+pub fn can_pay(actor: ActorRef, give: &Bundle, now: u64) -> bool {
+    affords(actor, &with_cost(actor, give))
+        && match actor.values.trade_cost {
+            Some(TradeCost::Labor(hours)) => actor.labor.left(now) >= hours,
+            _ => true,
+        }
+}
+
+/// Burn whatever it costs `actor` to make a trade that's just gone through.
+fn pay_cost(actor: &mut ActorMut, events: &mut Vec<SimEvent>) {
+    let cost = match actor.values.trade_cost {
+        Some(cost) => cost,
+        None => return,
+    };
+    match cost {
+        TradeCost::Labor(hours) => {
+            actor.labor.spend(hours, actor.now);
+        }
+        TradeCost::Item(item, amount) => {
+            actor.inventory.remove(item, amount);
+        }
+    }
+    events.push(SimEvent::TradeCostPaid {
+        actor: actor.id,
+        cost,
+    });
+}
+
+/// A legal limit on what one good can go for in another: a ceiling says
+/// nobody may pay more than so much of the quote good for a unit of it, a
+/// floor that nobody may pay less. Actors won't make or take an offer that
+/// breaks one (see `accepts` and `would_give`), so a ceiling below what the
+/// good would go for leaves buyers who'd happily pay more going without, and
+/// a floor above it leaves sellers stuck with what nobody will buy.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PriceControl {
+    /// The good being priced
+    pub item: ItemId,
+    /// The good it's priced in
+    pub quote: ItemId,
+    /// How much of `quote` a unit of `item` may go for, at most or at least
+    pub ratio: Quantity,
+    /// Whether that's the most it may go for (a ceiling) rather than the
+    /// least (a floor)
+    pub ceiling: bool,
+}
+
+impl PriceControl {
+    /// Whether a swap of `give` for `take`, either way round, keeps to the
+    /// control. Only swaps with `item` on one side and `quote` on the other
+    /// come into it, at however much of `quote` goes for each unit of `item`.
+    pub fn allows(&self, give: &Bundle, take: &Bundle) -> bool {
+        [(give, take), (take, give)].iter().all(|(sold, paid)| {
+            let (amount, price) = (sold.count(self.item), paid.count(self.quote));
+            if amount.is_zero() || price.is_zero() {
+                return true;
+            }
+            let ratio = price.divided_by(amount);
+            if self.ceiling {
+                ratio <= self.ratio
+            } else {
+                ratio >= self.ratio
+            }
+        })
+    }
+}
+
+/// Whether a swap of `give` for `take` keeps to every price control and
+/// legal tender law `actor` knows of (see `ValueScale::price_controls` and
+/// `ValueScale::legal_tender`).
+fn within_controls(actor: ActorRef, give: &Bundle, take: &Bundle) -> bool {
+    actor
+        .values
+        .price_controls
+        .iter()
+        .all(|control| control.allows(give, take))
+        && actor
+            .values
+            .legal_tender
+            .iter()
+            .all(|law| law.allows(give, take))
+}
+
+/// A legal tender law: a unit of one money has to be taken as so much of
+/// another, whatever anyone thinks of either. The two can only be swapped
+/// for each other at that ratio, and nobody being paid in whichever of them
+/// they value less can turn it down, or ask for more of it, where they'd
+/// take the other (see `at_face_value`). Since everyone spends the money
+/// they value least first (see `ActorRef::monies_held`), the money that's
+/// overvalued by law does all the circulating, and the money that's
+/// undervalued is kept back: bad money drives out good (Gresham's law).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LegalTender {
+    /// The money being valued
+    pub item: ItemId,
+    /// The money it's valued in
+    pub quote: ItemId,
+    /// How much of `quote` a unit of `item` has to be taken as
+    pub ratio: Quantity,
+}
+
+impl LegalTender {
+    /// Whether a swap of `give` for `take`, either way round, keeps to the
+    /// law: swapping the two moneys for each other only goes at the legal
+    /// ratio, neither above it nor below.
+    pub fn allows(&self, give: &Bundle, take: &Bundle) -> bool {
+        [true, false].iter().all(|&ceiling| {
+            PriceControl {
+                item: self.item,
+                quote: self.quote,
+                ratio: self.ratio,
+                ceiling,
+            }
+            .allows(give, take)
+        })
+    }
+
+    /// `bundle`, with whichever of the two moneys `actor` values less
+    /// counted as however much of the other it's legally worth.
+    fn at_face_value(&self, actor: ActorRef, bundle: &Bundle) -> Bundle {
+        let (worse, better, rate) = match actor.compare_item_values(self.item, self.quote) {
+            Ordering::Less => (self.item, self.quote, self.ratio),
+            Ordering::Greater if !self.ratio.is_zero() => {
+                (self.quote, self.item, Quantity::ONE.divided_by(self.ratio))
+            }
+            _ => return bundle.clone(),
+        };
+        let amount = bundle.count(worse);
+        if amount.is_zero() {
+            return bundle.clone();
+        }
+        bundle
+            .filter(|i| i != worse)
+            .with(better, amount.times(rate))
+    }
+}
+
+/// `bundle` as `actor` has to count it when it's being paid, with any money
+/// in it that's legal tender for another at face value (see `LegalTender`).
+fn at_face_value(actor: ActorRef, bundle: &Bundle) -> Bundle {
+    actor
+        .values
+        .legal_tender
+        .iter()
+        .fold(bundle.clone(), |bundle, law| {
+            law.at_face_value(actor, &bundle)
+        })
+}
+
+/// The goal `partner` would take the `offer` bundle for the `want` bundle to
+/// trade on towards, if it'd take it as a medium of exchange (see
+/// `ActorRef::would_take_as_medium`). Media are only ever taken one good for
+/// one good.
+pub fn takes_as_medium(partner: ActorRef, offer: &Bundle, want: &Bundle) -> Option<GoalId> {
+    if offer.len() != 1 || want.len() != 1 {
+        return None;
+    }
+    partner.would_take_as_medium(offer.first()?.0, want.first()?.0)
+}
+
+/// Whether `partner` would be giving up a good in the `want` bundle that it's
+/// only holding to trade on (see `ValueScale::media`) for the `offer` bundle,
+/// when nothing in that's as saleable. Swapping a medium of exchange for
+/// something harder to get rid of would defeat the point of having taken it.
+pub fn gives_up_medium(partner: ActorRef, offer: &Bundle, want: &Bundle) -> bool {
+    let offered = offer
+        .iter()
+        .map(|(item, _)| partner.values.saleability(item))
+        .max()
+        .unwrap_or(0);
+    want.iter().any(|(item, _)| {
+        partner.values.media.contains_key(&item) && partner.values.saleability(item) > offered
+    })
+}
+
+/// Everything the sides of a trade have handed over, held until everyone's
+/// put in their part, so nobody gets anything until the whole trade is good
+/// to go. Goods are handed over by what they are rather than where they sit
+/// in anyone's inventory, and each side's deposit is kept apart, so what one
+/// side gets is exactly what the other put in (however old it is), even when
+/// both sides trade the same kind of item.
+#[derive(Default)]
+struct Escrow {
+    /// What each actor has put in so far
+    deposits: Vec<(ActorId, Inventory)>,
+}
+
+impl Escrow {
+    /// Nothing held yet.
+    fn new() -> Self {
+        Escrow::default()
+    }
+
+    /// Take `bundle` off `from`, if it has all of it. Returns whether it did
+    /// (if not, nothing is taken).
+    fn deposit(&mut self, from: &mut ActorMut, bundle: &Bundle) -> bool {
+        if bundle
+            .iter()
+            .any(|(item, amount)| from.inventory.count(item) < amount)
+        {
+            return false;
+        }
+        let mut held = Inventory::new();
+        for (item, amount) in bundle.iter() {
+            from.inventory.set_aside(&mut held, item, amount);
+        }
+        self.deposits.push((from.id, held));
+        true
+    }
+
+    /// Give everything `to` put in back to it, just as it was, for when the
+    /// trade's called off.
+    fn refund(&mut self, to: &mut ActorMut) {
+        if let Some(n) = self.deposits.iter().position(|(id, _)| *id == to.id) {
+            let (_, mut held) = self.deposits.swap_remove(n);
+            let items: Vec<_> = held.iter().collect();
+            for (item, amount) in items {
+                held.set_aside(to.inventory, item, amount);
+            }
+        }
+    }
+
+    /// Hand everything `from` put in over to `to`.
+    fn release(&mut self, from: ActorId, to: &mut ActorMut) {
+        if let Some(n) = self.deposits.iter().position(|(id, _)| *id == from) {
+            let (_, mut held) = self.deposits.swap_remove(n);
+            let items: Vec<_> = held.iter().collect();
+            for (item, amount) in items {
+                held.transfer(to.inventory, item, amount, from, to.now);
+            }
+        }
+    }
+}
+
+/// Swap the `give` bundle from `actor` for the `take` bundle from `partner`.
+/// Returns whether the swap happened (it doesn't if either side doesn't have
+/// enough of everything in their bundle any more, can't pay what the trade
+/// costs them, or it breaks a price control). If it did, the trade goes in
+/// `events`, followed by what each side paid for it and anything either side
+/// had to throw out to make room for what it got.
+///
+/// # Notes
+///
+/// The swap is settled through an escrow (see `Escrow`): both sides' holdings
+/// are checked first, then both bundles are put in, and only then does
+/// either side get the other's, so it happens all at once or not at all (if
+/// either side can't put its bundle in after all, whatever the other put in
+/// goes back to it).
+pub fn exchange(
+    actor: &mut ActorMut,
+    give: &Bundle,
+    partner: &mut ActorMut,
+    take: &Bundle,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if !can_pay(actor.as_ref(), give, actor.now)
+        || !can_pay(partner.as_ref(), take, partner.now)
+        || !within_controls(actor.as_ref(), give, take)
+    {
+        return false;
+    }
+    // Whether either side is only taking what it gets to trade on again
+    // (anything it'd have no reason to want over what it's giving up), and
+    // which of what it's giving up it was only holding to trade on, if any
+    let medium = |taker: ActorRef, taking: &Bundle, giving: &Bundle| {
+        if taker.compare_bundles(taking, giving) == Ordering::Greater {
+            return None;
+        }
+        let goal = takes_as_medium(taker, taking, giving)?;
+        let swapped = giving
+            .iter()
+            .map(|(item, _)| item)
+            .find(|item| taker.values.media.contains_key(item));
+        Some((goal, swapped))
+    };
+    let actor_medium = medium(actor.as_ref(), take, give);
+    let partner_medium = medium(partner.as_ref(), give, take);
+    let mut escrow = Escrow::new();
+    if !escrow.deposit(actor, give) {
+        return false;
+    }
+    if !escrow.deposit(partner, take) {
+        escrow.refund(actor);
+        return false;
+    }
+    escrow.release(actor.id, partner);
+    escrow.release(partner.id, actor);
+    events.push(SimEvent::TradeCompleted {
+        actor: actor.id,
+        partner: partner.id,
+        gave: give.clone(),
+        received: take.clone(),
+    });
+    pay_cost(actor, events);
+    pay_cost(partner, events);
+    note_medium(actor, take, actor_medium, events);
+    note_medium(partner, give, partner_medium, events);
+    for (item, _) in take.iter() {
+        events.extend(actor.discard_overflow(item));
+    }
+    for (item, _) in give.iter() {
+        events.extend(partner.discard_overflow(item));
+    }
+    true
+}
+
+/// Hand `gift` over from `actor` to `to` for nothing in return, if the actor
+/// has all of it. Returns whether it did. Unlike a trade, a gift costs nothing
+/// to make (see `TradeCost`), and `to` throws out whatever it can't fit.
+///
+/// # Arguments
+///
+/// * `actor` - the actor giving it
+/// * `to` - the actor getting it
+/// * `gift` - what's being given
+/// * `events` - where to report what happened
+///
+pub fn give(
+    actor: &mut ActorMut,
+    to: &mut ActorMut,
+    gift: &Bundle,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    if gift.is_empty() || !affords(actor.as_ref(), gift) {
+        return false;
+    }
+    for (item, amount) in gift.iter() {
+        actor
+            .inventory
+            .transfer(to.inventory, item, amount, actor.id, actor.now);
+    }
+    events.push(SimEvent::GiftGiven {
+        actor: actor.id,
+        to: to.id,
+        gift: gift.clone(),
+    });
+    for (item, _) in gift.iter() {
+        events.extend(to.discard_overflow(item));
+    }
+    true
+}
+
+/// Forget about any media of exchange `taker` has since traded on, and
+/// remember that it took what it got in `taken` to trade on for `goal`, if it
+/// did (giving up the medium `swapped` for it, if it was one).
+fn note_medium(
+    taker: &mut ActorMut,
+    taken: &Bundle,
+    medium: Option<(GoalId, Option<ItemId>)>,
+    events: &mut Vec<SimEvent>,
+) {
+    let inventory = &*taker.inventory;
+    taker
+        .values
+        .media
+        .retain(|&item, _| inventory.contains(item));
+    if let (Some((goal, swapped)), Some((item, _))) = (medium, taken.first()) {
+        taker.values.media.insert(item, goal);
+        events.push(SimEvent::MediumAccepted {
+            actor: taker.id,
+            item,
+            goal,
+            swapped,
+        });
+    }
+}
+
+/// A three-way trade that would get `actor` something it wants for `goal`
+/// when nobody holding it wants anything `actor` has: some partner B has what
+/// `actor` wants, and would give it up for something a third actor C has,
+/// who in turn would give that up for something of `actor`'s. Only actors
+/// that aren't already in the middle of a negotiation are asked. Returns the
+/// cycle as who gives what, each to the next one along (and the last to the
+/// first), starting with `actor`, if there is one.
+///
+/// # Arguments
+///
+/// * `actor` - the actor trying to trade
+/// * `goal` - the goal the actor wants an item for
+/// * `actors` - every actor in the world, including the one trading
+///
+pub fn find_trade_cycle(
+    actor: ActorRef,
+    goal: GoalId,
+    actors: &ActorArena,
+) -> Option<Vec<(ActorId, Bundle)>> {
+    let wanted = actor.wanted_for(goal);
+    let free = |other: &ActorRef| {
+        other.id != actor.id
+            && matches!(
+                other.state,
+                ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+            )
+    };
+    // Goods the actor can spare, least valued first
+    let spare: Vec<ItemId> = actor
+        .ordered_inventory()
+        .into_iter()
+        .filter(|&i| !actor.needs_for(goal, i) && !wanted.contains(&i))
+        .collect();
+    for b in actors.iter().filter(free) {
+        for goal_item in b.has_item_of(&wanted) {
+            let want = Bundle::single(goal_item, asking_amount(b, goal_item));
+            for c in actors.iter().filter(|c| c.id != b.id && free(c)) {
+                for between in c.ordered_inventory() {
+                    let between = Bundle::single(between, offer_amount(c, between, want.total()));
+                    if between.contains(goal_item) || !accepts(b, &between, &want) {
+                        continue;
+                    }
+                    for &mine in &spare {
+                        let offer =
+                            Bundle::single(mine, offer_amount(actor, mine, between.total()));
+                        if accepts(c, &offer, &between) && would_give(actor, &offer, &want) {
+                            return Some(vec![(actor.id, offer), (c.id, between), (b.id, want)]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Carry out a trade around a cycle like the ones `find_trade_cycle` finds:
+/// everyone gives their bundle to the next one along, all at once. Returns
+/// whether it happened (it doesn't if anyone doesn't have enough of
+/// everything in their bundle, or can't pay what trading costs them). If it
+/// did, the trade goes in `events`, followed by what everyone paid for it and
+/// anything anyone had to throw out to make room for what they got, and
+/// everyone in it goes back to looking for a goal.
+pub fn trade_around(
+    cycle: &[(ActorId, Bundle)],
+    actors: &mut ActorArena,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    let short = cycle
+        .iter()
+        .any(|(id, bundle)| !can_pay(actors.get(*id).unwrap(), bundle, actors.now()));
+    if cycle.len() < 2 || short {
+        return false;
+    }
+    let next = |n: usize| cycle[(n + 1) % cycle.len()].0;
+    // Everyone puts in their part before anyone gets anything, so nobody
+    // passes on what they've only just been handed
+    let mut escrow = Escrow::new();
+    for (n, (giver, bundle)) in cycle.iter().enumerate() {
+        if !escrow.deposit(&mut actors.get_mut(*giver).unwrap(), bundle) {
+            for (giver, _) in cycle[..n].iter() {
+                escrow.refund(&mut actors.get_mut(*giver).unwrap());
+            }
+            return false;
+        }
+    }
+    for (n, (giver, _)) in cycle.iter().enumerate() {
+        escrow.release(*giver, &mut actors.get_mut(next(n)).unwrap());
+    }
+    events.push(SimEvent::TradeCycleCompleted {
+        actors: cycle.iter().map(|(id, _)| *id).collect(),
+        gave: cycle.iter().map(|(_, bundle)| bundle.clone()).collect(),
+    });
+    for (n, (_, bundle)) in cycle.iter().enumerate() {
+        let mut taker = actors.get_mut(next(n)).unwrap();
+        pay_cost(&mut taker, events);
+        for (item, _) in bundle.iter() {
+            events.extend(taker.discard_overflow(item));
+        }
+        *taker.state = ActorState::SearchingForGoal;
+    }
+    true
+}
+
+/// A trade for a medium of exchange that `actor` could make when nobody will
+/// trade it what it wants for `goal` directly: the most saleable good (as far
+/// as `actor` knows) that someone not busy negotiating would give it for
+/// something it can spare, and that it'd take to trade on (see
+/// `ActorRef::would_take_as_medium`). Returns who to trade with, what to
+/// give them, and what to get, if there's anything of the sort.
+///
+/// # Arguments
+///
+/// * `actor` - the actor trying to trade
+/// * `goal` - the goal the actor wants an item for
+/// * `actors` - every actor in the world, including the one trading
+///
+pub fn find_medium(
+    actor: ActorRef,
+    goal: GoalId,
+    actors: &ActorArena,
+) -> Option<(ActorId, Bundle, Bundle)> {
+    let wanted = actor.wanted_for(goal);
+    // Goods the actor can spare, least valued first
+    let spare: Vec<ItemId> = actor
+        .ordered_inventory()
+        .into_iter()
+        .filter(|&i| !actor.needs_for(goal, i) && !wanted.contains(&i))
+        .collect();
+    let mut best: Option<(u32, ActorId, Bundle, Bundle)> = None;
+    for other in actors.iter().filter(|other| {
+        other.id != actor.id
+            && matches!(
+                other.state,
+                ActorState::SearchingForGoal | ActorState::WillingToTrade(_)
+            )
+    }) {
+        for (medium, _) in other.inventory.iter() {
+            let saleability = actor.values.saleability(medium);
+            if wanted.contains(&medium) || best.as_ref().is_some_and(|b| b.0 >= saleability) {
+                continue;
+            }
+            let get = Bundle::single(medium, asking_amount(other, medium));
+            let trade = spare.iter().find_map(|&mine| {
+                let give = Bundle::single(mine, offer_amount(actor, mine, get.total()));
+                (actor.would_take_as_medium(medium, mine).is_some()
+                    && actor.can_fit(&get, &give)
+                    && affords(actor, &with_cost(actor, &give))
+                    && accepts(other, &give, &get))
+                .then_some(give)
+            });
+            if let Some(give) = trade {
+                best = Some((saleability, other.id, give, get));
+            }
+        }
+    }
+    best.map(|(_, other, give, get)| (other, give, get))
+}
+
+/// Make the trade `find_medium` comes up with for `id`, if there is one.
+/// Returns whether anything was traded.
+fn trade_for_medium(
+    id: ActorId,
+    goal: GoalId,
+    actors: &mut ActorArena,
+    events: &mut Vec<SimEvent>,
+) -> bool {
+    match find_medium(actors.get(id).unwrap(), goal, actors) {
+        Some((other, give, get)) => {
+            let (mut actor, mut other) = actors.pair_mut(id, other).unwrap();
+            exchange(&mut actor, &give, &mut other, &get, events)
+        }
+        None => false,
+    }
+}
+
+/// The next bid up from `last` that `actor` can make for `want` from
+/// `partner`, if there's anything left to bid at all. See `AscendingBid`.
+fn next_bid(
+    actor: ActorRef,
+    partner: ActorRef,
+    goal: GoalId,
+    last: &Bundle,
+    want: &Bundle,
+) -> Option<Bundle> {
+    let (goal_item, want_amount) = want.first()?;
+    // Goods the actor can spare, least valued first
+    let spare: Vec<ItemId> = actor
+        .ordered_inventory()
+        .into_iter()
+        .filter(|&i| !actor.values.is_money(i) && !actor.needs_for(goal, i) && !want.contains(i))
+        .collect();
+    let goods = last.filter(|i| !actor.values.is_money(i));
+    let money_bid = last.total() - goods.total();
+
+    // One good at a time, each valued higher by the partner than the last
+    if goods.len() <= 1 && money_bid.is_zero() {
+        let previous = goods.first().map(|(i, _)| i).unwrap_or(goal_item);
+        // (or, to start with, one the partner would take as a medium of
+        // exchange even if it has no use for it)
+        let good = spare.iter().rfind(|&&i| {
+            partner.compare_item_values(i, previous) == Ordering::Greater
+                || (goods.is_empty() && partner.would_take_as_medium(i, goal_item).is_some())
+        });
+        if let Some(&good) = good {
+            return Some(Bundle::single(good, offer_amount(actor, good, want_amount)));
+        }
+    }
+    // Then sweeten the last of those with everything else, least valued first
+    if money_bid.is_zero() {
+        if let Some(&good) = spare.iter().find(|&&i| !goods.contains(i)) {
+            if !goods.is_empty() {
+                return Some(goods.with(good, offer_amount(actor, good, want_amount)));
+            }
+        }
+    }
+    // And finally add money to it, a unit more every time, starting with
+    // the money it'd sooner spend and going on to the next once it's bid all
+    // of that (see `ActorRef::monies_held`)
+    let monies = actor.monies_held();
+    let current = monies
+        .iter()
+        .position(|&(money, _)| last.contains(money))
+        .unwrap_or(0);
+    let (money, held) = *monies.get(current)?;
+    let bid = (last.count(money) + Quantity::ONE).min(held);
+    if bid > last.count(money) {
+        Some(goods.with(money, bid))
+    } else {
+        let &(next, held) = monies.get(current + 1)?;
+        Some(goods.with(next, Quantity::ONE.min(held)))
+    }
+}
+
+/// What the partner on the receiving end of a bid asks for instead: the item
+/// of the initiator's it values most, sized like a bid would be, so long as
+/// it'd give up `want` for it and (if it'd already take `offer`) it likes it
+/// better than `offer` (counting any legal tender in it at face value, so it
+/// can't hold out for better money, see `LegalTender`). Once the initiator
+/// has turned one counter down (`refused`), the partner only asks for items
+/// it values less than that one, so it comes down a little each time rather
+/// than asking for the same thing forever. Returns `None` if there's nothing
+/// left worth asking for.
+///
+/// # Arguments
+///
+/// * `partner` - the actor on the receiving end of the bid
+/// * `initiator` - the actor bidding
+/// * `offer` - what the initiator bid this tick
+/// * `want` - what the initiator wants from the partner
+/// * `refused` - the last counter the initiator turned down, if any
+///
+pub fn counter_offer(
+    partner: ActorRef,
+    initiator: ActorRef,
+    offer: &Bundle,
+    want: &Bundle,
+    refused: Option<ItemId>,
+) -> Option<Bundle> {
+    let want_amount = want.first()?.1;
+    // The initiator's goods, as the partner sees them, most valued first
+    let mut theirs: Vec<ItemId> = initiator
+        .inventory
+        .iter()
+        .map(|(i, _)| i)
+        .filter(|&i| !want.contains(i))
+        .collect();
+    theirs.sort_by(|&a, &b| partner.compare_item_values(b, a).then(a.cmp(&b)));
+    let start = match refused {
+        Some(refused) => theirs.iter().position(|&i| i == refused)? + 1,
+        None => 0,
+    };
+    let would_take_offer = accepts(partner, offer, want);
+    let offered = at_face_value(partner, offer);
+    theirs[start..]
+        .iter()
+        .map(|&i| Bundle::single(i, offer_amount(initiator, i, want_amount)))
+        .filter(|counter| counter != offer)
+        .find(|counter| {
+            accepts(partner, counter, want)
+                && (!would_take_offer
+                    || partner.compare_bundles(counter, &offered) == Ordering::Greater)
+        })
+}
+
+/// Everything `actor` could end up giving for `want` in a bilateral
+/// negotiation, as far as it's worth looking before bidding starts: any one
+/// of its goods (which is what it bids first, and what counter-offers ask
+/// for), everything it can spare at once (its bids sweetened as far as they
+/// go), and its money, on its own and on top of that. See `AscendingBid`.
+fn possible_offers(actor: ActorRef, goal: GoalId, want: &Bundle) -> Vec<Bundle> {
+    let want_amount = match want.first() {
+        Some((_, amount)) => amount,
+        None => return vec![],
+    };
+    let mut offers: Vec<Bundle> = actor
+        .ordered_inventory()
+        .into_iter()
+        .filter(|&i| !want.contains(i))
+        .map(|i| Bundle::single(i, offer_amount(actor, i, want_amount)))
+        .collect();
+    let spare = actor
+        .ordered_inventory()
+        .into_iter()
+        .filter(|&i| !actor.values.is_money(i) && !actor.needs_for(goal, i) && !want.contains(i))
+        .fold(Bundle::new(), |all, i| {
+            all.with(i, offer_amount(actor, i, want_amount))
+        });
+    for (money, held) in actor.monies_held() {
+        offers.push(Bundle::single(money, held));
+        offers.push(spare.clone().with(money, held));
+    }
+    offers.push(spare);
+    offers.retain(|offer| !offer.is_empty());
+    offers
+}
+
+/// The most `actor` would give for `want` when trading for `goal`: the
+/// worst trade it'd still make, of the ones it could (see
+/// `possible_offers`), going by its own values. `None` if it wouldn't give
+/// anything it has for it at all.
+pub fn most_would_give(actor: ActorRef, goal: GoalId, want: &Bundle) -> Option<Bundle> {
+    possible_offers(actor, goal, want)
+        .into_iter()
+        .filter(|offer| would_give(actor, offer, want))
+        .max_by(|a, b| actor.compare_bundles(a, b))
+}
+
+/// The least `partner` would take from `initiator` for `want`: the worst
+/// trade it'd still make, of the ones the initiator could offer it (see
+/// `possible_offers`), going by the partner's values. `None` if there's
+/// nothing the initiator has that it would take.
+pub fn least_would_take(
+    partner: ActorRef,
+    initiator: ActorRef,
+    goal: GoalId,
+    want: &Bundle,
+) -> Option<Bundle> {
+    possible_offers(initiator, goal, want)
+        .into_iter()
+        .filter(|offer| accepts(partner, offer, want))
+        .min_by(|a, b| partner.compare_bundles(a, b))
+}
+
+/// Whether there's anything `actor` could give `partner` for `want` that
+/// both of them would go along with: if there isn't, the two sides'
+/// reservations cross (see `most_would_give` and `least_would_take`), and
+/// there's no point bidding at all.
+pub fn zone_of_agreement(actor: ActorRef, partner: ActorRef, goal: GoalId, want: &Bundle) -> bool {
+    possible_offers(actor, goal, want)
+        .iter()
+        .any(|offer| would_give(actor, offer, want) && accepts(partner, offer, want))
+}
+
+/// The original bilateral negotiation: the initiator finds a partner holding
+/// something it wants, then bids its own items one per tick, each one valued
+/// higher by the partner than the last. If none of those will do, it adds the
+/// rest of what it can spare to the last one, an item at a time, and then
+/// money, a unit more each tick, until both sides accept or it runs out of
+/// things to offer.
+///
+/// The partner isn't just along for the ride, either: each bid gets a
+/// counter-offer back, asking for something of the initiator's the partner
+/// likes better (see `counter_offer`). It starts with whatever it values most
+/// and comes down a step every time the initiator turns it down, while the
+/// initiator's bids climb, so the two meet somewhere in the middle. If the
+/// initiator takes the counter the trade goes through on those terms;
+/// otherwise, if the partner would take the bid, it settles for that.
+///
+/// Negotiations are open, too: anyone else after the same partner's items
+/// can get in on them. While more than one actor is bidding on a partner, it
+/// doesn't take a bid it likes straight away, but holds on to the best one so
+/// far, and every other bid (or counter) has to beat it. If nobody has by the
+/// time the bidder comes round again, the trade goes through.
+///
+/// # Notes
+///
+/// Nobody wants money for its own sake, so it only goes anywhere with a
+/// partner that has no use of its own for what's being bid on. Sweetening a
+/// bid only helps when its best item is valued the same as what's asked for
+/// (see `ValueScale::compare_bundles`).
+///
+/// The same bidding can settle forward contracts instead of spot trades (see
+/// `AscendingBid::forward`): whatever's agreed changes hands some ticks
+/// later rather than right away, and whoever hasn't still got what they
+/// promised by then defaults on it.
+///
+/// If either side walks away (see `ValueScale::walks_away`), the two work out
+/// the worst trade each would make before any bids go in, and break off
+/// straight away if those cross (see `zone_of_agreement`), instead of
+/// bidding up until the initiator runs out of things to offer. Nobody lends
+/// anything in that case, either.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct AscendingBid {
+    /// How many ticks after a bid is agreed the items change hands (right
+    /// away, if none)
+    delivery: u64,
+}
+
+impl AscendingBid {
+    /// Bidding for forward contracts, delivered `delivery` ticks after
+    /// they're agreed. Actors waiting on a contract for something they want
+    /// don't go looking to trade for it again in the meantime.
+    pub fn forward(delivery: u64) -> Self {
+        AscendingBid { delivery }
+    }
+
+    /// Close the deal on an agreed bid: swap the items now, or sign a
+    /// forward contract to swap them later. Returns whether the deal went
+    /// through.
+    fn close(
+        &self,
+        actor: &mut ActorMut,
+        give: &Bundle,
+        partner: &mut ActorMut,
+        take: &Bundle,
+        events: &mut Vec<SimEvent>,
+    ) -> bool {
+        if self.delivery == 0 {
+            exchange(actor, give, partner, take, events)
+        } else {
+            agree_forward(actor, give, partner, take, self.delivery, events)
+        }
+    }
+}
+
+impl TradeProtocol for AscendingBid {
+    fn name(&self) -> &'static str {
+        if self.delivery == 0 {
+            "bilateral"
+        } else {
+            "forward"
+        }
+    }
+
+    fn save(&self) -> Option<SavedProtocol> {
+        Some(SavedProtocol::AscendingBid(*self))
+    }
+
+    fn takes_matches(&self) -> bool {
+        true
+    }
+
+    fn step(&mut self, id: ActorId, goal: GoalId, actors: &mut ActorArena) -> Vec<SimEvent> {
+        let mut events = vec![];
+        match actors.get(id).unwrap().state.clone() {
+            ActorState::SearchingForGoal
+            | ActorState::BidRecipiant(..)
+            | ActorState::Producing(..) => {}
+            ActorState::WillingToTrade(_)
+                if actors
+                    .get(id)
+                    .map(|a| a.credit.expecting(id, &a.wanted_for(goal)))
+                    .unwrap_or(false) =>
+            {
+                // It's already on its way
+                *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+            }
+            ActorState::WillingToTrade(last_tried) => {
+                // Find trade partner
+                if let Some(partner) = find_next_partner(
+                    actors.get(id).unwrap(),
+                    goal,
+                    actors,
+                    last_tried,
+                    &mut events,
+                ) {
+                    let (actor, oa) = actors.pair_mut(id, partner).unwrap();
+                    *actor.state = ActorState::FoundTradePartner(partner);
+                    match oa.state {
+                        ActorState::BidRecipiant(..) => {}
+                        _ => {
+                            *oa.state = ActorState::BidRecipiant(None, None, None);
+                        }
+                    }
+                } else if search(actors.get(id).unwrap(), actors, &mut events) {
+                    // Nobody we know of has it, so go and see who does
+                    *actors.get_mut(id).unwrap().state = ActorState::WillingToTrade(None);
+                } else if let Some(cycle) = find_trade_cycle(actors.get(id).unwrap(), goal, actors)
+                {
+                    // Nobody will trade with us directly, but maybe they'll
+                    // go round in a circle
+                    if !trade_around(&cycle, actors, &mut events) {
+                        *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+                    }
+                } else {
+                    // Or failing that, get something we can trade on later
+                    trade_for_medium(id, goal, actors, &mut events);
+                    *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+                }
+            }
+            ActorState::FoundTradePartner(partner) => {
+                // Check if transaction is viable at all
+                let (actor, other_actor) = actors.pair_mut(id, partner).unwrap();
+                let actors_items = other_actor.has_item_of(&actor.as_ref().wanted_for(goal));
+                let walk_away = |goal_item| {
+                    let want = asking_bundle(actor.as_ref(), other_actor.as_ref(), goal, goal_item);
+                    (actor.values.walks_away || other_actor.values.walks_away)
+                        && !zone_of_agreement(actor.as_ref(), other_actor.as_ref(), goal, &want)
+                };
+                if let Some(&goal_item) = actors_items.first().filter(|&&i| walk_away(i)) {
+                    // Whatever one would give, the other won't take, so
+                    // there's no point haggling over it
+                    let want = asking_bundle(actor.as_ref(), other_actor.as_ref(), goal, goal_item);
+                    events.push(SimEvent::NoZoneOfAgreement {
+                        actor: actor.id,
+                        partner,
+                        most_given: most_would_give(actor.as_ref(), goal, &want),
+                        least_taken: least_would_take(
+                            other_actor.as_ref(),
+                            actor.as_ref(),
+                            goal,
+                            &want,
+                        ),
+                        want,
+                    });
+                    *other_actor.state = ActorState::SearchingForGoal;
+                    *actor.state = ActorState::WillingToTrade(Some(partner));
+                } else if let Some(&goal_item) = actors_items.first() {
+                    // prepare to bid
+                    *actor.state = ActorState::Bidding(partner, Bundle::new());
+                    // Keep hold of anyone else's bid, so we have to beat it
+                    let standing = match other_actor.state.clone() {
+                        ActorState::BidRecipiant(_, _, standing) => standing,
+                        _ => None,
+                    };
+                    *other_actor.state = ActorState::BidRecipiant(None, Some(goal_item), standing);
+                } else {
+                    // They've gotten rid of whatever it was we wanted since
+                    // we found them
+                    events.push(SimEvent::TradeAbandoned {
+                        actor: actor.id,
+                        partner,
+                    });
+                    *other_actor.state = ActorState::SearchingForGoal;
+                    *actor.state = ActorState::WillingToTrade(Some(partner));
+                }
+            }
+            ActorState::Bidding(partner, last) => {
+                // Anyone else bidding on the same partner
+                let rivals: Vec<ActorId> = actors
+                    .iter()
+                    .filter(|other| other.id != id)
+                    .filter(|other| match *other.state {
+                        ActorState::Bidding(p, _) | ActorState::FoundTradePartner(p) => {
+                            p == partner
+                        }
+                        _ => false,
+                    })
+                    .map(|other| other.id)
+                    .collect();
+                let (mut actor, mut other_actor) = actors.pair_mut(id, partner).unwrap();
+                // The best bid the partner is holding open, so long as
+                // whoever made it is still around to make good on it
+                let (refused, standing) = match other_actor.state.clone() {
+                    ActorState::BidRecipiant(refused, _, Some(standing))
+                        if standing.0 == id || rivals.contains(&standing.0) =>
+                    {
+                        (refused, Some(standing))
+                    }
+                    ActorState::BidRecipiant(refused, _, None) => (refused, None),
+                    _ => (None, None),
+                };
+                // Nobody beat our bid since last tick, so the items are ours
+                if let Some((_, offer, want)) = standing.as_ref().filter(|s| s.0 == id) {
+                    if would_give(actor.as_ref(), offer, want)
+                        && self.close(&mut actor, offer, &mut other_actor, want, &mut events)
+                    {
+                        *actor.state = ActorState::SearchingForGoal;
+                        *other_actor.state = ActorState::SearchingForGoal;
+                        return events;
+                    }
+                }
+                // if there's no more items for this actor, find another to
+                // trade with
+                let bid = match *other_actor.state {
+                    ActorState::BidRecipiant(_, Some(goal_item), _) => {
+                        let want =
+                            asking_bundle(actor.as_ref(), other_actor.as_ref(), goal, goal_item);
+                        next_bid(actor.as_ref(), other_actor.as_ref(), goal, &last, &want)
+                            .map(|offer| (offer, want, goal_item))
+                    }
+                    // Someone else bidding on the same partner gave up and
+                    // released it, so there's nothing left to bid on here
+                    _ => None,
+                };
+                if let Some((offer, want, goal_item)) = bid {
+                    events.push(SimEvent::BidMade {
+                        actor: actor.id,
+                        partner,
+                        offer: offer.clone(),
+                        want: want.clone(),
+                    });
+                    // Someone else's bid for the same things, which ours has
+                    // to beat
+                    let rival_bid = standing
+                        .filter(|(bidder, _, theirs)| {
+                            *bidder != id && theirs.iter().any(|(i, _)| want.contains(i))
+                        })
+                        .map(|(bidder, best, _)| (bidder, best));
+                    let beats_rival = |partner: ActorRef, offer: &Bundle| match &rival_bid {
+                        Some((_, best)) => {
+                            partner.compare_bundles(offer, best) == Ordering::Greater
+                        }
+                        None => true,
+                    };
+                    // The partner holds out for something better first
+                    let mut refused = refused;
+                    if let Some(counter) =
+                        counter_offer(other_actor.as_ref(), actor.as_ref(), &offer, &want, refused)
+                            .filter(|counter| beats_rival(other_actor.as_ref(), counter))
+                    {
+                        events.push(SimEvent::CounterOfferMade {
+                            actor: partner,
+                            partner: actor.id,
+                            offer: want.clone(),
+                            want: counter.clone(),
+                        });
+                        if would_give(actor.as_ref(), &counter, &want)
+                            && self.close(
+                                &mut actor,
+                                &counter,
+                                &mut other_actor,
+                                &want,
+                                &mut events,
+                            )
+                        {
+                            *actor.state = ActorState::SearchingForGoal;
+                            *other_actor.state = ActorState::SearchingForGoal;
+                            return events;
+                        }
+                        events.push(SimEvent::BidRejected {
+                            actor: actor.id,
+                            partner,
+                            rejected_by: actor.id,
+                        });
+                        refused = counter.first().map(|(i, _)| i);
+                    }
+                    let other = accepts(other_actor.as_ref(), &offer, &want)
+                        && beats_rival(other_actor.as_ref(), &offer);
+                    let me = would_give(actor.as_ref(), &offer, &want);
+                    if other && me && !rivals.is_empty() {
+                        // Others are bidding too, so the partner holds on to
+                        // this one and gives them a tick to beat it
+                        events.push(SimEvent::BidHeld {
+                            actor: partner,
+                            bidder: actor.id,
+                            offer: offer.clone(),
+                            outbid: rival_bid.map(|(bidder, _)| bidder),
+                        });
+                        *other_actor.state = ActorState::BidRecipiant(
+                            refused,
+                            Some(goal_item),
+                            Some((actor.id, offer.clone(), want)),
+                        );
+                        *actor.state = ActorState::Bidding(partner, offer);
+                    } else if other
+                        && me
+                        && self.close(&mut actor, &offer, &mut other_actor, &want, &mut events)
+                    {
+                        *actor.state = ActorState::SearchingForGoal;
+                        *other_actor.state = ActorState::SearchingForGoal;
+                    } else {
+                        events.push(SimEvent::BidRejected {
+                            actor: actor.id,
+                            partner,
+                            rejected_by: if !other && me { partner } else { actor.id },
+                        });
+                        // Anyone else's bid is still on the table (ours, if it
+                        // was, isn't any more)
+                        let standing = match other_actor.state.clone() {
+                            ActorState::BidRecipiant(_, _, Some(standing)) if standing.0 != id => {
+                                Some(standing)
+                            }
+                            _ => None,
+                        };
+                        *other_actor.state =
+                            ActorState::BidRecipiant(refused, Some(goal_item), standing);
+                        *actor.state = ActorState::Bidding(partner, offer);
+                    }
+                } else {
+                    // We've nothing left they'll take, but they might lend it
+                    // to us if they can spare it
+                    let loan = match *other_actor.state {
+                        ActorState::BidRecipiant(_, Some(goal_item), _) if rivals.is_empty() => {
+                            Some(asking_bundle(
+                                actor.as_ref(),
+                                other_actor.as_ref(),
+                                goal,
+                                goal_item,
+                            ))
+                            .filter(|want| would_lend(other_actor.as_ref(), actor.as_ref(), want))
+                        }
+                        _ => None,
+                    };
+                    if loan.is_some_and(|want| {
+                        borrow(&mut actor, &mut other_actor, &want, &mut events)
+                    }) {
+                        *actor.state = ActorState::SearchingForGoal;
+                        *other_actor.state = ActorState::SearchingForGoal;
+                        return events;
+                    }
+                    events.push(SimEvent::TradeAbandoned {
+                        actor: actor.id,
+                        partner,
+                    });
+                    // Only let the partner go if nobody else is bidding on it
+                    if rivals.is_empty() {
+                        *other_actor.state = ActorState::SearchingForGoal;
+                    }
+                    *actor.state = ActorState::WillingToTrade(Some(partner));
+                }
+            }
+        }
+        events
+    }
+}
+
+/// A single take-it-or-leave-it offer per partner: the initiator finds a
+/// partner holding something it wants, offers the least valuable item of its
+/// own that the partner would accept in exchange, and either the trade goes
+/// through on the spot or the initiator moves on to the next partner. There's
+/// no haggling, so each partner only ever costs one tick.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TakeItOrLeaveIt;
+
+impl TradeProtocol for TakeItOrLeaveIt {
+    fn name(&self) -> &'static str {
+        "offer"
+    }
+
+    fn save(&self) -> Option<SavedProtocol> {
+        Some(SavedProtocol::TakeItOrLeaveIt(*self))
+    }
+
+    fn step(&mut self, id: ActorId, goal: GoalId, actors: &mut ActorArena) -> Vec<SimEvent> {
+        let mut events = vec![];
+        let last_tried = match *actors.get(id).unwrap().state {
+            ActorState::WillingToTrade(last_tried) => last_tried,
+            _ => return events,
+        };
+        let partner = match find_next_partner(
+            actors.get(id).unwrap(),
+            goal,
+            actors,
+            last_tried,
+            &mut events,
+        ) {
+            Some(partner) => partner,
+            None if search(actors.get(id).unwrap(), actors, &mut events) => {
+                *actors.get_mut(id).unwrap().state = ActorState::WillingToTrade(None);
+                return events;
+            }
+            None => {
+                let cycle = find_trade_cycle(actors.get(id).unwrap(), goal, actors);
+                if !cycle.is_some_and(|cycle| trade_around(&cycle, actors, &mut events)) {
+                    trade_for_medium(id, goal, actors, &mut events);
+                    *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+                }
+                return events;
+            }
+        };
+        let (mut actor, mut other_actor) = actors.pair_mut(id, partner).unwrap();
+        let goal_item = match other_actor
+            .has_item_of(&actor.as_ref().wanted_for(goal))
+            .first()
+        {
+            Some(&goal_item) => goal_item,
+            None => {
+                // They've gotten rid of whatever it was we saw them with
+                events.push(SimEvent::TradeAbandoned {
+                    actor: actor.id,
+                    partner,
+                });
+                *actor.state = ActorState::WillingToTrade(Some(partner));
+                return events;
+            }
+        };
+        let want = asking_bundle(actor.as_ref(), other_actor.as_ref(), goal, goal_item);
+        let want_amount = want.count(goal_item);
+        // Go through our items least-valued first, so the first one the
+        // partner would accept is the cheapest one we can offer
+        let offer = actor
+            .ordered_inventory()
+            .into_iter()
+            .filter(|&i| !actor.as_ref().needs_for(goal, i) && !want.contains(i))
+            .map(|i| Bundle::single(i, offer_amount(actor.as_ref(), i, want_amount)))
+            .find(|offer| accepts(other_actor.as_ref(), offer, &want));
+        let traded = match offer {
+            Some(offer) if would_give(actor.as_ref(), &offer, &want) => {
+                events.push(SimEvent::BidMade {
+                    actor: actor.id,
+                    partner,
+                    offer: offer.clone(),
+                    want: want.clone(),
+                });
+                // (The offer was sized to what both sides hold, so this only
+                // falls through if one of them hasn't the time to trade)
+                exchange(&mut actor, &offer, &mut other_actor, &want, &mut events)
+            }
+            _ => false,
+        };
+        if traded {
+            *actor.state = ActorState::SearchingForGoal;
+        } else {
+            events.push(SimEvent::TradeAbandoned {
+                actor: actor.id,
+                partner,
+            });
+            *actor.state = ActorState::WillingToTrade(Some(partner));
+        }
+        events
+    }
+}
+
+/// A standing offer on a `PostedPrice` market's bulletin board: whoever
+/// posted it will give `offer` to anyone who gives it `want`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PostedOffer {
+    /// The actor that posted it
+    pub poster: ActorId,
+    /// What the poster gives up
+    pub offer: Bundle,
+    /// What the poster wants for it
+    pub want: Bundle,
+}
+
+/// A posted-price market: nobody haggles with anybody. Instead, an actor that
+/// wants to trade first looks over a bulletin board of standing offers for
+/// one that gets it what it wants for something it'd give up, and takes it if
+/// there is one. If there isn't, it posts its own offer (the least valued
+/// thing it can spare for what it's after) and gets on with its tick. Every
+/// tick its offer goes untaken, it sweetens it, with another unit of the same
+/// good if it has one and then with the next good up, until someone takes it
+/// or it runs out of things it'd give up.
+///
+/// # Notes
+///
+/// The board belongs to the market, not the actors, so it's saved along with
+/// the protocol; offers on it are checked again when they're taken, in case
+/// the poster's given away what it offered, or changed its mind.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PostedPrice {
+    board: Vec<PostedOffer>,
+}
+
+impl PostedPrice {
+    /// Every offer on the board, oldest first.
+    pub fn board(&self) -> &[PostedOffer] {
+        &self.board
+    }
+
+    /// The next offer `actor` posts for `want` when trading for `goal`, given
+    /// what it posted last time (if anything).
+    fn next_offer(
+        actor: ActorRef,
+        goal: GoalId,
+        want: &Bundle,
+        last: Option<&Bundle>,
+    ) -> Option<Bundle> {
+        // Goods the actor can spare, least valued first
+        let spare: Vec<ItemId> = actor
+            .ordered_inventory()
+            .into_iter()
+            .filter(|&i| !actor.needs_for(goal, i) && !want.contains(i))
+            .collect();
+        let gives_up = |offer: &Bundle| would_give(actor, offer, want);
+        let (item, amount) = match last.and_then(|last| last.first()) {
+            Some(last) => last,
+            None => return spare.first().map(|&i| Bundle::single(i, Quantity::ONE)),
+        };
+        // Another unit of the same good, if there's any more of it
+        let more = Bundle::single(item, amount + Quantity::ONE);
+        if actor.inventory.count(item) >= amount + Quantity::ONE && gives_up(&more) {
+            return Some(more);
+        }
+        // Or a unit of the next good up
+        spare
+            .iter()
+            .skip_while(|&&i| i != item)
+            .skip(1)
+            .map(|&i| Bundle::single(i, Quantity::ONE))
+            .find(gives_up)
+    }
+}
+
+impl TradeProtocol for PostedPrice {
+    fn name(&self) -> &'static str {
+        "posted"
+    }
+
+    fn save(&self) -> Option<SavedProtocol> {
+        Some(SavedProtocol::PostedPrice(self.clone()))
+    }
+
+    fn step(&mut self, id: ActorId, goal: GoalId, actors: &mut ActorArena) -> Vec<SimEvent> {
+        let mut events = vec![];
+        match *actors.get(id).unwrap().state {
+            ActorState::WillingToTrade(_) => {}
+            _ => return events,
+        }
+        let holds = |actor: ActorRef, bundle: &Bundle| {
+            bundle
+                .iter()
+                .all(|(item, amount)| actor.inventory.count(item) >= amount)
+        };
+        // Take down anything nobody could take anymore
+        self.board.retain(|post| {
+            actors
+                .get(post.poster)
+                .is_some_and(|p| holds(p, &post.offer))
+        });
+
+        let me = actors.get(id).unwrap();
+        let wanted = me.wanted_for(goal);
+        // Take the first offer on the board that gets us what we want, for
+        // something we'd give up for it (and the poster still would too)
+        let taken = self.board.iter().position(|post| {
+            let poster = actors.get(post.poster).unwrap();
+            post.poster != id
+                && post.offer.iter().any(|(i, _)| wanted.contains(&i))
+                && holds(me, &post.want)
+                && !me.needs_for(goal, post.want.first().unwrap().0)
+                && accepts(me, &post.offer, &post.want)
+                && accepts(poster, &post.want, &post.offer)
+        });
+        if let Some(n) = taken {
+            let post = self.board.remove(n);
+            let (mut actor, mut poster) = actors.pair_mut(id, post.poster).unwrap();
+            if exchange(
+                &mut actor,
+                &post.want,
+                &mut poster,
+                &post.offer,
+                &mut events,
+            ) {
+                *actor.state = ActorState::SearchingForGoal;
+                return events;
+            }
+        }
+
+        // Nothing doing, so post (or sweeten) our own
+        let me = actors.get(id).unwrap();
+        let mine = self.board.iter().position(|post| post.poster == id);
+        let want = match wanted.first() {
+            Some(&item) => Bundle::single(item, Quantity::ONE),
+            None => {
+                if let Some(n) = mine {
+                    self.board.remove(n);
+                }
+                *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+                return events;
+            }
+        };
+        let last = mine
+            .map(|n| &self.board[n])
+            .filter(|post| post.want == want)
+            .map(|post| &post.offer);
+        match PostedPrice::next_offer(me, goal, &want, last) {
+            Some(offer) => {
+                events.push(SimEvent::OfferPosted {
+                    actor: id,
+                    offer: offer.clone(),
+                    want: want.clone(),
+                });
+                let post = PostedOffer {
+                    poster: id,
+                    offer,
+                    want,
+                };
+                match mine {
+                    Some(n) => self.board[n] = post,
+                    None => self.board.push(post),
+                }
+            }
+            // Nothing better to offer, so leave whatever's up there be
+            None if last.is_some() => {}
+            None => {
+                if let Some(n) = mine {
+                    self.board.remove(n);
+                }
+            }
+        }
+        *actors.get_mut(id).unwrap().state = ActorState::SearchingForGoal;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preference_list::Actor;
+    use crate::scenario::Scenario;
+    use crate::simulation::Simulation;
+
+    /// The world the REPL sets up by default, with `actors` actors, each
+    /// made however `setup` says on top.
+    fn default_world<F>(actors: usize, seed: u64, setup: F) -> Simulation
+    where
+        F: Fn(crate::builder::ActorBuilder) -> crate::builder::ActorBuilder,
+    {
+        let mut sim = Simulation::with_seed(seed);
+        Scenario::default_scenario()
+            .populate(&mut sim, Some(actors), Quantity::ZERO, |_, a, _| {
+                setup(a.labor(Quantity::from(8u32)))
+            })
+            .unwrap();
+        sim
+    }
+
+    #[test]
+    fn escrow_gives_deposits_back_when_the_other_side_cant_deliver() {
+        let (gold, bread) = (ItemId(0), ItemId(1));
+        let two = Quantity::from(2u32);
+        let mut actors = ActorArena::new();
+        let holding = |name: &str, item| {
+            Actor::builder()
+                .name(name)
+                .starting_amount(item, two)
+                .build()
+                .unwrap()
+        };
+        let a = actors.insert(holding("A", gold));
+        let b = actors.insert(holding("B", bread));
+        actors.set_now(5);
+        let gold_lots: Vec<_> = actors
+            .get(a)
+            .unwrap()
+            .inventory
+            .lots(gold)
+            .copied()
+            .collect();
+        let (mut a, mut b) = actors.pair_mut(a, b).unwrap();
+        let mut escrow = Escrow::new();
+        assert!(escrow.deposit(&mut a, &Bundle::new().with(gold, two)));
+        assert!(a.inventory.count(gold).is_zero());
+        // B hasn't got that much bread, so none of it's taken
+        let more = Quantity::from(3u32);
+        assert!(!escrow.deposit(&mut b, &Bundle::new().with(bread, more)));
+        assert_eq!(b.inventory.count(bread), two);
+        // And A gets its gold back just as it was
+        escrow.refund(&mut a);
+        assert_eq!(a.inventory.count(gold), two);
+        let refunded: Vec<_> = a.inventory.lots(gold).copied().collect();
+        assert_eq!(refunded, gold_lots);
+    }
+
+    #[test]
+    fn offers_made_on_what_was_seen_go_on_searching() {
+        // Someone seen holding something may not have it any more by the
+        // time an offer's made for it
+        for seed in 0..5 {
+            let mut sim = default_world(12, seed, |a| a.imperfect_information());
+            sim.set_trade_protocol(protocol_by_name("offer").unwrap());
+            for _ in 0..120 {
+                sim.tick();
+            }
+        }
+    }
+}
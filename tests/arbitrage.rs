@@ -0,0 +1,204 @@
+//! Grain goes for two different prices in silver: poor buyers, who only have
+//! a little silver, pay farmers less for it than rich buyers do. An
+//! arbitrageur with silver to spare buys it cheap and sells it dear, and in
+//! competing for the trades at either end it brings the two prices together.
+
+use microeconomics::behavior::ARBITRAGE_WINDOW;
+use microeconomics::{
+    Actor, Arbitrageur, GoalRegistry, GoalTemplate, ItemRegistry, Quantity, SimEvent, Simulation,
+};
+
+/// A world with grain and silver, both divisible. Farmers hold grain and want
+/// silver, poor buyers hold a little silver and want grain, and so do rich
+/// buyers, who hold plenty of silver. Everyone values what they hold too,
+/// just less than what they want. If `arbitrage` is set, there's also an
+/// arbitrageur with silver and no use for either good.
+fn market(arbitrage: bool) -> Simulation {
+    let mut sim = Simulation::with_seed(0);
+    sim.set_items(
+        ItemRegistry::from_toml(
+            r#"
+[[item]]
+name = "Grain"
+divisible = true
+[[item]]
+name = "Silver"
+divisible = true
+"#,
+        )
+        .unwrap(),
+    );
+    sim.set_goals(
+        GoalRegistry::from_toml(
+            r#"
+[[goal]]
+name = "WantGrain"
+units_required = 1
+[[goal]]
+name = "WantSilver"
+units_required = 1
+[[goal]]
+name = "KeepGrain"
+units_required = 1
+[[goal]]
+name = "KeepSilver"
+units_required = 1
+"#,
+        )
+        .unwrap(),
+    );
+    let item = |name: &str| sim.items().id(name).unwrap();
+    let goal = |name: &str| sim.goals().id(name).unwrap();
+    let (grain, silver) = (item("Grain"), item("Silver"));
+    let (want_grain, want_silver) = (goal("WantGrain"), goal("WantSilver"));
+    let (keep_grain, keep_silver) = (goal("KeepGrain"), goal("KeepSilver"));
+    let amount = |n: f64| Quantity::from(n);
+    let mut people = vec![];
+    for i in 0..2 {
+        people.push((
+            format!("F{}", i),
+            (want_silver, silver, 20.0),
+            (keep_grain, grain, 20.0),
+        ));
+    }
+    for i in 0..3 {
+        people.push((
+            format!("P{}", i),
+            (want_grain, grain, 2.0),
+            (keep_silver, silver, 0.5),
+        ));
+    }
+    for i in 0..3 {
+        people.push((
+            format!("R{}", i),
+            (want_grain, grain, 6.0),
+            (keep_silver, silver, 20.0),
+        ));
+    }
+    for (name, (wants, wanted, needed), (has, held, amount_held)) in people {
+        let actor = Actor::builder()
+            .name(&name)
+            .template(
+                &GoalTemplate::once(wants, amount(needed), vec![wanted]),
+                sim.rng(),
+            )
+            .template(
+                &GoalTemplate::once(has, amount(40.0), vec![held]),
+                sim.rng(),
+            )
+            .starting_amount(held, amount(amount_held))
+            .build()
+            .unwrap();
+        sim.add_actor(actor);
+    }
+    if arbitrage {
+        let actor = Actor::builder()
+            .name("Arbitrageur")
+            .starting_amount(silver, amount(10.0))
+            .behavior(Box::new(Arbitrageur::default()))
+            .build()
+            .unwrap();
+        sim.add_actor(actor);
+    }
+    sim
+}
+
+fn run(sim: &mut Simulation, ticks: usize) -> Vec<SimEvent> {
+    (0..ticks).flat_map(|_| sim.tick()).collect()
+}
+
+/// Every unit of grain the arbitrageur bought and sold, in order, with the
+/// ratio in silver each went for, and whether it was a sale.
+fn deals(sim: &Simulation, events: &[SimEvent]) -> Vec<(bool, Quantity)> {
+    let arbitrageur = sim.find_actor("Arbitrageur").unwrap();
+    let silver = sim.items().id("Silver").unwrap();
+    events
+        .iter()
+        .filter_map(|e| match e {
+            SimEvent::TradeCompleted {
+                actor,
+                gave,
+                received,
+                ..
+            } if *actor == arbitrageur => Some(if gave.contains(silver) {
+                (false, gave.count(silver))
+            } else {
+                (true, received.count(silver))
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn grain_goes_for_different_ratios() {
+    let mut sim = market(false);
+    run(&mut sim, 80);
+    let grain = sim.items().id("Grain").unwrap();
+    let silver = sim.items().id("Silver").unwrap();
+    let (low, high) = sim.prices().range(grain, silver, usize::MAX).unwrap();
+    assert!(low < high, "grain always went for {}", low);
+}
+
+#[test]
+fn arbitrageur_sells_for_more_than_it_paid() {
+    let mut sim = market(true);
+    let events = run(&mut sim, 80);
+    let deals = deals(&sim, &events);
+    let sales = deals.iter().filter(|&&(sale, _)| sale).count();
+    assert!(sales >= 3, "hardly any arbitrage: {:?}", deals);
+    for pair in deals.windows(2) {
+        if let [(false, paid), (true, sold)] = *pair {
+            assert!(sold > paid, "sold for {} what it paid {} for", sold, paid);
+        }
+    }
+}
+
+#[test]
+fn buying_and_selling_ratios_come_together() {
+    let mut sim = market(true);
+    let events = run(&mut sim, 80);
+    let deals = deals(&sim, &events);
+    let bought: Vec<_> = deals.iter().filter(|d| !d.0).map(|d| d.1).collect();
+    let sold: Vec<_> = deals.iter().filter(|d| d.0).map(|d| d.1).collect();
+    assert!(
+        bought.windows(2).all(|w| w[0] <= w[1]),
+        "buying got cheaper: {:?}",
+        bought
+    );
+    let first = sold[0] - bought[0];
+    let last = sold[sold.len() - 1] - bought[sold.len() - 1];
+    assert!(last < first, "spread only went from {} to {}", first, last);
+}
+
+#[test]
+fn spread_narrows_once_the_arbitrageur_gets_going() {
+    let mut sim = market(true);
+    let grain = sim.items().id("Grain").unwrap();
+    let silver = sim.items().id("Silver").unwrap();
+    let arbitrageur = sim.find_actor("Arbitrageur").unwrap();
+    // Run until it makes its first deal
+    let mut before = None;
+    for _ in 0..80 {
+        let spread = sim.prices().range(grain, silver, ARBITRAGE_WINDOW);
+        let events = sim.tick();
+        if events
+            .iter()
+            .any(|e| matches!(e, SimEvent::TradeCompleted { actor, .. } if *actor == arbitrageur))
+        {
+            before = spread;
+            break;
+        }
+    }
+    let (low, high) = before.expect("the arbitrageur never traded");
+    run(&mut sim, 40);
+    let (new_low, new_high) = sim.prices().range(grain, silver, ARBITRAGE_WINDOW).unwrap();
+    assert!(
+        new_high - new_low < high - low,
+        "spread went from {}..{} to {}..{}",
+        low,
+        high,
+        new_low,
+        new_high
+    );
+}
@@ -0,0 +1,151 @@
+//! Bids of several goods for one. Values are ordinal, so a bundle is only
+//! worth as much as the best goods in it: a rejected bid can be sweetened
+//! with more goods, but no amount of goods valued less makes up for a good
+//! valued more, unless the actor weighs bundles by how many of its wants
+//! they'd go towards.
+
+use microeconomics::trade::accepts;
+use microeconomics::{
+    Actor, ActorId, Bundle, GoalRegistry, GoalTemplate, ItemRegistry, Quantity, Simulation,
+};
+
+/// A world where the keeper holds gold, which it values most, and wants
+/// bread, which it values less, and pebbles, which it values least; the
+/// bidder holds plenty of bread and pebbles and wants the gold. The keeper
+/// weighs bundles (see `ActorBuilder::weighs_bundles`) if `weighs` says to.
+fn vault(weighs: bool) -> Simulation {
+    let mut sim = Simulation::with_seed(0);
+    sim.set_items(
+        ItemRegistry::from_toml(
+            r#"
+[[item]]
+name = "Gold"
+[[item]]
+name = "Bread"
+[[item]]
+name = "Pebble"
+"#,
+        )
+        .unwrap(),
+    );
+    sim.set_goals(
+        GoalRegistry::from_toml(
+            r#"
+[[goal]]
+name = "Hoard"
+units_required = 1
+[[goal]]
+name = "Eat"
+units_required = 10
+[[goal]]
+name = "Play"
+units_required = 10
+"#,
+        )
+        .unwrap(),
+    );
+    let item = |name: &str| sim.items().id(name).unwrap();
+    let goal = |name: &str| sim.goals().id(name).unwrap();
+    let (gold, bread, pebble) = (item("Gold"), item("Bread"), item("Pebble"));
+    let (hoard, eat, play) = (goal("Hoard"), goal("Eat"), goal("Play"));
+    let ten = Quantity::from(10u32);
+    let keeper = if weighs {
+        Actor::builder().weighs_bundles()
+    } else {
+        Actor::builder()
+    };
+    let keeper = keeper
+        .name("Keeper")
+        .template(
+            &GoalTemplate::once(hoard, Quantity::ONE, vec![gold]),
+            sim.rng(),
+        )
+        .template(&GoalTemplate::once(eat, ten, vec![bread]), sim.rng())
+        .template(&GoalTemplate::once(play, ten, vec![pebble]), sim.rng())
+        .starting_amount(gold, Quantity::ONE)
+        .build()
+        .unwrap();
+    let bidder = Actor::builder()
+        .name("Bidder")
+        .template(
+            &GoalTemplate::once(hoard, Quantity::ONE, vec![gold]),
+            sim.rng(),
+        )
+        .starting_amount(bread, ten)
+        .starting_amount(pebble, ten)
+        .build()
+        .unwrap();
+    sim.add_actor(keeper);
+    sim.add_actor(bidder);
+    sim
+}
+
+fn bundle(sim: &Simulation, items: &[(&str, u32)]) -> Bundle {
+    let mut bundle = Bundle::new();
+    for &(name, amount) in items {
+        bundle.add(sim.items().id(name).unwrap(), Quantity::from(amount));
+    }
+    bundle
+}
+
+#[test]
+fn lesser_goods_never_outweigh_a_better_one() {
+    let sim = vault(false);
+    let keeper = sim.arena().get(ActorId(0)).unwrap();
+    let gold = bundle(&sim, &[("Gold", 1)]);
+    for offer in [
+        bundle(&sim, &[("Bread", 1)]),
+        bundle(&sim, &[("Bread", 10)]),
+        bundle(&sim, &[("Bread", 10), ("Pebble", 10)]),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            keeper.compare_bundles(offer, &gold),
+            std::cmp::Ordering::Less
+        );
+        assert!(!accepts(keeper, offer, &gold));
+    }
+}
+
+#[test]
+fn more_on_top_of_as_good_a_best_good_is_worth_more() {
+    let sim = vault(false);
+    let keeper = sim.arena().get(ActorId(0)).unwrap();
+    let gold = bundle(&sim, &[("Gold", 1)]);
+    let sweetened = bundle(&sim, &[("Gold", 1), ("Pebble", 1)]);
+    assert_eq!(
+        keeper.compare_bundles(&sweetened, &gold),
+        std::cmp::Ordering::Greater
+    );
+    assert!(accepts(keeper, &sweetened, &gold));
+    // And the more of the best goods, the better, whatever comes after
+    let bread = bundle(&sim, &[("Bread", 2)]);
+    let less_bread = bundle(&sim, &[("Bread", 1), ("Pebble", 10)]);
+    assert_eq!(
+        keeper.compare_bundles(&bread, &less_bread),
+        std::cmp::Ordering::Greater
+    );
+}
+
+#[test]
+fn lesser_goods_for_more_wants_outweigh_a_better_one_when_weighed() {
+    let sim = vault(true);
+    let keeper = sim.arena().get(ActorId(0)).unwrap();
+    let gold = bundle(&sim, &[("Gold", 1)]);
+    let both = bundle(&sim, &[("Bread", 10), ("Pebble", 10)]);
+    assert_eq!(keeper.values.wants_served(&both), 2);
+    assert_eq!(
+        keeper.compare_bundles(&both, &gold),
+        std::cmp::Ordering::Greater
+    );
+    assert!(accepts(keeper, &both, &gold));
+    // But a pile of one of them goes towards just the one want, so it's down
+    // to how it ranks
+    let bread = bundle(&sim, &[("Bread", 10)]);
+    assert_eq!(
+        keeper.compare_bundles(&bread, &gold),
+        std::cmp::Ordering::Less
+    );
+    assert!(!accepts(keeper, &bread, &gold));
+}
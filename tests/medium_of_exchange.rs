@@ -0,0 +1,200 @@
+//! Four producers each hold one good and want the next one round, in a ring
+//! too big to trade around in one go, so none of them can get what they want
+//! by trading directly. Salt is the one good that everyone else trades in,
+//! so it ends up the most saleable, and the producers take it (without
+//! wanting it) just to trade it on: salt becomes money.
+
+use microeconomics::{
+    Actor, ActorId, GoalRegistry, GoalTemplate, ItemId, ItemRegistry, Quantity, Scenario, SimEvent,
+    Simulation,
+};
+use std::collections::HashMap;
+
+/// A world with salt and four goods, G0 to G3. Producer Pi holds Gi and wants
+/// G(i + 1), going round. Salt lovers hold G0 or G2 and want salt, and salt
+/// holders Hi hold salt and want Gi. Everyone values what they hold too, just
+/// less than what they want.
+fn ring() -> Simulation {
+    let mut sim = Simulation::with_seed(0);
+    let mut items = String::from("[[item]]\nname = \"Salt\"\n");
+    let mut goals = String::from(
+        "[[goal]]\nname = \"WantSalt\"\nunits_required = 1\n\
+         [[goal]]\nname = \"KeepSalt\"\nunits_required = 1\n",
+    );
+    for i in 0..4 {
+        items += &format!("[[item]]\nname = \"G{}\"\n", i);
+        goals += &format!(
+            "[[goal]]\nname = \"Want{i}\"\nunits_required = 1\n\
+             [[goal]]\nname = \"Keep{i}\"\nunits_required = 1\n",
+            i = i
+        );
+    }
+    sim.set_items(ItemRegistry::from_toml(&items).unwrap());
+    sim.set_goals(GoalRegistry::from_toml(&goals).unwrap());
+    let item = |name: &str| sim.items().id(name).unwrap();
+    let goal = |name: &str| sim.goals().id(name).unwrap();
+    let salt = item("Salt");
+    let good: Vec<_> = (0..4).map(|i| item(&format!("G{}", i))).collect();
+    let want: Vec<_> = (0..4).map(|i| goal(&format!("Want{}", i))).collect();
+    let keep: Vec<_> = (0..4).map(|i| goal(&format!("Keep{}", i))).collect();
+    let (want_salt, keep_salt) = (goal("WantSalt"), goal("KeepSalt"));
+
+    let mut people = vec![];
+    for i in 0..4 {
+        let next = (i + 1) % 4;
+        people.push((
+            format!("P{}", i),
+            (want[next], good[next]),
+            (keep[i], good[i]),
+            2u32,
+        ));
+    }
+    for &i in [0, 2].iter() {
+        people.push((format!("L{}", i), (want_salt, salt), (keep[i], good[i]), 1));
+    }
+    for i in 0..4 {
+        people.push((format!("H{}", i), (want[i], good[i]), (keep_salt, salt), 2));
+    }
+    for (name, (wants, wanted), (has, held), amount) in people {
+        let actor = Actor::builder()
+            .name(&name)
+            .template(
+                &GoalTemplate::once(wants, Quantity::ONE, vec![wanted]),
+                sim.rng(),
+            )
+            .template(
+                &GoalTemplate::once(has, Quantity::from(5u32), vec![held]),
+                sim.rng(),
+            )
+            .starting_amount(held, Quantity::from(amount))
+            .build()
+            .unwrap();
+        sim.add_actor(actor);
+    }
+    sim
+}
+
+fn run(sim: &mut Simulation, ticks: usize) -> Vec<SimEvent> {
+    (0..ticks).flat_map(|_| sim.tick()).collect()
+}
+
+#[test]
+fn nothing_is_a_medium_before_anything_trades() {
+    let sim = ring();
+    let salt = sim.items().id("Salt").unwrap();
+    for a in sim.arena().iter() {
+        assert_eq!(a.values.saleability(salt), 0);
+        for (held, _) in a.inventory.iter() {
+            assert_eq!(a.would_take_as_medium(salt, held), None);
+        }
+    }
+}
+
+#[test]
+fn producers_take_salt_as_a_medium() {
+    let mut sim = ring();
+    let salt = sim.items().id("Salt").unwrap();
+    let events = run(&mut sim, 40);
+    let media: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            SimEvent::MediumAccepted { actor, item, .. } => Some((*actor, *item)),
+            _ => None,
+        })
+        .collect();
+    assert!(
+        media.len() >= 2,
+        "salt hardly used as a medium: {:?}",
+        media
+    );
+    for &(actor, item) in media.iter() {
+        assert_eq!(item, salt, "{} took something else as a medium", actor.0);
+        assert!(actor.0 < 4, "someone other than a producer took a medium");
+    }
+}
+
+#[test]
+fn salt_becomes_the_most_saleable_good() {
+    let mut sim = ring();
+    let salt = sim.items().id("Salt").unwrap();
+    run(&mut sim, 40);
+    for a in sim.arena().iter() {
+        let ranked = a.values.most_saleable();
+        assert_eq!(
+            ranked[0].0, salt,
+            "{} thinks something else is money",
+            a.name
+        );
+        assert!(
+            ranked[1..].iter().all(|&(_, n)| n < ranked[0].1),
+            "salt isn't clearly the most saleable to {}",
+            a.name
+        );
+    }
+}
+
+#[test]
+fn producers_get_what_they_want_through_salt() {
+    let mut sim = ring();
+    let events = run(&mut sim, 40);
+    let satisfied = (0..4)
+        .filter(|&i| {
+            let want = sim.goals().id(&format!("Want{}", (i + 1) % 4)).unwrap();
+            events.iter().any(|e| {
+                matches!(e, SimEvent::GoalSatisfied { actor, goal }
+                    if *actor == ActorId(i) && *goal == want)
+            })
+        })
+        .count();
+    assert!(
+        satisfied >= 2,
+        "only {} producers got what they wanted",
+        satisfied
+    );
+}
+
+/// The world the REPL sets up by default (see `Scenario::default_scenario`),
+/// with `actors` actors working the REPL's default hours.
+fn default_world(actors: usize, seed: u64) -> Simulation {
+    let mut sim = Simulation::with_seed(seed);
+    Scenario::default_scenario()
+        .populate(&mut sim, Some(actors), Quantity::ZERO, |_, a, _| {
+            a.labor(Quantity::from(8u32))
+        })
+        .unwrap();
+    sim
+}
+
+#[test]
+fn media_are_not_swapped_back_and_forth() {
+    // This used to have leisure goods go round and round between three
+    // actors, neither side of each trade wanting either of them
+    let mut sim = default_world(20, 1);
+    let events = run(&mut sim, 100);
+    let mut swaps: HashMap<(ActorId, Vec<ItemId>), usize> = HashMap::new();
+    for e in events.iter() {
+        if let SimEvent::TradeCompleted {
+            actor,
+            partner,
+            gave,
+            received,
+        } = e
+        {
+            let mut goods: Vec<_> = gave.iter().chain(received.iter()).map(|(i, _)| i).collect();
+            goods.sort();
+            for &side in [actor, partner].iter() {
+                *swaps.entry((*side, goods.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+    for ((actor, goods), times) in swaps {
+        let names: Vec<_> = goods.iter().map(|&i| sim.items().name(i)).collect();
+        assert!(
+            times <= 3,
+            "{} traded {:?} for each other {} times",
+            sim.actor_name(actor),
+            names,
+            times
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Three actors each want what the next one has, but none of them wants
+//! anything the one it'd be trading with has: no two of them can trade, so
+//! the only way anyone gets what they want is all three at once.
+
+use microeconomics::trade::{accepts, find_trade_cycle};
+use microeconomics::{
+    Actor, ActorId, Bundle, GoalRegistry, GoalTemplate, ItemRegistry, Quantity, SimEvent,
+    Simulation,
+};
+
+/// A world where A holds Xs and wants a Y, B holds Ys and wants a Z, and C
+/// holds Zs and wants an X. Each of them values what it holds too, just less
+/// than what it wants, so none of them will give it up for anything else.
+fn triangle(protocol: &str) -> Simulation {
+    let mut sim = Simulation::with_seed(0);
+    sim.set_items(
+        ItemRegistry::from_toml(
+            r#"
+[[item]]
+name = "X"
+[[item]]
+name = "Y"
+[[item]]
+name = "Z"
+"#,
+        )
+        .unwrap(),
+    );
+    sim.set_goals(
+        GoalRegistry::from_toml(
+            r#"
+[[goal]]
+name = "WantX"
+units_required = 1
+[[goal]]
+name = "WantY"
+units_required = 1
+[[goal]]
+name = "WantZ"
+units_required = 1
+"#,
+        )
+        .unwrap(),
+    );
+    sim.set_trade_protocol(microeconomics::trade::protocol_by_name(protocol).unwrap());
+    let item = |name: &str| sim.items().id(name).unwrap();
+    let goal = |name: &str| sim.goals().id(name).unwrap();
+    let (x, y, z) = (item("X"), item("Y"), item("Z"));
+    let (want_x, want_y, want_z) = (goal("WantX"), goal("WantY"), goal("WantZ"));
+    let one = Quantity::ONE;
+    let people = [
+        ("A", (want_y, y), (want_x, x)),
+        ("B", (want_z, z), (want_y, y)),
+        ("C", (want_x, x), (want_z, z)),
+    ];
+    for &(name, (wants, wanted), (has, held)) in people.iter() {
+        let actor = Actor::builder()
+            .name(name)
+            .template(&GoalTemplate::once(wants, one, vec![wanted]), sim.rng())
+            .template(
+                &GoalTemplate::once(has, Quantity::from(5u32), vec![held]),
+                sim.rng(),
+            )
+            .starting_amount(held, Quantity::from(2u32))
+            .build()
+            .unwrap();
+        sim.add_actor(actor);
+    }
+    sim
+}
+
+fn run(sim: &mut Simulation, ticks: usize) -> Vec<SimEvent> {
+    (0..ticks).flat_map(|_| sim.tick()).collect()
+}
+
+#[test]
+fn no_two_actors_will_trade_directly() {
+    let sim = triangle("bilateral");
+    let arena = sim.arena();
+    for a in arena.iter() {
+        for b in arena.iter().filter(|b| b.id != a.id) {
+            for (mine, _) in a.inventory.iter() {
+                for (theirs, _) in b.inventory.iter() {
+                    let offer = Bundle::single(mine, Quantity::ONE);
+                    let want = Bundle::single(theirs, Quantity::ONE);
+                    assert!(
+                        !accepts(b, &offer, &want) || !accepts(a, &want, &offer),
+                        "{} and {} would trade directly",
+                        a.name,
+                        b.name
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cycle_is_found() {
+    let sim = triangle("bilateral");
+    let a = sim.actor(ActorId(0)).unwrap();
+    let want_y = sim.goals().id("WantY").unwrap();
+    let cycle = find_trade_cycle(a, want_y, sim.arena()).expect("no cycle found");
+    let x = sim.items().id("X").unwrap();
+    let y = sim.items().id("Y").unwrap();
+    let z = sim.items().id("Z").unwrap();
+    assert_eq!(
+        cycle,
+        vec![
+            (ActorId(0), Bundle::single(x, Quantity::ONE)),
+            (ActorId(2), Bundle::single(z, Quantity::ONE)),
+            (ActorId(1), Bundle::single(y, Quantity::ONE)),
+        ]
+    );
+}
+
+#[test]
+fn everyone_gets_what_they_want() {
+    for protocol in ["bilateral", "offer"].iter() {
+        let mut sim = triangle(protocol);
+        let events = run(&mut sim, 30);
+        assert!(
+            events.iter().any(
+                |e| matches!(e, SimEvent::TradeCycleCompleted { actors, .. } if actors.len() == 3)
+            ),
+            "no three-way trade with {}",
+            protocol
+        );
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, SimEvent::TradeCompleted { .. })),
+            "a bilateral trade happened with {}",
+            protocol
+        );
+        for want in ["WantX", "WantY", "WantZ"].iter() {
+            let goal = sim.goals().id(want).unwrap();
+            assert!(
+                events
+                    .iter()
+                    .any(|e| matches!(e, SimEvent::GoalSatisfied { goal: g, .. } if *g == goal)),
+                "{} never satisfied with {}",
+                want,
+                protocol
+            );
+        }
+    }
+}